@@ -17,15 +17,20 @@ use std::time::Instant;
 use shinzou::{
     core::{
         input::{
-            types::{Button, GamepadEvent, Key},
+            types::{Button, GamepadEvent, Key, Mouse},
             InputManager,
         },
         window::{init_window, Evt, Flow, WinEvt},
     },
-    renderer::{entities::Camera, Renderer},
+    renderer::{
+        entities::{
+            CameraController, CameraControllerSettings, CameraInput, FpsController, Highlight,
+        },
+        Renderer,
+    },
     utils::Logger,
 };
-use ultraviolet::{rotor::Rotor3, Vec3};
+use ultraviolet::{Vec2, Vec3};
 //----------------------------------------------------------------------------------------------------------------------
 
 use input::{ActionContexts, InGameActions, InputActions};
@@ -79,11 +84,20 @@ fn main() {
     // let mut line = String::new();
     // std::io::stdin().read_line(&mut line).unwrap();
 
-    let mut renderer = Renderer::init(APP_NAME, &window_handle);
+    let mut renderer = Renderer::init(APP_NAME, &window_handle, None);
 
     input_actions.set_active_context(ActionContexts::InGame);
 
+    let mut camera_controller = FpsController::new(
+        renderer.camera_mut(),
+        CameraControllerSettings {
+            look_speed: 10.0,
+            ..CameraControllerSettings::default()
+        },
+    );
+
     let mut timer = Timer::new();
+    let mut cursor_position = Vec2::zero();
 
     info!("----- EventLoopHandle::run -----");
     event_loop_handle.run(move |event, _, control_flow| {
@@ -107,6 +121,9 @@ fn main() {
                     renderer.await_device_idle();
                     *control_flow = Flow::Exit;
                 }
+                WinEvt::CursorMoved { position, .. } => {
+                    cursor_position = Vec2::new(position.x as f32, position.y as f32);
+                }
                 _ => {}
             },
             Evt::DeviceEvent { event, .. } => {
@@ -130,10 +147,14 @@ fn main() {
                         event_proxy.emit(CoreEvent::CloseRequested);
                     }
 
-                    update_camera(
+                    if input_manager.is_mouse_down(Mouse::LMB) {
+                        renderer.pick(cursor_position.x as u32, cursor_position.y as u32);
+                    }
+                    report_picked_renderables(&mut renderer);
+
+                    camera_controller.update(
                         renderer.camera_mut(),
-                        &input_actions,
-                        &input_manager,
+                        &camera_input(&input_actions, &input_manager),
                         frame_delta,
                     );
 
@@ -149,44 +170,42 @@ fn main() {
 }
 //----------------------------------------------------------------------------------------------------------------------
 
-fn update_camera(
-    camera: &mut Camera,
-    input_actions: &InputActions,
-    mgr: &InputManager,
-    frame_delta: f32,
-) {
-    let look_up = input_actions.get_in_game_action_value(mgr, InGameActions::LookUp);
-    let look_down = input_actions.get_in_game_action_value(mgr, InGameActions::LookDown);
-    let look_left = input_actions.get_in_game_action_value(mgr, InGameActions::LookLeft);
-    let look_right = input_actions.get_in_game_action_value(mgr, InGameActions::LookRight);
-
-    const ROT_MULTIPLIER: f32 = 10.0;
-
-    let rotor = Rotor3::from_euler_angles(
-        0.0,
-        (look_down - look_up) * ROT_MULTIPLIER * frame_delta,
-        (look_left - look_right) * ROT_MULTIPLIER * frame_delta,
-    );
-    camera.rotate(rotor);
-
-    let fwd = input_actions.get_in_game_action_value(mgr, InGameActions::Forward);
-    let bwd = input_actions.get_in_game_action_value(mgr, InGameActions::Backward);
-    let left = input_actions.get_in_game_action_value(mgr, InGameActions::Left);
-    let right = input_actions.get_in_game_action_value(mgr, InGameActions::Right);
-    let lb_down = input_actions.is_in_game_action_down(mgr, InGameActions::Sprint);
-
-    const WALK_MULTIPLIER: f32 = 10.0;
-    const SPRINT_MULTIPLIER: f32 = 20.0;
-    let multiplier = if lb_down {
-        SPRINT_MULTIPLIER
-    } else {
-        WALK_MULTIPLIER
-    };
-
-    camera.translate(Vec3::new(
-        (left - right) * multiplier * frame_delta,
-        0.0,
-        (fwd - bwd) * multiplier * frame_delta,
-    ));
+fn camera_input(input_actions: &InputActions, mgr: &InputManager) -> CameraInput {
+    let value = |action| input_actions.get_in_game_action_value(mgr, action);
+
+    CameraInput {
+        movement: Vec3::new(
+            value(InGameActions::Right) - value(InGameActions::Left),
+            0.0,
+            value(InGameActions::Forward) - value(InGameActions::Backward),
+        ),
+        look: Vec2::new(
+            value(InGameActions::LookRight) - value(InGameActions::LookLeft),
+            value(InGameActions::LookUp) - value(InGameActions::LookDown),
+        ),
+        zoom: 0.0,
+        boost: input_actions.is_in_game_action_down(mgr, InGameActions::Sprint),
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+// Ray casting the lost empire on the CPU takes too long, clicks are picked on the GPU and reported once read back. The
+// picked renderable becomes the selection
+fn report_picked_renderables(renderer: &mut Renderer) {
+    while let Some(pick) = renderer.picked() {
+        renderer.clear_highlights(Some(Highlight::Selected));
+
+        match pick.renderable_index {
+            Some(index) => {
+                let renderable = &renderer.scene()[index];
+                info!(
+                    "Picked renderable {} ({}) at ({}, {}), {:.2} units away",
+                    index, renderable.mesh_name, pick.x, pick.y, pick.depth
+                );
+                renderer.set_highlight(index, Highlight::Selected);
+            }
+            None => info!("Nothing under the cursor at ({}, {})", pick.x, pick.y),
+        }
+    }
 }
 //----------------------------------------------------------------------------------------------------------------------
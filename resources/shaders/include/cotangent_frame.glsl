@@ -0,0 +1,27 @@
+// Shared by the material shaders, which must enable GL_GOOGLE_include_directive to include it.
+
+const float TANGENT_FRAME_EPSILON = 1e-12;
+
+// Tangent frame from screen-space derivatives, so normal maps work without per-vertex tangents.
+// http://www.thetenthplanet.de/archives/1180
+mat3 cotangentFrame(vec3 N, vec3 p, vec2 uv)
+{
+    vec3 dp1 = dFdx(p);
+    vec3 dp2 = dFdy(p);
+    vec2 duv1 = dFdx(uv);
+    vec2 duv2 = dFdy(uv);
+
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+
+    // Constant texture coordinates leave no tangent frame, the normal map then keeps the geometric normal
+    float lengthSquared = max(dot(T, T), dot(B, B));
+    if (lengthSquared < TANGENT_FRAME_EPSILON) {
+        return mat3(vec3(0.0), vec3(0.0), N);
+    }
+
+    float invmax = inversesqrt(lengthSquared);
+    return mat3(T * invmax, B * invmax, N);
+}
@@ -0,0 +1,28 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+layout (location = 1) in vec4 color;
+
+layout (location = 0) out vec4 outColor;
+
+layout (set = 1, binding = 0) uniform sampler2D billboardTexture;
+
+layout(push_constant) uniform BillboardConstants
+{
+    // x - 1 when the texture holds a signed distance field
+    vec4 params;
+} constants;
+
+void main()
+{
+    vec4 texel = texture(billboardTexture, texCoord);
+
+    // Same edge smoothing as sprite.frag
+    if (constants.params.x > 0.5)
+    {
+        float smoothing = max(fwidth(texel.a) * 0.5, 1e-4);
+        texel.a = smoothstep(0.5 - smoothing, 0.5 + smoothing, texel.a);
+    }
+
+    outColor = texel * color;
+}
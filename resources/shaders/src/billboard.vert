@@ -0,0 +1,32 @@
+#version 460
+
+// NB! Must match BillboardVertex in sprite.rs
+layout (location = 0) in vec3 vPosition;
+layout (location = 1) in vec2 vOffset;
+layout (location = 2) in vec2 vTexCoord;
+layout (location = 3) in vec4 vColor;
+
+layout (location = 0) out vec2 texCoord;
+layout (location = 1) out vec4 color;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// Quads offset in view space around their world position, so they always face the camera
+void main()
+{
+    texCoord = vTexCoord;
+    color = vColor;
+
+    // Offsets point y down like on screen, view space points it up
+    vec4 viewPosition = cameraUBO.view * vec4(vPosition, 1.0f);
+    viewPosition.xy += vec2(vOffset.x, -vOffset.y);
+    gl_Position = cameraUBO.projection * viewPosition;
+}
@@ -0,0 +1,68 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+layout (set = 0, binding = 0) uniform sampler2D sourceImage;
+
+// params: x - prefilter flag, y - threshold, z - knee
+// texelSize: xy - source texel size, zw - source size
+layout( push_constant ) uniform constants
+{
+    vec4 params;
+    vec4 extra;
+    vec4 texelSize;
+} PushConstants;
+
+
+vec3 prefilter(vec3 color)
+{
+    float threshold = PushConstants.params.y;
+    float knee = max(threshold * PushConstants.params.z, 1e-5f);
+
+    // Quadratic soft threshold
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - threshold + knee, 0.0f, 2.0f * knee);
+    soft = soft * soft / (4.0f * knee);
+
+    float contribution = max(soft, brightness - threshold) / max(brightness, 1e-5f);
+    return color * contribution;
+}
+
+
+vec3 sampleSource(vec2 offset)
+{
+    return texture(sourceImage, texCoord + offset * PushConstants.texelSize.xy).rgb;
+}
+
+
+// 13 tap downsample filter, weighted to avoid fireflies and pulsating bright pixels
+void main()
+{
+    vec3 a = sampleSource(vec2(-2.0f, -2.0f));
+    vec3 b = sampleSource(vec2( 0.0f, -2.0f));
+    vec3 c = sampleSource(vec2( 2.0f, -2.0f));
+    vec3 d = sampleSource(vec2(-2.0f,  0.0f));
+    vec3 e = sampleSource(vec2( 0.0f,  0.0f));
+    vec3 f = sampleSource(vec2( 2.0f,  0.0f));
+    vec3 g = sampleSource(vec2(-2.0f,  2.0f));
+    vec3 h = sampleSource(vec2( 0.0f,  2.0f));
+    vec3 i = sampleSource(vec2( 2.0f,  2.0f));
+    vec3 j = sampleSource(vec2(-1.0f, -1.0f));
+    vec3 k = sampleSource(vec2( 1.0f, -1.0f));
+    vec3 l = sampleSource(vec2(-1.0f,  1.0f));
+    vec3 m = sampleSource(vec2( 1.0f,  1.0f));
+
+    vec3 color = e * 0.125f;
+    color += (a + c + g + i) * 0.03125f;
+    color += (b + d + f + h) * 0.0625f;
+    color += (j + k + l + m) * 0.125f;
+
+    if (PushConstants.params.x > 0.0f)
+    {
+        color = prefilter(color);
+    }
+
+    outColor = vec4(color, 1.0f);
+}
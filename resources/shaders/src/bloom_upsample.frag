@@ -0,0 +1,38 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+// The smaller level being upsampled and the downsampled level of the target size
+layout (set = 0, binding = 0) uniform sampler2D sourceImage;
+layout (set = 0, binding = 1) uniform sampler2D secondaryImage;
+
+// params: x - filter radius in source texels
+// texelSize: xy - source texel size, zw - source size
+layout( push_constant ) uniform constants
+{
+    vec4 params;
+    vec4 extra;
+    vec4 texelSize;
+} PushConstants;
+
+
+// 3x3 tent filter upsample, accumulated on top of the matching downsample level
+void main()
+{
+    vec2 offset = PushConstants.texelSize.xy * PushConstants.params.x;
+
+    vec3 color = texture(sourceImage, texCoord).rgb * 4.0f;
+    color += texture(sourceImage, texCoord + vec2(-offset.x, 0.0f)).rgb * 2.0f;
+    color += texture(sourceImage, texCoord + vec2( offset.x, 0.0f)).rgb * 2.0f;
+    color += texture(sourceImage, texCoord + vec2(0.0f, -offset.y)).rgb * 2.0f;
+    color += texture(sourceImage, texCoord + vec2(0.0f,  offset.y)).rgb * 2.0f;
+    color += texture(sourceImage, texCoord + vec2(-offset.x, -offset.y)).rgb;
+    color += texture(sourceImage, texCoord + vec2( offset.x, -offset.y)).rgb;
+    color += texture(sourceImage, texCoord + vec2(-offset.x,  offset.y)).rgb;
+    color += texture(sourceImage, texCoord + vec2( offset.x,  offset.y)).rgb;
+    color /= 16.0f;
+
+    outColor = vec4(color + texture(secondaryImage, texCoord).rgb, 1.0f);
+}
@@ -0,0 +1,24 @@
+#version 460
+
+// NB! Must match DebugVertex in debug_draw.rs
+layout (location = 0) in vec3 vPosition;
+layout (location = 1) in vec4 vColor;
+
+layout (location = 0) out vec4 color;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// World space debug lines, drawn as is without any model transform
+void main()
+{
+    color = vColor;
+    gl_Position = cameraUBO.view_projection * vec4(vPosition, 1.0f);
+}
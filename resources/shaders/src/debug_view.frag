@@ -0,0 +1,114 @@
+#version 460
+
+layout (location = 2) in vec2 texCoord;
+layout (location = 3) flat in uint instanceIndex;
+layout (location = 4) in vec3 worldPosition;
+layout (location = 5) in vec3 worldNormal;
+
+layout (location = 0) out vec4 outColor;
+// Only stored while a pick is pending, NB! Must match the picking outputs of shader.frag
+layout (location = 1) out uint outObjectId;
+layout (location = 2) out uint outObjectDepth;
+
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// Base color slot of the material being drawn, NB! Must match TextureSlot::binding
+layout(set = 2, binding = 0) uniform sampler2D baseColorTexture;
+
+layout(push_constant) uniform DebugViewConstants
+{
+    // x - debug view mode, y - camera z near, z - camera z far
+    vec4 params;
+    // Wireframe color
+    vec4 color;
+} constants;
+
+// NB! Must match DebugViewMode::index
+const uint MODE_WIREFRAME = 1u;
+const uint MODE_NORMALS = 2u;
+const uint MODE_UVS = 3u;
+const uint MODE_INSTANCE_IDS = 4u;
+const uint MODE_OVERDRAW = 5u;
+const uint MODE_DEPTH = 6u;
+const uint MODE_MIP_LEVEL = 7u;
+
+const uint MIP_LEVEL_COLOR_COUNT = 6u;
+const vec3 MIP_LEVEL_COLORS[MIP_LEVEL_COLOR_COUNT] = vec3[](
+    vec3(0.0, 0.0, 1.0),
+    vec3(0.0, 0.5, 1.0),
+    vec3(0.0, 1.0, 0.0),
+    vec3(1.0, 1.0, 0.0),
+    vec3(1.0, 0.5, 0.0),
+    vec3(1.0, 0.0, 0.0)
+);
+
+
+// Well spread colors for consecutive indices
+vec3 hashColor(uint value)
+{
+    value ^= value >> 16;
+    value *= 0x7feb352du;
+    value ^= value >> 15;
+    value *= 0x846ca68bu;
+    value ^= value >> 16;
+
+    return vec3(value & 0xFFu, (value >> 8) & 0xFFu, (value >> 16) & 0xFFu) / 255.0;
+}
+
+// Level a fully mipmapped texture would be sampled from, whether or not this one has mips
+float mipLevel()
+{
+    vec2 texelCoord = texCoord * vec2(textureSize(baseColorTexture, 0));
+    vec2 dx = dFdx(texelCoord);
+    vec2 dy = dFdy(texelCoord);
+    float footprint = max(dot(dx, dx), dot(dy, dy));
+
+    return max(0.5 * log2(max(footprint, 1e-8)), 0.0);
+}
+
+void main()
+{
+    uint mode = uint(constants.params.x);
+    vec3 color = vec3(0.0);
+
+    vec3 forward = -vec3(cameraUBO.view[0][2], cameraUBO.view[1][2], cameraUBO.view[2][2]);
+    float depth = dot(worldPosition - cameraUBO.position.xyz, forward);
+    outObjectId = instanceIndex;
+    outObjectDepth = floatBitsToUint(depth);
+
+    // Written straight to the backbuffer, except for wireframes and overdraw which are blended
+    if (mode == MODE_WIREFRAME) {
+        outColor = constants.color;
+        return;
+    } else if (mode == MODE_OVERDRAW) {
+        // Every fragment adds one to the count, the heat map pass colors it
+        outColor = vec4(1.0);
+        return;
+    } else if (mode == MODE_NORMALS) {
+        color = normalize(worldNormal) * 0.5 + 0.5;
+    } else if (mode == MODE_UVS) {
+        color = vec3(fract(texCoord), 0.0);
+    } else if (mode == MODE_INSTANCE_IDS) {
+        color = hashColor(instanceIndex);
+    } else if (mode == MODE_DEPTH) {
+        float zNear = constants.params.y;
+        float zFar = constants.params.z;
+        color = vec3(clamp((depth - zNear) / (zFar - zNear), 0.0, 1.0));
+    } else if (mode == MODE_MIP_LEVEL) {
+        float level = min(mipLevel(), float(MIP_LEVEL_COLOR_COUNT - 1u));
+        uint lower = uint(floor(level));
+        uint upper = min(lower + 1u, MIP_LEVEL_COLOR_COUNT - 1u);
+        color = mix(MIP_LEVEL_COLORS[lower], MIP_LEVEL_COLORS[upper], fract(level));
+    }
+
+    outColor = vec4(color, 1.0);
+}
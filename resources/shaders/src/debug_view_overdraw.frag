@@ -0,0 +1,36 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+// Fragment count of every pixel
+layout (set = 0, binding = 0) uniform sampler2D sourceImage;
+
+// x - count shown as the hottest color
+layout( push_constant ) uniform constants
+{
+    vec4 params;
+    vec4 extra;
+    vec4 texelSize;
+} PushConstants;
+
+const uint HEAT_COLOR_COUNT = 5u;
+const vec3 HEAT_COLORS[HEAT_COLOR_COUNT] = vec3[](
+    vec3(0.0, 0.0, 0.0),
+    vec3(0.0, 0.0, 1.0),
+    vec3(0.0, 1.0, 0.0),
+    vec3(1.0, 1.0, 0.0),
+    vec3(1.0, 0.0, 0.0)
+);
+
+
+void main()
+{
+    float count = texture(sourceImage, texCoord).r;
+    float heat = clamp(count / max(PushConstants.params.x, 1.0), 0.0, 1.0) * float(HEAT_COLOR_COUNT - 1u);
+
+    uint lower = uint(floor(heat));
+    uint upper = min(lower + 1u, HEAT_COLOR_COUNT - 1u);
+    outColor = vec4(mix(HEAT_COLORS[lower], HEAT_COLORS[upper], fract(heat)), 1.0);
+}
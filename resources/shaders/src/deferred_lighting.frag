@@ -0,0 +1,306 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout(location = 0) out vec4 outColor;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+layout(set = 0, binding = 1) uniform SceneUBO
+{
+    vec4 ambientColor;
+    // x - ambient occlusion map is valid this frame
+    vec4 ambientOcclusion;
+    // x - environment map is bound, y - environment intensity, z - highest prefiltered environment mip
+    vec4 environment;
+} sceneUBO;
+
+const float LIGHT_DIRECTIONAL = 0.0;
+const float LIGHT_POINT = 1.0;
+const float LIGHT_SPOT = 2.0;
+
+struct LightSSBO
+{
+    vec4 positionRange;
+    vec4 directionType;
+    vec4 colorIntensity;
+    vec4 cone;
+};
+
+layout(std430, set = 0, binding = 2) readonly buffer LightBuffer
+{
+    uvec4 count;
+    LightSSBO lights[];
+} lightBuffer;
+
+const uint MAX_SHADOW_MAPS = 8u;
+
+layout(set = 0, binding = 3) uniform ShadowUBO
+{
+    mat4 viewProjections[MAX_SHADOW_MAPS];
+    vec4 cascadeSplits;
+    // xyz - camera forward, w - cascade count
+    vec4 cameraForward;
+    // x - normal offset, y - texel size
+    vec4 params;
+} shadowUBO;
+
+layout(set = 0, binding = 4) uniform sampler2DArrayShadow shadowMap;
+layout(set = 0, binding = 5) uniform sampler2D ambientOcclusionMap;
+layout(set = 0, binding = 6) uniform samplerCube environmentMap;
+layout(set = 0, binding = 7) uniform samplerCube irradianceMap;
+layout(set = 0, binding = 8) uniform samplerCube prefilteredMap;
+layout(set = 0, binding = 9) uniform sampler2D brdfLut;
+
+layout(std430, set = 1, binding = 2) readonly buffer ClusterBuffer
+{
+    uvec4 gridSize;
+    // x - z near, y - depth slice scale, z - 1 / screen width, w - 1 / screen height
+    vec4 params;
+    vec4 cameraForward;
+    // x - offset into the light index list, y - light count
+    uvec2 clusters[];
+} clusterBuffer;
+
+layout(std430, set = 1, binding = 3) readonly buffer ClusterLightIndexBuffer
+{
+    uint lightIndices[];
+} clusterLightIndexBuffer;
+
+// NB! Must match the binding constants in deferred.rs
+layout(set = 2, binding = 0) uniform sampler2D gbufferAlbedo;
+layout(set = 2, binding = 1) uniform sampler2D gbufferNormal;
+layout(set = 2, binding = 2) uniform sampler2D gbufferMaterial;
+layout(set = 2, binding = 3) uniform sampler2D gbufferEmissive;
+layout(set = 2, binding = 4) uniform sampler2D gbufferDepth;
+
+layout(push_constant) uniform DeferredConstants
+{
+    mat4 inverseViewProjection;
+    // x - G-buffer debug channel, y - camera z near, z - camera z far
+    vec4 params;
+} constants;
+
+const float PI = 3.14159265359;
+
+// Reconstructed from the depth buffer in main, the lighting functions below are shared with pbr.frag
+vec3 worldPosition;
+
+float distributionGGX(float NdotH, float roughness)
+{
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+float geometrySchlickGGX(float NdotX, float roughness)
+{
+    float r = roughness + 1.0;
+    float k = (r * r) / 8.0;
+    return NdotX / (NdotX * (1.0 - k) + k);
+}
+
+float geometrySmith(float NdotV, float NdotL, float roughness)
+{
+    return geometrySchlickGGX(NdotV, roughness) * geometrySchlickGGX(NdotL, roughness);
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness)
+{
+    return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+// Diffuse irradiance plus split-sum specular from the precomputed environment maps.
+// https://cdn2.unrealengine.com/Resources/files/2013SiggraphPresentationsNotes-26915738.pdf
+vec3 imageBasedLighting(vec3 albedo, float metallic, float roughness, vec3 F0, vec3 N, vec3 V)
+{
+    float NdotV = max(dot(N, V), 0.0);
+    vec3 F = fresnelSchlickRoughness(NdotV, F0, roughness);
+    vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
+
+    vec3 diffuse = texture(irradianceMap, N).rgb * albedo;
+
+    vec3 R = reflect(-V, N);
+    vec3 prefiltered = textureLod(prefilteredMap, R, roughness * sceneUBO.environment.z).rgb;
+    vec2 scaleBias = texture(brdfLut, vec2(NdotV, roughness)).rg;
+    vec3 specular = prefiltered * (F * scaleBias.x + scaleBias.y);
+
+    return (kD * diffuse + specular) * sceneUBO.environment.y;
+}
+
+uvec2 fragmentCluster()
+{
+    uvec3 gridSize = clusterBuffer.gridSize.xyz;
+    float zNear = clusterBuffer.params.x;
+
+    float viewDepth = max(dot(worldPosition - cameraUBO.position.xyz, clusterBuffer.cameraForward.xyz), zNear);
+    uint slice = min(uint(log(viewDepth / zNear) * clusterBuffer.params.y), gridSize.z - 1u);
+    uvec2 tile = min(uvec2(gl_FragCoord.xy * clusterBuffer.params.zw * vec2(gridSize.xy)), gridSize.xy - 1u);
+
+    return clusterBuffer.clusters[tile.x + tile.y * gridSize.x + slice * gridSize.x * gridSize.y];
+}
+
+float sampleShadowMap(uint layer, vec3 N)
+{
+    vec3 offsetPosition = worldPosition + N * shadowUBO.params.x;
+    vec4 lightSpace = shadowUBO.viewProjections[layer] * vec4(offsetPosition, 1.0);
+    vec3 ndc = lightSpace.xyz / lightSpace.w;
+    vec2 uv = ndc.xy * 0.5 + 0.5;
+
+    if (ndc.z > 1.0) {
+        return 1.0;
+    }
+
+    // 3x3 PCF on top of the hardware 2x2 comparison filtering
+    float texelSize = shadowUBO.params.y;
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * texelSize;
+            lit += texture(shadowMap, vec4(uv + offset, float(layer), ndc.z));
+        }
+    }
+
+    return lit / 9.0;
+}
+
+float shadowFactor(LightSSBO light, vec3 N)
+{
+    if (light.cone.z < 0.0) {
+        return 1.0;
+    }
+
+    uint layer = uint(light.cone.z);
+
+    if (light.directionType.w == LIGHT_DIRECTIONAL) {
+        float viewDepth = dot(worldPosition - cameraUBO.position.xyz, shadowUBO.cameraForward.xyz);
+        uint cascadeCount = uint(shadowUBO.cameraForward.w);
+
+        uint cascade = 0u;
+        while (cascade < cascadeCount && viewDepth > shadowUBO.cascadeSplits[cascade]) {
+            cascade++;
+        }
+
+        if (cascade == cascadeCount) {
+            return 1.0;
+        }
+
+        layer += cascade;
+    }
+
+    return sampleShadowMap(layer, N);
+}
+
+float rangeAttenuation(float distance, float range)
+{
+    float ratio = distance / range;
+    float falloff = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+    return falloff * falloff / (distance * distance + 1.0);
+}
+
+vec3 cookTorrance(LightSSBO light, vec3 albedo, float metallic, float roughness, vec3 F0, vec3 N, vec3 V)
+{
+    vec3 L;
+    float attenuation = 1.0;
+
+    if (light.directionType.w == LIGHT_DIRECTIONAL) {
+        L = normalize(-light.directionType.xyz);
+    } else {
+        vec3 toLight = light.positionRange.xyz - worldPosition;
+        float distance = length(toLight);
+        L = toLight / max(distance, 0.0001);
+        attenuation = rangeAttenuation(distance, light.positionRange.w);
+
+        if (light.directionType.w == LIGHT_SPOT) {
+            float cosTheta = dot(-L, normalize(light.directionType.xyz));
+            attenuation *= smoothstep(light.cone.y, light.cone.x, cosTheta);
+        }
+    }
+
+    vec3 H = normalize(V + L);
+    float NdotL = max(dot(N, L), 0.0);
+    float NdotV = max(dot(N, V), 0.0001);
+    float NdotH = max(dot(N, H), 0.0);
+    float HdotV = max(dot(H, V), 0.0);
+
+    float D = distributionGGX(NdotH, roughness);
+    float G = geometrySmith(NdotV, NdotL, roughness);
+    vec3 F = fresnelSchlick(HdotV, F0);
+
+    vec3 specular = (D * G * F) / max(4.0 * NdotV * NdotL, 0.0001);
+    vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
+
+    vec3 radiance = light.colorIntensity.rgb * light.colorIntensity.a * attenuation;
+    return (kD * albedo / PI + specular) * radiance * NdotL;
+}
+
+void main() {
+    float depth = texture(gbufferDepth, texCoord).r;
+
+    // Nothing was drawn here, leave the skybox or background clear color
+    if (depth == cameraUBO.depth.x) {
+        discard;
+    }
+
+    vec4 worldPoint = constants.inverseViewProjection * vec4(texCoord * 2.0 - 1.0, depth, 1.0);
+    worldPosition = worldPoint.xyz / worldPoint.w;
+
+    vec4 albedoOcclusion = texture(gbufferAlbedo, texCoord);
+    vec3 albedo = albedoOcclusion.rgb;
+    float occlusion = albedoOcclusion.a;
+    if (sceneUBO.ambientOcclusion.x > 0.5) {
+        occlusion *= texture(ambientOcclusionMap, texCoord).r;
+    }
+
+    vec4 normalShadows = texture(gbufferNormal, texCoord);
+    vec3 N = normalize(normalShadows.xyz);
+    bool receiveShadows = normalShadows.w > 0.5;
+
+    vec2 metallicRoughness = texture(gbufferMaterial, texCoord).rg;
+    float metallic = metallicRoughness.x;
+    float roughness = metallicRoughness.y;
+
+    vec3 V = normalize(cameraUBO.position.xyz - worldPosition);
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+
+    vec3 color = vec3(0.0);
+
+    // Directional lights come first in the light buffer and reach every fragment
+    uint directionalLightCount = lightBuffer.count.y;
+    for (uint i = 0u; i < directionalLightCount; i++) {
+        LightSSBO light = lightBuffer.lights[i];
+        float shadow = receiveShadows ? shadowFactor(light, N) : 1.0;
+        color += shadow * cookTorrance(light, albedo, metallic, roughness, F0, N, V);
+    }
+
+    uvec2 cluster = fragmentCluster();
+    for (uint i = 0u; i < cluster.y; i++) {
+        LightSSBO light = lightBuffer.lights[clusterLightIndexBuffer.lightIndices[cluster.x + i]];
+        float shadow = receiveShadows ? shadowFactor(light, N) : 1.0;
+        color += shadow * cookTorrance(light, albedo, metallic, roughness, F0, N, V);
+    }
+
+    // The flat ambient term only stands in for the environment while none is set
+    if (sceneUBO.environment.x > 0.5) {
+        color += imageBasedLighting(albedo, metallic, roughness, F0, N, V) * occlusion;
+    } else {
+        color += albedo * sceneUBO.ambientColor.rgb * sceneUBO.ambientColor.a * occlusion;
+    }
+    color += texture(gbufferEmissive, texCoord).rgb;
+
+    outColor = vec4(color, 1.0);
+}
@@ -0,0 +1,12 @@
+#version 460
+
+layout (location = 5) in vec3 worldNormal;
+
+// NB! Must match SSAO_NORMAL_FORMAT
+layout (location = 0) out vec4 outNormal;
+
+// Geometric normals only, ambient occlusion doesn't need the detail of normal maps
+void main()
+{
+    outNormal = vec4(normalize(worldNormal), 1.0);
+}
@@ -0,0 +1,10 @@
+#version 460
+
+layout (location = 0) out vec2 texCoord;
+
+// A single triangle covering the whole screen, generated from the vertex index
+void main()
+{
+    texCoord = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(texCoord * 2.0f - 1.0f, 0.0f, 1.0f);
+}
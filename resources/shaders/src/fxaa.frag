@@ -0,0 +1,70 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+layout (set = 0, binding = 0) uniform sampler2D sourceImage;
+
+// texelSize: xy - source texel size, zw - source size
+layout( push_constant ) uniform constants
+{
+    vec4 params;
+    vec4 extra;
+    vec4 texelSize;
+} PushConstants;
+
+const float FXAA_REDUCE_MIN = 1.0 / 128.0;
+const float FXAA_REDUCE_MUL = 1.0 / 8.0;
+const float FXAA_SPAN_MAX = 8.0;
+
+
+// The source holds linear colors, the square root approximates perceptual luma
+float luma(vec3 color)
+{
+    return dot(sqrt(color), vec3(0.299f, 0.587f, 0.114f));
+}
+
+
+void main()
+{
+    vec2 texel = PushConstants.texelSize.xy;
+
+    vec3 rgbM = texture(sourceImage, texCoord).rgb;
+    float lumaNW = luma(texture(sourceImage, texCoord + vec2(-1.0f, -1.0f) * texel).rgb);
+    float lumaNE = luma(texture(sourceImage, texCoord + vec2( 1.0f, -1.0f) * texel).rgb);
+    float lumaSW = luma(texture(sourceImage, texCoord + vec2(-1.0f,  1.0f) * texel).rgb);
+    float lumaSE = luma(texture(sourceImage, texCoord + vec2( 1.0f,  1.0f) * texel).rgb);
+    float lumaM = luma(rgbM);
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    // Blur along the edge, perpendicular to the luma gradient
+    vec2 direction = vec2(
+        -((lumaNW + lumaNE) - (lumaSW + lumaSE)),
+        (lumaNW + lumaSW) - (lumaNE + lumaSE)
+    );
+
+    float directionReduce = max(
+        (lumaNW + lumaNE + lumaSW + lumaSE) * 0.25f * FXAA_REDUCE_MUL,
+        FXAA_REDUCE_MIN
+    );
+    float inverseMinDirection = 1.0f / (min(abs(direction.x), abs(direction.y)) + directionReduce);
+    direction = clamp(direction * inverseMinDirection, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * texel;
+
+    vec3 rgbA = 0.5f * (
+        texture(sourceImage, texCoord + direction * (1.0f / 3.0f - 0.5f)).rgb +
+        texture(sourceImage, texCoord + direction * (2.0f / 3.0f - 0.5f)).rgb
+    );
+    vec3 rgbB = rgbA * 0.5f + 0.25f * (
+        texture(sourceImage, texCoord + direction * -0.5f).rgb +
+        texture(sourceImage, texCoord + direction * 0.5f).rgb
+    );
+
+    // Fall back to the narrower blur if the wider one picked up colors from outside the local range
+    float lumaB = luma(rgbB);
+    vec3 color = (lumaB < lumaMin || lumaB > lumaMax) ? rgbA : rgbB;
+
+    outColor = vec4(color, 1.0f);
+}
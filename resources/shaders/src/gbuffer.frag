@@ -0,0 +1,101 @@
+#version 460
+#extension GL_GOOGLE_include_directive : require
+
+layout(location = 0) in vec3 inColor;
+layout (location = 1) in vec3 dbgColor;
+layout (location = 2) in vec2 texCoord;
+layout (location = 3) flat in uint instanceIndex;
+layout (location = 4) in vec3 worldPosition;
+layout (location = 5) in vec3 worldNormal;
+
+// NB! Must match GBUFFER_COLOR_FORMATS
+// rgb - albedo, a - ambient occlusion
+layout(location = 0) out vec4 outAlbedo;
+// xyz - world space normal, w - receives shadows
+layout(location = 1) out vec4 outNormal;
+// r - metallic, g - roughness
+layout(location = 2) out vec4 outMaterial;
+layout(location = 3) out vec4 outEmissive;
+// Picking targets, only bound while a pick is pending. NB! Must match OBJECT_ID_FORMAT and OBJECT_DEPTH_FORMAT
+// The view depth is stored as its bits, so multisampled targets resolve it from a single sample like the id
+layout(location = 4) out uint outObjectId;
+layout(location = 5) out uint outObjectDepth;
+
+const uint META_FLAG_RECEIVE_SHADOWS = 1u;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+struct EntityMetaSSBO
+{
+    vec4 tint;
+    vec4 custom;
+    float emissive;
+    uint id;
+    uint flags;
+};
+
+layout(std140, set = 1, binding = 1) readonly buffer EntityMetaBuffer
+{
+    EntityMetaSSBO entityMetas[];
+} entityMetaBuffer;
+
+layout(set = 2, binding = 0) uniform sampler2D baseColorMap;
+layout(set = 2, binding = 1) uniform sampler2D metallicRoughnessMap;
+layout(set = 2, binding = 2) uniform sampler2D normalMap;
+layout(set = 2, binding = 3) uniform sampler2D occlusionMap;
+layout(set = 2, binding = 4) uniform sampler2D emissiveMap;
+
+layout(set = 2, binding = 5) uniform MaterialUBO
+{
+    vec4 baseColorFactor;
+    // xyz - emissive factor, w - alpha cutoff, 0 unless the material is a cutout
+    vec4 emissiveFactor;
+    // x - metallic, y - roughness, z - normal scale, w - occlusion strength
+    vec4 params;
+} materialUBO;
+
+const float MIN_ROUGHNESS = 0.04;
+
+#include "cotangent_frame.glsl"
+
+void main() {
+    EntityMetaSSBO meta = entityMetaBuffer.entityMetas[instanceIndex];
+
+    vec4 baseColor = texture(baseColorMap, texCoord) * materialUBO.baseColorFactor * meta.tint;
+    if (baseColor.a < materialUBO.emissiveFactor.w) {
+        discard;
+    }
+
+    vec3 albedo = baseColor.rgb;
+
+    vec4 metallicRoughness = texture(metallicRoughnessMap, texCoord);
+    float metallic = clamp(metallicRoughness.b * materialUBO.params.x, 0.0, 1.0);
+    float roughness = clamp(metallicRoughness.g * materialUBO.params.y, MIN_ROUGHNESS, 1.0);
+
+    vec3 N = normalize(worldNormal);
+    vec3 tangentNormal = texture(normalMap, texCoord).xyz * 2.0 - 1.0;
+    tangentNormal.xy *= materialUBO.params.z;
+    N = normalize(cotangentFrame(N, worldPosition, texCoord) * tangentNormal);
+
+    float occlusion = mix(1.0, texture(occlusionMap, texCoord).r, materialUBO.params.w);
+    bool receiveShadows = (meta.flags & META_FLAG_RECEIVE_SHADOWS) != 0u;
+
+    vec3 emissive = texture(emissiveMap, texCoord).rgb * materialUBO.emissiveFactor.rgb;
+
+    outAlbedo = vec4(albedo, occlusion);
+    outNormal = vec4(N, receiveShadows ? 1.0 : 0.0);
+    outMaterial = vec4(metallic, roughness, 0.0, 0.0);
+    outEmissive = vec4(emissive + albedo * meta.emissive, 1.0);
+
+    vec3 forward = -vec3(cameraUBO.view[0][2], cameraUBO.view[1][2], cameraUBO.view[2][2]);
+    outObjectId = instanceIndex;
+    outObjectDepth = floatBitsToUint(dot(worldPosition - cameraUBO.position.xyz, forward));
+}
@@ -0,0 +1,73 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// NB! Must match the binding constants in deferred.rs
+layout(set = 2, binding = 0) uniform sampler2D gbufferAlbedo;
+layout(set = 2, binding = 1) uniform sampler2D gbufferNormal;
+layout(set = 2, binding = 2) uniform sampler2D gbufferMaterial;
+layout(set = 2, binding = 3) uniform sampler2D gbufferEmissive;
+layout(set = 2, binding = 4) uniform sampler2D gbufferDepth;
+
+layout(push_constant) uniform DeferredConstants
+{
+    mat4 inverseViewProjection;
+    // x - G-buffer debug channel, y - camera z near, z - camera z far
+    vec4 params;
+} constants;
+
+// NB! Must match GBufferChannel::index
+const uint CHANNEL_ALBEDO = 0u;
+const uint CHANNEL_NORMAL = 1u;
+const uint CHANNEL_METALLIC = 2u;
+const uint CHANNEL_ROUGHNESS = 3u;
+const uint CHANNEL_OCCLUSION = 4u;
+const uint CHANNEL_EMISSIVE = 5u;
+const uint CHANNEL_DEPTH = 6u;
+
+// Distance along the camera's forward axis, reconstructed so it holds for every depth mode
+float viewDepth(float depth)
+{
+    vec4 worldPoint = constants.inverseViewProjection * vec4(texCoord * 2.0 - 1.0, depth, 1.0);
+    vec3 forward = -vec3(cameraUBO.view[0][2], cameraUBO.view[1][2], cameraUBO.view[2][2]);
+    return dot(worldPoint.xyz / worldPoint.w - cameraUBO.position.xyz, forward);
+}
+
+void main()
+{
+    uint channel = uint(constants.params.x);
+    vec3 color = vec3(0.0);
+
+    // Written straight to the backbuffer, values are shown as stored except for normals and depth
+    if (channel == CHANNEL_ALBEDO) {
+        color = texture(gbufferAlbedo, texCoord).rgb;
+    } else if (channel == CHANNEL_NORMAL) {
+        color = texture(gbufferNormal, texCoord).xyz * 0.5 + 0.5;
+    } else if (channel == CHANNEL_METALLIC) {
+        color = vec3(texture(gbufferMaterial, texCoord).r);
+    } else if (channel == CHANNEL_ROUGHNESS) {
+        color = vec3(texture(gbufferMaterial, texCoord).g);
+    } else if (channel == CHANNEL_OCCLUSION) {
+        color = vec3(texture(gbufferAlbedo, texCoord).a);
+    } else if (channel == CHANNEL_EMISSIVE) {
+        color = texture(gbufferEmissive, texCoord).rgb;
+    } else if (channel == CHANNEL_DEPTH) {
+        float depth = texture(gbufferDepth, texCoord).r;
+        float zFar = constants.params.z;
+        color = depth == cameraUBO.depth.x ? vec3(0.0) : vec3(clamp(1.0 - viewDepth(depth) / zFar, 0.0, 1.0));
+    }
+
+    outColor = vec4(color, 1.0);
+}
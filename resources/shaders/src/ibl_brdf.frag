@@ -0,0 +1,72 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outScaleBias;
+
+layout(push_constant) uniform IblConstants
+{
+    // x - cube face, y - roughness, z - sample count
+    vec4 params;
+} constants;
+
+float radicalInverse(uint bits)
+{
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+vec3 importanceSampleGGX(vec2 xi, float roughness)
+{
+    float a = roughness * roughness;
+
+    float phi = 2.0 * 3.14159265359 * xi.x;
+    float cosTheta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sinTheta = sqrt(1.0 - cosTheta * cosTheta);
+
+    return vec3(cos(phi) * sinTheta, sin(phi) * sinTheta, cosTheta);
+}
+
+// Image based lighting uses a smaller k than analytic lights
+float geometrySchlickGGX(float NdotX, float roughness)
+{
+    float k = (roughness * roughness) / 2.0;
+    return NdotX / (NdotX * (1.0 - k) + k);
+}
+
+// Scale and bias applied to F0 by the split-sum approximation, for NdotV along x and roughness along y
+void main()
+{
+    float NdotV = max(texCoord.x, 0.0001);
+    float roughness = texCoord.y;
+    uint sampleCount = uint(constants.params.z);
+
+    vec3 V = vec3(sqrt(1.0 - NdotV * NdotV), 0.0, NdotV);
+
+    float scale = 0.0;
+    float bias = 0.0;
+    for (uint i = 0u; i < sampleCount; i++) {
+        vec2 xi = vec2(float(i) / float(sampleCount), radicalInverse(i));
+        vec3 H = importanceSampleGGX(xi, roughness);
+        vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+        float NdotL = max(L.z, 0.0);
+        float NdotH = max(H.z, 0.0);
+        float VdotH = max(dot(V, H), 0.0);
+
+        if (NdotL > 0.0) {
+            float G = geometrySchlickGGX(NdotV, roughness) * geometrySchlickGGX(NdotL, roughness);
+            float visibility = (G * VdotH) / max(NdotH * NdotV, 0.0001);
+            float Fc = pow(1.0 - VdotH, 5.0);
+
+            scale += (1.0 - Fc) * visibility;
+            bias += Fc * visibility;
+        }
+    }
+
+    outScaleBias = vec4(scale / float(sampleCount), bias / float(sampleCount), 0.0, 1.0);
+}
@@ -0,0 +1,54 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+layout(set = 0, binding = 0) uniform samplerCube environmentMap;
+
+layout(push_constant) uniform IblConstants
+{
+    // x - cube face, y - roughness, z - sample count
+    vec4 params;
+} constants;
+
+const float PI = 3.14159265359;
+const float SAMPLE_DELTA = 0.025;
+
+// NB! Must match cubemap_direction in texture.rs
+vec3 cubeDirection(uint face, vec2 uv)
+{
+    uv = uv * 2.0 - 1.0;
+
+    switch (face) {
+        case 0u: return vec3(1.0, -uv.y, -uv.x);
+        case 1u: return vec3(-1.0, -uv.y, uv.x);
+        case 2u: return vec3(uv.x, 1.0, uv.y);
+        case 3u: return vec3(uv.x, -1.0, -uv.y);
+        case 4u: return vec3(uv.x, -uv.y, 1.0);
+        default: return vec3(-uv.x, -uv.y, -1.0);
+    }
+}
+
+// Cosine weighted convolution of the hemisphere around N, stored divided by PI so shading only multiplies by albedo
+void main()
+{
+    vec3 N = normalize(cubeDirection(uint(constants.params.x), texCoord));
+    vec3 up = abs(N.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(0.0, 0.0, 1.0);
+    vec3 right = normalize(cross(up, N));
+    up = cross(N, right);
+
+    vec3 irradiance = vec3(0.0);
+    float sampleCount = 0.0;
+    for (float phi = 0.0; phi < 2.0 * PI; phi += SAMPLE_DELTA) {
+        for (float theta = 0.0; theta < 0.5 * PI; theta += SAMPLE_DELTA) {
+            vec3 tangentSample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 direction = tangentSample.x * right + tangentSample.y * up + tangentSample.z * N;
+
+            irradiance += texture(environmentMap, direction).rgb * cos(theta) * sin(theta);
+            sampleCount += 1.0;
+        }
+    }
+
+    outColor = vec4(PI * irradiance / sampleCount, 1.0);
+}
@@ -0,0 +1,80 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+layout(set = 0, binding = 0) uniform samplerCube environmentMap;
+
+layout(push_constant) uniform IblConstants
+{
+    // x - cube face, y - roughness, z - sample count
+    vec4 params;
+} constants;
+
+// NB! Must match cubemap_direction in texture.rs
+vec3 cubeDirection(uint face, vec2 uv)
+{
+    uv = uv * 2.0 - 1.0;
+
+    switch (face) {
+        case 0u: return vec3(1.0, -uv.y, -uv.x);
+        case 1u: return vec3(-1.0, -uv.y, uv.x);
+        case 2u: return vec3(uv.x, 1.0, uv.y);
+        case 3u: return vec3(uv.x, -1.0, -uv.y);
+        case 4u: return vec3(uv.x, -uv.y, 1.0);
+        default: return vec3(-uv.x, -uv.y, -1.0);
+    }
+}
+
+float radicalInverse(uint bits)
+{
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+vec3 importanceSampleGGX(vec2 xi, vec3 N, float roughness)
+{
+    float a = roughness * roughness;
+
+    float phi = 2.0 * 3.14159265359 * xi.x;
+    float cosTheta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sinTheta = sqrt(1.0 - cosTheta * cosTheta);
+    vec3 H = vec3(cos(phi) * sinTheta, sin(phi) * sinTheta, cosTheta);
+
+    vec3 up = abs(N.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, N));
+    vec3 bitangent = cross(N, tangent);
+
+    return normalize(tangent * H.x + bitangent * H.y + N * H.z);
+}
+
+// GGX filtered environment for the roughness of this mip, assuming the view direction equals the normal.
+// https://cdn2.unrealengine.com/Resources/files/2013SiggraphPresentationsNotes-26915738.pdf
+void main()
+{
+    vec3 N = normalize(cubeDirection(uint(constants.params.x), texCoord));
+    vec3 V = N;
+    float roughness = constants.params.y;
+    uint sampleCount = uint(constants.params.z);
+
+    vec3 prefiltered = vec3(0.0);
+    float totalWeight = 0.0;
+    for (uint i = 0u; i < sampleCount; i++) {
+        vec2 xi = vec2(float(i) / float(sampleCount), radicalInverse(i));
+        vec3 H = importanceSampleGGX(xi, N, roughness);
+        vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+        float NdotL = dot(N, L);
+        if (NdotL > 0.0) {
+            prefiltered += texture(environmentMap, L).rgb * NdotL;
+            totalWeight += NdotL;
+        }
+    }
+
+    outColor = vec4(prefiltered / max(totalWeight, 0.0001), 1.0);
+}
@@ -0,0 +1,74 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+// Stencil aspect of the selection image
+layout(set = 0, binding = 0) uniform usampler2D selectionStencil;
+
+layout(push_constant) uniform SelectionOutlineConstants
+{
+    vec4 selectedColor;
+    vec4 hoveredColor;
+    // x - thickness in pixels
+    vec4 params;
+} constants;
+
+// NB! Must match SELECTED_STENCIL and HOVERED_STENCIL in selection.rs
+const uint SELECTED = 1u;
+const uint HOVERED = 2u;
+
+// Keeps the search bounded, thicker outlines are clamped
+const int MAX_RADIUS = 8;
+
+// Outlines the pixels around highlighted renderables, by the distance to the nearest pixel marked in the stencil.
+// Selected outlines are drawn over hovered ones, and neither covers the renderables themselves.
+void main()
+{
+    ivec2 size = textureSize(selectionStencil, 0);
+    ivec2 center = ivec2(texCoord * vec2(size));
+    uint centerValue = texelFetch(selectionStencil, center, 0).r;
+
+    float thickness = clamp(constants.params.x, 0.0, float(MAX_RADIUS));
+    int radius = int(ceil(thickness));
+
+    float selectedDistance = float(MAX_RADIUS + 1);
+    float hoveredDistance = float(MAX_RADIUS + 1);
+
+    for (int y = -radius; y <= radius; ++y)
+    {
+        for (int x = -radius; x <= radius; ++x)
+        {
+            ivec2 neighbor = clamp(center + ivec2(x, y), ivec2(0), size - 1);
+            uint value = texelFetch(selectionStencil, neighbor, 0).r;
+            float distance = length(vec2(x, y));
+
+            if (value == SELECTED)
+            {
+                selectedDistance = min(selectedDistance, distance);
+            }
+            else if (value == HOVERED)
+            {
+                hoveredDistance = min(hoveredDistance, distance);
+            }
+        }
+    }
+
+    // Fades out over the last pixel, so outlines keep a soft edge
+    float selectedCoverage = centerValue == SELECTED ? 0.0 : clamp(thickness + 1.0 - selectedDistance, 0.0, 1.0);
+    float hoveredCoverage = centerValue != 0u ? 0.0 : clamp(thickness + 1.0 - hoveredDistance, 0.0, 1.0);
+
+    if (selectedCoverage > 0.0)
+    {
+        outColor = vec4(constants.selectedColor.rgb, constants.selectedColor.a * selectedCoverage);
+    }
+    else if (hoveredCoverage > 0.0)
+    {
+        outColor = vec4(constants.hoveredColor.rgb, constants.hoveredColor.a * hoveredCoverage);
+    }
+    else
+    {
+        discard;
+    }
+}
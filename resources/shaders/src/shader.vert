@@ -8,6 +8,9 @@ layout (location = 3) in vec2 vTexCoord;
 layout (location = 0) out vec3 outColor;
 layout (location = 1) out vec3 dbgColor;
 layout (location = 2) out vec2 texCoord;
+layout (location = 3) flat out uint instanceIndex;
+layout (location = 4) out vec3 worldPosition;
+layout (location = 5) out vec3 worldNormal;
 
 
 layout(set = 0, binding = 0) uniform CameraBuffer
@@ -15,6 +18,9 @@ layout(set = 0, binding = 0) uniform CameraBuffer
     mat4 view;
     mat4 projection;
     mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
 } cameraUBO;
 
 
@@ -31,7 +37,11 @@ layout(std140, set = 1, binding = 0) readonly buffer EntityBuffer
 
 struct EntityMetaSSBO
 {
-    vec4 color;
+    vec4 tint;
+    vec4 custom;
+    float emissive;
+    uint id;
+    uint flags;
 };
 
 layout(std140, set = 1, binding = 1) readonly buffer EntityMetaBuffer
@@ -49,10 +59,14 @@ layout(std140, set = 1, binding = 1) readonly buffer EntityMetaBuffer
 void main()
 {
     mat4 modelMatrix = entityBuffer.entities[gl_BaseInstance].model;
-    mat4 transformMatrix = (cameraUBO.view_projection * modelMatrix);
-    gl_Position = transformMatrix * vec4(vPosition, 1.0f);
+    vec4 worldPosition4 = modelMatrix * vec4(vPosition, 1.0f);
+    gl_Position = cameraUBO.view_projection * worldPosition4;
 
-    dbgColor = entityMetaBuffer.entityMetas[gl_BaseInstance].color.xyz;
+    worldPosition = worldPosition4.xyz;
+    worldNormal = normalize(transpose(inverse(mat3(modelMatrix))) * vNormal);
+
+    dbgColor = entityMetaBuffer.entityMetas[gl_BaseInstance].tint.xyz;
     outColor = vColor;
     texCoord = vTexCoord;
+    instanceIndex = uint(gl_BaseInstance);
 }
@@ -0,0 +1,30 @@
+#version 460
+
+layout (location = 0) in vec3 vPosition;
+layout (location = 1) in vec3 vNormal;
+layout (location = 2) in vec3 vColor;
+layout (location = 3) in vec2 vTexCoord;
+
+
+struct EntitySSBO
+{
+    mat4 model;
+};
+
+layout(std140, set = 1, binding = 0) readonly buffer EntityBuffer
+{
+    EntitySSBO entities[];
+} entityBuffer;
+
+
+layout( push_constant ) uniform constants
+{
+    mat4 lightViewProjection;
+} PushConstants;
+
+
+void main()
+{
+    mat4 modelMatrix = entityBuffer.entities[gl_BaseInstance].model;
+    gl_Position = PushConstants.lightViewProjection * modelMatrix * vec4(vPosition, 1.0f);
+}
@@ -0,0 +1,40 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+// Picking targets of the forward pass, only bound while a pick is pending
+layout (location = 1) out uint outObjectId;
+layout (location = 2) out uint outObjectDepth;
+
+// NB! Must match NO_OBJECT_ID
+const uint NO_OBJECT_ID = 0xFFFFFFFFu;
+
+layout(set = 0, binding = 1) uniform SceneUBO
+{
+    vec4 ambientColor;
+    // x - ambient occlusion map is valid this frame
+    vec4 ambientOcclusion;
+    // x - environment map is bound, y - environment intensity, z - highest prefiltered environment mip
+    vec4 environment;
+} sceneUBO;
+
+layout(set = 0, binding = 6) uniform samplerCube environmentMap;
+
+layout(push_constant) uniform SkyboxConstants
+{
+    // Inverse view projection without the camera translation, maps screen positions to view directions
+    mat4 inverseViewProjection;
+} constants;
+
+void main()
+{
+    vec4 farPoint = constants.inverseViewProjection * vec4(texCoord * 2.0 - 1.0, 1.0, 1.0);
+    vec3 direction = normalize(farPoint.xyz / farPoint.w);
+
+    outColor = vec4(texture(environmentMap, direction).rgb * sceneUBO.environment.y, 1.0);
+
+    // The background is never picked, same as the cleared targets
+    outObjectId = NO_OBJECT_ID;
+    outObjectDepth = 0u;
+}
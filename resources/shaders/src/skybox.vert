@@ -0,0 +1,20 @@
+#version 460
+
+layout (location = 0) out vec2 texCoord;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// A single full-screen triangle on the far plane, so the depth test keeps it behind everything drawn before it
+void main()
+{
+    texCoord = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(texCoord * 2.0f - 1.0f, cameraUBO.depth.x, 1.0f);
+}
@@ -0,0 +1,30 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+layout (location = 1) in vec4 color;
+
+layout (location = 0) out vec4 outColor;
+
+layout (set = 0, binding = 0) uniform sampler2D spriteTexture;
+
+// NB! Must match the block declared in sprite.vert
+layout(push_constant) uniform SpriteConstants
+{
+    mat4 projection;
+    // x - 1 when the texture holds a signed distance field
+    vec4 params;
+} constants;
+
+void main()
+{
+    vec4 texel = texture(spriteTexture, texCoord);
+
+    // Distance fields keep the outline at 0.5, smoothed over about a pixel whatever the scale
+    if (constants.params.x > 0.5)
+    {
+        float smoothing = max(fwidth(texel.a) * 0.5, 1e-4);
+        texel.a = smoothstep(0.5 - smoothing, 0.5 + smoothing, texel.a);
+    }
+
+    outColor = texel * color;
+}
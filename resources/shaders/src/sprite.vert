@@ -0,0 +1,24 @@
+#version 460
+
+// NB! Must match SpriteVertex in sprite.rs
+layout (location = 0) in vec2 vPosition;
+layout (location = 1) in vec2 vTexCoord;
+layout (location = 2) in vec4 vColor;
+
+layout (location = 0) out vec2 texCoord;
+layout (location = 1) out vec4 color;
+
+layout(push_constant) uniform SpriteConstants
+{
+    // Orthographic projection from window pixels, origin at the top left
+    mat4 projection;
+    // x - 1 when the texture holds a signed distance field
+    vec4 params;
+} constants;
+
+void main()
+{
+    texCoord = vTexCoord;
+    color = vColor;
+    gl_Position = constants.projection * vec4(vPosition, 0.0, 1.0);
+}
@@ -0,0 +1,106 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out float outOcclusion;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// NB! Must match the binding constants in ssao.rs
+layout(set = 1, binding = 0) uniform sampler2D depthMap;
+layout(set = 1, binding = 1) uniform sampler2D normalMap;
+
+layout(push_constant) uniform SsaoConstants
+{
+    mat4 inverseViewProjection;
+    // x - radius, y - bias, z - sample count, w - intensity
+    vec4 params;
+} constants;
+
+const float PI = 3.14159265359;
+
+vec3 worldPositionAt(vec2 uv, float depth)
+{
+    vec4 worldPoint = constants.inverseViewProjection * vec4(uv * 2.0 - 1.0, depth, 1.0);
+    return worldPoint.xyz / worldPoint.w;
+}
+
+float radicalInverse(uint bits)
+{
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+// Per-pixel rotation of the sample kernel, the blur pass removes the resulting noise.
+// http://www.iryoku.com/next-generation-post-processing-in-call-of-duty-advanced-warfare
+float interleavedGradientNoise(vec2 pixel)
+{
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main()
+{
+    float depth = texture(depthMap, texCoord).r;
+
+    // Nothing was drawn here
+    if (depth == cameraUBO.depth.x) {
+        outOcclusion = 1.0;
+        return;
+    }
+
+    float radius = constants.params.x;
+    float bias = constants.params.y;
+    uint sampleCount = uint(constants.params.z);
+
+    vec3 P = worldPositionAt(texCoord, depth);
+    vec3 N = normalize(texture(normalMap, texCoord).xyz);
+    vec3 forward = -vec3(cameraUBO.view[0][2], cameraUBO.view[1][2], cameraUBO.view[2][2]);
+    float viewDepth = dot(P - cameraUBO.position.xyz, forward);
+
+    // Orthonormal basis around the normal, rotated by the per-pixel noise
+    // https://graphics.pixar.com/library/OrthonormalB/paper.pdf
+    float s = N.z >= 0.0 ? 1.0 : -1.0;
+    float a = -1.0 / (s + N.z);
+    float b = N.x * N.y * a;
+    vec3 T = vec3(1.0 + s * N.x * N.x * a, s * b, -s * N.x);
+    vec3 B = vec3(b, s + N.y * N.y * a, -N.y);
+
+    float angle = 2.0 * PI * interleavedGradientNoise(gl_FragCoord.xy);
+    vec3 rotatedT = T * cos(angle) + B * sin(angle);
+    mat3 TBN = mat3(rotatedT, cross(N, rotatedT), N);
+
+    float occlusion = 0.0;
+    for (uint i = 0u; i < sampleCount; i++) {
+        // Cosine weighted hemisphere directions, with more samples close to the surface
+        float u = (float(i) + 0.5) / float(sampleCount);
+        float phi = 2.0 * PI * radicalInverse(i);
+        vec3 direction = vec3(cos(phi) * sqrt(u), sin(phi) * sqrt(u), sqrt(1.0 - u));
+        float scale = mix(0.1, 1.0, u * u);
+
+        vec3 samplePosition = P + TBN * direction * radius * scale;
+        vec4 sampleClip = cameraUBO.view_projection * vec4(samplePosition, 1.0);
+        vec2 sampleUv = sampleClip.xy / sampleClip.w * 0.5 + 0.5;
+
+        float sceneDepth = texture(depthMap, sampleUv).r;
+        float sceneViewDepth = dot(worldPositionAt(sampleUv, sceneDepth) - cameraUBO.position.xyz, forward);
+        float sampleViewDepth = dot(samplePosition - cameraUBO.position.xyz, forward);
+
+        // Occluders much further away than the radius don't darken the pixel
+        float rangeCheck = smoothstep(0.0, 1.0, radius / abs(viewDepth - sceneViewDepth));
+        occlusion += (sceneViewDepth <= sampleViewDepth - bias ? 1.0 : 0.0) * rangeCheck;
+    }
+
+    outOcclusion = 1.0 - occlusion / float(sampleCount);
+}
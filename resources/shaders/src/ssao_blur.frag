@@ -0,0 +1,66 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out float outOcclusion;
+
+layout(set = 0, binding = 0) uniform CameraBuffer
+{
+    mat4 view;
+    mat4 projection;
+    mat4 view_projection;
+    vec4 position;
+    // x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    vec4 depth;
+} cameraUBO;
+
+// NB! Must match the binding constants in ssao.rs
+layout(set = 1, binding = 0) uniform sampler2D depthMap;
+layout(set = 1, binding = 2) uniform sampler2D rawOcclusionMap;
+
+layout(push_constant) uniform SsaoConstants
+{
+    mat4 inverseViewProjection;
+    // x - radius, y - bias, z - sample count, w - intensity
+    vec4 params;
+} constants;
+
+const int BLUR_RADIUS = 2;
+
+float viewDepthAt(vec2 uv)
+{
+    float depth = texture(depthMap, uv).r;
+    vec4 worldPoint = constants.inverseViewProjection * vec4(uv * 2.0 - 1.0, depth, 1.0);
+    vec3 forward = -vec3(cameraUBO.view[0][2], cameraUBO.view[1][2], cameraUBO.view[2][2]);
+    return dot(worldPoint.xyz / worldPoint.w - cameraUBO.position.xyz, forward);
+}
+
+// Bilateral blur, neighbours at a different depth than the center are left out so occlusion doesn't bleed across edges
+void main()
+{
+    // Nothing was drawn here, the far plane may be infinitely far away
+    if (texture(depthMap, texCoord).r == cameraUBO.depth.x) {
+        outOcclusion = 1.0;
+        return;
+    }
+
+    vec2 texelSize = 1.0 / vec2(textureSize(rawOcclusionMap, 0));
+    float centerDepth = viewDepthAt(texCoord);
+    float radius = constants.params.x;
+
+    float occlusion = 0.0;
+    float totalWeight = 0.0;
+    for (int x = -BLUR_RADIUS; x <= BLUR_RADIUS; x++) {
+        for (int y = -BLUR_RADIUS; y <= BLUR_RADIUS; y++) {
+            vec2 uv = texCoord + vec2(x, y) * texelSize;
+            float depthDifference = abs(viewDepthAt(uv) - centerDepth);
+            float weight = max(1.0 - depthDifference / radius, 0.0);
+
+            occlusion += texture(rawOcclusionMap, uv).r * weight;
+            totalWeight += weight;
+        }
+    }
+
+    occlusion = totalWeight > 0.0 ? occlusion / totalWeight : texture(rawOcclusionMap, texCoord).r;
+    outOcclusion = pow(occlusion, constants.params.w);
+}
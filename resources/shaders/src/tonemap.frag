@@ -0,0 +1,102 @@
+#version 460
+
+layout (location = 0) in vec2 texCoord;
+
+layout (location = 0) out vec4 outColor;
+
+// HDR scene, bloom result (or the HDR scene again when bloom is off) and the color grading LUT strip
+layout (set = 0, binding = 0) uniform sampler2D sourceImage;
+layout (set = 0, binding = 1) uniform sampler2D secondaryImage;
+layout (set = 0, binding = 2) uniform sampler2D colorGradingLut;
+
+// params: x - exposure multiplier, y - bloom intensity, z - vignette intensity, w - vignette smoothness
+// extra: x - tonemapper (0 - none, 1 - Reinhard, 2 - ACES), y - color grading flag
+layout( push_constant ) uniform constants
+{
+    vec4 params;
+    vec4 extra;
+    vec4 texelSize;
+} PushConstants;
+
+const float TONEMAPPER_REINHARD = 1.0;
+const float TONEMAPPER_ACES = 2.0;
+
+
+vec3 reinhard(vec3 color)
+{
+    return color / (1.0f + color);
+}
+
+
+// Narkowicz's fit of the ACES filmic curve
+vec3 aces(vec3 color)
+{
+    const float a = 2.51f;
+    const float b = 0.03f;
+    const float c = 2.43f;
+    const float d = 0.59f;
+    const float e = 0.14f;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0f, 1.0f);
+}
+
+
+vec3 linearToSrgb(vec3 color)
+{
+    vec3 low = color * 12.92f;
+    vec3 high = 1.055f * pow(color, vec3(1.0f / 2.4f)) - 0.055f;
+    return mix(high, low, lessThanEqual(color, vec3(0.0031308f)));
+}
+
+
+// The LUT is a strip of N slices of NxN texels: red along each slice, green downwards, blue from slice to slice.
+// It is indexed with sRGB encoded colors, the sampled texels come back linear.
+vec3 colorGrade(vec3 color)
+{
+    vec2 lutSize = vec2(textureSize(colorGradingLut, 0));
+    float size = lutSize.y;
+
+    vec3 coords = linearToSrgb(clamp(color, 0.0f, 1.0f)) * (size - 1.0f);
+    float slice = floor(coords.b);
+    float sliceFraction = coords.b - slice;
+
+    vec2 uv = (vec2(coords.r, coords.g) + 0.5f) / lutSize;
+    uv.x += slice / size;
+
+    vec3 lower = texture(colorGradingLut, uv).rgb;
+    vec3 upper = texture(colorGradingLut, uv + vec2(min(1.0f, size - 1.0f - slice) / size, 0.0f)).rgb;
+    return mix(lower, upper, sliceFraction);
+}
+
+
+void main()
+{
+    vec3 color = texture(sourceImage, texCoord).rgb;
+    color += texture(secondaryImage, texCoord).rgb * PushConstants.params.y;
+    color *= PushConstants.params.x;
+
+    if (PushConstants.extra.x == TONEMAPPER_REINHARD)
+    {
+        color = reinhard(color);
+    }
+    else if (PushConstants.extra.x == TONEMAPPER_ACES)
+    {
+        color = aces(color);
+    }
+
+    color = clamp(color, 0.0f, 1.0f);
+
+    // Darken towards the corners, fading in over the smoothness range
+    if (PushConstants.params.z > 0.0f)
+    {
+        float distanceToCenter = length(texCoord - 0.5f) / length(vec2(0.5f));
+        float vignette = smoothstep(1.0f - PushConstants.params.w, 1.0f, distanceToCenter);
+        color *= 1.0f - vignette * PushConstants.params.z;
+    }
+
+    if (PushConstants.extra.y > 0.0f)
+    {
+        color = colorGrade(color);
+    }
+
+    outColor = vec4(color, 1.0f);
+}
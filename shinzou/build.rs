@@ -3,7 +3,7 @@ use std::io::Write;
 use std::{env, fs, path::PathBuf, process::Command};
 //-----------------------------------------------------------------------------
 
-fn compile_glsl_shader(file: &DirEntry, mut out_pathbuf: PathBuf) {
+fn compile_glsl_shader(file: &DirEntry, mut out_pathbuf: PathBuf, include_dir: &str) {
     // get filename
     let file_name = file.file_name().into_string().unwrap();
 
@@ -28,6 +28,7 @@ fn compile_glsl_shader(file: &DirEntry, mut out_pathbuf: PathBuf) {
 
     let output = Command::new("glslangValidator")
         .arg("-V")
+        .arg(format!("-I{}", include_dir))
         .arg(&infile)
         .arg("-o")
         .arg(&outfile)
@@ -54,6 +55,11 @@ fn compile_shaders() {
     let mut dist_pathbuf = shaders_pathbuf.clone();
     dist_pathbuf.push("dist");
 
+    // Shared code the shaders #include, not compiled on its own
+    let mut include_pathbuf = shaders_pathbuf.clone();
+    include_pathbuf.push("include");
+    let include_dir = include_pathbuf.to_str().unwrap();
+
     let dir = src_pathbuf.to_str().unwrap();
     let shader_entries = fs::read_dir(src_pathbuf.clone())
         .expect(&format!("Failed to read shaders src folder {}!", dir));
@@ -63,7 +69,7 @@ fn compile_shaders() {
         .collect::<Vec<DirEntry>>();
 
     for shader in shaders {
-        compile_glsl_shader(&shader, dist_pathbuf.clone());
+        compile_glsl_shader(&shader, dist_pathbuf.clone(), include_dir);
     }
 
     println!("build - Shader compilation successful!")
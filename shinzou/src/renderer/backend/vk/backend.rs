@@ -1,5 +1,11 @@
+use std::{
+    collections::VecDeque,
+    mem::{self, size_of},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
 use ash::{version::DeviceV1_0, vk, Device};
-use ultraviolet::Vec4;
+use ultraviolet::{Mat4, Vec4};
 //----------------------------------------------------------------------------------------------------------------------
 
 use crate::{
@@ -9,16 +15,91 @@ use crate::{
             handles::{
                 AllocatorHandle, DeviceHandle, InstanceHandle, PhysicalDeviceHandle, SurfaceHandle,
             },
+            render_graph::{
+                FrameGraph, GraphPass, ImageSize, ImportedImage, PassContext, RenderGraph,
+                TransientImageDesc,
+            },
             resources::{
-                MeshMetaSSBO, MeshSSBO, ResourceManager, SceneUBO, VkBuffer, VkDepthBuffer,
-                SCENE_UBO_SIZE,
+                BillboardPushConstants, BillboardVertex, CameraFrustum, ClusterGrid, ClusterHeader,
+                DebugVertex, DebugViewPushConstants, DeferredPushConstants, DrawKey, DrawLayer,
+                DrawList, LightSSBO, MeshMetaSSBO, MeshSSBO, PickReadback,
+                PostProcessPushConstants, ResourceManager, SceneUBO, SelectionOutlinePushConstants,
+                ShadowLayout, ShadowPushConstants, ShadowUBO, SkyboxPushConstants, SpriteDraw,
+                SpritePushConstants, SpriteVertex, SsaoPushConstants, VkBuffer, VkDepthBuffer,
+                VkMaterialPass, VkShadowMaps, BILLBOARD_PIPELINE, BILLBOARD_PIPELINE_LAYOUT,
+                BLOOM_DOWNSAMPLE_PIPELINE, BLOOM_MIP_COUNT, BLOOM_UPSAMPLE_PIPELINE,
+                CLUSTER_HEADER_SIZE, DEBUG_LINE_OVERLAY_PIPELINE, DEBUG_LINE_PIPELINE,
+                DEBUG_LINE_PIPELINE_LAYOUT, DEBUG_VIEW_HEAT_MAP_PIPELINE,
+                DEBUG_VIEW_OVERDRAW_PIPELINE, DEBUG_VIEW_PICKING_PIPELINE, DEBUG_VIEW_PIPELINE,
+                DEBUG_VIEW_PIPELINE_LAYOUT, DEFAULT_COLOR_GRADING_LUT, DEFERRED_BILLBOARD_PIPELINE,
+                DEFERRED_DEBUG_LINE_OVERLAY_PIPELINE, DEFERRED_DEBUG_LINE_PIPELINE,
+                DEFERRED_LIGHTING_PIPELINE, DEFERRED_LIGHTING_PIPELINE_LAYOUT,
+                DEFERRED_WIREFRAME_PIPELINE, DEPTH_NORMAL_PIPELINE, DEPTH_NORMAL_PIPELINE_LAYOUT,
+                FXAA_PIPELINE, GBUFFER_COLOR_FORMATS, GBUFFER_DEBUG_PIPELINE, HDR_COLOR_FORMAT,
+                HOVERED_STENCIL, LIGHT_BUFFER_HEADER_SIZE, LIGHT_BUFFER_SIZE, MAX_LIGHTS,
+                NO_OBJECT_ID, OBJECT_DEPTH_FORMAT, OBJECT_ID_FORMAT, OVERDRAW_COUNT_FORMAT,
+                OVERDRAW_HEAT_MAP_MAX, POST_PROCESS_PIPELINE_LAYOUT, SELECTED_STENCIL,
+                SELECTION_OUTLINE_PIPELINE, SELECTION_OUTLINE_PIPELINE_LAYOUT,
+                SELECTION_STENCIL_PIPELINE, SELECTION_STENCIL_PIPELINE_LAYOUT, SHADOW_PIPELINE,
+                SHADOW_PIPELINE_LAYOUT, SHADOW_UBO_SIZE, SKYBOX_BACKGROUND_PIPELINE,
+                SKYBOX_PICKING_PIPELINE, SKYBOX_PIPELINE, SKYBOX_PIPELINE_LAYOUT, SPRITE_PIPELINE,
+                SPRITE_PIPELINE_LAYOUT, SSAO_BLUR_PIPELINE, SSAO_FORMAT, SSAO_NORMAL_FORMAT,
+                SSAO_PIPELINE, SSAO_PIPELINE_LAYOUT, TONEMAP_PIPELINE, WIREFRAME_PIPELINE,
             },
             VkRendererConfig,
         },
-        entities::{Camera, CameraUBO, Material, Mesh, Renderable, Texture, CAMERA_UBO_SIZE},
+        entities::{
+            AmbientOcclusion, Billboard, Camera, CameraUBO, DebugDraw, DebugViewMode,
+            GBufferChannel, Highlight, Light, LightType, Lighting, Material, Mesh, PickResult,
+            PostProcess, Renderable, SelectionOutline, Sprite, Texture, Tonemapper,
+        },
         hal::RendererBackend,
+        RenderPath, RendererConfig,
     },
-    utils::traits::Destroy,
+    utils::{ffi, traits::Destroy},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+// Render graph resource names
+const BACKBUFFER: &str = "backbuffer";
+const DEPTH_BUFFER: &str = "depth_buffer";
+const HDR_COLOR: &str = "hdr_color";
+const HDR_COLOR_MULTISAMPLED: &str = "hdr_color_multisampled";
+const GBUFFER_ALBEDO: &str = "gbuffer_albedo";
+const GBUFFER_NORMAL: &str = "gbuffer_normal";
+const GBUFFER_MATERIAL: &str = "gbuffer_material";
+const GBUFFER_EMISSIVE: &str = "gbuffer_emissive";
+const GBUFFER_COLORS: [&str; 4] = [
+    GBUFFER_ALBEDO,
+    GBUFFER_NORMAL,
+    GBUFFER_MATERIAL,
+    GBUFFER_EMISSIVE,
+];
+const LDR_COLOR: &str = "ldr_color";
+const SSAO_DEPTH: &str = "ssao_depth";
+const SSAO_NORMALS: &str = "ssao_normals";
+const SSAO_RAW: &str = "ssao_raw";
+const AMBIENT_OCCLUSION: &str = "ambient_occlusion";
+const SHADOW_MAPS: &str = "shadow_maps";
+const ENTITY_BUFFER: &str = "entity_buffer";
+const LIGHT_BUFFER: &str = "light_buffer";
+const CLUSTER_BUFFER: &str = "cluster_buffer";
+const CLUSTER_LIGHT_INDEX_BUFFER: &str = "cluster_light_index_buffer";
+const OBJECT_ID: &str = "object_id";
+const OBJECT_DEPTH: &str = "object_depth";
+const OBJECT_ID_MULTISAMPLED: &str = "object_id_multisampled";
+const OBJECT_DEPTH_MULTISAMPLED: &str = "object_depth_multisampled";
+const PICK_BUFFER: &str = "pick_buffer";
+const SELECTION_STENCIL: &str = "selection_stencil";
+const DEBUG_VIEW_DEPTH_BUFFER: &str = "debug_view_depth_buffer";
+const OVERDRAW_COUNT: &str = "overdraw_count";
+
+/// Blended over the HDR scene, so it goes through exposure and tonemapping like debug lines.
+const WIREFRAME_COLOR: Vec4 = Vec4 {
+    x: 0.0,
+    y: 1.0,
+    z: 0.5,
+    w: 0.6,
 };
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -32,6 +113,15 @@ pub(in crate::renderer::backend) trait DeviceAllocatorDestroy {
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+/// Pick a frame in flight copies into its pick buffer, with the id of every renderable it drew by entity index, so the
+/// index read back resolves against the scene of that frame.
+#[derive(Clone)]
+struct PickInFlight {
+    pixel: (u32, u32),
+    ids: Vec<u32>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
 pub struct VkRenderer {
     pub config: VkRendererConfig,
     pub instance_handle: InstanceHandle,
@@ -44,16 +134,36 @@ pub struct VkRenderer {
     pub debug_utils_manager: crate::renderer::backend::vk::DebugUtilsManager,
 
     pub resource_manager: ResourceManager,
+    pub render_graph: RenderGraph,
     //------------------------------------------------------------------------------------------------------------------
+    depth_attachment_format: vk::Format,
+    /// Depth/stencil format of the selection stencil, `None` disables selection outlines.
+    selection_format: Option<vk::Format>,
+    msaa_samples: vk::SampleCountFlags,
+    render_path: RenderPath,
+    gbuffer_view: Option<GBufferChannel>,
+    debug_view: DebugViewMode,
+    /// Whether the device can rasterize polygons as lines, the wireframe debug view is skipped otherwise.
+    wireframe_supported: bool,
+    /// Kept between frames, so sorting the draws doesn't allocate.
+    draw_list: DrawList,
+    /// Pixel to pick on the next frame.
+    pending_pick: Option<(u32, u32)>,
+    /// Pick of each frame in flight, by frame index.
+    picks_in_flight: Vec<Option<PickInFlight>>,
+    pick_results: VecDeque<PickResult>,
+    /// Kept between frames, so assigning lights to clusters doesn't allocate.
+    cluster_grid: ClusterGrid,
     frame_counter: u32,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
 impl VkRenderer {
-    pub fn init(app_name: &str, window: &WindowHandle) -> Self {
+    pub fn init(app_name: &str, window: &WindowHandle, renderer_config: &RendererConfig) -> Self {
         info!("----- VkBackend::init -----");
 
         let (instance_handle, mut config) = InstanceHandle::init(app_name);
+        config.apply_renderer_config(renderer_config);
 
         let surface_handle = SurfaceHandle::init(&instance_handle, window);
 
@@ -72,6 +182,40 @@ impl VkRenderer {
         let resource_manager =
             ResourceManager::init(&allocator_handle, &physical_device_handle, &config);
 
+        let depth_attachment_format = VkDepthBuffer::find_supported_depth_format(
+            &instance_handle,
+            &physical_device_handle,
+            config.depth_mode.is_reversed(),
+            false,
+        );
+        let selection_format = VkDepthBuffer::find_supported_depth_format(
+            &instance_handle,
+            &physical_device_handle,
+            config.depth_mode.is_reversed(),
+            true,
+        );
+        let selection_format = if VkDepthBuffer::has_stencil(selection_format) {
+            Some(selection_format)
+        } else {
+            warn!("VkBackend::init - No depth format with a stencil aspect, selection outlines are disabled!");
+            None
+        };
+        let msaa_samples = VkDepthBuffer::find_supported_sample_count(
+            &physical_device_handle,
+            config.msaa_samples,
+        );
+        let wireframe_supported = physical_device_handle
+            .physical_device_attributes
+            .features
+            .fill_mode_non_solid
+            == vk::TRUE;
+        if !wireframe_supported {
+            warn!("VkBackend::init - Line fill mode is not supported, the wireframe debug view is disabled!");
+        }
+
+        let render_path = config.render_path;
+        let picks_in_flight = vec![None; config.buffering as usize];
+
         Self {
             #[cfg(debug_assertions)]
             debug_utils_manager: crate::renderer::backend::vk::DebugUtilsManager::init(
@@ -86,7 +230,20 @@ impl VkRenderer {
             device_handle,
             allocator_handle,
             resource_manager,
+            render_graph: RenderGraph::new(),
             //----------------------------------------------------------------------------------------------------------
+            depth_attachment_format,
+            selection_format,
+            msaa_samples,
+            render_path,
+            gbuffer_view: None,
+            debug_view: DebugViewMode::default(),
+            wireframe_supported,
+            draw_list: DrawList::default(),
+            pending_pick: None,
+            picks_in_flight,
+            pick_results: VecDeque::new(),
+            cluster_grid: ClusterGrid::default(),
             frame_counter: 0,
         }
     }
@@ -103,11 +260,14 @@ impl Drop for VkRenderer {
     fn drop(&mut self) {
         let VkRenderer {
             resource_manager,
+            render_graph,
             device_handle,
             allocator_handle,
             ..
         } = self;
 
+        render_graph.destroy(&device_handle.device, &allocator_handle.allocator);
+
         unsafe {
             resource_manager.destroy(&device_handle.device, &allocator_handle.allocator);
         }
@@ -142,6 +302,10 @@ impl RendererBackend for VkRenderer {
             device_handle,
             resource_manager,
             allocator_handle,
+            depth_attachment_format,
+            selection_format,
+            msaa_samples,
+            wireframe_supported,
             ..
         } = self;
 
@@ -160,6 +324,7 @@ impl RendererBackend for VkRenderer {
                 device,
                 physical_device_handle.graphics_queue_index,
                 allocator_handle,
+                config.entity_buffer_capacity,
             );
         });
 
@@ -171,47 +336,129 @@ impl RendererBackend for VkRenderer {
             config,
         );
 
-        let depth_attachment_format =
-            VkDepthBuffer::find_supported_depth_format(instance_handle, physical_device_handle);
-
+        // Only used for pipeline creation, the render graph begins compatible passes of its own
         let render_pass = resource_manager.create_render_pass(
             device,
             None,
-            swapchain.surface_format(),
-            depth_attachment_format,
+            Some(HDR_COLOR_FORMAT),
+            *depth_attachment_format,
+            *msaa_samples,
+        );
+        resource_manager.create_forward_picking_render_pass(
+            device,
+            *depth_attachment_format,
+            *msaa_samples,
+        );
+
+        resource_manager.create_descriptors(device);
+
+        resource_manager.create_shadow_maps(
+            device,
+            allocator_handle,
+            config,
+            VkShadowMaps::find_supported_format(instance_handle, physical_device_handle),
+            resource_manager
+                .get_command_pool("upload")
+                .expect("Failed to obtain upload command pool!")
+                .get(),
+            resource_manager
+                .get_fence("upload")
+                .expect("Failed to obtain upload fence!")
+                .get(),
+            &device_handle.graphics_queue,
         );
 
-        resource_manager.create_framebuffers(
+        resource_manager.create_ssao(
             device,
             allocator_handle,
-            &swapchain,
-            &render_pass,
-            depth_attachment_format,
+            config,
+            *depth_attachment_format,
+            resource_manager
+                .get_command_pool("upload")
+                .expect("Failed to obtain upload command pool!")
+                .get(),
+            resource_manager
+                .get_fence("upload")
+                .expect("Failed to obtain upload fence!")
+                .get(),
+            &device_handle.graphics_queue,
         );
 
-        resource_manager.create_descriptors(device);
+        if let Some(selection_format) = selection_format {
+            resource_manager.create_selection(
+                device,
+                config,
+                swapchain.surface_format(),
+                *selection_format,
+            );
+        }
+
+        resource_manager.create_post_process(device, config, swapchain.surface_format());
+        resource_manager.create_deferred(
+            device,
+            config,
+            swapchain.surface_format(),
+            *depth_attachment_format,
+        );
+        resource_manager.create_skybox(device, &render_pass, *msaa_samples);
+        resource_manager.create_debug_lines(device, &render_pass, *msaa_samples);
+        resource_manager.create_sprites(device, swapchain.surface_format());
+        resource_manager.create_billboards(device, &render_pass, *msaa_samples);
+        resource_manager.create_debug_view(
+            device,
+            &render_pass,
+            *msaa_samples,
+            swapchain.surface_format(),
+            *depth_attachment_format,
+            *wireframe_supported,
+        );
+        resource_manager.create_ibl(
+            device,
+            allocator_handle,
+            config,
+            resource_manager
+                .get_command_pool("upload")
+                .expect("Failed to obtain upload command pool!")
+                .get(),
+            resource_manager
+                .get_fence("upload")
+                .expect("Failed to obtain upload fence!")
+                .get(),
+            &device_handle.graphics_queue,
+        );
 
         for mesh in meshes {
             let mesh_resource = resource_manager.create_mesh(mesh, allocator_handle);
             mesh_resource.upload(
                 allocator_handle,
                 device,
-                resource_manager
-                    .get_command_pool("upload".into())
-                    .unwrap()
-                    .get(),
-                resource_manager.get_fence("upload".into()).unwrap().get(),
+                resource_manager.get_command_pool("upload").unwrap().get(),
+                resource_manager.get_fence("upload").unwrap().get(),
                 &device_handle.graphics_queue,
             );
         }
 
+        resource_manager.create_default_textures(
+            &device_handle.device,
+            resource_manager
+                .get_command_pool("upload")
+                .expect("Failed to obtain upload command pool!")
+                .get(),
+            resource_manager
+                .get_fence("upload")
+                .expect("Failed to obtain upload fence!")
+                .get(),
+            &device_handle.graphics_queue,
+            allocator_handle,
+        );
+
         for texture in textures {
             let command_pool = resource_manager
-                .get_command_pool("upload".into())
+                .get_command_pool("upload")
                 .expect("Failed to obtain upload command pool!")
                 .get();
             let fence = resource_manager
-                .get_fence("upload".into())
+                .get_fence("upload")
                 .expect("Failed to obtain upload fence!")
                 .get();
 
@@ -226,28 +473,50 @@ impl RendererBackend for VkRenderer {
         }
 
         for material in materials {
-            resource_manager.create_material(device, &render_pass, &material);
+            resource_manager.create_material(
+                device,
+                allocator_handle,
+                &render_pass,
+                *msaa_samples,
+                &material,
+            );
         }
     }
     //------------------------------------------------------------------------------------------------------------------
 
-    fn draw(&mut self, camera: &Camera, renderables: &[Renderable]) {
+    fn draw(
+        &mut self,
+        camera: &Camera,
+        lighting: &Lighting,
+        post_process: &PostProcess,
+        selection_outline: &SelectionOutline,
+        debug_draw: &DebugDraw,
+        sprites: &[&Sprite],
+        billboards: &[&Billboard],
+        renderables: &[Renderable],
+    ) {
         let VkRenderer {
             device_handle,
             resource_manager,
+            render_graph,
             allocator_handle,
+            draw_list,
+            pending_pick,
+            picks_in_flight,
+            pick_results,
+            cluster_grid,
             ..
         } = self;
 
-        let frame_data = resource_manager.get_current_frame(self.frame_counter as usize);
-
         let DeviceHandle {
             device,
             graphics_queue,
             present_queue,
         } = &device_handle;
 
-        let render_fences = [frame_data.render_fence];
+        let render_fences = [resource_manager
+            .get_current_frame(self.frame_counter as usize)
+            .render_fence];
 
         unsafe {
             // wait for the GPU to finish rendering last frame. Timeout of 1s - fences need to be explicitly reset after use.
@@ -259,6 +528,54 @@ impl RendererBackend for VkRenderer {
                 .expect("VkBackend::draw - Failed to reset fences!");
         };
 
+        // Frame buffers are no longer in use by the GPU, so they can be safely reallocated if the scene outgrew them
+        resource_manager.reserve_frame_entities(
+            device,
+            allocator_handle,
+            self.frame_counter as usize,
+            renderables.len(),
+        );
+
+        let (debug_vertices, depth_tested_debug_vertex_count) =
+            DebugVertex::from_lines(debug_draw.lines());
+        resource_manager.reserve_frame_debug_vertices(
+            allocator_handle,
+            self.frame_counter as usize,
+            debug_vertices.len(),
+        );
+
+        let (sprite_vertices, sprite_batches) = SpriteVertex::from_sprites(sprites);
+        resource_manager.reserve_frame_sprite_vertices(
+            allocator_handle,
+            self.frame_counter as usize,
+            sprite_vertices.len(),
+        );
+
+        let sprite_draws = resource_manager.prepare_sprite_draws(device, &sprite_batches);
+
+        let (billboard_vertices, billboard_batches) = BillboardVertex::from_billboards(billboards);
+        resource_manager.reserve_frame_billboard_vertices(
+            allocator_handle,
+            self.frame_counter as usize,
+            billboard_vertices.len(),
+        );
+        let billboard_draws = resource_manager.prepare_sprite_draws(device, &billboard_batches);
+
+        let frame_data = resource_manager.get_current_frame(self.frame_counter as usize);
+        let frame_index = resource_manager.get_current_frame_number(self.frame_counter as usize);
+
+        // The pick this frame made last time around has landed in its buffer now that the fence was waited on
+        if let Some(PickInFlight { pixel, ids }) = picks_in_flight[frame_index].take() {
+            let mut readback = PickReadback::default();
+            allocator_handle.read_buffer(
+                &frame_data.pick_buffer,
+                &mut readback as *mut PickReadback,
+                1,
+            );
+
+            pick_results.push_back(pick_result(pixel, readback, &ids));
+        }
+
         let swapchain_resource = resource_manager.get_swapchain().unwrap();
         let swapchain = swapchain_resource.get();
         let swapchain_khr = swapchain_resource.khr();
@@ -294,49 +611,6 @@ impl RendererBackend for VkRenderer {
                 .expect("VkBackend::draw - Failed to begin command buffer!")
         };
 
-        const BG: f32 = 0.035;
-        let clear_values = [
-            vk::ClearValue {
-                color: vk::ClearColorValue {
-                    float32: [BG, BG, BG, 1.0],
-                },
-            },
-            vk::ClearValue {
-                depth_stencil: vk::ClearDepthStencilValue {
-                    depth: 1.0,
-                    stencil: 0,
-                },
-            },
-        ];
-
-        let render_pass = resource_manager.get_render_pass(None).get();
-        let framebuffer = resource_manager
-            .get_framebuffers()
-            .get(swapchain_image_index as usize)
-            .expect("VkBackend::draw - Failed to retrieve framebuffer by swapchain index!")
-            .get();
-
-        let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
-            .render_pass(render_pass)
-            .render_area(
-                vk::Rect2D::builder()
-                    .offset(vk::Offset2D::builder().x(0).y(0).build())
-                    .extent(swapchain_resource.surface_extent())
-                    .build(),
-            )
-            .framebuffer(framebuffer)
-            .clear_values(&clear_values);
-
-        unsafe {
-            device.cmd_begin_render_pass(
-                frame_data.command_buffer,
-                &render_pass_begin_info,
-                vk::SubpassContents::INLINE,
-            );
-        }
-
-        let frame_index = resource_manager.get_current_frame_number(self.frame_counter as usize);
-
         // Write entity SSBO
         let ssbo_buffer_data = renderables
             .into_iter()
@@ -355,32 +629,46 @@ impl RendererBackend for VkRenderer {
         // Write entity meta SSBO
         let ssbo_meta_buffer_data = renderables
             .into_iter()
-            .enumerate()
-            .map(|(i, renderable)| {
-                let mut color = Vec4::new(f32::cos(i as f32), f32::sin(i as f32), 0.4, 1.0);
-                let pos = renderable.transform.cols[3];
-                if pos.x.abs() == pos.z.abs() {
-                    color = Vec4::one();
-                }
-                MeshMetaSSBO { color }
-            })
+            .map(MeshMetaSSBO::new)
             .collect::<Vec<MeshMetaSSBO>>();
 
         allocator_handle.write_buffer(
             &frame_data.entity_meta_buffer,
             ssbo_meta_buffer_data.as_ptr() as *const MeshMetaSSBO,
-            ssbo_buffer_data.len(),
+            ssbo_meta_buffer_data.len(),
             None,
         );
 
-        let scene = resource_manager.get_scene();
-        let scene_buffer_stride = VkBuffer::pad_ubo_size(
-            &self.physical_device_handle,
-            SCENE_UBO_SIZE + CAMERA_UBO_SIZE,
-        );
+        if !debug_vertices.is_empty() {
+            allocator_handle.write_buffer(
+                &frame_data.debug_vertex_buffer,
+                debug_vertices.as_ptr(),
+                debug_vertices.len(),
+                None,
+            );
+        }
 
+        if !sprite_vertices.is_empty() {
+            allocator_handle.write_buffer(
+                &frame_data.sprite_vertex_buffer,
+                sprite_vertices.as_ptr(),
+                sprite_vertices.len(),
+                None,
+            );
+        }
+
+        if !billboard_vertices.is_empty() {
+            allocator_handle.write_buffer(
+                &frame_data.billboard_vertex_buffer,
+                billboard_vertices.as_ptr(),
+                billboard_vertices.len(),
+                None,
+            );
+        }
+
+        let scene = resource_manager.get_scene();
         // Write Camera UBO
-        let camera_ubo_offset: u32 = (scene_buffer_stride * frame_index as u64) as u32;
+        let camera_ubo_offset: u32 = (scene.buffer_stride * frame_index as u64) as u32;
 
         let camera_ubo = CameraUBO::new(camera);
         allocator_handle.write_buffer(
@@ -391,11 +679,16 @@ impl RendererBackend for VkRenderer {
         );
 
         // Write Scene UBO
-        let framed = self.frame_counter as f32 / 120.0;
-        let scene_ubo = SceneUBO::new(Vec4::new(f32::sin(framed), 0.0, f32::cos(framed), 1.0));
+        let ambient_occlusion = &lighting.ambient_occlusion;
+        let environment = resource_manager.has_environment();
+        let scene_ubo = SceneUBO::new(
+            lighting.ambient,
+            ambient_occlusion.enabled,
+            environment,
+            lighting.environment_intensity,
+        );
 
-        let scene_ubo_offset: u32 =
-            ((scene_buffer_stride) * frame_index as u64) as u32 + CAMERA_UBO_SIZE as u32;
+        let scene_ubo_offset: u32 = camera_ubo_offset + scene.scene_ubo_offset as u32;
 
         allocator_handle.write_buffer(
             &scene.buffer,
@@ -404,128 +697,832 @@ impl RendererBackend for VkRenderer {
             Some(scene_ubo_offset as isize),
         );
 
-        draw_renderables(
-            device,
-            frame_data.command_buffer,
-            &resource_manager,
-            renderables,
-            &[scene.descriptor_set],
-            &[camera_ubo_offset, camera_ubo_offset],
-            &[frame_data.entity_descriptor_set],
-        );
+        let frustum = CameraFrustum::new(camera);
+        let shadow_layout = ShadowLayout::new(&frustum, &lighting.lights, &self.config);
 
-        unsafe {
-            device.cmd_end_render_pass(frame_data.command_buffer);
-            device
-                .end_command_buffer(frame_data.command_buffer)
-                .expect("VkBackend::draw - Failed to end command buffer!");
+        // Write light SSBO
+        if lighting.lights.len() > MAX_LIGHTS {
+            warn!(
+                "VkBackend::draw - {} lights in scene, only the first {} will be uploaded!",
+                lighting.lights.len(),
+                MAX_LIGHTS
+            );
         }
 
-        let wait_dst_stage_mask = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-        let present_semaphores = [frame_data.present_semaphore];
+        // Directional lights go first, the shaders apply them to every fragment before walking its cluster
+        let (directional_lights, local_lights): (Vec<_>, Vec<_>) = lighting
+            .lights
+            .iter()
+            .zip(shadow_layout.light_layers.iter())
+            .partition(|(light, _)| light.light_type == LightType::Directional);
 
-        let render_semaphores = [frame_data.render_semaphore];
+        let uploaded_lights = directional_lights
+            .into_iter()
+            .chain(local_lights)
+            .take(MAX_LIGHTS)
+            .collect::<Vec<(&Light, &Option<u32>)>>();
+
+        let light_ssbo_data = uploaded_lights
+            .iter()
+            .map(|(light, shadow_layer)| LightSSBO::new(light, **shadow_layer))
+            .collect::<Vec<LightSSBO>>();
+
+        let directional_light_count = uploaded_lights
+            .iter()
+            .filter(|(light, _)| light.light_type == LightType::Directional)
+            .count();
+
+        let light_buffer_offset: u32 =
+            (VkBuffer::pad_ssbo_size(&self.physical_device_handle, LIGHT_BUFFER_SIZE)
+                * frame_index as u64) as u32;
+
+        let light_buffer_header: [u32; 4] = [
+            light_ssbo_data.len() as u32,
+            directional_light_count as u32,
+            0,
+            0,
+        ];
+        allocator_handle.write_buffer(
+            &scene.light_buffer,
+            &light_buffer_header as *const [u32; 4],
+            1,
+            Some(light_buffer_offset as isize),
+        );
 
-        let submits = [vk::SubmitInfo::builder()
-            .wait_dst_stage_mask(&wait_dst_stage_mask)
-            .wait_semaphores(&present_semaphores)
-            .signal_semaphores(&render_semaphores)
-            .command_buffers(&[frame_data.command_buffer])
-            .build()];
+        allocator_handle.write_buffer(
+            &scene.light_buffer,
+            light_ssbo_data.as_ptr(),
+            light_ssbo_data.len(),
+            Some((light_buffer_offset as usize + LIGHT_BUFFER_HEADER_SIZE) as isize),
+        );
 
-        unsafe {
-            device
-                .queue_submit(*graphics_queue, &submits, frame_data.render_fence)
-                .expect("VkBackend::DeviceHandle::draw - Failed to submit to queue!")
-        }
+        // Write light clusters
+        let cluster_lights = uploaded_lights
+            .iter()
+            .map(|(light, _)| *light)
+            .collect::<Vec<&Light>>();
+        cluster_grid.build(
+            &frustum,
+            &cluster_lights,
+            swapchain_resource.surface_extent(),
+        );
 
-        let swapchains = [swapchain_khr];
-        let image_indices = [swapchain_image_index];
-        let present_info = vk::PresentInfoKHR::builder()
-            .swapchains(&swapchains)
-            .wait_semaphores(&render_semaphores)
-            .image_indices(&image_indices);
+        allocator_handle.write_buffer(
+            &frame_data.cluster_buffer,
+            &cluster_grid.header as *const ClusterHeader,
+            1,
+            None,
+        );
 
-        unsafe {
-            swapchain
-                .queue_present(*present_queue, &present_info)
-                .expect("VkBackend::draw - Failed to present swapchain image!");
-        }
+        allocator_handle.write_buffer(
+            &frame_data.cluster_buffer,
+            cluster_grid.clusters.as_ptr(),
+            cluster_grid.clusters.len(),
+            Some(CLUSTER_HEADER_SIZE as isize),
+        );
 
-        self.frame_counter += 1;
-    }
-    //------------------------------------------------------------------------------------------------------------------
+        allocator_handle.write_buffer(
+            &frame_data.cluster_light_index_buffer,
+            cluster_grid.light_indices.as_ptr(),
+            cluster_grid.light_indices.len(),
+            None,
+        );
 
-    fn await_device_idle(&mut self) {
-        unsafe {
-            self.device()
-                .device_wait_idle()
-                .expect("VkBackend::await_device_idle - Failed to wait for dev to become idle!");
-        }
-    }
-    //------------------------------------------------------------------------------------------------------------------
-}
-//----------------------------------------------------------------------------------------------------------------------
+        // Write shadow UBO
+        let shadow_ubo_offset: u32 =
+            (VkBuffer::pad_ubo_size(&self.physical_device_handle, SHADOW_UBO_SIZE)
+                * frame_index as u64) as u32;
 
-fn draw_renderables(
-    device: &Device,
-    command_buffer: vk::CommandBuffer,
-    resource_manager: &ResourceManager,
-    renderables: &[Renderable],
-    global_descriptor_sets: &[vk::DescriptorSet],
-    global_dynamic_offsets: &[u32],
-    entity_descriptor_sets: &[vk::DescriptorSet],
-) {
-    let mut last_mesh = None;
-    let mut last_material = None;
-    for (i, renderable) in renderables.iter().enumerate() {
-        let Renderable {
-            mesh_name,
-            material_name,
-            ..
-        } = renderable;
+        allocator_handle.write_buffer(
+            &scene.shadow_buffer,
+            &shadow_layout.ubo as *const ShadowUBO,
+            1,
+            Some(shadow_ubo_offset as isize),
+        );
 
-        let material = resource_manager.get_material(material_name);
+        let resource_manager: &ResourceManager = resource_manager;
+        let surface_extent = swapchain_resource.surface_extent();
+        let shadow_maps = resource_manager.get_shadow_maps();
+        let ssao = resource_manager.get_ssao();
+
+        let (opaque_renderables, transparent_renderables) =
+            sort_renderables(draw_list, resource_manager, renderables, &frustum);
+
+        let mut frame_graph = FrameGraph::new(surface_extent);
+
+        frame_graph.import_image(
+            BACKBUFFER,
+            ImportedImage::new(
+                swapchain_resource.images()[swapchain_image_index as usize],
+                swapchain_resource.image_views()[swapchain_image_index as usize],
+                swapchain_resource.surface_format(),
+                surface_extent,
+                vk::ImageLayout::UNDEFINED,
+                vk::ImageLayout::PRESENT_SRC_KHR,
+            ),
+        );
 
-        let material_cmp = Some(material_name.clone());
-        if material_cmp != last_material {
-            last_material = material_cmp;
+        frame_graph.import_image(
+            SHADOW_MAPS,
+            ImportedImage::new(
+                shadow_maps.image(),
+                shadow_maps.image_view(),
+                shadow_maps.format(),
+                shadow_maps.extent(),
+                vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+                vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+            )
+            .with_layer_views(shadow_maps.layer_views()),
+        );
 
-            unsafe {
-                device.cmd_bind_pipeline(
-                    command_buffer,
-                    vk::PipelineBindPoint::GRAPHICS,
-                    material.pipeline,
+        // Only written when ambient occlusion is enabled, the lighting shaders skip it otherwise
+        frame_graph.import_image(
+            AMBIENT_OCCLUSION,
+            ImportedImage::new(
+                ssao.image(),
+                ssao.image_view(),
+                SSAO_FORMAT,
+                ssao.extent(),
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            ),
+        );
+
+        frame_graph.import_buffer(ENTITY_BUFFER, frame_data.entity_buffer.get());
+        frame_graph.import_buffer(LIGHT_BUFFER, scene.light_buffer.get());
+        frame_graph.import_buffer(CLUSTER_BUFFER, frame_data.cluster_buffer.get());
+        frame_graph.import_buffer(
+            CLUSTER_LIGHT_INDEX_BUFFER,
+            frame_data.cluster_light_index_buffer.get(),
+        );
+
+        frame_graph.create_image(
+            HDR_COLOR,
+            TransientImageDesc::new(HDR_COLOR_FORMAT, ImageSize::Backbuffer),
+        );
+
+        // The G-buffer of the deferred path is never multisampled
+        let deferred = self.render_path == RenderPath::Deferred;
+        let scene_samples = if deferred {
+            vk::SampleCountFlags::TYPE_1
+        } else {
+            self.msaa_samples
+        };
+
+        frame_graph.create_image(
+            DEPTH_BUFFER,
+            TransientImageDesc::new(self.depth_attachment_format, ImageSize::Backbuffer)
+                .with_samples(scene_samples),
+        );
+
+        // Multisampled scenes render to a separate target that gets resolved into the HDR color image
+        let multisampled = scene_samples != vk::SampleCountFlags::TYPE_1;
+        if multisampled {
+            frame_graph.create_image(
+                HDR_COLOR_MULTISAMPLED,
+                TransientImageDesc::new(HDR_COLOR_FORMAT, ImageSize::Backbuffer)
+                    .with_samples(scene_samples),
+            );
+        }
+
+        // Picks outside the window are answered right away, there is nothing to render. Otherwise the scene passes
+        // also write the entity index and view depth of every pixel, which are read back at the picked one.
+        let pick = pending_pick.take().filter(|&(x, y)| {
+            let in_window = x < surface_extent.width && y < surface_extent.height;
+            if !in_window {
+                pick_results.push_back(pick_result((x, y), PickReadback::default(), &[]));
+            }
+            in_window
+        });
+        let picking = pick.is_some();
+        if let Some(pixel) = pick {
+            picks_in_flight[frame_index] = Some(PickInFlight {
+                pixel,
+                ids: renderables
+                    .iter()
+                    .map(|renderable| renderable.instance_data.id)
+                    .collect(),
+            });
+
+            frame_graph.create_image(
+                OBJECT_ID,
+                TransientImageDesc::new(OBJECT_ID_FORMAT, ImageSize::Backbuffer),
+            );
+            frame_graph.create_image(
+                OBJECT_DEPTH,
+                TransientImageDesc::new(OBJECT_DEPTH_FORMAT, ImageSize::Backbuffer),
+            );
+            // Debug views replacing shading draw single-sampled straight to the backbuffer
+            if multisampled && !self.debug_view.replaces_shading() {
+                frame_graph.create_image(
+                    OBJECT_ID_MULTISAMPLED,
+                    TransientImageDesc::new(OBJECT_ID_FORMAT, ImageSize::Backbuffer)
+                        .with_samples(scene_samples),
                 );
-                device.cmd_bind_descriptor_sets(
-                    command_buffer,
-                    vk::PipelineBindPoint::GRAPHICS,
-                    material.pipeline_layout,
-                    0,
-                    global_descriptor_sets,
-                    global_dynamic_offsets,
+                frame_graph.create_image(
+                    OBJECT_DEPTH_MULTISAMPLED,
+                    TransientImageDesc::new(OBJECT_DEPTH_FORMAT, ImageSize::Backbuffer)
+                        .with_samples(scene_samples),
                 );
-                device.cmd_bind_descriptor_sets(
-                    command_buffer,
-                    vk::PipelineBindPoint::GRAPHICS,
-                    material.pipeline_layout,
-                    1,
-                    entity_descriptor_sets,
-                    &[],
-                )
             }
         }
 
-        if material.descriptor_set != Default::default() {
-            unsafe {
-                device.cmd_bind_descriptor_sets(
-                    command_buffer,
-                    vk::PipelineBindPoint::GRAPHICS,
-                    material.pipeline_layout,
-                    2,
-                    &[material.descriptor_set],
-                    &[],
-                );
+        let entity_descriptor_sets = [frame_data.entity_descriptor_set];
+        let global_descriptor_sets = [scene.descriptor_set];
+        let global_dynamic_offsets = [
+            camera_ubo_offset,
+            camera_ubo_offset,
+            light_buffer_offset,
+            shadow_ubo_offset,
+        ];
+
+        // Debug views replacing the shading draw the renderables straight to the backbuffer, none of the scene is lit
+        let debug_view = self.debug_view;
+        let shadow_layer_count = if debug_view.replaces_shading() {
+            0
+        } else {
+            shadow_layout.layer_count
+        };
+
+        add_shadow_passes(
+            &mut frame_graph,
+            resource_manager,
+            renderables,
+            &shadow_layout.ubo.view_projections[..shadow_layer_count],
+            entity_descriptor_sets,
+        );
+
+        const BG: f32 = 0.035;
+        let background = [BG, BG, BG, 1.0];
+        // Shadow maps keep the standard depth range, only the camera's depth buffers follow the depth mode
+        let far_depth = self.config.depth_mode.far_depth();
+
+        let inverse_view_projection = (camera.projection() * camera.view()).inversed();
+        let skybox_push_constants = SkyboxPushConstants::new(camera);
+        let ssao_descriptor_sets = [scene.descriptor_set, ssao.descriptor_set(frame_index)];
+        let ssao_push_constants = SsaoPushConstants {
+            inverse_view_projection,
+            params: Vec4::new(
+                ambient_occlusion.radius,
+                ambient_occlusion.bias,
+                ambient_occlusion
+                    .sample_count
+                    .clamp(1, AmbientOcclusion::MAX_SAMPLE_COUNT) as f32,
+                ambient_occlusion.intensity,
+            ),
+        };
+
+        let debug_view_push_constants = DebugViewPushConstants {
+            params: Vec4::new(
+                debug_view.index() as f32,
+                camera.z_near(),
+                camera.z_far(),
+                0.0,
+            ),
+            color: WIREFRAME_COLOR,
+        };
+
+        if debug_view.replaces_shading() {
+            add_debug_view_passes(
+                &mut frame_graph,
+                resource_manager,
+                opaque_renderables
+                    .iter()
+                    .chain(transparent_renderables.iter())
+                    .copied()
+                    .collect(),
+                debug_view,
+                picking,
+                debug_view_push_constants,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+                entity_descriptor_sets,
+                self.depth_attachment_format,
+                far_depth,
+                frame_index,
+            );
+        } else if deferred {
+            add_gbuffer_pass(
+                &mut frame_graph,
+                resource_manager,
+                &opaque_renderables,
+                picking,
+                far_depth,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+                entity_descriptor_sets,
+            );
+
+            if ambient_occlusion.enabled {
+                add_ssao_passes(
+                    &mut frame_graph,
+                    resource_manager,
+                    DEPTH_BUFFER,
+                    GBUFFER_NORMAL,
+                    ssao_descriptor_sets,
+                    global_dynamic_offsets,
+                    ssao_push_constants,
+                );
+            }
+
+            let push_constants = DeferredPushConstants {
+                inverse_view_projection,
+                params: Vec4::new(
+                    self.gbuffer_view
+                        .map_or(0.0, |channel| channel.index() as f32),
+                    camera.z_near(),
+                    camera.z_far(),
+                    0.0,
+                ),
+            };
+            let descriptor_sets = [
+                scene.descriptor_set,
+                frame_data.entity_descriptor_set,
+                resource_manager.get_gbuffer().descriptor_set(frame_index),
+            ];
+
+            add_deferred_lighting_pass(
+                &mut frame_graph,
+                resource_manager,
+                self.gbuffer_view.is_some(),
+                environment,
+                background,
+                push_constants,
+                skybox_push_constants,
+                descriptor_sets,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+            );
+
+            // Transparent materials never reach the G-buffer, they are shaded forward over the lit scene
+            if self.gbuffer_view.is_none() && !transparent_renderables.is_empty() {
+                add_transparent_pass(
+                    &mut frame_graph,
+                    resource_manager,
+                    &transparent_renderables,
+                    picking,
+                    global_descriptor_sets,
+                    global_dynamic_offsets,
+                    entity_descriptor_sets,
+                );
+            }
+        } else {
+            // The forward pass can't share its possibly multisampled depth, ambient occlusion gets a prepass of its own
+            if ambient_occlusion.enabled {
+                add_depth_normal_pass(
+                    &mut frame_graph,
+                    resource_manager,
+                    &opaque_renderables,
+                    self.depth_attachment_format,
+                    far_depth,
+                    global_descriptor_sets,
+                    global_dynamic_offsets,
+                    entity_descriptor_sets,
+                );
+
+                add_ssao_passes(
+                    &mut frame_graph,
+                    resource_manager,
+                    SSAO_DEPTH,
+                    SSAO_NORMALS,
+                    ssao_descriptor_sets,
+                    global_dynamic_offsets,
+                    ssao_push_constants,
+                );
+            }
+
+            add_forward_pass(
+                &mut frame_graph,
+                resource_manager,
+                (&opaque_renderables, &transparent_renderables),
+                multisampled,
+                picking,
+                background,
+                far_depth,
+                environment,
+                skybox_push_constants,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+                entity_descriptor_sets,
+            );
+        }
+
+        // Overlays go over the finished scene, before post-processing so MSAA smooths them too. G-buffer and shading
+        // replacing debug views have no lit scene to draw them over.
+        let scene_overlays =
+            !(debug_view.replaces_shading() || deferred && self.gbuffer_view.is_some());
+
+        if scene_overlays && debug_view == DebugViewMode::Wireframe && self.wireframe_supported {
+            let pipeline_id = if deferred {
+                DEFERRED_WIREFRAME_PIPELINE
+            } else {
+                WIREFRAME_PIPELINE
+            };
+
+            add_wireframe_pass(
+                &mut frame_graph,
+                resource_manager,
+                opaque_renderables
+                    .iter()
+                    .chain(transparent_renderables.iter())
+                    .copied()
+                    .collect(),
+                pipeline_id,
+                multisampled,
+                debug_view_push_constants,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+                entity_descriptor_sets,
+            );
+        }
+
+        if scene_overlays && !debug_vertices.is_empty() {
+            let pipelines = if deferred {
+                (
+                    DEFERRED_DEBUG_LINE_PIPELINE,
+                    DEFERRED_DEBUG_LINE_OVERLAY_PIPELINE,
+                )
+            } else {
+                (DEBUG_LINE_PIPELINE, DEBUG_LINE_OVERLAY_PIPELINE)
+            };
+
+            add_debug_lines_pass(
+                &mut frame_graph,
+                resource_manager,
+                pipelines,
+                frame_data.debug_vertex_buffer.get(),
+                (
+                    depth_tested_debug_vertex_count,
+                    debug_vertices.len() as u32 - depth_tested_debug_vertex_count,
+                ),
+                multisampled,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+            );
+        }
+
+        if scene_overlays && !billboard_draws.is_empty() {
+            let pipeline_id = if deferred {
+                DEFERRED_BILLBOARD_PIPELINE
+            } else {
+                BILLBOARD_PIPELINE
+            };
+
+            add_billboards_pass(
+                &mut frame_graph,
+                resource_manager,
+                pipeline_id,
+                frame_data.billboard_vertex_buffer.get(),
+                billboard_draws,
+                multisampled,
+                global_descriptor_sets,
+                global_dynamic_offsets,
+            );
+        }
+
+        if let Some(pixel) = pick {
+            add_pick_readback_pass(&mut frame_graph, frame_data.pick_buffer.get(), pixel);
+        }
+
+        // G-buffer and shading replacing debug views go straight to the backbuffer
+        if !debug_view.replaces_shading() && (!deferred || self.gbuffer_view.is_none()) {
+            add_post_process_passes(
+                &mut frame_graph,
+                resource_manager,
+                post_process,
+                frame_index,
+                surface_extent,
+                swapchain_resource.surface_format(),
+            );
+        }
+
+        // Hovered renderables first, so selected ones overwrite their stencil where they overlap
+        let mut highlighted_renderables = opaque_renderables
+            .iter()
+            .chain(transparent_renderables.iter())
+            .filter(|draw| draw.renderable.highlight != Highlight::None)
+            .copied()
+            .collect::<SortedDraws>();
+        highlighted_renderables
+            .sort_by_key(|draw| draw.renderable.highlight == Highlight::Selected);
+
+        if let Some(selection_format) = self.selection_format {
+            if selection_outline.enabled && !highlighted_renderables.is_empty() {
+                add_selection_passes(
+                    &mut frame_graph,
+                    resource_manager,
+                    selection_outline,
+                    highlighted_renderables,
+                    global_descriptor_sets,
+                    global_dynamic_offsets,
+                    entity_descriptor_sets,
+                    selection_format,
+                    frame_index,
+                );
+            }
+        }
+
+        // Sprites go over everything else, in screen space
+        if !sprite_draws.is_empty() {
+            add_sprites_pass(
+                &mut frame_graph,
+                resource_manager,
+                frame_data.sprite_vertex_buffer.get(),
+                sprite_draws,
+            );
+        }
+
+        render_graph.execute(
+            device,
+            allocator_handle,
+            frame_data.command_buffer,
+            frame_graph,
+        );
+
+        unsafe {
+            device
+                .end_command_buffer(frame_data.command_buffer)
+                .expect("VkBackend::draw - Failed to end command buffer!");
+        }
+
+        let wait_dst_stage_mask = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+        let present_semaphores = [frame_data.present_semaphore];
+
+        let render_semaphores = [frame_data.render_semaphore];
+
+        let submits = [vk::SubmitInfo::builder()
+            .wait_dst_stage_mask(&wait_dst_stage_mask)
+            .wait_semaphores(&present_semaphores)
+            .signal_semaphores(&render_semaphores)
+            .command_buffers(&[frame_data.command_buffer])
+            .build()];
+
+        unsafe {
+            device
+                .queue_submit(*graphics_queue, &submits, frame_data.render_fence)
+                .expect("VkBackend::DeviceHandle::draw - Failed to submit to queue!")
+        }
+
+        let swapchains = [swapchain_khr];
+        let image_indices = [swapchain_image_index];
+        let present_info = vk::PresentInfoKHR::builder()
+            .swapchains(&swapchains)
+            .wait_semaphores(&render_semaphores)
+            .image_indices(&image_indices);
+
+        unsafe {
+            swapchain
+                .queue_present(*present_queue, &present_info)
+                .expect("VkBackend::draw - Failed to present swapchain image!");
+        }
+
+        self.frame_counter += 1;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn set_msaa_samples(&mut self, samples: u32) {
+        let msaa_samples =
+            VkDepthBuffer::find_supported_sample_count(&self.physical_device_handle, samples);
+        if msaa_samples == self.msaa_samples {
+            return;
+        }
+
+        let VkRenderer {
+            config,
+            device_handle,
+            resource_manager,
+            depth_attachment_format,
+            wireframe_supported,
+            ..
+        } = self;
+
+        let device = &device_handle.device;
+
+        // Pipelines and the render pass they were created against may still be in use by frames in flight
+        unsafe {
+            device
+                .device_wait_idle()
+                .expect("VkBackend::set_msaa_samples - Failed to wait for device!");
+        }
+
+        config.msaa_samples = samples;
+        self.msaa_samples = msaa_samples;
+
+        let render_pass = resource_manager.create_render_pass(
+            device,
+            None,
+            Some(HDR_COLOR_FORMAT),
+            *depth_attachment_format,
+            msaa_samples,
+        );
+        resource_manager.create_forward_picking_render_pass(
+            device,
+            *depth_attachment_format,
+            msaa_samples,
+        );
+        resource_manager.rebuild_material_pipelines(device, &render_pass, msaa_samples);
+        resource_manager.rebuild_skybox_pipeline(device, &render_pass, msaa_samples);
+        resource_manager.rebuild_debug_line_pipelines(device, &render_pass, msaa_samples);
+        resource_manager.rebuild_billboard_pipeline(device, &render_pass, msaa_samples);
+        if *wireframe_supported {
+            resource_manager.rebuild_wireframe_pipeline(device, &render_pass, msaa_samples);
+        }
+
+        // The render graph reallocates the scene targets and their framebuffers on the next frame
+        info!(
+            "VkBackend::set_msaa_samples - Using {} samples per pixel",
+            msaa_samples.as_raw()
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn msaa_samples(&self) -> u32 {
+        self.msaa_samples.as_raw()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn set_render_path(&mut self, render_path: RenderPath) {
+        // Both paths have their pipelines created up front, the render graph reallocates the scene targets
+        self.config.render_path = render_path;
+        self.render_path = render_path;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn render_path(&self) -> RenderPath {
+        self.render_path
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn set_gbuffer_view(&mut self, channel: Option<GBufferChannel>) {
+        self.gbuffer_view = channel;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn set_debug_view_mode(&mut self, mode: DebugViewMode) {
+        self.debug_view = mode;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn debug_view_mode(&self) -> DebugViewMode {
+        self.debug_view
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn set_environment(&mut self, environment: Option<Texture>) {
+        let VkRenderer {
+            device_handle,
+            resource_manager,
+            allocator_handle,
+            ..
+        } = self;
+
+        let device = &device_handle.device;
+
+        // The previous environment may still be sampled by frames in flight
+        unsafe {
+            device
+                .device_wait_idle()
+                .expect("VkBackend::set_environment - Failed to wait for device!");
+        }
+
+        let command_pool = resource_manager
+            .get_command_pool("upload")
+            .expect("Failed to obtain upload command pool!")
+            .get();
+        let fence = resource_manager
+            .get_fence("upload")
+            .expect("Failed to obtain upload fence!")
+            .get();
+
+        resource_manager.set_environment(
+            device,
+            allocator_handle,
+            command_pool,
+            fence,
+            &device_handle.graphics_queue,
+            environment,
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn load_texture(&mut self, texture: Texture) {
+        let VkRenderer {
+            device_handle,
+            resource_manager,
+            allocator_handle,
+            ..
+        } = self;
+
+        // Materials and sprite descriptor sets keep the views of loaded textures, so they are never replaced
+        if resource_manager.get_texture(&texture.name).is_some() {
+            warn!(
+                "VkBackend::load_texture - Texture {} is already loaded, ignoring it!",
+                texture.name
+            );
+            return;
+        }
+
+        let command_pool = resource_manager
+            .get_command_pool("upload")
+            .expect("Failed to obtain upload command pool!")
+            .get();
+        let fence = resource_manager
+            .get_fence("upload")
+            .expect("Failed to obtain upload fence!")
+            .get();
+
+        resource_manager.create_texture(
+            texture,
+            &device_handle.device,
+            command_pool,
+            fence,
+            &device_handle.graphics_queue,
+            allocator_handle,
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn request_pick(&mut self, x: u32, y: u32) {
+        self.pending_pick = Some((x, y));
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn take_pick_result(&mut self) -> Option<PickResult> {
+        self.pick_results.pop_front()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn await_device_idle(&mut self) {
+        unsafe {
+            self.device()
+                .device_wait_idle()
+                .expect("VkBackend::await_device_idle - Failed to wait for dev to become idle!");
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Draws `draws` in order, binding a material and mesh only when they differ from the previous draw's. `picking` selects
+/// the material pipelines that also write the object id and depth targets, which the pass must have as its last color
+/// attachments.
+#[allow(clippy::too_many_arguments)]
+fn draw_renderables(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    draws: &[SortedDraw],
+    pass: VkMaterialPass,
+    picking: bool,
+    global_descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+    entity_descriptor_sets: &[vk::DescriptorSet],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let mut last_mesh = None;
+    let mut last_material = None;
+    let mut vertex_count = 0;
+    for draw in draws.iter() {
+        if last_material != Some(draw.material_id) {
+            last_material = Some(draw.material_id);
+
+            let material = resource_manager.get_material(&draw.renderable.material_name);
+            unsafe {
+                device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    material.pipeline(pass, picking),
+                );
+                device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    material.pipeline_layout,
+                    0,
+                    global_descriptor_sets,
+                    global_dynamic_offsets,
+                );
+                device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    material.pipeline_layout,
+                    1,
+                    entity_descriptor_sets,
+                    &[],
+                );
+                if material.descriptor_set != Default::default() {
+                    device.cmd_bind_descriptor_sets(
+                        command_buffer,
+                        vk::PipelineBindPoint::GRAPHICS,
+                        material.pipeline_layout,
+                        2,
+                        &[material.descriptor_set],
+                        &[],
+                    );
+                }
             }
         }
 
@@ -540,9 +1537,1793 @@ fn draw_renderables(
         //     );
         // }
 
-        let mesh_resource = resource_manager.get_mesh(mesh_name);
+        if last_mesh != Some(draw.mesh_id) {
+            last_mesh = Some(draw.mesh_id);
+
+            let mesh_resource = resource_manager.get_mesh(&draw.renderable.mesh_name);
+            vertex_count = mesh_resource.get_mesh().vertices.len() as u32;
+            unsafe {
+                device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[mesh_resource.get_buffer().get()],
+                    &[0],
+                );
+            }
+        }
+
+        unsafe {
+            device.cmd_draw(command_buffer, vertex_count, 1, 0, draw.index);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// A renderable in draw order, with its index into the entity buffers and the sort ids of its material and mesh, which
+/// tell whether consecutive draws share state without comparing names.
+#[derive(Clone, Copy)]
+struct SortedDraw<'a> {
+    index: u32,
+    renderable: &'a Renderable,
+    material_id: u32,
+    mesh_id: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+type SortedDraws<'a> = Vec<SortedDraw<'a>>;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Sorts the draws of `renderables` by their `DrawKey` and splits them into the opaque and cutout ones, grouped by state
+/// and front-to-back, and the transparent ones, back-to-front.
+fn sort_renderables<'a>(
+    draw_list: &mut DrawList,
+    resource_manager: &ResourceManager,
+    renderables: &'a [Renderable],
+    frustum: &CameraFrustum,
+) -> (SortedDraws<'a>, SortedDraws<'a>) {
+    draw_list.clear();
+
+    let mut draws = Vec::with_capacity(renderables.len());
+    for (i, renderable) in renderables.iter().enumerate() {
+        let material = resource_manager.get_material(&renderable.material_name);
+        let mesh = resource_manager.get_mesh(&renderable.mesh_name);
+
+        let layer = if material.is_transparent() {
+            DrawLayer::Transparent
+        } else {
+            DrawLayer::Opaque
+        };
+
+        // Depth of the bounds center along the view direction
+        let (min, max) = mesh.bounds();
+        let center = renderable.transform * ((min + max) * 0.5).into_homogeneous_point();
+
+        draw_list.push(
+            DrawKey::new(
+                layer,
+                material.pipeline_sort_id,
+                material.sort_id,
+                mesh.sort_id(),
+                frustum.depth(center.truncated()),
+                (frustum.near, frustum.far),
+            ),
+            i as u32,
+        );
+        draws.push(SortedDraw {
+            index: i as u32,
+            renderable,
+            material_id: material.sort_id,
+            mesh_id: mesh.sort_id(),
+        });
+    }
+
+    draw_list.sort();
+
+    // Layers are the most significant bits of the keys, so the transparent draws follow the opaque ones
+    let transparent_start = draw_list
+        .items()
+        .iter()
+        .position(|item| item.key.layer() == DrawLayer::Transparent)
+        .unwrap_or(renderables.len());
+
+    let mut opaque = draw_list
+        .items()
+        .iter()
+        .map(|item| draws[item.index as usize])
+        .collect::<SortedDraws>();
+    let transparent = opaque.split_off(transparent_start);
+
+    (opaque, transparent)
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds a pass drawing the shadow casting `renderables` into each shadow map layer, one per light view projection.
+fn add_shadow_passes<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    renderables: &'a [Renderable],
+    light_view_projections: &[Mat4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+) {
+    for (layer, &light_view_projection) in light_view_projections.iter().enumerate() {
+        frame_graph.add_pass(
+            GraphPass::new(&format!("shadow_{}", layer))
+                .depth_attachment_layer(SHADOW_MAPS, layer as u32, Some(1.0))
+                .buffer(
+                    ENTITY_BUFFER,
+                    vk::PipelineStageFlags::VERTEX_SHADER,
+                    vk::AccessFlags::SHADER_READ,
+                )
+                .record(move |context| {
+                    draw_shadow_map(
+                        context.device,
+                        context.command_buffer,
+                        resource_manager,
+                        renderables,
+                        light_view_projection,
+                        &entity_descriptor_sets,
+                    )
+                }),
+        );
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the G-buffer targets and the pass filling them with the opaque `renderables` of the deferred path.
+#[allow(clippy::too_many_arguments)]
+fn add_gbuffer_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    renderables: &'a [SortedDraw<'a>],
+    picking: bool,
+    far_depth: f32,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+) {
+    for (name, format) in GBUFFER_COLORS.iter().zip(GBUFFER_COLOR_FORMATS.iter()) {
+        frame_graph.create_image(
+            name,
+            TransientImageDesc::new(*format, ImageSize::Backbuffer),
+        );
+    }
+
+    let mut gbuffer_pass = GraphPass::new("gbuffer");
+    for name in GBUFFER_COLORS.iter() {
+        gbuffer_pass = gbuffer_pass.color_attachment(name, Some([0.0, 0.0, 0.0, 0.0]));
+    }
+    if picking {
+        gbuffer_pass = picking_attachments(gbuffer_pass, false, true);
+    }
+
+    frame_graph.add_pass(
+        gbuffer_pass
+            .depth_attachment(DEPTH_BUFFER, Some(far_depth))
+            .buffer(
+                ENTITY_BUFFER,
+                vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                set_viewport(context);
+                draw_renderables(
+                    context,
+                    resource_manager,
+                    renderables,
+                    VkMaterialPass::GBuffer,
+                    picking,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                )
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the full-screen pass lighting the G-buffer into the HDR color image, over the skybox if there is an
+/// environment. `gbuffer_debug` shows a single channel on the backbuffer instead. `descriptor_sets` are the global,
+/// entity and G-buffer sets.
+#[allow(clippy::too_many_arguments)]
+fn add_deferred_lighting_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    gbuffer_debug: bool,
+    environment: bool,
+    background: [f32; 4],
+    push_constants: DeferredPushConstants,
+    skybox_push_constants: SkyboxPushConstants,
+    descriptor_sets: [vk::DescriptorSet; 3],
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+) {
+    // Debug views replace lighting and the post-process chain, and show a single channel as is
+    let (mut deferred_pass, pipeline_id) = if gbuffer_debug {
+        (
+            GraphPass::new("gbuffer_debug")
+                .color_attachment(BACKBUFFER, Some([0.0, 0.0, 0.0, 1.0])),
+            GBUFFER_DEBUG_PIPELINE,
+        )
+    } else {
+        (
+            GraphPass::new("deferred_lighting").color_attachment(HDR_COLOR, Some(background)),
+            DEFERRED_LIGHTING_PIPELINE,
+        )
+    };
+    for name in GBUFFER_COLORS.iter() {
+        deferred_pass = deferred_pass.sampled_image(name);
+    }
+
+    frame_graph.add_pass(
+        deferred_pass
+            .sampled_image(DEPTH_BUFFER)
+            .sampled_image(SHADOW_MAPS)
+            .sampled_image(AMBIENT_OCCLUSION)
+            .buffer(
+                LIGHT_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                CLUSTER_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                CLUSTER_LIGHT_INDEX_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                // Lighting leaves the pixels without geometry untouched
+                if environment && !gbuffer_debug {
+                    draw_fullscreen_with_sets(
+                        context,
+                        resource_manager,
+                        (SKYBOX_BACKGROUND_PIPELINE, SKYBOX_PIPELINE_LAYOUT),
+                        &global_descriptor_sets,
+                        &global_dynamic_offsets,
+                        &skybox_push_constants,
+                    );
+                }
+
+                resource_manager.get_gbuffer().write_descriptor_set(
+                    context.device,
+                    descriptor_sets[2],
+                    [
+                        context.image_view(GBUFFER_ALBEDO),
+                        context.image_view(GBUFFER_NORMAL),
+                        context.image_view(GBUFFER_MATERIAL),
+                        context.image_view(GBUFFER_EMISSIVE),
+                    ],
+                    context.image_view(DEPTH_BUFFER),
+                );
+
+                draw_fullscreen_with_sets(
+                    context,
+                    resource_manager,
+                    (pipeline_id, DEFERRED_LIGHTING_PIPELINE_LAYOUT),
+                    &descriptor_sets,
+                    &global_dynamic_offsets,
+                    &push_constants,
+                );
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the pass shading the transparent `renderables` of the deferred path forward, over the lit scene.
+fn add_transparent_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    renderables: &'a [SortedDraw<'a>],
+    picking: bool,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+) {
+    let mut transparent_pass = GraphPass::new("transparent").color_attachment(HDR_COLOR, None);
+    if picking {
+        transparent_pass = picking_attachments(transparent_pass, false, false);
+    }
+
+    frame_graph.add_pass(
+        transparent_pass
+            .depth_attachment(DEPTH_BUFFER, None)
+            .sampled_image(SHADOW_MAPS)
+            .sampled_image(AMBIENT_OCCLUSION)
+            .buffer(
+                ENTITY_BUFFER,
+                vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                LIGHT_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                CLUSTER_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                CLUSTER_LIGHT_INDEX_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                draw_renderables(
+                    context,
+                    resource_manager,
+                    renderables,
+                    VkMaterialPass::Transparent,
+                    picking,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                )
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the single-sample depth and normal targets of ambient occlusion in the forward path, and the prepass drawing
+/// the opaque `renderables` into them.
+#[allow(clippy::too_many_arguments)]
+fn add_depth_normal_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    renderables: &'a [SortedDraw<'a>],
+    depth_attachment_format: vk::Format,
+    far_depth: f32,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+) {
+    frame_graph.create_image(
+        SSAO_DEPTH,
+        TransientImageDesc::new(depth_attachment_format, ImageSize::Backbuffer),
+    );
+    frame_graph.create_image(
+        SSAO_NORMALS,
+        TransientImageDesc::new(SSAO_NORMAL_FORMAT, ImageSize::Backbuffer),
+    );
+
+    frame_graph.add_pass(
+        GraphPass::new("depth_normal")
+            .color_attachment(SSAO_NORMALS, Some([0.0, 0.0, 0.0, 0.0]))
+            .depth_attachment(SSAO_DEPTH, Some(far_depth))
+            .buffer(
+                ENTITY_BUFFER,
+                vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                draw_depth_normals(
+                    context,
+                    resource_manager,
+                    renderables,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                )
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the forward pass shading the opaque and then the transparent draws of `renderables` into the HDR color image,
+/// with the skybox in between if there is an environment. Multisampled scenes are resolved at the end of the pass.
+#[allow(clippy::too_many_arguments)]
+fn add_forward_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    renderables: (&'a [SortedDraw<'a>], &'a [SortedDraw<'a>]),
+    multisampled: bool,
+    picking: bool,
+    background: [f32; 4],
+    far_depth: f32,
+    environment: bool,
+    skybox_push_constants: SkyboxPushConstants,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+) {
+    let (opaque_renderables, transparent_renderables) = renderables;
+
+    let mut forward_pass = if multisampled {
+        GraphPass::new("forward").resolved_color_attachment(
+            HDR_COLOR_MULTISAMPLED,
+            HDR_COLOR,
+            Some(background),
+        )
+    } else {
+        GraphPass::new("forward").color_attachment(HDR_COLOR, Some(background))
+    };
+    if picking {
+        forward_pass = picking_attachments(forward_pass, multisampled, true);
+    }
+    let skybox_pipeline = if picking {
+        SKYBOX_PICKING_PIPELINE
+    } else {
+        SKYBOX_PIPELINE
+    };
+
+    frame_graph.add_pass(
+        forward_pass
+            .depth_attachment(DEPTH_BUFFER, Some(far_depth))
+            .sampled_image(SHADOW_MAPS)
+            .sampled_image(AMBIENT_OCCLUSION)
+            .buffer(
+                ENTITY_BUFFER,
+                vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                LIGHT_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                CLUSTER_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .buffer(
+                CLUSTER_LIGHT_INDEX_BUFFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                draw_renderables(
+                    context,
+                    resource_manager,
+                    opaque_renderables,
+                    VkMaterialPass::Forward,
+                    picking,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                );
+
+                // After the opaques, so the depth test rejects every pixel already covered by geometry
+                if environment {
+                    draw_fullscreen_with_sets(
+                        context,
+                        resource_manager,
+                        (skybox_pipeline, SKYBOX_PIPELINE_LAYOUT),
+                        &global_descriptor_sets,
+                        &global_dynamic_offsets,
+                        &skybox_push_constants,
+                    );
+                }
+
+                // Transparents don't write depth, so they are blended over the background as well
+                draw_renderables(
+                    context,
+                    resource_manager,
+                    transparent_renderables,
+                    VkMaterialPass::Forward,
+                    picking,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                );
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Pass drawing over the finished scene, resolving it again if it is multisampled.
+fn overlay_pass<'a>(name: &str, multisampled: bool) -> GraphPass<'a> {
+    let pass = if multisampled {
+        GraphPass::new(name).resolved_color_attachment(HDR_COLOR_MULTISAMPLED, HDR_COLOR, None)
+    } else {
+        GraphPass::new(name).color_attachment(HDR_COLOR, None)
+    };
+    pass.depth_attachment(DEPTH_BUFFER, None)
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the overlay pass drawing the edges of `renderables` with the wireframe pipeline `pipeline_id`.
+#[allow(clippy::too_many_arguments)]
+fn add_wireframe_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    renderables: SortedDraws<'a>,
+    pipeline_id: &'static str,
+    multisampled: bool,
+    push_constants: DebugViewPushConstants,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+) {
+    frame_graph.add_pass(
+        overlay_pass("wireframe", multisampled)
+            .buffer(
+                ENTITY_BUFFER,
+                vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                draw_debug_view(
+                    context,
+                    resource_manager,
+                    &renderables,
+                    pipeline_id,
+                    &push_constants,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                )
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the overlay pass drawing the debug lines of `vertex_buffer`, see `draw_debug_lines`.
+#[allow(clippy::too_many_arguments)]
+fn add_debug_lines_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    pipelines: (&'static str, &'static str),
+    vertex_buffer: vk::Buffer,
+    vertex_counts: (u32, u32),
+    multisampled: bool,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+) {
+    frame_graph.add_pass(
+        overlay_pass("debug_lines", multisampled).record(move |context| {
+            draw_debug_lines(
+                context,
+                resource_manager,
+                pipelines,
+                vertex_buffer,
+                vertex_counts,
+                &global_descriptor_sets,
+                &global_dynamic_offsets,
+            )
+        }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the overlay pass drawing the billboard batches `draws` from `vertex_buffer`.
+#[allow(clippy::too_many_arguments)]
+fn add_billboards_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    pipeline_id: &'static str,
+    vertex_buffer: vk::Buffer,
+    draws: Vec<SpriteDraw>,
+    multisampled: bool,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+) {
+    frame_graph.add_pass(
+        overlay_pass("billboards", multisampled).record(move |context| {
+            draw_billboards(
+                context,
+                resource_manager,
+                pipeline_id,
+                vertex_buffer,
+                &draws,
+                &global_descriptor_sets,
+                &global_dynamic_offsets,
+            )
+        }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the pass drawing the sprite batches `draws` from `vertex_buffer` over the backbuffer, in screen space.
+fn add_sprites_pass<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    vertex_buffer: vk::Buffer,
+    draws: Vec<SpriteDraw>,
+) {
+    frame_graph.add_pass(
+        GraphPass::new("sprites")
+            .color_attachment(BACKBUFFER, None)
+            .record(move |context| draw_sprites(context, resource_manager, vertex_buffer, &draws)),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the post-process chain that takes the HDR scene color to the backbuffer: an optional bloom downsample and
+/// upsample chain, a combined exposure, tonemapping, vignette and color grading pass, and optionally FXAA.
+fn add_post_process_passes<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    post_process: &PostProcess,
+    frame_index: usize,
+    surface_extent: vk::Extent2D,
+    output_format: vk::Format,
+) {
+    let color_grading_lut = post_process
+        .color_grading_lut
+        .as_ref()
+        .and_then(|lut| resource_manager.get_texture(lut))
+        .or_else(|| resource_manager.get_texture(DEFAULT_COLOR_GRADING_LUT))
+        .expect("VkBackend::draw - Failed to obtain color grading LUT!")
+        .image_view();
+
+    let mut pass_slot = 0;
+    let mut add_fullscreen_pass =
+        |frame_graph: &mut FrameGraph<'a>,
+         pipeline_id: &'static str,
+         output: &str,
+         source: &str,
+         secondary: Option<&str>,
+         push_constants: PostProcessPushConstants| {
+            let descriptor_set = resource_manager
+                .get_post_process()
+                .descriptor_set(frame_index, pass_slot);
+            pass_slot += 1;
+
+            let source = source.to_owned();
+            let secondary = secondary.unwrap_or(&source).to_owned();
+
+            let mut pass = GraphPass::new(&format!("{}_{}", pipeline_id, output))
+                .color_attachment(output, Some([0.0, 0.0, 0.0, 1.0]))
+                .sampled_image(&source);
+            if secondary != source {
+                pass = pass.sampled_image(&secondary);
+            }
+
+            frame_graph.add_pass(pass.record(move |context| {
+                resource_manager.get_post_process().write_descriptor_set(
+                    context.device,
+                    descriptor_set,
+                    context.image_view(&source),
+                    context.image_view(&secondary),
+                    color_grading_lut,
+                );
+
+                draw_fullscreen(
+                    context,
+                    resource_manager,
+                    pipeline_id,
+                    descriptor_set,
+                    &push_constants,
+                );
+            }));
+        };
+
+    let bloom = if post_process.bloom_enabled {
+        let mut source = HDR_COLOR.to_owned();
+        let mut source_extent = surface_extent;
+
+        // Downsample into successively halved levels, thresholding the first one
+        for level in 0..BLOOM_MIP_COUNT {
+            let size = ImageSize::Scaled(0.5_f32.powi(level as i32 + 1));
+            let target = format!("bloom_down_{}", level);
+            frame_graph.create_image(&target, TransientImageDesc::new(HDR_COLOR_FORMAT, size));
+
+            let prefilter = if level == 0 { 1.0 } else { 0.0 };
+            add_fullscreen_pass(
+                frame_graph,
+                BLOOM_DOWNSAMPLE_PIPELINE,
+                &target,
+                &source,
+                None,
+                PostProcessPushConstants::new(
+                    Vec4::new(
+                        prefilter,
+                        post_process.bloom_threshold,
+                        post_process.bloom_knee,
+                        0.0,
+                    ),
+                    Vec4::zero(),
+                    source_extent,
+                ),
+            );
+
+            source = target;
+            source_extent = size.extent(surface_extent);
+        }
+
+        // Upsample back up the chain, accumulating every level on the way
+        for level in (0..BLOOM_MIP_COUNT - 1).rev() {
+            let size = ImageSize::Scaled(0.5_f32.powi(level as i32 + 1));
+            let target = format!("bloom_up_{}", level);
+            frame_graph.create_image(&target, TransientImageDesc::new(HDR_COLOR_FORMAT, size));
+
+            add_fullscreen_pass(
+                frame_graph,
+                BLOOM_UPSAMPLE_PIPELINE,
+                &target,
+                &source,
+                Some(&format!("bloom_down_{}", level)),
+                PostProcessPushConstants::new(
+                    Vec4::new(1.0, 0.0, 0.0, 0.0),
+                    Vec4::zero(),
+                    source_extent,
+                ),
+            );
+
+            source = target;
+            source_extent = size.extent(surface_extent);
+        }
+
+        Some(source)
+    } else {
+        None
+    };
+
+    let tonemap_output = if post_process.fxaa_enabled {
+        frame_graph.create_image(
+            LDR_COLOR,
+            TransientImageDesc::new(output_format, ImageSize::Backbuffer),
+        );
+        LDR_COLOR
+    } else {
+        BACKBUFFER
+    };
+
+    let exposure = if post_process.exposure_enabled {
+        2.0_f32.powf(post_process.exposure)
+    } else {
+        1.0
+    };
+    let bloom_intensity = match bloom {
+        Some(_) => post_process.bloom_intensity,
+        None => 0.0,
+    };
+    let vignette_intensity = if post_process.vignette_enabled {
+        post_process.vignette_intensity
+    } else {
+        0.0
+    };
+    let tonemapper = match (post_process.tonemapping_enabled, post_process.tonemapper) {
+        (false, _) => 0.0,
+        (true, Tonemapper::Reinhard) => 1.0,
+        (true, Tonemapper::Aces) => 2.0,
+    };
+    let color_grading = if post_process.color_grading_enabled {
+        1.0
+    } else {
+        0.0
+    };
+
+    add_fullscreen_pass(
+        frame_graph,
+        TONEMAP_PIPELINE,
+        tonemap_output,
+        HDR_COLOR,
+        bloom.as_deref(),
+        PostProcessPushConstants::new(
+            Vec4::new(
+                exposure,
+                bloom_intensity,
+                vignette_intensity,
+                post_process.vignette_smoothness,
+            ),
+            Vec4::new(tonemapper, color_grading, 0.0, 0.0),
+            surface_extent,
+        ),
+    );
+
+    if post_process.fxaa_enabled {
+        add_fullscreen_pass(
+            frame_graph,
+            FXAA_PIPELINE,
+            BACKBUFFER,
+            LDR_COLOR,
+            None,
+            PostProcessPushConstants::new(Vec4::zero(), Vec4::zero(), surface_extent),
+        );
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn draw_fullscreen(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    pipeline_id: &str,
+    descriptor_set: vk::DescriptorSet,
+    push_constants: &PostProcessPushConstants,
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let pipeline = resource_manager.get_pipeline(pipeline_id).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(POST_PROCESS_PIPELINE_LAYOUT)
+        .get();
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+    }
+    set_viewport(context);
+
+    unsafe {
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            &[descriptor_set],
+            &[],
+        );
+        device.cmd_push_constants(
+            command_buffer,
+            pipeline_layout,
+            PostProcessPushConstants::get_range().stage_flags,
+            0,
+            ffi::any_as_u8_slice(push_constants),
+        );
+        device.cmd_draw(command_buffer, 3, 1, 0, 0);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the ambient occlusion pass reading `depth` and `normals`, and the depth-aware blur that writes the result to
+/// the ambient occlusion map. `descriptor_sets` are the global and SSAO sets.
+fn add_ssao_passes<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    depth: &'static str,
+    normals: &'static str,
+    descriptor_sets: [vk::DescriptorSet; 2],
+    global_dynamic_offsets: [u32; 4],
+    push_constants: SsaoPushConstants,
+) {
+    frame_graph.create_image(
+        SSAO_RAW,
+        TransientImageDesc::new(SSAO_FORMAT, ImageSize::Backbuffer),
+    );
+
+    frame_graph.add_pass(
+        GraphPass::new("ssao")
+            .color_attachment(SSAO_RAW, Some([1.0, 1.0, 1.0, 1.0]))
+            .sampled_image(depth)
+            .sampled_image(normals)
+            .record(move |context| {
+                // Written once for both passes, updating a set already bound in the command buffer is not allowed
+                resource_manager.get_ssao().write_descriptor_set(
+                    context.device,
+                    descriptor_sets[1],
+                    context.image_view(depth),
+                    context.image_view(normals),
+                    context.image_view(SSAO_RAW),
+                );
+
+                draw_fullscreen_with_sets(
+                    context,
+                    resource_manager,
+                    (SSAO_PIPELINE, SSAO_PIPELINE_LAYOUT),
+                    &descriptor_sets,
+                    &global_dynamic_offsets,
+                    &push_constants,
+                );
+            }),
+    );
+
+    frame_graph.add_pass(
+        GraphPass::new("ssao_blur")
+            .color_attachment(AMBIENT_OCCLUSION, None)
+            .sampled_image(SSAO_RAW)
+            .sampled_image(depth)
+            .record(move |context| {
+                draw_fullscreen_with_sets(
+                    context,
+                    resource_manager,
+                    (SSAO_BLUR_PIPELINE, SSAO_PIPELINE_LAYOUT),
+                    &descriptor_sets,
+                    &global_dynamic_offsets,
+                    &push_constants,
+                );
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Full-screen triangle for passes that read the scene, `descriptor_sets` start with the global set. `pipeline` holds
+/// the pipeline and pipeline layout ids.
+fn draw_fullscreen_with_sets<T>(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    pipeline: (&str, &str),
+    descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+    push_constants: &T,
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let (pipeline_id, pipeline_layout_id) = pipeline;
+    let pipeline = resource_manager.get_pipeline(pipeline_id).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(pipeline_layout_id)
+        .get();
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+    }
+    set_viewport(context);
+
+    unsafe {
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            descriptor_sets,
+            global_dynamic_offsets,
+        );
+        device.cmd_push_constants(
+            command_buffer,
+            pipeline_layout,
+            vk::ShaderStageFlags::FRAGMENT,
+            0,
+            ffi::any_as_u8_slice(push_constants),
+        );
+        device.cmd_draw(command_buffer, 3, 1, 0, 0);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// World space normals and depth of every renderable, the input of ambient occlusion in the forward path.
+fn draw_depth_normals(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    draws: &[SortedDraw],
+    global_descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+    entity_descriptor_sets: &[vk::DescriptorSet],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let pipeline = resource_manager.get_pipeline(DEPTH_NORMAL_PIPELINE).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(DEPTH_NORMAL_PIPELINE_LAYOUT)
+        .get();
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            global_descriptor_sets,
+            global_dynamic_offsets,
+        );
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            1,
+            entity_descriptor_sets,
+            &[],
+        );
+    }
+    set_viewport(context);
+
+    let mut last_mesh = None;
+    let mut vertex_count = 0;
+    for draw in draws.iter() {
+        if last_mesh != Some(draw.mesh_id) {
+            last_mesh = Some(draw.mesh_id);
+
+            let mesh_resource = resource_manager.get_mesh(&draw.renderable.mesh_name);
+            vertex_count = mesh_resource.get_mesh().vertices.len() as u32;
+            unsafe {
+                device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[mesh_resource.get_buffer().get()],
+                    &[0],
+                );
+            }
+        }
+
+        unsafe {
+            device.cmd_draw(command_buffer, vertex_count, 1, 0, draw.index);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the object id and depth targets to a scene pass drawn while a pick is pending, after its other color
+/// attachments. Both are integer targets, so multisampled ones resolve to a single sample instead of an average.
+fn picking_attachments(pass: GraphPass, multisampled: bool, clear: bool) -> GraphPass {
+    let (id_clear, depth_clear) = if clear {
+        (Some([NO_OBJECT_ID; 4]), Some([0; 4]))
+    } else {
+        (None, None)
+    };
+
+    if multisampled {
+        pass.resolved_uint_color_attachment(OBJECT_ID_MULTISAMPLED, OBJECT_ID, id_clear)
+            .resolved_uint_color_attachment(OBJECT_DEPTH_MULTISAMPLED, OBJECT_DEPTH, depth_clear)
+    } else {
+        pass.uint_color_attachment(OBJECT_ID, id_clear)
+            .uint_color_attachment(OBJECT_DEPTH, depth_clear)
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds a copy of the picked pixel of the object id and depth targets into the imported pick buffer.
+fn add_pick_readback_pass(
+    frame_graph: &mut FrameGraph,
+    pick_buffer: vk::Buffer,
+    pixel: (u32, u32),
+) {
+    frame_graph.import_buffer(PICK_BUFFER, pick_buffer);
+    frame_graph.add_pass(
+        GraphPass::new("pick_readback")
+            .copy_source(OBJECT_ID)
+            .copy_source(OBJECT_DEPTH)
+            .buffer(
+                PICK_BUFFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::AccessFlags::TRANSFER_WRITE,
+            )
+            .record(move |context| {
+                let PassContext {
+                    device,
+                    command_buffer,
+                    ..
+                } = *context;
+
+                let (x, y) = pixel;
+
+                // Same layout as PickReadback, the index followed by the depth
+                for &(image, offset) in [(OBJECT_ID, 0), (OBJECT_DEPTH, size_of::<u32>())].iter() {
+                    let region = vk::BufferImageCopy::builder()
+                        .buffer_offset(offset as vk::DeviceSize)
+                        .buffer_row_length(0)
+                        .buffer_image_height(0)
+                        .image_subresource(
+                            vk::ImageSubresourceLayers::builder()
+                                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                                .mip_level(0)
+                                .base_array_layer(0)
+                                .layer_count(1)
+                                .build(),
+                        )
+                        .image_offset(vk::Offset3D {
+                            x: x as i32,
+                            y: y as i32,
+                            z: 0,
+                        })
+                        .image_extent(vk::Extent3D {
+                            width: 1,
+                            height: 1,
+                            depth: 1,
+                        })
+                        .build();
+
+                    unsafe {
+                        device.cmd_copy_image_to_buffer(
+                            command_buffer,
+                            context.image(image),
+                            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                            pick_buffer,
+                            &[region],
+                        );
+                    }
+                }
+
+                // The host reads the buffer once the frame's fence has signaled
+                let host_barrier = vk::BufferMemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .dst_access_mask(vk::AccessFlags::HOST_READ)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .buffer(pick_buffer)
+                    .offset(0)
+                    .size(vk::WHOLE_SIZE)
+                    .build();
+
+                unsafe {
+                    device.cmd_pipeline_barrier(
+                        command_buffer,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::PipelineStageFlags::HOST,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[host_barrier],
+                        &[],
+                    );
+                }
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the selection passes: `renderables` marked in the stencil of a dedicated single-sample image, hovered ones
+/// before selected ones, and the outline pass blending the edges of the marked areas over the backbuffer.
+#[allow(clippy::too_many_arguments)]
+fn add_selection_passes<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    selection_outline: &SelectionOutline,
+    renderables: SortedDraws<'a>,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+    selection_format: vk::Format,
+    frame_index: usize,
+) {
+    frame_graph.create_image(
+        SELECTION_STENCIL,
+        TransientImageDesc::new(selection_format, ImageSize::Backbuffer),
+    );
+
+    // Only the stencil is used, the depth clear value doesn't matter
+    frame_graph.add_pass(
+        GraphPass::new("selection_stencil")
+            .depth_attachment(SELECTION_STENCIL, Some(1.0))
+            .buffer(
+                ENTITY_BUFFER,
+                vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                draw_selection_stencil(
+                    context,
+                    resource_manager,
+                    &renderables,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                )
+            }),
+    );
+
+    let push_constants = SelectionOutlinePushConstants {
+        selected_color: selection_outline.selected_color,
+        hovered_color: selection_outline.hovered_color,
+        params: Vec4::new(
+            selection_outline
+                .thickness
+                .clamp(0.0, SelectionOutline::MAX_THICKNESS),
+            0.0,
+            0.0,
+            0.0,
+        ),
+    };
+    let descriptor_sets = [resource_manager.get_selection().descriptor_set(frame_index)];
+
+    frame_graph.add_pass(
+        GraphPass::new("selection_outline")
+            .color_attachment(BACKBUFFER, None)
+            .sampled_image(SELECTION_STENCIL)
+            .record(move |context| {
+                resource_manager.get_selection().write_descriptor_set(
+                    context.device,
+                    descriptor_sets[0],
+                    context.stencil_view(SELECTION_STENCIL),
+                );
+
+                draw_fullscreen_with_sets(
+                    context,
+                    resource_manager,
+                    (
+                        SELECTION_OUTLINE_PIPELINE,
+                        SELECTION_OUTLINE_PIPELINE_LAYOUT,
+                    ),
+                    &descriptor_sets,
+                    &[],
+                    &push_constants,
+                );
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Adds the pass drawing `renderables` with the debug view shader to the backbuffer. Overdraw is counted into a
+/// separate target first and colored by a fullscreen pass, reusing the post-process descriptor sets the skipped
+/// post-process chain leaves free. While `picking`, the debug view pass also writes the object id and depth targets.
+#[allow(clippy::too_many_arguments)]
+fn add_debug_view_passes<'a>(
+    frame_graph: &mut FrameGraph<'a>,
+    resource_manager: &'a ResourceManager,
+    mut renderables: SortedDraws<'a>,
+    mode: DebugViewMode,
+    picking: bool,
+    push_constants: DebugViewPushConstants,
+    global_descriptor_sets: [vk::DescriptorSet; 1],
+    global_dynamic_offsets: [u32; 4],
+    entity_descriptor_sets: [vk::DescriptorSet; 1],
+    depth_attachment_format: vk::Format,
+    far_depth: f32,
+    frame_index: usize,
+) {
+    let entity_buffer_stages =
+        vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER;
+
+    // Overdraw has no depth test to find the renderable covering a pixel, picks draw the debug view under the heat map
+    if mode != DebugViewMode::Overdraw || picking {
+        let (debug_view_pass, pipeline_id) = if picking {
+            (
+                picking_attachments(
+                    GraphPass::new("debug_view")
+                        .color_attachment(BACKBUFFER, Some([0.0, 0.0, 0.0, 1.0])),
+                    false,
+                    true,
+                ),
+                DEBUG_VIEW_PICKING_PIPELINE,
+            )
+        } else {
+            (
+                GraphPass::new("debug_view")
+                    .color_attachment(BACKBUFFER, Some([0.0, 0.0, 0.0, 1.0])),
+                DEBUG_VIEW_PIPELINE,
+            )
+        };
+        let debug_view_renderables = if mode == DebugViewMode::Overdraw {
+            renderables.clone()
+        } else {
+            mem::take(&mut renderables)
+        };
+
+        frame_graph.create_image(
+            DEBUG_VIEW_DEPTH_BUFFER,
+            TransientImageDesc::new(depth_attachment_format, ImageSize::Backbuffer),
+        );
+
+        frame_graph.add_pass(
+            debug_view_pass
+                .depth_attachment(DEBUG_VIEW_DEPTH_BUFFER, Some(far_depth))
+                .buffer(
+                    ENTITY_BUFFER,
+                    entity_buffer_stages,
+                    vk::AccessFlags::SHADER_READ,
+                )
+                .record(move |context| {
+                    draw_debug_view(
+                        context,
+                        resource_manager,
+                        &debug_view_renderables,
+                        pipeline_id,
+                        &push_constants,
+                        &global_descriptor_sets,
+                        &global_dynamic_offsets,
+                        &entity_descriptor_sets,
+                    )
+                }),
+        );
+
+        if mode != DebugViewMode::Overdraw {
+            return;
+        }
+    }
+
+    frame_graph.create_image(
+        OVERDRAW_COUNT,
+        TransientImageDesc::new(OVERDRAW_COUNT_FORMAT, ImageSize::Backbuffer),
+    );
+
+    frame_graph.add_pass(
+        GraphPass::new("overdraw")
+            .color_attachment(OVERDRAW_COUNT, Some([0.0, 0.0, 0.0, 0.0]))
+            .buffer(
+                ENTITY_BUFFER,
+                entity_buffer_stages,
+                vk::AccessFlags::SHADER_READ,
+            )
+            .record(move |context| {
+                draw_debug_view(
+                    context,
+                    resource_manager,
+                    &renderables,
+                    DEBUG_VIEW_OVERDRAW_PIPELINE,
+                    &push_constants,
+                    &global_descriptor_sets,
+                    &global_dynamic_offsets,
+                    &entity_descriptor_sets,
+                )
+            }),
+    );
+
+    let descriptor_set = resource_manager
+        .get_post_process()
+        .descriptor_set(frame_index, 0);
+    let color_grading_lut = resource_manager
+        .get_texture(DEFAULT_COLOR_GRADING_LUT)
+        .expect("VkBackend::draw - Failed to obtain color grading LUT!")
+        .image_view();
+
+    frame_graph.add_pass(
+        GraphPass::new("overdraw_heat_map")
+            .color_attachment(BACKBUFFER, Some([0.0, 0.0, 0.0, 1.0]))
+            .sampled_image(OVERDRAW_COUNT)
+            .record(move |context| {
+                let overdraw_count = context.image_view(OVERDRAW_COUNT);
+                resource_manager.get_post_process().write_descriptor_set(
+                    context.device,
+                    descriptor_set,
+                    overdraw_count,
+                    overdraw_count,
+                    color_grading_lut,
+                );
+
+                draw_fullscreen(
+                    context,
+                    resource_manager,
+                    DEBUG_VIEW_HEAT_MAP_PIPELINE,
+                    descriptor_set,
+                    &PostProcessPushConstants::new(
+                        Vec4::new(OVERDRAW_HEAT_MAP_MAX, 0.0, 0.0, 0.0),
+                        Vec4::zero(),
+                        context.extent,
+                    ),
+                );
+            }),
+    );
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Draws `renderables` with one of the debug view pipelines, binding the material textures the mip level view samples.
+#[allow(clippy::too_many_arguments)]
+fn draw_debug_view(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    renderables: &[SortedDraw],
+    pipeline_id: &str,
+    push_constants: &DebugViewPushConstants,
+    global_descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+    entity_descriptor_sets: &[vk::DescriptorSet],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let pipeline = resource_manager.get_pipeline(pipeline_id).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(DEBUG_VIEW_PIPELINE_LAYOUT)
+        .get();
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+    }
+    set_viewport(context);
+
+    unsafe {
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            global_descriptor_sets,
+            global_dynamic_offsets,
+        );
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            1,
+            entity_descriptor_sets,
+            &[],
+        );
+        device.cmd_push_constants(
+            command_buffer,
+            pipeline_layout,
+            DebugViewPushConstants::get_range().stage_flags,
+            0,
+            ffi::any_as_u8_slice(push_constants),
+        );
+    }
+
+    let mut last_mesh = None;
+    let mut last_material = None;
+    let mut vertex_count = 0;
+    for draw in renderables.iter() {
+        if last_material != Some(draw.material_id) {
+            last_material = Some(draw.material_id);
+
+            let material = resource_manager.get_material(&draw.renderable.material_name);
+            if material.descriptor_set != Default::default() {
+                unsafe {
+                    device.cmd_bind_descriptor_sets(
+                        command_buffer,
+                        vk::PipelineBindPoint::GRAPHICS,
+                        pipeline_layout,
+                        2,
+                        &[material.descriptor_set],
+                        &[],
+                    );
+                }
+            }
+        }
+
+        if last_mesh != Some(draw.mesh_id) {
+            last_mesh = Some(draw.mesh_id);
+
+            let mesh_resource = resource_manager.get_mesh(&draw.renderable.mesh_name);
+            vertex_count = mesh_resource.get_mesh().vertices.len() as u32;
+            unsafe {
+                device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[mesh_resource.get_buffer().get()],
+                    &[0],
+                );
+            }
+        }
+
+        unsafe {
+            device.cmd_draw(command_buffer, vertex_count, 1, 0, draw.index);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Draws the depth tested lines at the start of `vertex_buffer`, then the overlay lines following them. `pipelines` and
+/// `vertex_counts` hold the depth tested and overlay pipeline ids and vertex counts.
+fn draw_debug_lines(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    pipelines: (&str, &str),
+    vertex_buffer: vk::Buffer,
+    vertex_counts: (u32, u32),
+    global_descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(DEBUG_LINE_PIPELINE_LAYOUT)
+        .get();
+
+    unsafe {
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            global_descriptor_sets,
+            global_dynamic_offsets,
+        );
+        device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer], &[0]);
+    }
+
+    let (depth_tested_pipeline, overlay_pipeline) = pipelines;
+    let (depth_tested_count, overlay_count) = vertex_counts;
+    let draws = [
+        (depth_tested_pipeline, depth_tested_count, 0),
+        (overlay_pipeline, overlay_count, depth_tested_count),
+    ];
+
+    for &(pipeline_id, vertex_count, first_vertex) in draws.iter() {
+        if vertex_count == 0 {
+            continue;
+        }
+
+        let pipeline = resource_manager.get_pipeline(pipeline_id).get();
+        unsafe {
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+        }
+        set_viewport(context);
+
+        unsafe {
+            device.cmd_draw(command_buffer, vertex_count, 1, first_vertex, 0);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Draws the sprite `draws` from `vertex_buffer`.
+fn draw_sprites(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    vertex_buffer: vk::Buffer,
+    draws: &[SpriteDraw],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        extent,
+        ..
+    } = *context;
+
+    let pipeline = resource_manager.get_pipeline(SPRITE_PIPELINE).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(SPRITE_PIPELINE_LAYOUT)
+        .get();
+    let mut push_constants = SpritePushConstants::screen_space(extent);
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+    }
+    set_viewport(context);
+
+    unsafe {
+        device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer], &[0]);
+    }
+
+    for draw in draws.iter() {
+        push_constants.params.x = if draw.distance_field { 1.0 } else { 0.0 };
+
+        unsafe {
+            device.cmd_push_constants(
+                command_buffer,
+                pipeline_layout,
+                SpritePushConstants::get_range().stage_flags,
+                0,
+                ffi::any_as_u8_slice(&push_constants),
+            );
+            device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                pipeline_layout,
+                0,
+                &[draw.descriptor_set],
+                &[],
+            );
+            device.cmd_draw(command_buffer, draw.vertex_count, 1, draw.first_vertex, 0);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Draws the billboard `draws` from `vertex_buffer` with the `pipeline_id` pipeline of the current path.
+fn draw_billboards(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    pipeline_id: &str,
+    vertex_buffer: vk::Buffer,
+    draws: &[SpriteDraw],
+    global_descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let pipeline = resource_manager.get_pipeline(pipeline_id).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(BILLBOARD_PIPELINE_LAYOUT)
+        .get();
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+    }
+    set_viewport(context);
+
+    unsafe {
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            global_descriptor_sets,
+            global_dynamic_offsets,
+        );
+        device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer], &[0]);
+    }
+
+    for draw in draws.iter() {
+        let push_constants = BillboardPushConstants {
+            params: Vec4::new(if draw.distance_field { 1.0 } else { 0.0 }, 0.0, 0.0, 0.0),
+        };
+
+        unsafe {
+            device.cmd_push_constants(
+                command_buffer,
+                pipeline_layout,
+                BillboardPushConstants::get_range().stage_flags,
+                0,
+                ffi::any_as_u8_slice(&push_constants),
+            );
+            device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                pipeline_layout,
+                1,
+                &[draw.descriptor_set],
+                &[],
+            );
+            device.cmd_draw(command_buffer, draw.vertex_count, 1, draw.first_vertex, 0);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Writes the stencil value of their highlight under `renderables`, ignoring depth.
+fn draw_selection_stencil(
+    context: &PassContext,
+    resource_manager: &ResourceManager,
+    renderables: &[SortedDraw],
+    global_descriptor_sets: &[vk::DescriptorSet],
+    global_dynamic_offsets: &[u32],
+    entity_descriptor_sets: &[vk::DescriptorSet],
+) {
+    let PassContext {
+        device,
+        command_buffer,
+        ..
+    } = *context;
+
+    let pipeline = resource_manager
+        .get_pipeline(SELECTION_STENCIL_PIPELINE)
+        .get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(SELECTION_STENCIL_PIPELINE_LAYOUT)
+        .get();
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            0,
+            global_descriptor_sets,
+            global_dynamic_offsets,
+        );
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            1,
+            entity_descriptor_sets,
+            &[],
+        );
+    }
+    set_viewport(context);
+
+    let mut last_mesh = None;
+    let mut last_highlight = None;
+    let mut vertex_count = 0;
+    for draw in renderables.iter() {
+        if last_highlight != Some(draw.renderable.highlight) {
+            last_highlight = Some(draw.renderable.highlight);
+
+            let reference = if draw.renderable.highlight == Highlight::Selected {
+                SELECTED_STENCIL
+            } else {
+                HOVERED_STENCIL
+            };
+            unsafe {
+                device.cmd_set_stencil_reference(
+                    command_buffer,
+                    vk::StencilFaceFlags::FRONT_AND_BACK,
+                    reference,
+                );
+            }
+        }
+
+        if last_mesh != Some(draw.mesh_id) {
+            last_mesh = Some(draw.mesh_id);
+
+            let mesh_resource = resource_manager.get_mesh(&draw.renderable.mesh_name);
+            vertex_count = mesh_resource.get_mesh().vertices.len() as u32;
+            unsafe {
+                device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &[mesh_resource.get_buffer().get()],
+                    &[0],
+                );
+            }
+        }
+
+        unsafe {
+            device.cmd_draw(command_buffer, vertex_count, 1, 0, draw.index);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Resolves a read back entity index against `ids`, the ids of the renderables drawn on the frame it was read from.
+fn pick_result(pixel: (u32, u32), readback: PickReadback, ids: &[u32]) -> PickResult {
+    let (x, y) = pixel;
+    let id = ids.get(readback.index as usize).copied();
+    let hit = id.is_some();
+
+    PickResult {
+        x,
+        y,
+        renderable_index: if hit {
+            Some(readback.index as usize)
+        } else {
+            None
+        },
+        id,
+        depth: if hit { readback.depth } else { f32::INFINITY },
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Covers the whole pass with the viewport and scissor, for pipelines that leave them dynamic.
+fn set_viewport(context: &PassContext) {
+    let PassContext {
+        device,
+        command_buffer,
+        extent,
+        ..
+    } = *context;
+
+    let viewports = [vk::Viewport::builder()
+        .x(0.0)
+        .y(0.0)
+        .width(extent.width as f32)
+        .height(extent.height as f32)
+        .min_depth(0.0)
+        .max_depth(1.0)
+        .build()];
+    let scissors = [vk::Rect2D::builder()
+        .offset(vk::Offset2D::default())
+        .extent(extent)
+        .build()];
+
+    unsafe {
+        device.cmd_set_viewport(command_buffer, 0, &viewports);
+        device.cmd_set_scissor(command_buffer, 0, &scissors);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn draw_shadow_map(
+    device: &Device,
+    command_buffer: vk::CommandBuffer,
+    resource_manager: &ResourceManager,
+    renderables: &[Renderable],
+    light_view_projection: Mat4,
+    entity_descriptor_sets: &[vk::DescriptorSet],
+) {
+    let pipeline = resource_manager.get_pipeline(SHADOW_PIPELINE).get();
+    let pipeline_layout = resource_manager
+        .get_pipeline_layout(SHADOW_PIPELINE_LAYOUT)
+        .get();
+
+    let push_constants = ShadowPushConstants::new(light_view_projection);
+
+    unsafe {
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            pipeline_layout,
+            1,
+            entity_descriptor_sets,
+            &[],
+        );
+        device.cmd_push_constants(
+            command_buffer,
+            pipeline_layout,
+            ShadowPushConstants::get_range().stage_flags,
+            0,
+            ffi::any_as_u8_slice(&push_constants),
+        );
+    }
+
+    let mut last_mesh = None;
+    for (i, renderable) in renderables.iter().enumerate() {
+        if !renderable.cast_shadows {
+            continue;
+        }
+
+        let mesh_resource = resource_manager.get_mesh(&renderable.mesh_name);
 
-        let mesh_cmp = Some(mesh_name.clone());
+        let mesh_cmp = Some(renderable.mesh_name.clone());
         if mesh_cmp != last_mesh {
             last_mesh = mesh_cmp;
             unsafe {
@@ -555,6 +3336,7 @@ fn draw_renderables(
             }
         }
 
+        // first_instance stays the renderable index, so the shadow shader can look up the entity SSBO
         let vertex_count = mesh_resource.get_mesh().vertices.len() as u32;
         unsafe {
             device.cmd_draw(command_buffer, vertex_count, 1, 0, i as u32);
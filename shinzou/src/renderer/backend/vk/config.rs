@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+//----------------------------------------------------------------------------------------------------------------------
+
 use ash::{
     extensions::{
         ext::DebugUtils,
@@ -8,13 +11,32 @@ use ash::{
 };
 //----------------------------------------------------------------------------------------------------------------------
 
-use crate::{renderer::backend::vk::platform, utils::ffi};
+use crate::{
+    renderer::{
+        backend::vk::{
+            platform,
+            resources::{MAX_SHADOW_CASCADES, MESH_SSBO_INITIAL_CAPACITY},
+        },
+        DepthMode, RenderPath, RendererConfig, DEFAULT_MSAA_SAMPLES, DEFAULT_SHADOW_CASCADE_COUNT,
+        DEFAULT_SHADOW_DISTANCE, DEFAULT_SHADOW_MAP_SIZE,
+    },
+    utils::ffi,
+};
 //----------------------------------------------------------------------------------------------------------------------
 
 pub struct VkRendererConfig {
     pub instance_extensions: Vec<ffi::CString>,
     pub device_extensions: Vec<ffi::CString>,
     pub buffering: u32,
+    pub entity_buffer_capacity: u64,
+    pub shadow_map_size: u32,
+    pub shadow_cascade_count: u32,
+    pub shadow_distance: f32,
+    /// Requested sample count, the renderer clamps it to what the selected device supports.
+    pub msaa_samples: u32,
+    pub render_path: RenderPath,
+    pub ibl_cache_dir: Option<PathBuf>,
+    pub depth_mode: DepthMode,
 
     #[cfg(debug_assertions)]
     pub validation_layers: Vec<ffi::CString>,
@@ -31,6 +53,14 @@ impl VkRendererConfig {
         let device_extensions = vec![ffi::cstr_to_cstring(Swapchain::name())];
 
         let buffering: u32 = 3;
+        let entity_buffer_capacity = MESH_SSBO_INITIAL_CAPACITY;
+        let shadow_map_size = DEFAULT_SHADOW_MAP_SIZE;
+        let shadow_cascade_count = DEFAULT_SHADOW_CASCADE_COUNT;
+        let shadow_distance = DEFAULT_SHADOW_DISTANCE;
+        let msaa_samples = DEFAULT_MSAA_SAMPLES;
+        let render_path = RenderPath::Forward;
+        let ibl_cache_dir = None;
+        let depth_mode = DepthMode::Standard;
 
         #[cfg(debug_assertions)]
         {
@@ -51,6 +81,14 @@ impl VkRendererConfig {
                 instance_extensions,
                 device_extensions,
                 buffering,
+                entity_buffer_capacity,
+                shadow_map_size,
+                shadow_cascade_count,
+                shadow_distance,
+                msaa_samples,
+                render_path,
+                ibl_cache_dir,
+                depth_mode,
 
                 validation_layers,
                 instance_debug_extensions,
@@ -66,6 +104,14 @@ impl VkRendererConfig {
                 instance_extensions,
                 device_extensions,
                 buffering,
+                entity_buffer_capacity,
+                shadow_map_size,
+                shadow_cascade_count,
+                shadow_distance,
+                msaa_samples,
+                render_path,
+                ibl_cache_dir,
+                depth_mode,
             }
         }
     }
@@ -76,6 +122,20 @@ impl VkRendererConfig {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn apply_renderer_config(&mut self, renderer_config: &RendererConfig) {
+        self.entity_buffer_capacity = renderer_config.entity_buffer_capacity.max(1);
+        self.shadow_map_size = renderer_config.shadow_map_size.max(1);
+        self.shadow_cascade_count = renderer_config
+            .shadow_cascade_count
+            .clamp(1, MAX_SHADOW_CASCADES as u32);
+        self.shadow_distance = renderer_config.shadow_distance.max(0.0);
+        self.msaa_samples = renderer_config.msaa_samples.max(1);
+        self.render_path = renderer_config.render_path;
+        self.ibl_cache_dir = renderer_config.ibl_cache_dir.clone();
+        self.depth_mode = renderer_config.depth_mode;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     fn check_instance_extension_support(
         entry: &Entry,
         requested_instance_extensions: &[ffi::CString],
@@ -114,6 +114,25 @@ impl AllocatorHandle {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Copies `count` elements out of a host visible buffer, e.g. one the device copied an image into.
+    pub fn read_buffer<T>(&self, buffer: &VkBuffer, data: *mut T, count: usize) {
+        let allocation = buffer.allocation();
+
+        let mapped_memory = self
+            .allocator
+            .map_memory(allocation)
+            .expect("VkBackend::AllocatorHandle::read_buffer - Failed to map buffer allocation!");
+
+        unsafe {
+            copy_nonoverlapping(mapped_memory as *const T, data, count);
+        }
+
+        self.allocator
+            .unmap_memory(allocation)
+            .expect("VkBackend::AllocatorHandle::read_buffer - Failed to unmap buffer allocation!");
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn create_image(
         &self,
         info: &ImageCreateInfo,
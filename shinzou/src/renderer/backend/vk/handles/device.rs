@@ -83,7 +83,11 @@ fn init_device_and_queue_handle(
             .build(),
     ];
 
-    let enabled_features = vk::PhysicalDeviceFeatures::builder().sampler_anisotropy(true);
+    // Wireframe debug views need non-solid fill modes, they are turned off on devices without them
+    let supported_features = &physical_device_handle.physical_device_attributes.features;
+    let enabled_features = vk::PhysicalDeviceFeatures::builder()
+        .sampler_anisotropy(true)
+        .fill_mode_non_solid(supported_features.fill_mode_non_solid == vk::TRUE);
 
     let enabled_extension_names = ffi::vec_cstring_to_char_ptr(&config.device_extensions);
     let mut device_create_info = vk::DeviceCreateInfo::builder()
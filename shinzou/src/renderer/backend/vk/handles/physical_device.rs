@@ -158,6 +158,7 @@ impl PhysicalDeviceHandle {
 pub struct PhysicalDeviceAttributes {
     pub name: String,
     pub properties: vk::PhysicalDeviceProperties,
+    pub features: vk::PhysicalDeviceFeatures,
     pub queue_family_properties: Vec<vk::QueueFamilyProperties>,
     pub extensions_properties: Vec<vk::ExtensionProperties>,
     pub surface_capabilities: vk::SurfaceCapabilitiesKHR,
@@ -206,6 +207,7 @@ impl PhysicalDeviceAttributes {
             Self {
                 name: String::from(name),
                 properties,
+                features: instance.get_physical_device_features(physical_device),
                 queue_family_properties: instance
                     .get_physical_device_queue_family_properties(physical_device),
                 extensions_properties,
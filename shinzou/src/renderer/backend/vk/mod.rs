@@ -7,6 +7,7 @@ mod backend;
 mod config;
 mod handles;
 mod platform;
+mod render_graph;
 mod resources;
 mod utils;
 
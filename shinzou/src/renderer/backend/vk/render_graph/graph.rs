@@ -0,0 +1,809 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use vk_mem::Allocator;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::backend::vk::{
+    handles::AllocatorHandle,
+    render_graph::{
+        image::{format_aspect, TransientImage},
+        GraphPass, ImportedImage, PassContext, TransientImageDesc,
+    },
+    resources::{VkAttachmentInfo, VkFramebuffer, VkRenderPass},
+    DeviceAllocatorDestroy, DeviceDestroy,
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Passes and resources of a single frame. Pass closures may borrow anything that outlives the frame graph, it is
+/// consumed by `RenderGraph::execute`.
+pub struct FrameGraph<'a> {
+    backbuffer_extent: vk::Extent2D,
+    transient_images: HashMap<String, TransientImageDesc>,
+    imported_images: HashMap<String, ImportedImage>,
+    imported_buffers: HashMap<String, vk::Buffer>,
+    passes: Vec<GraphPass<'a>>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl<'a> FrameGraph<'a> {
+    pub fn new(backbuffer_extent: vk::Extent2D) -> Self {
+        Self {
+            backbuffer_extent,
+            transient_images: HashMap::new(),
+            imported_images: HashMap::new(),
+            imported_buffers: HashMap::new(),
+            passes: Vec::new(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn create_image(&mut self, name: &str, desc: TransientImageDesc) {
+        self.transient_images.insert(name.to_owned(), desc);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn import_image(&mut self, name: &str, image: ImportedImage) {
+        self.imported_images.insert(name.to_owned(), image);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn import_buffer(&mut self, name: &str, buffer: vk::Buffer) {
+        self.imported_buffers.insert(name.to_owned(), buffer);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn add_pass(&mut self, pass: GraphPass<'a>) {
+        self.passes.push(pass);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn is_imported(&self, resource: &str) -> bool {
+        self.imported_images.contains_key(resource) || self.imported_buffers.contains_key(resource)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Orders the passes so that every read happens after the writes it depends on and drops the passes that do not
+    /// contribute to an imported resource. A read depends on the writes declared before it, or on the writes declared
+    /// after it if there are none. Writes to a resource happen in declaration order, after any unrelated reads of it.
+    fn schedule(&self) -> Vec<usize> {
+        let pass_count = self.passes.len();
+        let mut producers: Vec<Vec<usize>> = vec![Vec::new(); pass_count];
+        let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); pass_count];
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for resource in pass.resources() {
+                if pass.reads(resource) {
+                    let mut writers = (0..index)
+                        .filter(|&other| self.passes[other].writes(resource))
+                        .collect::<Vec<usize>>();
+
+                    if writers.is_empty() {
+                        writers = (index + 1..pass_count)
+                            .filter(|&other| self.passes[other].writes(resource))
+                            .collect();
+                    }
+
+                    producers[index].extend(writers);
+                }
+
+                if pass.writes(resource) {
+                    for (other, other_pass) in self.passes[..index].iter().enumerate() {
+                        let feeds_other =
+                            other_pass.reads(resource) && !producers[other].contains(&index);
+
+                        if other_pass.writes(resource) || feeds_other {
+                            dependencies[index].push(other);
+                        }
+                    }
+                }
+            }
+
+            producers[index].sort_unstable();
+            producers[index].dedup();
+
+            let pass_producers = producers[index].clone();
+            dependencies[index].extend(pass_producers);
+            dependencies[index].sort_unstable();
+            dependencies[index].dedup();
+        }
+
+        // Cull passes whose results never reach an imported resource
+        let mut needed = vec![false; pass_count];
+        let mut pending = (0..pass_count)
+            .filter(|&index| {
+                self.passes[index].resources().any(|resource| {
+                    self.is_imported(resource) && self.passes[index].writes(resource)
+                })
+            })
+            .collect::<Vec<usize>>();
+
+        while let Some(index) = pending.pop() {
+            if !needed[index] {
+                needed[index] = true;
+                pending.extend(producers[index].iter().copied());
+            }
+        }
+
+        // Topological sort, preferring declaration order among passes that are ready
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); pass_count];
+        let mut remaining = vec![0usize; pass_count];
+        for index in (0..pass_count).filter(|&index| needed[index]) {
+            for &dependency in dependencies[index].iter().filter(|&&other| needed[other]) {
+                dependents[dependency].push(index);
+                remaining[index] += 1;
+            }
+        }
+
+        let mut ready = (0..pass_count)
+            .filter(|&index| needed[index] && remaining[index] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<Reverse<usize>>>();
+
+        let mut order = Vec::with_capacity(pass_count);
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+
+            for &dependent in dependents[index].iter() {
+                remaining[dependent] -= 1;
+                if remaining[dependent] == 0 {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+
+        if order.len() != needed.iter().filter(|&&needed| needed).count() {
+            panic!("FrameGraph::schedule - Passes have cyclic dependencies!");
+        }
+
+        order
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Last synchronization scope an image or buffer was used in.
+#[derive(Clone, Copy)]
+struct ResourceState {
+    layout: vk::ImageLayout,
+    stage: vk::PipelineStageFlags,
+    access: vk::AccessFlags,
+    written: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl ResourceState {
+    /// Start of frame state. Waits on everything submitted before, so images reused across frames in flight are safe.
+    fn initial(layout: vk::ImageLayout) -> Self {
+        Self {
+            layout,
+            stage: vk::PipelineStageFlags::ALL_COMMANDS,
+            access: vk::AccessFlags::empty(),
+            written: false,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct BarrierBatch {
+    src_stage: vk::PipelineStageFlags,
+    dst_stage: vk::PipelineStageFlags,
+    image_barriers: Vec<vk::ImageMemoryBarrier>,
+    buffer_barriers: Vec<vk::BufferMemoryBarrier>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl BarrierBatch {
+    /// Queues a barrier if the image changes layout or either access writes. Reads in the same layout need none.
+    fn image(
+        &mut self,
+        state: &mut ResourceState,
+        image: vk::Image,
+        format: vk::Format,
+        next: ResourceState,
+    ) {
+        if state.layout != next.layout || state.written || next.written {
+            self.src_stage |= state.stage;
+            self.dst_stage |= next.stage;
+            self.image_barriers.push(
+                vk::ImageMemoryBarrier::builder()
+                    .old_layout(state.layout)
+                    .new_layout(next.layout)
+                    .src_access_mask(state.access)
+                    .dst_access_mask(next.access)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(image)
+                    .subresource_range(
+                        vk::ImageSubresourceRange::builder()
+                            .aspect_mask(format_aspect(format))
+                            .base_mip_level(0)
+                            .level_count(vk::REMAINING_MIP_LEVELS)
+                            .base_array_layer(0)
+                            .layer_count(vk::REMAINING_ARRAY_LAYERS)
+                            .build(),
+                    )
+                    .build(),
+            );
+        }
+
+        *state = next;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn buffer(&mut self, state: &mut ResourceState, buffer: vk::Buffer, next: ResourceState) {
+        if state.written || next.written {
+            self.src_stage |= state.stage;
+            self.dst_stage |= next.stage;
+            self.buffer_barriers.push(
+                vk::BufferMemoryBarrier::builder()
+                    .src_access_mask(state.access)
+                    .dst_access_mask(next.access)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .buffer(buffer)
+                    .offset(0)
+                    .size(vk::WHOLE_SIZE)
+                    .build(),
+            );
+        }
+
+        *state = next;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn record(&self, device: &Device, command_buffer: vk::CommandBuffer) {
+        if self.image_barriers.is_empty() && self.buffer_barriers.is_empty() {
+            return;
+        }
+
+        unsafe {
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                self.src_stage,
+                self.dst_stage,
+                vk::DependencyFlags::empty(),
+                &[],
+                &self.buffer_barriers,
+                &self.image_barriers,
+            );
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Image a pass attaches or samples, resolved to the Vulkan objects backing it this frame.
+struct GraphImage {
+    image: vk::Image,
+    view: vk::ImageView,
+    format: vk::Format,
+    samples: vk::SampleCountFlags,
+    extent: vk::Extent2D,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(PartialEq, Eq, Hash)]
+struct RenderPassKey {
+    color_attachments: Vec<VkAttachmentInfo>,
+    depth_attachment: Option<VkAttachmentInfo>,
+    resolve_attachments: Vec<VkAttachmentInfo>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(PartialEq, Eq, Hash)]
+struct FramebufferKey {
+    render_pass: vk::RenderPass,
+    attachments: Vec<vk::ImageView>,
+    width: u32,
+    height: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Records frame graphs. Owns the transient images, render passes and framebuffers the passes need, and keeps them
+/// across frames so that only the first frame with a new configuration pays for their creation.
+pub struct RenderGraph {
+    transient_images: HashMap<String, TransientImage>,
+    render_passes: HashMap<RenderPassKey, VkRenderPass>,
+    framebuffers: HashMap<FramebufferKey, VkFramebuffer>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self {
+            transient_images: HashMap::new(),
+            render_passes: HashMap::new(),
+            framebuffers: HashMap::new(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Records every contributing pass of `frame_graph` into `command_buffer` in dependency order, with the layout
+    /// transitions and barriers between them. Imported images are left in their final layouts.
+    pub fn execute(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        command_buffer: vk::CommandBuffer,
+        mut frame_graph: FrameGraph,
+    ) {
+        let schedule = frame_graph.schedule();
+        self.allocate_transient_images(device, allocator_handle, &frame_graph, &schedule);
+
+        let mut image_states = frame_graph
+            .imported_images
+            .iter()
+            .map(|(name, image)| (name.clone(), ResourceState::initial(image.initial_layout)))
+            .chain(frame_graph.transient_images.keys().map(|name| {
+                (
+                    name.clone(),
+                    ResourceState::initial(vk::ImageLayout::UNDEFINED),
+                )
+            }))
+            .collect::<HashMap<String, ResourceState>>();
+
+        let mut buffer_states = frame_graph
+            .imported_buffers
+            .keys()
+            .map(|name| {
+                (
+                    name.clone(),
+                    ResourceState::initial(vk::ImageLayout::UNDEFINED),
+                )
+            })
+            .collect::<HashMap<String, ResourceState>>();
+
+        let images = frame_graph
+            .imported_images
+            .iter()
+            .map(|(name, image)| (name.clone(), image.image))
+            .chain(
+                self.transient_images
+                    .iter()
+                    .filter(|(name, _)| frame_graph.transient_images.contains_key(*name))
+                    .map(|(name, image)| (name.clone(), image.get())),
+            )
+            .collect::<HashMap<String, vk::Image>>();
+
+        let image_views = frame_graph
+            .imported_images
+            .iter()
+            .map(|(name, image)| (name.clone(), image.view))
+            .chain(
+                self.transient_images
+                    .iter()
+                    .filter(|(name, _)| frame_graph.transient_images.contains_key(*name))
+                    .map(|(name, image)| (name.clone(), image.sampled_view())),
+            )
+            .collect::<HashMap<String, vk::ImageView>>();
+
+        let stencil_views = self
+            .transient_images
+            .iter()
+            .filter(|(name, _)| frame_graph.transient_images.contains_key(*name))
+            .filter_map(|(name, image)| Some((name.clone(), image.stencil_view()?)))
+            .collect::<HashMap<String, vk::ImageView>>();
+
+        for index in schedule {
+            let record = frame_graph.passes[index].record.take();
+            let pass = &frame_graph.passes[index];
+
+            let mut barriers = BarrierBatch::default();
+
+            let color_targets = pass
+                .color_attachments
+                .iter()
+                .map(|attachment| attachment.image.as_str())
+                .chain(pass.resolve_targets())
+                .collect::<Vec<&str>>();
+
+            for color_target in color_targets {
+                let image = self.image(&frame_graph, color_target, None);
+                barriers.image(
+                    Self::state(&mut image_states, color_target),
+                    image.image,
+                    image.format,
+                    ResourceState {
+                        layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                        stage: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                        access: vk::AccessFlags::COLOR_ATTACHMENT_READ
+                            | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                        written: true,
+                    },
+                );
+            }
+
+            if let Some(attachment) = &pass.depth_attachment {
+                let image = self.image(&frame_graph, &attachment.image, None);
+                barriers.image(
+                    Self::state(&mut image_states, &attachment.image),
+                    image.image,
+                    image.format,
+                    ResourceState {
+                        layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+                        stage: vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                            | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                        access: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                        written: true,
+                    },
+                );
+            }
+
+            for sampled_image in pass.sampled_images.iter() {
+                let image = self.image(&frame_graph, sampled_image, None);
+                let layout = if format_aspect(image.format).contains(vk::ImageAspectFlags::COLOR) {
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+                } else {
+                    vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL
+                };
+
+                barriers.image(
+                    Self::state(&mut image_states, sampled_image),
+                    image.image,
+                    image.format,
+                    ResourceState {
+                        layout,
+                        stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        access: vk::AccessFlags::SHADER_READ,
+                        written: false,
+                    },
+                );
+            }
+
+            for copied_image in pass.copied_images.iter() {
+                let image = self.image(&frame_graph, copied_image, None);
+                barriers.image(
+                    Self::state(&mut image_states, copied_image),
+                    image.image,
+                    image.format,
+                    ResourceState {
+                        layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                        stage: vk::PipelineStageFlags::TRANSFER,
+                        access: vk::AccessFlags::TRANSFER_READ,
+                        written: false,
+                    },
+                );
+            }
+
+            for buffer in pass.buffers.iter() {
+                let handle = *frame_graph
+                    .imported_buffers
+                    .get(&buffer.buffer)
+                    .unwrap_or_else(|| {
+                        panic!("RenderGraph::execute - Unknown buffer {}!", buffer.buffer)
+                    });
+
+                barriers.buffer(
+                    Self::state(&mut buffer_states, &buffer.buffer),
+                    handle,
+                    ResourceState {
+                        layout: vk::ImageLayout::UNDEFINED,
+                        stage: buffer.stage,
+                        access: buffer.access,
+                        written: buffer.writes(),
+                    },
+                );
+            }
+
+            barriers.record(device, command_buffer);
+
+            let mut context = PassContext {
+                device,
+                command_buffer,
+                render_pass: vk::RenderPass::null(),
+                extent: frame_graph.backbuffer_extent,
+                images: &images,
+                image_views: &image_views,
+                stencil_views: &stencil_views,
+            };
+
+            let resolve_count = pass.resolve_targets().count();
+            if resolve_count != 0 && resolve_count != pass.color_attachments.len() {
+                panic!(
+                    "RenderGraph::execute - Pass {} resolves only some of its color attachments!",
+                    pass.name
+                );
+            }
+
+            // Color, depth and resolve attachments, in framebuffer order
+            let attachments = pass
+                .attachments()
+                .map(|attachment| self.image(&frame_graph, &attachment.image, attachment.layer))
+                .chain(
+                    pass.resolve_targets()
+                        .map(|image| self.image(&frame_graph, image, None)),
+                )
+                .collect::<Vec<GraphImage>>();
+
+            if let Some(first_attachment) = attachments.first() {
+                context.extent = first_attachment.extent;
+                if attachments
+                    .iter()
+                    .any(|attachment| attachment.extent != context.extent)
+                {
+                    panic!(
+                        "RenderGraph::execute - Attachments of pass {} differ in size!",
+                        pass.name
+                    );
+                }
+
+                let attachment_info =
+                    |image: &GraphImage, clear: &Option<vk::ClearValue>| VkAttachmentInfo {
+                        format: image.format,
+                        samples: image.samples,
+                        load_op: match clear {
+                            Some(_) => vk::AttachmentLoadOp::CLEAR,
+                            None => vk::AttachmentLoadOp::LOAD,
+                        },
+                    };
+
+                let color_count = pass.color_attachments.len();
+                let color_attachments = attachments[..color_count]
+                    .iter()
+                    .zip(pass.color_attachments.iter())
+                    .map(|(image, attachment)| attachment_info(image, &attachment.clear))
+                    .collect::<Vec<VkAttachmentInfo>>();
+                let depth_count = pass.depth_attachment.iter().count();
+                let depth_attachment = pass.depth_attachment.as_ref().map(|attachment| {
+                    attachment_info(&attachments[color_count], &attachment.clear)
+                });
+                // Resolves overwrite the whole image, there is nothing to load
+                let resolve_attachments = attachments[color_count + depth_count..]
+                    .iter()
+                    .map(|image| VkAttachmentInfo {
+                        load_op: vk::AttachmentLoadOp::DONT_CARE,
+                        ..attachment_info(image, &None)
+                    })
+                    .collect::<Vec<VkAttachmentInfo>>();
+
+                context.render_pass = self.render_pass(
+                    device,
+                    RenderPassKey {
+                        color_attachments,
+                        depth_attachment,
+                        resolve_attachments,
+                    },
+                );
+
+                let framebuffer = self.framebuffer(
+                    device,
+                    context.render_pass,
+                    attachments
+                        .iter()
+                        .map(|attachment| attachment.view)
+                        .collect(),
+                    context.extent,
+                );
+
+                let clear_values = pass
+                    .attachments()
+                    .map(|attachment| attachment.clear.unwrap_or_default())
+                    .chain((0..resolve_count).map(|_| vk::ClearValue::default()))
+                    .collect::<Vec<vk::ClearValue>>();
+
+                let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
+                    .render_pass(context.render_pass)
+                    .render_area(
+                        vk::Rect2D::builder()
+                            .offset(vk::Offset2D::builder().x(0).y(0).build())
+                            .extent(context.extent)
+                            .build(),
+                    )
+                    .framebuffer(framebuffer)
+                    .clear_values(&clear_values);
+
+                unsafe {
+                    device.cmd_begin_render_pass(
+                        command_buffer,
+                        &render_pass_begin_info,
+                        vk::SubpassContents::INLINE,
+                    );
+                }
+            }
+
+            if let Some(record) = record {
+                record(&context);
+            }
+
+            if !attachments.is_empty() {
+                unsafe {
+                    device.cmd_end_render_pass(command_buffer);
+                }
+            }
+        }
+
+        let mut barriers = BarrierBatch::default();
+        for (name, image) in frame_graph.imported_images.iter() {
+            let state = Self::state(&mut image_states, name);
+            if state.layout != image.final_layout {
+                barriers.image(
+                    state,
+                    image.image,
+                    image.format,
+                    ResourceState {
+                        layout: image.final_layout,
+                        stage: vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                        access: vk::AccessFlags::empty(),
+                        written: false,
+                    },
+                );
+            }
+        }
+        barriers.record(device, command_buffer);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn state<'s>(
+        states: &'s mut HashMap<String, ResourceState>,
+        name: &str,
+    ) -> &'s mut ResourceState {
+        states
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("RenderGraph::execute - Unknown resource {}!", name))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn image(&self, frame_graph: &FrameGraph, name: &str, layer: Option<u32>) -> GraphImage {
+        if let Some(imported) = frame_graph.imported_images.get(name) {
+            let view = match layer {
+                Some(layer) => *imported.layer_views.get(layer as usize).unwrap_or_else(|| {
+                    panic!(
+                        "RenderGraph::image - Image {} has no layer {}!",
+                        name, layer
+                    )
+                }),
+                None => imported.view,
+            };
+
+            return GraphImage {
+                image: imported.image,
+                view,
+                format: imported.format,
+                samples: vk::SampleCountFlags::TYPE_1,
+                extent: imported.extent,
+            };
+        }
+
+        if layer.unwrap_or(0) != 0 {
+            panic!(
+                "RenderGraph::image - Transient image {} has a single layer!",
+                name
+            );
+        }
+
+        let transient = self
+            .transient_images
+            .get(name)
+            .unwrap_or_else(|| panic!("RenderGraph::image - Unknown image {}!", name));
+
+        GraphImage {
+            image: transient.get(),
+            view: transient.view,
+            format: transient.desc.format,
+            samples: transient.desc.samples,
+            extent: transient.extent,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// (Re)creates the transient images scheduled passes use whose description, size or usage changed.
+    fn allocate_transient_images(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        frame_graph: &FrameGraph,
+        schedule: &[usize],
+    ) {
+        for (name, desc) in frame_graph.transient_images.iter() {
+            let usage = schedule
+                .iter()
+                .fold(vk::ImageUsageFlags::empty(), |usage, &index| {
+                    usage | frame_graph.passes[index].image_usage(name)
+                });
+
+            if usage.is_empty() {
+                continue;
+            }
+
+            let extent = desc.size.extent(frame_graph.backbuffer_extent);
+
+            if let Some(existing) = self.transient_images.get(name) {
+                if existing.desc == *desc
+                    && existing.extent == extent
+                    && existing.usage.contains(usage)
+                {
+                    continue;
+                }
+            }
+
+            if let Some(stale) = self.transient_images.remove(name) {
+                // Frames still in flight may be using the old image
+                unsafe {
+                    device.device_wait_idle().expect(
+                        "RenderGraph::allocate_transient_images - Failed to wait for device!",
+                    );
+                }
+
+                self.framebuffers.retain(|key, framebuffer| {
+                    let keep = !key.attachments.contains(&stale.view);
+                    if !keep {
+                        framebuffer.destroy(device);
+                    }
+                    keep
+                });
+
+                stale.destroy(device, &allocator_handle.allocator);
+            }
+
+            self.transient_images.insert(
+                name.clone(),
+                TransientImage::new(device, allocator_handle, *desc, extent, usage),
+            );
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn render_pass(&mut self, device: &Device, key: RenderPassKey) -> vk::RenderPass {
+        self.render_passes
+            .entry(key)
+            .or_insert_with_key(|key| {
+                VkRenderPass::with_attachments(
+                    device,
+                    &key.color_attachments,
+                    key.depth_attachment,
+                    &key.resolve_attachments,
+                )
+            })
+            .get()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn framebuffer(
+        &mut self,
+        device: &Device,
+        render_pass: vk::RenderPass,
+        attachments: Vec<vk::ImageView>,
+        extent: vk::Extent2D,
+    ) -> vk::Framebuffer {
+        let key = FramebufferKey {
+            render_pass,
+            attachments,
+            width: extent.width,
+            height: extent.height,
+        };
+
+        self.framebuffers
+            .entry(key)
+            .or_insert_with_key(|key| {
+                VkFramebuffer::new(device, &key.attachments, key.render_pass, extent)
+            })
+            .get()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceAllocatorDestroy for RenderGraph {
+    fn destroy(&self, device: &Device, allocator: &Allocator) {
+        for framebuffer in self.framebuffers.values() {
+            framebuffer.destroy(device);
+        }
+
+        for render_pass in self.render_passes.values() {
+            render_pass.destroy(device);
+        }
+
+        for transient_image in self.transient_images.values() {
+            transient_image.destroy(device, allocator);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,236 @@
+use ash::{version::DeviceV1_0, vk, Device};
+use vk_mem::Allocator;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::backend::vk::{
+    handles::{AllocatorFree, AllocatorHandle},
+    resources::VkImage,
+    DeviceAllocatorDestroy,
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Size of a transient image. Backbuffer relative sizes follow the swapchain.
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageSize {
+    Backbuffer,
+    Scaled(f32),
+    Fixed(u32, u32),
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl ImageSize {
+    pub fn extent(&self, backbuffer_extent: vk::Extent2D) -> vk::Extent2D {
+        match *self {
+            ImageSize::Backbuffer => backbuffer_extent,
+            ImageSize::Scaled(scale) => vk::Extent2D {
+                width: ((backbuffer_extent.width as f32 * scale) as u32).max(1),
+                height: ((backbuffer_extent.height as f32 * scale) as u32).max(1),
+            },
+            ImageSize::Fixed(width, height) => vk::Extent2D { width, height },
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Image owned by the render graph. It is allocated the first time a pass uses it and kept across frames for as long
+/// as its description stays the same, its contents are undefined at the start of every frame.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TransientImageDesc {
+    pub format: vk::Format,
+    pub size: ImageSize,
+    pub samples: vk::SampleCountFlags,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl TransientImageDesc {
+    pub fn new(format: vk::Format, size: ImageSize) -> Self {
+        Self {
+            format,
+            size,
+            samples: vk::SampleCountFlags::TYPE_1,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_samples(mut self, samples: vk::SampleCountFlags) -> Self {
+        self.samples = samples;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Image owned outside of the render graph, like a swapchain image. It enters the frame in `initial_layout` and is
+/// transitioned to `final_layout` once every pass has been recorded.
+pub struct ImportedImage {
+    pub(super) image: vk::Image,
+    pub(super) view: vk::ImageView,
+    /// Single layer views of array images, used when a pass renders to one layer.
+    pub(super) layer_views: Vec<vk::ImageView>,
+    pub(super) format: vk::Format,
+    pub(super) extent: vk::Extent2D,
+    pub(super) initial_layout: vk::ImageLayout,
+    pub(super) final_layout: vk::ImageLayout,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl ImportedImage {
+    pub fn new(
+        image: vk::Image,
+        view: vk::ImageView,
+        format: vk::Format,
+        extent: vk::Extent2D,
+        initial_layout: vk::ImageLayout,
+        final_layout: vk::ImageLayout,
+    ) -> Self {
+        Self {
+            image,
+            view,
+            layer_views: Vec::new(),
+            format,
+            extent,
+            initial_layout,
+            final_layout,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_layer_views(mut self, layer_views: &[vk::ImageView]) -> Self {
+        self.layer_views = layer_views.to_vec();
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub(super) struct TransientImage {
+    image: VkImage,
+    /// Covers every aspect, for attachments.
+    pub(super) view: vk::ImageView,
+    /// Depth and stencil aspects of depth/stencil images, descriptors can only view one aspect at a time.
+    pub(super) aspect_views: Option<(vk::ImageView, vk::ImageView)>,
+    pub(super) desc: TransientImageDesc,
+    pub(super) extent: vk::Extent2D,
+    pub(super) usage: vk::ImageUsageFlags,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl TransientImage {
+    pub(super) fn new(
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        desc: TransientImageDesc,
+        extent: vk::Extent2D,
+        usage: vk::ImageUsageFlags,
+    ) -> Self {
+        let mut create_info = VkImage::image_create_info(
+            desc.format,
+            usage,
+            vk::Extent3D::builder()
+                .width(extent.width)
+                .height(extent.height)
+                .depth(1)
+                .build(),
+            vk::ImageTiling::OPTIMAL,
+        );
+        create_info.samples = desc.samples;
+
+        let image = allocator_handle.create_image(
+            &create_info,
+            &AllocatorHandle::allocation_create_info(
+                vk_mem::MemoryUsage::GpuOnly,
+                None,
+                Some(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            ),
+        );
+
+        let create_view = |aspect: vk::ImageAspectFlags| {
+            let view_info = VkImage::image_view_create_info(
+                desc.format,
+                image.get(),
+                aspect,
+                vk::ImageViewType::TYPE_2D,
+            );
+
+            unsafe {
+                device
+                    .create_image_view(&view_info, None)
+                    .expect("TransientImage::new - Failed to create image view!")
+            }
+        };
+
+        let aspect = format_aspect(desc.format);
+        let view = create_view(aspect);
+        let aspect_views =
+            if aspect.contains(vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL) {
+                Some((
+                    create_view(vk::ImageAspectFlags::DEPTH),
+                    create_view(vk::ImageAspectFlags::STENCIL),
+                ))
+            } else {
+                None
+            };
+
+        Self {
+            image,
+            view,
+            aspect_views,
+            desc,
+            extent,
+            usage,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn get(&self) -> vk::Image {
+        self.image.get()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// View for descriptors, the depth aspect of depth/stencil images.
+    pub(super) fn sampled_view(&self) -> vk::ImageView {
+        self.aspect_views
+            .map_or(self.view, |(depth_view, _)| depth_view)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn stencil_view(&self) -> Option<vk::ImageView> {
+        self.aspect_views.map(|(_, stencil_view)| stencil_view)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceAllocatorDestroy for TransientImage {
+    fn destroy(&self, device: &Device, allocator: &Allocator) {
+        unsafe {
+            device.destroy_image_view(self.view, None);
+
+            if let Some((depth_view, stencil_view)) = self.aspect_views {
+                device.destroy_image_view(depth_view, None);
+                device.destroy_image_view(stencil_view, None);
+            }
+        }
+
+        self.image.free(allocator);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub(super) fn format_aspect(format: vk::Format) -> vk::ImageAspectFlags {
+    match format {
+        vk::Format::D16_UNORM | vk::Format::X8_D24_UNORM_PACK32 | vk::Format::D32_SFLOAT => {
+            vk::ImageAspectFlags::DEPTH
+        }
+        vk::Format::D16_UNORM_S8_UINT
+        | vk::Format::D24_UNORM_S8_UINT
+        | vk::Format::D32_SFLOAT_S8_UINT => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        vk::Format::S8_UINT => vk::ImageAspectFlags::STENCIL,
+        _ => vk::ImageAspectFlags::COLOR,
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,9 @@
+mod graph;
+mod image;
+mod pass;
+//----------------------------------------------------------------------------------------------------------------------
+
+pub(in crate::renderer::backend::vk) use graph::*;
+pub(in crate::renderer::backend::vk) use image::*;
+pub(in crate::renderer::backend::vk) use pass::*;
+//----------------------------------------------------------------------------------------------------------------------
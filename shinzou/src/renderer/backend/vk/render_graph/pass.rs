@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{vk, Device};
+//----------------------------------------------------------------------------------------------------------------------
+
+pub(super) struct AttachmentUse {
+    pub(super) image: String,
+    pub(super) layer: Option<u32>,
+    /// Attachments without a clear value load their previous contents.
+    pub(super) clear: Option<vk::ClearValue>,
+    /// Single-sample image a multisampled color attachment is resolved into at the end of the pass.
+    pub(super) resolve: Option<String>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub(super) struct BufferUse {
+    pub(super) buffer: String,
+    pub(super) stage: vk::PipelineStageFlags,
+    pub(super) access: vk::AccessFlags,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl BufferUse {
+    pub(super) fn writes(&self) -> bool {
+        self.access.intersects(
+            vk::AccessFlags::SHADER_WRITE
+                | vk::AccessFlags::TRANSFER_WRITE
+                | vk::AccessFlags::HOST_WRITE
+                | vk::AccessFlags::MEMORY_WRITE,
+        )
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Handed to a pass while it records. Graphics passes run inside `render_pass`, passes without attachments get a null
+/// render pass and the backbuffer extent.
+pub struct PassContext<'a> {
+    pub device: &'a Device,
+    pub command_buffer: vk::CommandBuffer,
+    pub render_pass: vk::RenderPass,
+    pub extent: vk::Extent2D,
+    pub(super) images: &'a HashMap<String, vk::Image>,
+    pub(super) image_views: &'a HashMap<String, vk::ImageView>,
+    pub(super) stencil_views: &'a HashMap<String, vk::ImageView>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl<'a> PassContext<'a> {
+    /// Graph image as it is backed this frame, for transfer commands.
+    pub fn image(&self, image: &str) -> vk::Image {
+        *self
+            .images
+            .get(image)
+            .unwrap_or_else(|| panic!("PassContext::image - Unknown image {}!", image))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// View of a graph image as it is backed this frame, for descriptor writes. Depth/stencil images are viewed through
+    /// their depth aspect.
+    pub fn image_view(&self, image: &str) -> vk::ImageView {
+        *self
+            .image_views
+            .get(image)
+            .unwrap_or_else(|| panic!("PassContext::image_view - Unknown image {}!", image))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Stencil aspect of a transient depth/stencil image, for descriptor writes.
+    pub fn stencil_view(&self, image: &str) -> vk::ImageView {
+        *self.stencil_views.get(image).unwrap_or_else(|| {
+            panic!(
+                "PassContext::stencil_view - Image {} has no stencil aspect!",
+                image
+            )
+        })
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+type RecordFn<'a> = Box<dyn FnOnce(&PassContext) + 'a>;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// A single pass of a frame graph. Declares the images and buffers it reads and writes, the graph derives pass order,
+/// layout transitions and barriers from those declarations.
+pub struct GraphPass<'a> {
+    pub(super) name: String,
+    pub(super) color_attachments: Vec<AttachmentUse>,
+    pub(super) depth_attachment: Option<AttachmentUse>,
+    pub(super) sampled_images: Vec<String>,
+    pub(super) copied_images: Vec<String>,
+    pub(super) buffers: Vec<BufferUse>,
+    pub(super) record: Option<RecordFn<'a>>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl<'a> GraphPass<'a> {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            color_attachments: Vec::new(),
+            depth_attachment: None,
+            sampled_images: Vec::new(),
+            copied_images: Vec::new(),
+            buffers: Vec::new(),
+            record: None,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Renders to `image`, cleared to `clear` or loaded if there is none.
+    pub fn color_attachment(mut self, image: &str, clear: Option<[f32; 4]>) -> Self {
+        self.color_attachments
+            .push(Self::color_attachment_use(image, None, clear));
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Renders to the unsigned integer `image`, cleared to `clear` or loaded if there is none.
+    pub fn uint_color_attachment(mut self, image: &str, clear: Option<[u32; 4]>) -> Self {
+        let mut attachment = Self::color_attachment_use(image, None, None);
+        attachment.clear = clear.map(|uint32| vk::ClearValue {
+            color: vk::ClearColorValue { uint32 },
+        });
+
+        self.color_attachments.push(attachment);
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Renders to the multisampled `image` and resolves it into `resolve` at the end of the pass. Either every color
+    /// attachment of a pass is resolved or none is.
+    pub fn resolved_color_attachment(
+        mut self,
+        image: &str,
+        resolve: &str,
+        clear: Option<[f32; 4]>,
+    ) -> Self {
+        self.color_attachments
+            .push(Self::color_attachment_use(image, Some(resolve), clear));
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Like `resolved_color_attachment` for unsigned integer images, which resolve to the value of a single sample.
+    pub fn resolved_uint_color_attachment(
+        mut self,
+        image: &str,
+        resolve: &str,
+        clear: Option<[u32; 4]>,
+    ) -> Self {
+        let mut attachment = Self::color_attachment_use(image, Some(resolve), None);
+        attachment.clear = clear.map(|uint32| vk::ClearValue {
+            color: vk::ClearColorValue { uint32 },
+        });
+
+        self.color_attachments.push(attachment);
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Depth tests against and writes to `image`, cleared to `clear` or loaded if there is none.
+    pub fn depth_attachment(mut self, image: &str, clear: Option<f32>) -> Self {
+        self.depth_attachment = Some(Self::depth_attachment_use(image, None, clear));
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Like `depth_attachment`, but renders to a single layer of an imported array image.
+    pub fn depth_attachment_layer(mut self, image: &str, layer: u32, clear: Option<f32>) -> Self {
+        self.depth_attachment = Some(Self::depth_attachment_use(image, Some(layer), clear));
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Samples `image` from the fragment shader.
+    pub fn sampled_image(mut self, image: &str) -> Self {
+        self.sampled_images.push(image.to_owned());
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Copies from `image` with transfer commands recorded outside of any render pass.
+    pub fn copy_source(mut self, image: &str) -> Self {
+        self.copied_images.push(image.to_owned());
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Accesses `buffer` from `stage`. Write access flags make the pass a writer of the buffer.
+    pub fn buffer(
+        mut self,
+        buffer: &str,
+        stage: vk::PipelineStageFlags,
+        access: vk::AccessFlags,
+    ) -> Self {
+        self.buffers.push(BufferUse {
+            buffer: buffer.to_owned(),
+            stage,
+            access,
+        });
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn record(mut self, record: impl FnOnce(&PassContext) + 'a) -> Self {
+        self.record = Some(Box::new(record));
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn attachments(&self) -> impl Iterator<Item = &AttachmentUse> {
+        self.color_attachments
+            .iter()
+            .chain(self.depth_attachment.iter())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn resolve_targets(&self) -> impl Iterator<Item = &str> {
+        self.color_attachments
+            .iter()
+            .filter_map(|attachment| attachment.resolve.as_deref())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn resources(&self) -> impl Iterator<Item = &str> {
+        self.attachments()
+            .map(|attachment| attachment.image.as_str())
+            .chain(self.resolve_targets())
+            .chain(self.sampled_images.iter().map(String::as_str))
+            .chain(self.copied_images.iter().map(String::as_str))
+            .chain(self.buffers.iter().map(|buffer| buffer.buffer.as_str()))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn reads(&self, resource: &str) -> bool {
+        self.sampled_images.iter().any(|image| image == resource)
+            || self.copied_images.iter().any(|image| image == resource)
+            || self
+                .attachments()
+                .any(|attachment| attachment.image == resource && attachment.clear.is_none())
+            || self
+                .buffers
+                .iter()
+                .any(|buffer| buffer.buffer == resource && !buffer.writes())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn writes(&self, resource: &str) -> bool {
+        self.attachments()
+            .any(|attachment| attachment.image == resource)
+            || self.resolve_targets().any(|image| image == resource)
+            || self
+                .buffers
+                .iter()
+                .any(|buffer| buffer.buffer == resource && buffer.writes())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub(super) fn image_usage(&self, image: &str) -> vk::ImageUsageFlags {
+        let mut usage = vk::ImageUsageFlags::empty();
+
+        if self
+            .color_attachments
+            .iter()
+            .any(|attachment| attachment.image == image)
+            || self.resolve_targets().any(|resolve| resolve == image)
+        {
+            usage |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
+        }
+
+        if self
+            .depth_attachment
+            .iter()
+            .any(|attachment| attachment.image == image)
+        {
+            usage |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
+        }
+
+        if self.sampled_images.iter().any(|sampled| sampled == image) {
+            usage |= vk::ImageUsageFlags::SAMPLED;
+        }
+
+        if self.copied_images.iter().any(|copied| copied == image) {
+            usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+        }
+
+        usage
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn color_attachment_use(
+        image: &str,
+        resolve: Option<&str>,
+        clear: Option<[f32; 4]>,
+    ) -> AttachmentUse {
+        AttachmentUse {
+            image: image.to_owned(),
+            layer: None,
+            clear: clear.map(|float32| vk::ClearValue {
+                color: vk::ClearColorValue { float32 },
+            }),
+            resolve: resolve.map(str::to_owned),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn depth_attachment_use(image: &str, layer: Option<u32>, clear: Option<f32>) -> AttachmentUse {
+        AttachmentUse {
+            image: image.to_owned(),
+            layer,
+            clear: clear.map(|depth| vk::ClearValue {
+                depth_stencil: vk::ClearDepthStencilValue { depth, stencil: 0 },
+            }),
+            resolve: None,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
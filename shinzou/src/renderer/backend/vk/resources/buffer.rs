@@ -27,12 +27,21 @@ impl VkBuffer {
             .limits
             .min_uniform_buffer_offset_alignment as DeviceSize;
 
-        let mut aligned_size = ubo_size as DeviceSize;
-        // https://github.com/SaschaWillems/Vulkan/tree/master/examples/dynamicuniformbuffer
-        if min_ubo_alignment > 0 {
-            aligned_size = (aligned_size + min_ubo_alignment - 1) & !(min_ubo_alignment - 1);
-        }
-        aligned_size as DeviceSize
+        align_size(ubo_size as DeviceSize, min_ubo_alignment)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn pad_ssbo_size(
+        physical_device_handle: &PhysicalDeviceHandle,
+        ssbo_size: usize,
+    ) -> DeviceSize {
+        let min_ssbo_alignment = physical_device_handle
+            .physical_device_attributes
+            .properties
+            .limits
+            .min_storage_buffer_offset_alignment as DeviceSize;
+
+        align_size(ssbo_size as DeviceSize, min_ssbo_alignment)
     }
     //------------------------------------------------------------------------------------------------------------------
 
@@ -61,3 +70,13 @@ impl AllocatorFree for VkBuffer {
     }
 }
 //----------------------------------------------------------------------------------------------------------------------
+
+fn align_size(size: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+    // https://github.com/SaschaWillems/Vulkan/tree/master/examples/dynamicuniformbuffer
+    if alignment > 0 {
+        (size + alignment - 1) & !(alignment - 1)
+    } else {
+        size
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
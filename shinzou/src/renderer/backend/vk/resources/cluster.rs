@@ -0,0 +1,175 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::vk;
+use ultraviolet::{Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::{
+    backend::vk::resources::CameraFrustum,
+    entities::{Light, LightType},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const CLUSTER_GRID_X: usize = 16;
+pub const CLUSTER_GRID_Y: usize = 9;
+pub const CLUSTER_GRID_Z: usize = 24;
+pub const CLUSTER_COUNT: usize = CLUSTER_GRID_X * CLUSTER_GRID_Y * CLUSTER_GRID_Z;
+pub const MAX_LIGHTS_PER_CLUSTER: usize = 128;
+pub const MAX_CLUSTER_LIGHT_INDICES: usize = CLUSTER_COUNT * MAX_LIGHTS_PER_CLUSTER;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the header of the ClusterBuffer block declared in the lit shaders.
+#[repr(C)]
+#[derive(Default)]
+pub struct ClusterHeader {
+    /// xyz - cluster grid dimensions
+    pub grid_size: [u32; 4],
+    /// x - z near, y - depth slice scale, z - 1 / screen width, w - 1 / screen height
+    pub params: Vec4,
+    /// xyz - camera forward
+    pub camera_forward: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const CLUSTER_HEADER_SIZE: usize = size_of::<ClusterHeader>();
+/// Offset into the light index list and light count of every cluster.
+pub const CLUSTER_SIZE: usize = size_of::<[u32; 2]>();
+pub const CLUSTER_BUFFER_SIZE: u64 = (CLUSTER_HEADER_SIZE + CLUSTER_SIZE * CLUSTER_COUNT) as u64;
+pub const CLUSTER_LIGHT_INDEX_BUFFER_SIZE: u64 =
+    (size_of::<u32>() * MAX_CLUSTER_LIGHT_INDICES) as u64;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// View space cluster grid with the lights overlapping every cluster. Tiles split the screen evenly, depth slices are
+/// exponentially distributed between the camera's near and far planes. Directional lights affect every cluster and
+/// are never assigned. The lists are kept between frames, so rebuilding the grid doesn't allocate once they reached the
+/// size of the scene.
+#[derive(Default)]
+pub struct ClusterGrid {
+    pub header: ClusterHeader,
+    pub clusters: Vec<[u32; 2]>,
+    pub light_indices: Vec<u32>,
+    cluster_lights: Vec<Vec<u32>>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl ClusterGrid {
+    pub fn build(&mut self, frustum: &CameraFrustum, lights: &[&Light], extent: vk::Extent2D) {
+        let slice_scale = CLUSTER_GRID_Z as f32 / (frustum.far / frustum.near).ln();
+        let depth_slice = |depth: f32| -> usize {
+            let slice = ((depth / frustum.near).ln() * slice_scale).floor();
+            (slice.max(0.0) as usize).min(CLUSTER_GRID_Z - 1)
+        };
+
+        self.cluster_lights.resize_with(CLUSTER_COUNT, Vec::new);
+        for cluster in self.cluster_lights.iter_mut() {
+            cluster.clear();
+        }
+
+        for (light_index, light) in lights.iter().enumerate() {
+            if light.light_type == LightType::Directional {
+                continue;
+            }
+
+            let depth = frustum.depth(light.position);
+            if depth + light.range < frustum.near || depth - light.range > frustum.far {
+                continue;
+            }
+
+            let (x_range, y_range) = match screen_tiles(frustum, light.position, light.range) {
+                Some(tiles) => tiles,
+                None => continue,
+            };
+
+            let z_first = depth_slice((depth - light.range).max(frustum.near));
+            let z_last = depth_slice((depth + light.range).min(frustum.far));
+
+            for z in z_first..=z_last {
+                for y in y_range.0..=y_range.1 {
+                    for x in x_range.0..=x_range.1 {
+                        let cluster = &mut self.cluster_lights[cluster_index(x, y, z)];
+                        if cluster.len() < MAX_LIGHTS_PER_CLUSTER {
+                            cluster.push(light_index as u32);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.clusters.clear();
+        self.light_indices.clear();
+        for cluster in self.cluster_lights.iter() {
+            self.clusters
+                .push([self.light_indices.len() as u32, cluster.len() as u32]);
+            self.light_indices.extend_from_slice(cluster);
+        }
+
+        self.header = ClusterHeader {
+            grid_size: [
+                CLUSTER_GRID_X as u32,
+                CLUSTER_GRID_Y as u32,
+                CLUSTER_GRID_Z as u32,
+                0,
+            ],
+            params: Vec4::new(
+                frustum.near,
+                slice_scale,
+                1.0 / extent.width as f32,
+                1.0 / extent.height as f32,
+            ),
+            camera_forward: Vec4::new(frustum.forward.x, frustum.forward.y, frustum.forward.z, 0.0),
+        };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn cluster_index(x: usize, y: usize, z: usize) -> usize {
+    x + y * CLUSTER_GRID_X + z * CLUSTER_GRID_X * CLUSTER_GRID_Y
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Conservative range of screen tiles covered by a light's bounding sphere, or `None` if it is off screen.
+fn screen_tiles(
+    frustum: &CameraFrustum,
+    position: Vec3,
+    range: f32,
+) -> Option<((usize, usize), (usize, usize))> {
+    let mut min = Vec3::new(1.0, 1.0, 0.0);
+    let mut max = Vec3::new(-1.0, -1.0, 0.0);
+
+    for i in 0..8 {
+        let offset = Vec3::new(
+            if i & 1 == 0 { -range } else { range },
+            if i & 2 == 0 { -range } else { range },
+            if i & 4 == 0 { -range } else { range },
+        );
+
+        let clip = frustum.view_projection * (position + offset).into_homogeneous_point();
+        if clip.w <= 0.0 {
+            // Bounds straddle the camera plane, so the projected rectangle is unbounded
+            min = Vec3::new(-1.0, -1.0, 0.0);
+            max = Vec3::new(1.0, 1.0, 0.0);
+            break;
+        }
+
+        let ndc = clip.truncated() / clip.w;
+        min = min.min_by_component(ndc);
+        max = max.max_by_component(ndc);
+    }
+
+    if max.x < -1.0 || max.y < -1.0 || min.x > 1.0 || min.y > 1.0 {
+        return None;
+    }
+
+    let tile = |ndc: f32, count: usize| -> usize {
+        let tile = ((ndc.clamp(-1.0, 1.0) * 0.5 + 0.5) * count as f32).floor() as usize;
+        tile.min(count - 1)
+    };
+
+    Some((
+        (tile(min.x, CLUSTER_GRID_X), tile(max.x, CLUSTER_GRID_X)),
+        (tile(min.y, CLUSTER_GRID_Y), tile(max.y, CLUSTER_GRID_Y)),
+    ))
+}
+//----------------------------------------------------------------------------------------------------------------------
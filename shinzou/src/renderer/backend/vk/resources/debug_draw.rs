@@ -0,0 +1,92 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::vk;
+use field_offset::offset_of;
+use ultraviolet::{Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::{backend::vk::resources::VertexInputDescription, entities::DebugLine};
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const DEBUG_LINE_PIPELINE_LAYOUT: &str = "debug_line_pipeline_layout";
+/// Depth tested debug lines of the forward pass, created against its sample count.
+pub const DEBUG_LINE_PIPELINE: &str = "debug_line_pipeline";
+pub const DEBUG_LINE_OVERLAY_PIPELINE: &str = "debug_line_overlay_pipeline";
+/// Debug lines of the deferred path, drawn over the lit scene like transparent materials.
+pub const DEFERRED_DEBUG_LINE_PIPELINE: &str = "deferred_debug_line_pipeline";
+pub const DEFERRED_DEBUG_LINE_OVERLAY_PIPELINE: &str = "deferred_debug_line_overlay_pipeline";
+
+/// Vertices the per-frame debug line buffers start out with, they grow as needed.
+pub const DEFAULT_DEBUG_VERTEX_CAPACITY: u64 = 4096;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the vertex inputs declared in debug_line.vert.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DebugVertex {
+    pub position: Vec3,
+    pub color: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const DEBUG_VERTEX_SIZE: u64 = size_of::<DebugVertex>() as u64;
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DebugVertex {
+    pub fn input_description() -> VertexInputDescription {
+        let bindings = vec![vk::VertexInputBindingDescription::builder()
+            .binding(0)
+            .stride(DEBUG_VERTEX_SIZE as u32)
+            .input_rate(vk::VertexInputRate::VERTEX)
+            .build()];
+
+        let attributes = vec![
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(0)
+                .format(vk::Format::R32G32B32_SFLOAT)
+                .offset(offset_of!(DebugVertex => position).get_byte_offset() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(1)
+                .format(vk::Format::R32G32B32A32_SFLOAT)
+                .offset(offset_of!(DebugVertex => color).get_byte_offset() as u32)
+                .build(),
+        ];
+
+        VertexInputDescription {
+            bindings,
+            attributes,
+            flags: vk::PipelineVertexInputStateCreateFlags::empty(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Both ends of every line, the depth tested ones first. Returns the vertices and how many are depth tested.
+    pub fn from_lines<'a>(lines: impl Iterator<Item = &'a DebugLine>) -> (Vec<DebugVertex>, u32) {
+        let (depth_tested, overlay): (Vec<&DebugLine>, Vec<&DebugLine>) =
+            lines.partition(|line| line.depth_test);
+
+        let vertices = depth_tested
+            .iter()
+            .chain(overlay.iter())
+            .flat_map(|line| {
+                let start = DebugVertex {
+                    position: line.start,
+                    color: line.color,
+                };
+                let end = DebugVertex {
+                    position: line.end,
+                    color: line.color,
+                };
+                vec![start, end]
+            })
+            .collect::<Vec<DebugVertex>>();
+
+        (vertices, depth_tested.len() as u32 * 2)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
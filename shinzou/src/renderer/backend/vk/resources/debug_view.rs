@@ -0,0 +1,48 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::vk;
+use ultraviolet::Vec4;
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const DEBUG_VIEW_PIPELINE_LAYOUT: &str = "debug_view_pipeline_layout";
+/// Draws the renderables straight to the backbuffer for every mode showing a single value per pixel.
+pub const DEBUG_VIEW_PIPELINE: &str = "debug_view_pipeline";
+/// Same as the above, also writing the object id and depth targets while a pick is pending.
+pub const DEBUG_VIEW_PICKING_PIPELINE: &str = "debug_view_picking_pipeline";
+/// Adds one to the overdraw count for every fragment, with no depth test.
+pub const DEBUG_VIEW_OVERDRAW_PIPELINE: &str = "debug_view_overdraw_pipeline";
+/// Colors the overdraw count, uses the post-process pipeline layout.
+pub const DEBUG_VIEW_HEAT_MAP_PIPELINE: &str = "debug_view_heat_map_pipeline";
+/// Wireframe overlay of the forward pass, created against its sample count.
+pub const WIREFRAME_PIPELINE: &str = "wireframe_pipeline";
+/// Wireframe overlay of the deferred path, drawn over the lit scene like transparent materials.
+pub const DEFERRED_WIREFRAME_PIPELINE: &str = "deferred_wireframe_pipeline";
+
+pub const OVERDRAW_COUNT_FORMAT: vk::Format = vk::Format::R16_SFLOAT;
+/// Overdraw shown as the hottest color of the heat map.
+pub const OVERDRAW_HEAT_MAP_MAX: f32 = 8.0;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the DebugViewConstants block declared in debug_view.frag.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct DebugViewPushConstants {
+    /// x - debug view mode, y - camera z near, z - camera z far
+    pub params: Vec4,
+    /// Wireframe color
+    pub color: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DebugViewPushConstants {
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<DebugViewPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
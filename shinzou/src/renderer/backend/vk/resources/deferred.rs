@@ -0,0 +1,220 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::{Mat4, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::backend::vk::DeviceDestroy;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// rgb - albedo, a - ambient occlusion
+pub const GBUFFER_ALBEDO_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+/// xyz - world space normal, w - receives shadows
+pub const GBUFFER_NORMAL_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+/// r - metallic, g - roughness
+pub const GBUFFER_MATERIAL_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+/// rgb - emitted radiance
+pub const GBUFFER_EMISSIVE_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+pub const GBUFFER_COLOR_FORMATS: [vk::Format; 4] = [
+    GBUFFER_ALBEDO_FORMAT,
+    GBUFFER_NORMAL_FORMAT,
+    GBUFFER_MATERIAL_FORMAT,
+    GBUFFER_EMISSIVE_FORMAT,
+];
+
+pub const DEFERRED_LIGHTING_PIPELINE_LAYOUT: &str = "deferred_lighting_pipeline_layout";
+pub const DEFERRED_LIGHTING_PIPELINE: &str = "deferred_lighting_pipeline";
+pub const GBUFFER_DEBUG_PIPELINE: &str = "gbuffer_debug_pipeline";
+
+const GBUFFER_ALBEDO_BINDING: u32 = 0;
+const GBUFFER_NORMAL_BINDING: u32 = 1;
+const GBUFFER_MATERIAL_BINDING: u32 = 2;
+const GBUFFER_EMISSIVE_BINDING: u32 = 3;
+const GBUFFER_DEPTH_BINDING: u32 = 4;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the DeferredConstants block declared in deferred_lighting.frag and gbuffer_debug.frag.
+#[repr(C)]
+pub struct DeferredPushConstants {
+    /// Reconstructs world positions from the depth buffer.
+    pub inverse_view_projection: Mat4,
+    /// x - G-buffer debug channel, y - camera z near, z - camera z far
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeferredPushConstants {
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<DeferredPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// G-buffer descriptor sets of the deferred lighting and G-buffer debug passes, one per frame in flight since the
+/// render graph images behind them can change between frames.
+pub struct VkGBuffer {
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_sets: Vec<vk::DescriptorSet>,
+    sampler: vk::Sampler,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkGBuffer {
+    pub(in crate::renderer::backend::vk::resources) fn new(
+        device: &Device,
+        buffering: u32,
+    ) -> Self {
+        let bindings = [
+            GBUFFER_ALBEDO_BINDING,
+            GBUFFER_NORMAL_BINDING,
+            GBUFFER_MATERIAL_BINDING,
+            GBUFFER_EMISSIVE_BINDING,
+            GBUFFER_DEPTH_BINDING,
+        ]
+        .iter()
+        .map(|binding| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(*binding)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build()
+        })
+        .collect::<Vec<vk::DescriptorSetLayoutBinding>>();
+
+        let pool_sizes = [vk::DescriptorPoolSize::builder()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(buffering * bindings.len() as u32)
+            .build()];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(buffering)
+            .pool_sizes(&pool_sizes);
+
+        let descriptor_pool = unsafe {
+            device
+                .create_descriptor_pool(&pool_info, None)
+                .expect("VkGBuffer::new - Failed to create descriptor pool!")
+        };
+
+        let descriptor_set_layout = unsafe {
+            device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                    None,
+                )
+                .expect("VkGBuffer::new - Failed to create descriptor set layout!")
+        };
+
+        let set_layouts = vec![descriptor_set_layout; buffering as usize];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(descriptor_pool)
+            .set_layouts(&set_layouts);
+
+        let descriptor_sets = unsafe {
+            device
+                .allocate_descriptor_sets(&allocate_info)
+                .expect("VkGBuffer::new - Failed to allocate descriptor sets!")
+        };
+
+        // G-buffer texels map 1:1 to the lit pixels
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkGBuffer::new - Failed to create sampler!")
+        };
+
+        Self {
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_sets,
+            sampler,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.descriptor_set_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set(&self, frame_index: usize) -> vk::DescriptorSet {
+        self.descriptor_sets[frame_index]
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Points the set at this frame's G-buffer images, `color_views` in `GBUFFER_COLOR_FORMATS` order. The set must
+    /// belong to the frame being recorded, whose previous submission is known to have finished.
+    pub fn write_descriptor_set(
+        &self,
+        device: &Device,
+        descriptor_set: vk::DescriptorSet,
+        color_views: [vk::ImageView; 4],
+        depth_view: vk::ImageView,
+    ) {
+        let image_infos = color_views
+            .iter()
+            .map(|view| (*view, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL))
+            .chain(std::iter::once((
+                depth_view,
+                vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+            )))
+            .map(|(image_view, image_layout)| {
+                [vk::DescriptorImageInfo::builder()
+                    .sampler(self.sampler)
+                    .image_view(image_view)
+                    .image_layout(image_layout)
+                    .build()]
+            })
+            .collect::<Vec<[vk::DescriptorImageInfo; 1]>>();
+
+        let writes = [
+            GBUFFER_ALBEDO_BINDING,
+            GBUFFER_NORMAL_BINDING,
+            GBUFFER_MATERIAL_BINDING,
+            GBUFFER_EMISSIVE_BINDING,
+            GBUFFER_DEPTH_BINDING,
+        ]
+        .iter()
+        .zip(image_infos.iter())
+        .map(|(binding, image_info)| {
+            vk::WriteDescriptorSet::builder()
+                .dst_set(descriptor_set)
+                .dst_binding(*binding)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .image_info(image_info)
+                .build()
+        })
+        .collect::<Vec<vk::WriteDescriptorSet>>();
+
+        unsafe { device.update_descriptor_sets(&writes, &[]) };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceDestroy for VkGBuffer {
+    fn destroy(&self, device: &Device) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+            device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -1,89 +1,89 @@
-use ash::{version::DeviceV1_0, vk, Device};
-use vk_mem::Allocator;
+use ash::vk;
 //----------------------------------------------------------------------------------------------------------------------
 
 use crate::renderer::backend::vk::{
-    handles::{AllocatorFree, AllocatorHandle, InstanceHandle, PhysicalDeviceHandle},
+    handles::{InstanceHandle, PhysicalDeviceHandle},
     resources::image::VkImage,
-    DeviceAllocatorDestroy,
 };
 //----------------------------------------------------------------------------------------------------------------------
 
-pub struct VkDepthBuffer {
-    pub(in crate::renderer::backend::vk::resources) image: VkImage,
-    pub(in crate::renderer::backend::vk::resources) image_view: vk::ImageView,
-}
+/// Depth buffers are transient render graph images, only the format and sample count selection lives here.
+pub struct VkDepthBuffer;
 //----------------------------------------------------------------------------------------------------------------------
 
 impl VkDepthBuffer {
+    /// Reversed depth only pays off with floating-point depth, so fixed-point formats are left out for it. With
+    /// `stencil`, formats with a stencil component come first and depth-only ones are the fallback, check the result
+    /// with `has_stencil`.
     pub fn find_supported_depth_format(
         instance_handle: &InstanceHandle,
         physical_device_handle: &PhysicalDeviceHandle,
+        reversed_depth: bool,
+        stencil: bool,
     ) -> vk::Format {
-        VkImage::find_supported_format(
-            instance_handle,
-            physical_device_handle,
-            &[
+        let candidates: &[vk::Format] = match (reversed_depth, stencil) {
+            (true, false) => &[vk::Format::D32_SFLOAT, vk::Format::D32_SFLOAT_S8_UINT],
+            (true, true) => &[vk::Format::D32_SFLOAT_S8_UINT, vk::Format::D32_SFLOAT],
+            (false, false) => &[
                 vk::Format::D32_SFLOAT,
                 vk::Format::D32_SFLOAT_S8_UINT,
                 vk::Format::D24_UNORM_S8_UINT,
             ],
+            (false, true) => &[
+                vk::Format::D24_UNORM_S8_UINT,
+                vk::Format::D32_SFLOAT_S8_UINT,
+                vk::Format::D32_SFLOAT,
+            ],
+        };
+
+        // Depth buffers are sampled by later passes, and stencils by the selection outline
+        VkImage::find_supported_format(
+            instance_handle,
+            physical_device_handle,
+            candidates,
             vk::ImageTiling::OPTIMAL,
-            vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+            vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT
+                | vk::FormatFeatureFlags::SAMPLED_IMAGE,
         )
     }
     //------------------------------------------------------------------------------------------------------------------
 
-    pub fn new(
-        device: &Device,
-        allocator_handle: &AllocatorHandle,
-        surface_extent: vk::Extent2D,
-        depth_attachment_format: vk::Format,
-    ) -> Self {
-        let vk::Extent2D { width, height } = surface_extent;
-
-        let create_info = VkImage::image_create_info(
-            depth_attachment_format,
-            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
-            vk::Extent3D::builder()
-                .width(width)
-                .height(height)
-                .depth(1)
-                .build(),
-            vk::ImageTiling::OPTIMAL,
-        );
-
-        let allocation_info = AllocatorHandle::allocation_create_info(
-            vk_mem::MemoryUsage::GpuOnly,
-            None,
-            Some(vk::MemoryPropertyFlags::DEVICE_LOCAL),
-        );
-
-        let image = allocator_handle.create_image(&create_info, &allocation_info);
-
-        let create_info = VkImage::image_view_create_info(
-            depth_attachment_format,
-            image.get(),
-            vk::ImageAspectFlags::DEPTH,
-        );
-
-        let image_view = unsafe {
-            device
-                .create_image_view(&create_info, None)
-                .expect("VkRenderer::create_depth_buffers - Failed to create image view!")
-        };
-
-        Self { image, image_view }
+    pub fn has_stencil(format: vk::Format) -> bool {
+        matches!(
+            format,
+            vk::Format::D16_UNORM_S8_UINT
+                | vk::Format::D24_UNORM_S8_UINT
+                | vk::Format::D32_SFLOAT_S8_UINT
+                | vk::Format::S8_UINT
+        )
     }
-}
-//----------------------------------------------------------------------------------------------------------------------
+    //------------------------------------------------------------------------------------------------------------------
 
-impl DeviceAllocatorDestroy for VkDepthBuffer {
-    fn destroy(&self, device: &Device, allocator: &Allocator) {
-        unsafe {
-            device.destroy_image_view(self.image_view, None);
-            self.image.free(allocator);
-        }
+    /// Highest sample count not above `requested` that the device supports for both color and depth attachments.
+    pub fn find_supported_sample_count(
+        physical_device_handle: &PhysicalDeviceHandle,
+        requested: u32,
+    ) -> vk::SampleCountFlags {
+        let limits = &physical_device_handle
+            .physical_device_attributes
+            .properties
+            .limits;
+        let supported =
+            limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts;
+
+        [
+            vk::SampleCountFlags::TYPE_64,
+            vk::SampleCountFlags::TYPE_32,
+            vk::SampleCountFlags::TYPE_16,
+            vk::SampleCountFlags::TYPE_8,
+            vk::SampleCountFlags::TYPE_4,
+            vk::SampleCountFlags::TYPE_2,
+        ]
+        .iter()
+        .copied()
+        .find(|samples| samples.as_raw() <= requested && supported.contains(*samples))
+        .unwrap_or(vk::SampleCountFlags::TYPE_1)
     }
+    //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,266 @@
+/// Group of draws recorded together, in the order they are drawn. Occupies the most significant bits of a sort key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawLayer {
+    /// Opaque and cutout materials, sorted by state and then front-to-back.
+    Opaque = 0,
+    /// Blended materials, sorted back-to-front and then by state.
+    Transparent = 1,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+const LAYER_BITS: u32 = 4;
+const PIPELINE_BITS: u32 = 12;
+const MATERIAL_BITS: u32 = 16;
+const MESH_BITS: u32 = 16;
+const DEPTH_BITS: u32 = 16;
+
+const LAYER_SHIFT: u32 = 64 - LAYER_BITS;
+const RADIX_BITS: u32 = 8;
+const RADIX_BUCKETS: usize = 1 << RADIX_BITS;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Packed 64-bit key ordering a draw, compared as a plain integer. From the most significant bits:
+///
+/// - opaque: layer (4) | pipeline (12) | material (16) | mesh (16) | depth (16)
+/// - transparent: layer (4) | inverted depth (16) | pipeline (12) | material (16) | mesh (16)
+///
+/// Ids wider than their field wrap around, which only costs state changes, while depth is quantized over
+/// `[near, far]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DrawKey(u64);
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DrawKey {
+    pub fn new(
+        layer: DrawLayer,
+        pipeline_id: u32,
+        material_id: u32,
+        mesh_id: u32,
+        depth: f32,
+        depth_range: (f32, f32),
+    ) -> Self {
+        let field = |value: u32, bits: u32| value as u64 & ((1 << bits) - 1);
+
+        let layer_bits = (layer as u64) << LAYER_SHIFT;
+        let state_bits = field(pipeline_id, PIPELINE_BITS) << (MATERIAL_BITS + MESH_BITS)
+            | field(material_id, MATERIAL_BITS) << MESH_BITS
+            | field(mesh_id, MESH_BITS);
+        let depth_bits = quantize_depth(depth, depth_range) as u64;
+
+        let key = match layer {
+            DrawLayer::Opaque => layer_bits | state_bits << DEPTH_BITS | depth_bits,
+            DrawLayer::Transparent => {
+                let inverted_depth = ((1 << DEPTH_BITS) - 1) - depth_bits;
+                layer_bits
+                    | inverted_depth << (PIPELINE_BITS + MATERIAL_BITS + MESH_BITS)
+                    | state_bits
+            }
+        };
+
+        Self(key)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn layer(&self) -> DrawLayer {
+        match self.0 >> LAYER_SHIFT {
+            0 => DrawLayer::Opaque,
+            _ => DrawLayer::Transparent,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn quantize_depth(depth: f32, (near, far): (f32, f32)) -> u32 {
+    let range = (far - near).max(f32::EPSILON);
+    let normalized = ((depth - near) / range).clamp(0.0, 1.0);
+
+    (normalized * ((1 << DEPTH_BITS) - 1) as f32) as u32
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// A draw waiting to be sorted, `index` points into the renderables and entity buffers of the frame.
+#[derive(Clone, Copy, Debug)]
+pub struct DrawItem {
+    pub key: DrawKey,
+    pub index: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Per-frame list of draws, sorted by key with a least significant digit radix sort. Both buffers are kept between
+/// frames, so filling and sorting the list doesn't allocate once it reached the size of the scene.
+#[derive(Default)]
+pub struct DrawList {
+    items: Vec<DrawItem>,
+    scratch: Vec<DrawItem>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DrawList {
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn push(&mut self, key: DrawKey, index: u32) {
+        self.items.push(DrawItem { key, index });
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn items(&self) -> &[DrawItem] {
+        &self.items
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Stable sort by key. Digits every key shares, like the unused layers, are skipped.
+    pub fn sort(&mut self) {
+        let mut histograms = [[0usize; RADIX_BUCKETS]; (64 / RADIX_BITS) as usize];
+
+        for item in self.items.iter() {
+            for (digit, histogram) in histograms.iter_mut().enumerate() {
+                histogram[radix_digit(item.key, digit)] += 1;
+            }
+        }
+
+        self.scratch.clear();
+        self.scratch.extend_from_slice(&self.items);
+
+        for (digit, histogram) in histograms.iter().enumerate() {
+            if histogram.contains(&self.items.len()) {
+                continue;
+            }
+
+            let mut offsets = [0usize; RADIX_BUCKETS];
+            let mut offset = 0;
+            for (bucket, count) in histogram.iter().enumerate() {
+                offsets[bucket] = offset;
+                offset += count;
+            }
+
+            for item in self.items.iter() {
+                let bucket = radix_digit(item.key, digit);
+                self.scratch[offsets[bucket]] = *item;
+                offsets[bucket] += 1;
+            }
+
+            std::mem::swap(&mut self.items, &mut self.scratch);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn radix_digit(key: DrawKey, digit: usize) -> usize {
+    ((key.0 >> (digit as u32 * RADIX_BITS)) as usize) & (RADIX_BUCKETS - 1)
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPTH_RANGE: (f32, f32) = (0.1, 100.0);
+
+    fn sorted_indices(keys: &[DrawKey]) -> Vec<u32> {
+        let mut draw_list = DrawList::default();
+        for (i, key) in keys.iter().enumerate() {
+            draw_list.push(*key, i as u32);
+        }
+        draw_list.sort();
+
+        draw_list.items().iter().map(|item| item.index).collect()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    #[test]
+    fn opaque_draws_sort_by_state_then_front_to_back() {
+        let keys = [
+            DrawKey::new(DrawLayer::Opaque, 0, 1, 0, 10.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Opaque, 0, 0, 0, 50.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Opaque, 0, 0, 0, 5.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Opaque, 0, 1, 0, 1.0, DEPTH_RANGE),
+        ];
+
+        assert_eq!(sorted_indices(&keys), vec![2, 1, 3, 0]);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    #[test]
+    fn transparent_draws_sort_back_to_front_across_states() {
+        let keys = [
+            DrawKey::new(DrawLayer::Transparent, 0, 0, 0, 5.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Transparent, 1, 2, 3, 50.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Transparent, 0, 0, 0, 20.0, DEPTH_RANGE),
+        ];
+
+        assert_eq!(sorted_indices(&keys), vec![1, 2, 0]);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    #[test]
+    fn layers_take_precedence_over_state_and_depth() {
+        let keys = [
+            DrawKey::new(DrawLayer::Transparent, 0, 0, 0, 1.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Opaque, 4095, 65535, 65535, 100.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Transparent, 0, 0, 0, 100.0, DEPTH_RANGE),
+        ];
+
+        assert_eq!(sorted_indices(&keys), vec![1, 2, 0]);
+        assert_eq!(keys[0].layer(), DrawLayer::Transparent);
+        assert_eq!(keys[1].layer(), DrawLayer::Opaque);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    #[test]
+    fn out_of_range_ids_wrap_within_their_field() {
+        let wide = DrawKey::new(
+            DrawLayer::Opaque,
+            u32::MAX,
+            u32::MAX,
+            u32::MAX,
+            1.0,
+            DEPTH_RANGE,
+        );
+        let wrapped = DrawKey::new(
+            DrawLayer::Opaque,
+            (1 << PIPELINE_BITS) - 1,
+            (1 << MATERIAL_BITS) - 1,
+            (1 << MESH_BITS) - 1,
+            1.0,
+            DEPTH_RANGE,
+        );
+
+        assert_eq!(wide, wrapped);
+        assert_eq!(wide.layer(), DrawLayer::Opaque);
+        assert_eq!(
+            DrawKey::new(
+                DrawLayer::Opaque,
+                1 << PIPELINE_BITS,
+                0,
+                0,
+                1.0,
+                DEPTH_RANGE
+            ),
+            DrawKey::new(DrawLayer::Opaque, 0, 0, 0, 1.0, DEPTH_RANGE)
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    #[test]
+    fn out_of_range_depths_clamp_to_the_depth_range() {
+        let max_depth = (1 << DEPTH_BITS) - 1;
+
+        assert_eq!(quantize_depth(-10.0, DEPTH_RANGE), 0);
+        assert_eq!(quantize_depth(DEPTH_RANGE.0, DEPTH_RANGE), 0);
+        assert_eq!(quantize_depth(1000.0, DEPTH_RANGE), max_depth);
+        assert_eq!(quantize_depth(DEPTH_RANGE.1, DEPTH_RANGE), max_depth);
+        assert_eq!(quantize_depth(f32::INFINITY, DEPTH_RANGE), max_depth);
+        assert_eq!(quantize_depth(5.0, (1.0, 1.0)), max_depth);
+
+        assert_eq!(
+            DrawKey::new(DrawLayer::Transparent, 0, 0, 0, 1000.0, DEPTH_RANGE),
+            DrawKey::new(DrawLayer::Transparent, 0, 0, 0, DEPTH_RANGE.1, DEPTH_RANGE)
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
@@ -2,7 +2,12 @@ use ash::vk;
 use vk_mem::Allocator;
 //----------------------------------------------------------------------------------------------------------------------
 
-use crate::renderer::backend::vk::resources::{MESH_META_SSBO_SIZE, MESH_SSBO_MAX, MESH_SSBO_SIZE};
+use crate::renderer::backend::vk::resources::{
+    BILLBOARD_VERTEX_SIZE, CLUSTER_BUFFER_SIZE, CLUSTER_LIGHT_INDEX_BUFFER_SIZE, DEBUG_VERTEX_SIZE,
+    DEFAULT_BILLBOARD_VERTEX_CAPACITY, DEFAULT_DEBUG_VERTEX_CAPACITY,
+    DEFAULT_SPRITE_VERTEX_CAPACITY, MESH_META_SSBO_SIZE, MESH_SSBO_SIZE, PICK_READBACK_SIZE,
+    SPRITE_VERTEX_SIZE,
+};
 use crate::renderer::backend::vk::{
     handles::{AllocatorFree, AllocatorHandle},
     resources::VkBuffer,
@@ -18,37 +23,69 @@ pub struct VkFrame {
     pub command_buffer: vk::CommandBuffer,
 
     pub entity_descriptor_set: vk::DescriptorSet,
+    pub entity_capacity: u64,
     pub entity_buffer: VkBuffer,
     pub entity_meta_buffer: VkBuffer,
+    pub cluster_buffer: VkBuffer,
+    pub cluster_light_index_buffer: VkBuffer,
+    /// Host readable copy of the picked pixel, valid once the frame's render fence has been waited on.
+    pub pick_buffer: VkBuffer,
+    pub debug_vertex_capacity: u64,
+    pub debug_vertex_buffer: VkBuffer,
+    pub sprite_vertex_capacity: u64,
+    pub sprite_vertex_buffer: VkBuffer,
+    pub billboard_vertex_capacity: u64,
+    pub billboard_vertex_buffer: VkBuffer,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
 impl VkFrame {
     pub fn new(
         allocator_handle: &AllocatorHandle,
+        entity_capacity: u64,
         present_semaphore: vk::Semaphore,
         render_semaphore: vk::Semaphore,
         render_fence: vk::Fence,
         command_pool: vk::CommandPool,
         command_buffer: vk::CommandBuffer,
     ) -> Self {
+        let (entity_buffer, entity_meta_buffer) =
+            create_entity_buffers(allocator_handle, entity_capacity);
+
         let allocation_info =
             AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None);
 
-        let entity_buffer = allocator_handle.create_buffer(
-            &vk::BufferCreateInfo::builder()
-                .size(MESH_SSBO_SIZE * MESH_SSBO_MAX)
-                .usage(vk::BufferUsageFlags::STORAGE_BUFFER),
+        let cluster_buffer = allocator_handle.create_buffer(
+            &VkBuffer::create_info(CLUSTER_BUFFER_SIZE, vk::BufferUsageFlags::STORAGE_BUFFER),
             &allocation_info,
         );
 
-        let entity_meta_buffer = allocator_handle.create_buffer(
-            &vk::BufferCreateInfo::builder()
-                .size(MESH_META_SSBO_SIZE * MESH_SSBO_MAX)
-                .usage(vk::BufferUsageFlags::STORAGE_BUFFER),
+        let cluster_light_index_buffer = allocator_handle.create_buffer(
+            &VkBuffer::create_info(
+                CLUSTER_LIGHT_INDEX_BUFFER_SIZE,
+                vk::BufferUsageFlags::STORAGE_BUFFER,
+            ),
             &allocation_info,
         );
 
+        let pick_buffer = allocator_handle.create_buffer(
+            &VkBuffer::create_info(PICK_READBACK_SIZE, vk::BufferUsageFlags::TRANSFER_DST),
+            &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::GpuToCpu, None, None),
+        );
+
+        let debug_vertex_buffer = create_vertex_buffer(
+            allocator_handle,
+            DEBUG_VERTEX_SIZE * DEFAULT_DEBUG_VERTEX_CAPACITY,
+        );
+        let sprite_vertex_buffer = create_vertex_buffer(
+            allocator_handle,
+            SPRITE_VERTEX_SIZE * DEFAULT_SPRITE_VERTEX_CAPACITY,
+        );
+        let billboard_vertex_buffer = create_vertex_buffer(
+            allocator_handle,
+            BILLBOARD_VERTEX_SIZE * DEFAULT_BILLBOARD_VERTEX_CAPACITY,
+        );
+
         Self {
             present_semaphore,
             render_semaphore,
@@ -56,10 +93,90 @@ impl VkFrame {
             command_pool,
             command_buffer,
             entity_descriptor_set: Default::default(),
+            entity_capacity,
             entity_buffer,
             entity_meta_buffer,
+            cluster_buffer,
+            cluster_light_index_buffer,
+            pick_buffer,
+            debug_vertex_capacity: DEFAULT_DEBUG_VERTEX_CAPACITY,
+            debug_vertex_buffer,
+            sprite_vertex_capacity: DEFAULT_SPRITE_VERTEX_CAPACITY,
+            sprite_vertex_buffer,
+            billboard_vertex_capacity: DEFAULT_BILLBOARD_VERTEX_CAPACITY,
+            billboard_vertex_buffer,
         }
     }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Reallocates the entity buffers to hold `entity_capacity` entries. Previous contents are discarded, and the
+    /// entity descriptor set must be rewritten afterwards. Only call once the frame's render fence has been waited on.
+    pub fn grow_entity_buffers(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        entity_capacity: u64,
+    ) {
+        self.entity_buffer.free(&allocator_handle.allocator);
+        self.entity_meta_buffer.free(&allocator_handle.allocator);
+
+        let (entity_buffer, entity_meta_buffer) =
+            create_entity_buffers(allocator_handle, entity_capacity);
+
+        self.entity_capacity = entity_capacity;
+        self.entity_buffer = entity_buffer;
+        self.entity_meta_buffer = entity_meta_buffer;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Reallocates the debug vertex buffer to hold `debug_vertex_capacity` vertices, discarding its contents. Only call
+    /// once the frame's render fence has been waited on.
+    pub fn grow_debug_vertex_buffer(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        debug_vertex_capacity: u64,
+    ) {
+        self.debug_vertex_buffer.free(&allocator_handle.allocator);
+
+        self.debug_vertex_capacity = debug_vertex_capacity;
+        self.debug_vertex_buffer =
+            create_vertex_buffer(allocator_handle, DEBUG_VERTEX_SIZE * debug_vertex_capacity);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Reallocates the sprite vertex buffer to hold `sprite_vertex_capacity` vertices, discarding its contents. Only
+    /// call once the frame's render fence has been waited on.
+    pub fn grow_sprite_vertex_buffer(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        sprite_vertex_capacity: u64,
+    ) {
+        self.sprite_vertex_buffer.free(&allocator_handle.allocator);
+
+        self.sprite_vertex_capacity = sprite_vertex_capacity;
+        self.sprite_vertex_buffer = create_vertex_buffer(
+            allocator_handle,
+            SPRITE_VERTEX_SIZE * sprite_vertex_capacity,
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Reallocates the billboard vertex buffer to hold `billboard_vertex_capacity` vertices, discarding its contents.
+    /// Only call once the frame's render fence has been waited on.
+    pub fn grow_billboard_vertex_buffer(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        billboard_vertex_capacity: u64,
+    ) {
+        self.billboard_vertex_buffer
+            .free(&allocator_handle.allocator);
+
+        self.billboard_vertex_capacity = billboard_vertex_capacity;
+        self.billboard_vertex_buffer = create_vertex_buffer(
+            allocator_handle,
+            BILLBOARD_VERTEX_SIZE * billboard_vertex_capacity,
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -67,6 +184,46 @@ impl AllocatorFree for VkFrame {
     fn free(&self, allocator: &Allocator) {
         self.entity_buffer.free(allocator);
         self.entity_meta_buffer.free(allocator);
+        self.cluster_buffer.free(allocator);
+        self.cluster_light_index_buffer.free(allocator);
+        self.pick_buffer.free(allocator);
+        self.debug_vertex_buffer.free(allocator);
+        self.sprite_vertex_buffer.free(allocator);
+        self.billboard_vertex_buffer.free(allocator);
     }
 }
 //----------------------------------------------------------------------------------------------------------------------
+
+fn create_entity_buffers(
+    allocator_handle: &AllocatorHandle,
+    entity_capacity: u64,
+) -> (VkBuffer, VkBuffer) {
+    let allocation_info =
+        AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None);
+
+    let entity_buffer = allocator_handle.create_buffer(
+        &vk::BufferCreateInfo::builder()
+            .size(MESH_SSBO_SIZE * entity_capacity)
+            .usage(vk::BufferUsageFlags::STORAGE_BUFFER),
+        &allocation_info,
+    );
+
+    let entity_meta_buffer = allocator_handle.create_buffer(
+        &vk::BufferCreateInfo::builder()
+            .size(MESH_META_SSBO_SIZE * entity_capacity)
+            .usage(vk::BufferUsageFlags::STORAGE_BUFFER),
+        &allocation_info,
+    );
+
+    (entity_buffer, entity_meta_buffer)
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Host written vertex buffer of `size` bytes, for geometry rebuilt every frame.
+fn create_vertex_buffer(allocator_handle: &AllocatorHandle, size: vk::DeviceSize) -> VkBuffer {
+    allocator_handle.create_buffer(
+        &VkBuffer::create_info(size, vk::BufferUsageFlags::VERTEX_BUFFER),
+        &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None),
+    )
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -10,18 +10,15 @@ pub struct VkFramebuffer {
 //----------------------------------------------------------------------------------------------------------------------
 
 impl VkFramebuffer {
-    pub(in crate::renderer::backend::vk::resources) fn new(
+    pub(in crate::renderer::backend::vk) fn new(
         device: &Device,
-        color_image_view: &vk::ImageView,
-        depth_image_view: &vk::ImageView,
+        attachments: &[vk::ImageView],
         render_pass: vk::RenderPass,
         extent: vk::Extent2D,
     ) -> Self {
-        let attachments = [color_image_view.to_owned(), depth_image_view.to_owned()];
-
         let create_info = vk::FramebufferCreateInfo::builder()
             .render_pass(render_pass)
-            .attachments(&attachments)
+            .attachments(attachments)
             .width(extent.width)
             .height(extent.height)
             .layers(1);
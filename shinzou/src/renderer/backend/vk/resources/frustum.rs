@@ -0,0 +1,74 @@
+use ultraviolet::{Mat4, Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::Camera;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// World space camera frustum, with depths measured along the camera's forward axis.
+pub struct CameraFrustum {
+    /// Near plane corners followed by the matching far plane corners.
+    pub corners: [Vec3; 8],
+    pub view_projection: Mat4,
+    pub eye: Vec3,
+    pub forward: Vec3,
+    pub near: f32,
+    pub far: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl CameraFrustum {
+    pub fn new(camera: &Camera) -> Self {
+        let view = camera.view();
+        let view_projection = camera.frustum_projection() * view;
+        let inverse_view_projection = view_projection.inversed();
+
+        let mut corners = [Vec3::zero(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let x = if i & 1 == 0 { -1.0 } else { 1.0 };
+            let y = if i & 2 == 0 { -1.0 } else { 1.0 };
+            let z = if i < 4 { 0.0 } else { 1.0 };
+
+            let world = inverse_view_projection * Vec4::new(x, y, z, 1.0);
+            *corner = world.truncated() / world.w;
+        }
+
+        let near_center = corners[..4].iter().fold(Vec3::zero(), |acc, c| acc + *c) / 4.0;
+        let far_center = corners[4..].iter().fold(Vec3::zero(), |acc, c| acc + *c) / 4.0;
+
+        let eye = view.inversed().cols[3].truncated();
+        let forward = (far_center - near_center).normalized();
+
+        Self {
+            corners,
+            view_projection,
+            eye,
+            forward,
+            near: (near_center - eye).dot(forward),
+            far: (far_center - eye).dot(forward),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn slice_corners(&self, slice_near: f32, slice_far: f32) -> [Vec3; 8] {
+        let depth = self.far - self.near;
+        let t_near = (slice_near - self.near) / depth;
+        let t_far = (slice_far - self.near) / depth;
+
+        let mut corners = [Vec3::zero(); 8];
+        for i in 0..4 {
+            let ray = self.corners[i + 4] - self.corners[i];
+            corners[i] = self.corners[i] + ray * t_near;
+            corners[i + 4] = self.corners[i] + ray * t_far;
+        }
+
+        corners
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Distance of `position` along the camera's forward axis.
+    pub fn depth(&self, position: Vec3) -> f32 {
+        (position - self.eye).dot(self.forward)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,827 @@
+use std::{fs, io, mem::size_of, path::Path};
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::Vec4;
+use vk_mem::Allocator;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::{
+    renderer::{
+        backend::vk::{
+            handles::{AllocatorFree, AllocatorHandle},
+            resources::{VkBuffer, VkFramebuffer, VkImage},
+            utils, DeviceAllocatorDestroy, DeviceDestroy,
+        },
+        entities::TextureRaw,
+    },
+    utils::ffi,
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Format of the irradiance and prefiltered environment cubemaps.
+pub const IBL_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+/// r - scale, g - bias applied to F0 by the split-sum approximation
+pub const BRDF_LUT_FORMAT: vk::Format = vk::Format::R16G16_SFLOAT;
+pub const IRRADIANCE_SIZE: u32 = 32;
+/// Size of the first prefiltered mip, each following mip halves it and covers a rougher surface.
+pub const PREFILTERED_SIZE: u32 = 128;
+pub const PREFILTERED_MIP_COUNT: u32 = 5;
+pub const BRDF_LUT_SIZE: u32 = 256;
+
+pub const IBL_PIPELINE_LAYOUT: &str = "ibl_pipeline_layout";
+pub const IRRADIANCE_PIPELINE: &str = "ibl_irradiance_pipeline";
+pub const PREFILTER_PIPELINE: &str = "ibl_prefilter_pipeline";
+pub const BRDF_LUT_PIPELINE: &str = "ibl_brdf_lut_pipeline";
+
+const PREFILTER_SAMPLE_COUNT: u32 = 1024;
+const BRDF_LUT_SAMPLE_COUNT: u32 = 1024;
+const IBL_TEXEL_SIZE: u64 = 8;
+const CUBE_FACE_COUNT: u32 = 6;
+
+const CACHE_MAGIC: &[u8; 8] = b"SHZIBL01";
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the IblConstants block declared in the ibl_*.frag shaders.
+#[repr(C)]
+pub struct IblPushConstants {
+    /// x - cube face, y - roughness, z - sample count
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl IblPushConstants {
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<IblPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IblMap {
+    /// Cosine convolved environment, looked up with the surface normal.
+    Irradiance,
+    /// GGX filtered environment, looked up with the reflection vector at the mip matching the roughness.
+    Prefiltered,
+    /// Environment independent, indexed by NdotV and roughness.
+    BrdfLut,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl IblMap {
+    fn format(&self) -> vk::Format {
+        match self {
+            IblMap::BrdfLut => BRDF_LUT_FORMAT,
+            _ => IBL_FORMAT,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn size(&self) -> u32 {
+        match self {
+            IblMap::Irradiance => IRRADIANCE_SIZE,
+            IblMap::Prefiltered => PREFILTERED_SIZE,
+            IblMap::BrdfLut => BRDF_LUT_SIZE,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn mip_count(&self) -> u32 {
+        match self {
+            IblMap::Prefiltered => PREFILTERED_MIP_COUNT,
+            _ => 1,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn layer_count(&self) -> u32 {
+        match self {
+            IblMap::BrdfLut => 1,
+            _ => CUBE_FACE_COUNT,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn subresource_range(&self) -> vk::ImageSubresourceRange {
+        vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(0)
+            .level_count(self.mip_count())
+            .base_array_layer(0)
+            .layer_count(self.layer_count())
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Fragment shader inputs of the draw filling `layer` and `mip`.
+    fn push_constants(&self, layer: u32, mip: u32) -> IblPushConstants {
+        let params = match self {
+            IblMap::Irradiance => Vec4::new(layer as f32, 0.0, 0.0, 0.0),
+            IblMap::Prefiltered => Vec4::new(
+                layer as f32,
+                mip as f32 / (PREFILTERED_MIP_COUNT - 1) as f32,
+                PREFILTER_SAMPLE_COUNT as f32,
+                0.0,
+            ),
+            IblMap::BrdfLut => Vec4::new(0.0, 0.0, BRDF_LUT_SAMPLE_COUNT as f32, 0.0),
+        };
+
+        IblPushConstants { params }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Render pass, pipeline and pipeline layout filling one of the maps.
+#[derive(Clone, Copy)]
+pub struct VkIblPass {
+    pub render_pass: vk::RenderPass,
+    pub pipeline: vk::Pipeline,
+    pub pipeline_layout: vk::PipelineLayout,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Image based lighting maps sampled by the lighting shaders through the global descriptor set, plus the descriptor set
+/// the precomputation passes read the environment through. The maps are rendered once per environment at load and
+/// stay in the read-only layout outside of that.
+pub struct VkIbl {
+    irradiance: VkImage,
+    irradiance_view: vk::ImageView,
+    prefiltered: VkImage,
+    prefiltered_view: vk::ImageView,
+    brdf_lut: VkImage,
+    brdf_lut_view: vk::ImageView,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_set: vk::DescriptorSet,
+    sampler: vk::Sampler,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkIbl {
+    /// Creates the maps cleared to black.
+    pub(in crate::renderer::backend::vk::resources) fn new(
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) -> Self {
+        let create_map = |map: IblMap| {
+            let mut create_info = VkImage::image_create_info(
+                map.format(),
+                vk::ImageUsageFlags::COLOR_ATTACHMENT
+                    | vk::ImageUsageFlags::SAMPLED
+                    | vk::ImageUsageFlags::TRANSFER_SRC
+                    | vk::ImageUsageFlags::TRANSFER_DST,
+                vk::Extent3D::builder()
+                    .width(map.size())
+                    .height(map.size())
+                    .depth(1)
+                    .build(),
+                vk::ImageTiling::OPTIMAL,
+            );
+            create_info.mip_levels = map.mip_count();
+            create_info.array_layers = map.layer_count();
+
+            let view_type = if map.layer_count() == CUBE_FACE_COUNT {
+                create_info.flags = vk::ImageCreateFlags::CUBE_COMPATIBLE;
+                vk::ImageViewType::CUBE
+            } else {
+                vk::ImageViewType::TYPE_2D
+            };
+
+            let image = allocator_handle.create_image(
+                &create_info,
+                &AllocatorHandle::allocation_create_info(
+                    vk_mem::MemoryUsage::GpuOnly,
+                    None,
+                    Some(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+                ),
+            );
+
+            let mut view_info = VkImage::image_view_create_info(
+                map.format(),
+                image.get(),
+                vk::ImageAspectFlags::COLOR,
+                view_type,
+            );
+            view_info.subresource_range = map.subresource_range();
+
+            let image_view = unsafe {
+                device
+                    .create_image_view(&view_info, None)
+                    .expect("VkIbl::new - Failed to create image view!")
+            };
+
+            (image, image_view)
+        };
+
+        let (irradiance, irradiance_view) = create_map(IblMap::Irradiance);
+        let (prefiltered, prefiltered_view) = create_map(IblMap::Prefiltered);
+        let (brdf_lut, brdf_lut_view) = create_map(IblMap::BrdfLut);
+
+        let maps = [
+            (irradiance.get(), IblMap::Irradiance),
+            (prefiltered.get(), IblMap::Prefiltered),
+            (brdf_lut.get(), IblMap::BrdfLut),
+        ];
+
+        let clear = |cmd: &vk::CommandBuffer| {
+            for (image, map) in maps.iter() {
+                let subresource_range = map.subresource_range();
+
+                let to_transfer_barrier = [vk::ImageMemoryBarrier::builder()
+                    .old_layout(vk::ImageLayout::UNDEFINED)
+                    .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .image(*image)
+                    .subresource_range(subresource_range)
+                    .src_access_mask(vk::AccessFlags::empty())
+                    .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .build()];
+
+                let to_shader_barrier = [vk::ImageMemoryBarrier::builder()
+                    .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .image(*image)
+                    .subresource_range(subresource_range)
+                    .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                    .build()];
+
+                unsafe {
+                    device.cmd_pipeline_barrier(
+                        *cmd,
+                        vk::PipelineStageFlags::TOP_OF_PIPE,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[],
+                        &to_transfer_barrier,
+                    );
+                    device.cmd_clear_color_image(
+                        *cmd,
+                        *image,
+                        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        &vk::ClearColorValue {
+                            float32: [0.0, 0.0, 0.0, 1.0],
+                        },
+                        &[subresource_range],
+                    );
+                    device.cmd_pipeline_barrier(
+                        *cmd,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[],
+                        &to_shader_barrier,
+                    );
+                }
+            }
+        };
+
+        utils::immediate_submit(device, command_pool, fence, queue, &clear);
+
+        let bindings = [vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_count(1)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()];
+
+        let pool_sizes = [vk::DescriptorPoolSize::builder()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(1)
+            .build()];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(1)
+            .pool_sizes(&pool_sizes);
+
+        let descriptor_pool = unsafe {
+            device
+                .create_descriptor_pool(&pool_info, None)
+                .expect("VkIbl::new - Failed to create descriptor pool!")
+        };
+
+        let descriptor_set_layout = unsafe {
+            device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                    None,
+                )
+                .expect("VkIbl::new - Failed to create descriptor set layout!")
+        };
+
+        let set_layouts = [descriptor_set_layout];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(descriptor_pool)
+            .set_layouts(&set_layouts);
+
+        let descriptor_set = unsafe {
+            device
+                .allocate_descriptor_sets(&allocate_info)
+                .expect("VkIbl::new - Failed to allocate descriptor set!")[0]
+        };
+
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkIbl::new - Failed to create sampler!")
+        };
+
+        Self {
+            irradiance,
+            irradiance_view,
+            prefiltered,
+            prefiltered_view,
+            brdf_lut,
+            brdf_lut_view,
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_set,
+            sampler,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn image_view(&self, map: IblMap) -> vk::ImageView {
+        match map {
+            IblMap::Irradiance => self.irradiance_view,
+            IblMap::Prefiltered => self.prefiltered_view,
+            IblMap::BrdfLut => self.brdf_lut_view,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn image(&self, map: IblMap) -> vk::Image {
+        match map {
+            IblMap::Irradiance => self.irradiance.get(),
+            IblMap::Prefiltered => self.prefiltered.get(),
+            IblMap::BrdfLut => self.brdf_lut.get(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.descriptor_set_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Points the precomputation passes at the environment cubemap. The device must not be using the set.
+    pub fn write_environment(&self, device: &Device, environment_view: vk::ImageView) {
+        let image_info = [vk::DescriptorImageInfo::builder()
+            .sampler(self.sampler)
+            .image_view(environment_view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let writes = [vk::WriteDescriptorSet::builder()
+            .dst_set(self.descriptor_set)
+            .dst_binding(0)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&image_info)
+            .build()];
+
+        unsafe { device.update_descriptor_sets(&writes, &[]) };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Fills every face and mip of `map` with a full-screen draw each and waits for the result. Passes of the
+    /// environment dependent maps read the environment set by `write_environment`.
+    pub fn render(
+        &self,
+        device: &Device,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+        map: IblMap,
+        pass: VkIblPass,
+    ) {
+        let image = self.image(map);
+
+        // One view and framebuffer per face and mip, only needed while recording
+        let targets = (0..map.mip_count())
+            .flat_map(|mip| (0..map.layer_count()).map(move |layer| (layer, mip)))
+            .map(|(layer, mip)| {
+                let mut view_info = VkImage::image_view_create_info(
+                    map.format(),
+                    image,
+                    vk::ImageAspectFlags::COLOR,
+                    vk::ImageViewType::TYPE_2D,
+                );
+                view_info.subresource_range.base_array_layer = layer;
+                view_info.subresource_range.base_mip_level = mip;
+
+                let image_view = unsafe {
+                    device
+                        .create_image_view(&view_info, None)
+                        .expect("VkIbl::render - Failed to create target image view!")
+                };
+
+                let size = (map.size() >> mip).max(1);
+                let extent = vk::Extent2D::builder().width(size).height(size).build();
+                let framebuffer =
+                    VkFramebuffer::new(device, &[image_view], pass.render_pass, extent);
+
+                (layer, mip, extent, image_view, framebuffer)
+            })
+            .collect::<Vec<(u32, u32, vk::Extent2D, vk::ImageView, VkFramebuffer)>>();
+
+        let record = |cmd: &vk::CommandBuffer| {
+            let subresource_range = map.subresource_range();
+
+            let to_attachment_barrier = [vk::ImageMemoryBarrier::builder()
+                .old_layout(vk::ImageLayout::UNDEFINED)
+                .new_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                .image(image)
+                .subresource_range(subresource_range)
+                .src_access_mask(vk::AccessFlags::SHADER_READ)
+                .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .build()];
+
+            unsafe {
+                device.cmd_pipeline_barrier(
+                    *cmd,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &to_attachment_barrier,
+                );
+                device.cmd_bind_pipeline(*cmd, vk::PipelineBindPoint::GRAPHICS, pass.pipeline);
+            }
+
+            if map != IblMap::BrdfLut {
+                unsafe {
+                    device.cmd_bind_descriptor_sets(
+                        *cmd,
+                        vk::PipelineBindPoint::GRAPHICS,
+                        pass.pipeline_layout,
+                        0,
+                        &[self.descriptor_set],
+                        &[],
+                    );
+                }
+            }
+
+            for (layer, mip, extent, _, framebuffer) in targets.iter() {
+                let render_area = vk::Rect2D::builder()
+                    .offset(vk::Offset2D::default())
+                    .extent(*extent)
+                    .build();
+
+                let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
+                    .render_pass(pass.render_pass)
+                    .framebuffer(framebuffer.get())
+                    .render_area(render_area);
+
+                let viewports = [vk::Viewport::builder()
+                    .x(0.0)
+                    .y(0.0)
+                    .width(extent.width as f32)
+                    .height(extent.height as f32)
+                    .min_depth(0.0)
+                    .max_depth(1.0)
+                    .build()];
+
+                let push_constants = map.push_constants(*layer, *mip);
+
+                unsafe {
+                    device.cmd_begin_render_pass(
+                        *cmd,
+                        &render_pass_begin_info,
+                        vk::SubpassContents::INLINE,
+                    );
+                    device.cmd_set_viewport(*cmd, 0, &viewports);
+                    device.cmd_set_scissor(*cmd, 0, &[render_area]);
+                    device.cmd_push_constants(
+                        *cmd,
+                        pass.pipeline_layout,
+                        vk::ShaderStageFlags::FRAGMENT,
+                        0,
+                        ffi::any_as_u8_slice(&push_constants),
+                    );
+                    device.cmd_draw(*cmd, 3, 1, 0, 0);
+                    device.cmd_end_render_pass(*cmd);
+                }
+            }
+
+            let to_shader_barrier = [vk::ImageMemoryBarrier::builder()
+                .old_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .image(image)
+                .subresource_range(subresource_range)
+                .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build()];
+
+            unsafe {
+                device.cmd_pipeline_barrier(
+                    *cmd,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &to_shader_barrier,
+                );
+            }
+        };
+
+        utils::immediate_submit(device, command_pool, fence, queue, &record);
+
+        for (_, _, _, image_view, framebuffer) in targets.iter() {
+            framebuffer.destroy(device);
+            unsafe { device.destroy_image_view(*image_view, None) };
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Copies the irradiance and prefiltered maps back to the host, laid out as expected by `upload`.
+    pub fn read_back(
+        &self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) -> Vec<u8> {
+        let size = environment_maps_size();
+        let buffer = allocator_handle.create_buffer(
+            &VkBuffer::create_info(size, vk::BufferUsageFlags::TRANSFER_DST),
+            &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::GpuToCpu, None, None),
+        );
+
+        self.copy_environment_maps(device, command_pool, fence, queue, &buffer, false);
+
+        let mut data = vec![0u8; size as usize];
+        allocator_handle.read_buffer(&buffer, data.as_mut_ptr(), data.len());
+        buffer.free(&allocator_handle.allocator);
+
+        data
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Replaces the irradiance and prefiltered maps with `data` from `read_back`, e.g. loaded from the cache.
+    pub fn upload(
+        &self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+        data: &[u8],
+    ) {
+        assert_eq!(
+            data.len() as u64,
+            environment_maps_size(),
+            "VkIbl::upload - Data does not match the size of the environment maps!"
+        );
+
+        let buffer = allocator_handle.create_buffer(
+            &VkBuffer::create_info(data.len() as u64, vk::BufferUsageFlags::TRANSFER_SRC),
+            &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuOnly, None, None),
+        );
+        allocator_handle.write_buffer(&buffer, data.as_ptr(), data.len(), None);
+
+        self.copy_environment_maps(device, command_pool, fence, queue, &buffer, true);
+
+        buffer.free(&allocator_handle.allocator);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Copies between `buffer` and every face and mip of the environment dependent maps, in `environment_map_regions`
+    /// order. The maps are left shader readable either way.
+    fn copy_environment_maps(
+        &self,
+        device: &Device,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+        buffer: &VkBuffer,
+        to_images: bool,
+    ) {
+        let (transfer_layout, transfer_access) = if to_images {
+            (
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::AccessFlags::TRANSFER_WRITE,
+            )
+        } else {
+            (
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                vk::AccessFlags::TRANSFER_READ,
+            )
+        };
+
+        let record = |cmd: &vk::CommandBuffer| {
+            for map in [IblMap::Irradiance, IblMap::Prefiltered].iter() {
+                let image = self.image(*map);
+
+                let to_transfer_barrier = [vk::ImageMemoryBarrier::builder()
+                    .old_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .new_layout(transfer_layout)
+                    .image(image)
+                    .subresource_range(map.subresource_range())
+                    .src_access_mask(vk::AccessFlags::SHADER_READ)
+                    .dst_access_mask(transfer_access)
+                    .build()];
+
+                let regions = environment_map_regions()
+                    .into_iter()
+                    .filter(|(region_map, ..)| region_map == map)
+                    .map(|(_, mip, size, offset)| {
+                        vk::BufferImageCopy::builder()
+                            .buffer_offset(offset)
+                            .image_subresource(
+                                vk::ImageSubresourceLayers::builder()
+                                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                                    .mip_level(mip)
+                                    .base_array_layer(0)
+                                    .layer_count(CUBE_FACE_COUNT)
+                                    .build(),
+                            )
+                            .image_extent(
+                                vk::Extent3D::builder()
+                                    .width(size)
+                                    .height(size)
+                                    .depth(1)
+                                    .build(),
+                            )
+                            .build()
+                    })
+                    .collect::<Vec<vk::BufferImageCopy>>();
+
+                let to_shader_barrier = [vk::ImageMemoryBarrier::builder()
+                    .old_layout(transfer_layout)
+                    .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .image(image)
+                    .subresource_range(map.subresource_range())
+                    .src_access_mask(transfer_access)
+                    .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                    .build()];
+
+                unsafe {
+                    device.cmd_pipeline_barrier(
+                        *cmd,
+                        vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[],
+                        &to_transfer_barrier,
+                    );
+
+                    if to_images {
+                        device.cmd_copy_buffer_to_image(
+                            *cmd,
+                            buffer.get(),
+                            image,
+                            transfer_layout,
+                            &regions,
+                        );
+                    } else {
+                        device.cmd_copy_image_to_buffer(
+                            *cmd,
+                            image,
+                            transfer_layout,
+                            buffer.get(),
+                            &regions,
+                        );
+                    }
+
+                    device.cmd_pipeline_barrier(
+                        *cmd,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[],
+                        &to_shader_barrier,
+                    );
+                }
+            }
+        };
+
+        utils::immediate_submit(device, command_pool, fence, queue, &record);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceAllocatorDestroy for VkIbl {
+    fn destroy(&self, device: &Device, allocator: &Allocator) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+            device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+            device.destroy_image_view(self.irradiance_view, None);
+            device.destroy_image_view(self.prefiltered_view, None);
+            device.destroy_image_view(self.brdf_lut_view, None);
+        }
+
+        self.irradiance.free(allocator);
+        self.prefiltered.free(allocator);
+        self.brdf_lut.free(allocator);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Map, mip, size and buffer offset of every mip of the environment dependent maps, each holding all six faces.
+fn environment_map_regions() -> Vec<(IblMap, u32, u32, u64)> {
+    let mut offset = 0;
+
+    [IblMap::Irradiance, IblMap::Prefiltered]
+        .iter()
+        .flat_map(|map| (0..map.mip_count()).map(move |mip| (*map, mip)))
+        .map(|(map, mip)| {
+            let size = (map.size() >> mip).max(1);
+            let region = (map, mip, size, offset);
+            offset += (size * size * CUBE_FACE_COUNT) as u64 * IBL_TEXEL_SIZE;
+            region
+        })
+        .collect()
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn environment_maps_size() -> u64 {
+    environment_map_regions()
+        .last()
+        .map(|(_, _, size, offset)| {
+            offset + (size * size * CUBE_FACE_COUNT) as u64 * IBL_TEXEL_SIZE
+        })
+        .unwrap_or(0)
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Identifies the texels of an environment across runs, so its maps can be cached on disk. FNV-1a, since the hashers of
+/// the standard library are not guaranteed to stay the same between releases.
+pub fn environment_cache_key(environment: &TextureRaw) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let dimensions = [environment.width, environment.height, environment.layers]
+        .iter()
+        .flat_map(|value| value.to_le_bytes().to_vec())
+        .collect::<Vec<u8>>();
+
+    dimensions
+        .iter()
+        .chain(environment.buffer.iter())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn cache_header() -> Vec<u8> {
+    [IRRADIANCE_SIZE, PREFILTERED_SIZE, PREFILTERED_MIP_COUNT]
+        .iter()
+        .fold(CACHE_MAGIC.to_vec(), |mut header, value| {
+            header.extend_from_slice(&value.to_le_bytes());
+            header
+        })
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Environment maps cached at `path` by `write_cache`, unless missing or written with different map sizes.
+pub fn read_cache(path: &Path) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    let header = cache_header();
+
+    if data.len() as u64 != header.len() as u64 + environment_maps_size()
+        || data[..header.len()] != header[..]
+    {
+        return None;
+    }
+
+    Some(data[header.len()..].to_vec())
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub fn write_cache(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let mut contents = cache_header();
+    contents.extend_from_slice(data);
+
+    fs::write(path, contents)
+}
+//----------------------------------------------------------------------------------------------------------------------
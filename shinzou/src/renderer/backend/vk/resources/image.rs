@@ -31,21 +31,29 @@ impl VkImage {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Covers all six layers for `CUBE` views and the first layer otherwise.
     pub fn image_view_create_info(
         format: vk::Format,
         image: vk::Image,
         aspect_flags: vk::ImageAspectFlags,
+        view_type: vk::ImageViewType,
     ) -> vk::ImageViewCreateInfo {
+        let layer_count = if view_type == vk::ImageViewType::CUBE {
+            6
+        } else {
+            1
+        };
+
         let subresource_range = vk::ImageSubresourceRange::builder()
             .base_mip_level(0)
             .level_count(1)
             .base_array_layer(0)
-            .layer_count(1)
+            .layer_count(layer_count)
             .aspect_mask(aspect_flags)
             .build();
 
         vk::ImageViewCreateInfo::builder()
-            .view_type(vk::ImageViewType::TYPE_2D)
+            .view_type(view_type)
             .image(image)
             .format(format)
             .subresource_range(subresource_range)
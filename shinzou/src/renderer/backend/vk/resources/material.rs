@@ -1,25 +1,147 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
 use ash::vk;
+use ultraviolet::Vec4;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::{BlendMode, PbrFactors};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Pass a material is drawn in, each one has a pipeline of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VkMaterialPass {
+    /// Shades and lights the material in the main scene pass.
+    Forward,
+    /// Writes the material's surface parameters to the G-buffer of the deferred path.
+    GBuffer,
+    /// Shades and blends a transparent material over the lit scene of the deferred path, which is never multisampled.
+    /// Forward scenes blend transparent materials in the `Forward` pass.
+    Transparent,
+}
 //----------------------------------------------------------------------------------------------------------------------
 
 #[derive(Copy, Clone)]
 pub struct VkMaterial {
     pub descriptor_set: vk::DescriptorSet,
     pub pipeline: vk::Pipeline,
+    /// Null for transparent materials, which never write the G-buffer.
+    pub gbuffer_pipeline: vk::Pipeline,
+    /// Null unless the material is transparent.
+    pub transparent_pipeline: vk::Pipeline,
+    /// Variants of the pipelines above that also write the object id and depth targets, drawn while a pick is pending.
+    pub picking_pipeline: vk::Pipeline,
+    pub gbuffer_picking_pipeline: vk::Pipeline,
+    pub transparent_picking_pipeline: vk::Pipeline,
     pub pipeline_layout: vk::PipelineLayout,
+    pub blend_mode: BlendMode,
+    /// Shared by materials with the same shaders and blend mode, draws are sorted by it first.
+    pub pipeline_sort_id: u32,
+    pub sort_id: u32,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
 impl VkMaterial {
     pub fn new(
         pipeline: vk::Pipeline,
+        gbuffer_pipeline: vk::Pipeline,
+        transparent_pipeline: vk::Pipeline,
         pipeline_layout: vk::PipelineLayout,
         descriptor_set: vk::DescriptorSet,
+        blend_mode: BlendMode,
     ) -> Self {
         Self {
             descriptor_set,
             pipeline,
+            gbuffer_pipeline,
+            transparent_pipeline,
+            picking_pipeline: vk::Pipeline::null(),
+            gbuffer_picking_pipeline: vk::Pipeline::null(),
+            transparent_picking_pipeline: vk::Pipeline::null(),
             pipeline_layout,
+            blend_mode,
+            pipeline_sort_id: 0,
+            sort_id: 0,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_picking_pipelines(
+        mut self,
+        picking_pipeline: vk::Pipeline,
+        gbuffer_picking_pipeline: vk::Pipeline,
+        transparent_picking_pipeline: vk::Pipeline,
+    ) -> Self {
+        self.picking_pipeline = picking_pipeline;
+        self.gbuffer_picking_pipeline = gbuffer_picking_pipeline;
+        self.transparent_picking_pipeline = transparent_picking_pipeline;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_sort_ids(mut self, pipeline_sort_id: u32, sort_id: u32) -> Self {
+        self.pipeline_sort_id = pipeline_sort_id;
+        self.sort_id = sort_id;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Pipeline of `pass`, the picking variant when the pass writes the object id and depth targets.
+    pub fn pipeline(&self, pass: VkMaterialPass, picking: bool) -> vk::Pipeline {
+        match (pass, picking) {
+            (VkMaterialPass::Forward, false) => self.pipeline,
+            (VkMaterialPass::Forward, true) => self.picking_pipeline,
+            (VkMaterialPass::GBuffer, false) => self.gbuffer_pipeline,
+            (VkMaterialPass::GBuffer, true) => self.gbuffer_picking_pipeline,
+            (VkMaterialPass::Transparent, false) => self.transparent_pipeline,
+            (VkMaterialPass::Transparent, true) => self.transparent_picking_pipeline,
         }
     }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn is_transparent(&self) -> bool {
+        self.blend_mode == BlendMode::Transparent
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the MaterialUBO block declared in the material shaders.
+#[repr(C)]
+pub struct MaterialUBO {
+    pub base_color_factor: Vec4,
+    /// xyz - emissive factor, w - alpha cutoff, 0 unless the material is a cutout
+    pub emissive_factor: Vec4,
+    /// x - metallic, y - roughness, z - normal scale, w - occlusion strength
+    pub params: Vec4,
 }
 //----------------------------------------------------------------------------------------------------------------------
+
+impl MaterialUBO {
+    pub fn new(factors: &PbrFactors, blend_mode: BlendMode) -> Self {
+        let PbrFactors {
+            base_color,
+            metallic,
+            roughness,
+            normal_scale,
+            occlusion_strength,
+            emissive,
+            alpha_cutoff,
+        } = *factors;
+
+        let alpha_cutoff = match blend_mode {
+            BlendMode::Cutout => alpha_cutoff,
+            _ => 0.0,
+        };
+
+        Self {
+            base_color_factor: base_color,
+            emissive_factor: Vec4::new(emissive.x, emissive.y, emissive.z, alpha_cutoff),
+            params: Vec4::new(metallic, roughness, normal_scale, occlusion_strength),
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const MATERIAL_UBO_SIZE: usize = size_of::<MaterialUBO>();
+//----------------------------------------------------------------------------------------------------------------------
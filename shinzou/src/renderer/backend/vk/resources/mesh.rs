@@ -3,7 +3,7 @@ use std::mem::size_of;
 
 use ash::{vk, Device};
 use field_offset::offset_of;
-use ultraviolet::{Mat4, Vec4};
+use ultraviolet::{Mat4, Vec3, Vec4};
 use vk_mem::{Allocator, MemoryUsage};
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -13,7 +13,8 @@ use crate::renderer::{
         resources::VkBuffer,
         utils::immediate_submit,
     },
-    entities::{Mesh, Vertex, VERTEX_SIZE},
+    entities::{InstanceData, Mesh, Renderable, Vertex, INSTANCE_CUSTOM_DATA_LEN, VERTEX_SIZE},
+    DEFAULT_ENTITY_BUFFER_CAPACITY,
 };
 use ash::version::DeviceV1_0;
 //----------------------------------------------------------------------------------------------------------------------
@@ -106,12 +107,47 @@ pub struct MeshSSBO {
 //----------------------------------------------------------------------------------------------------------------------
 
 pub const MESH_SSBO_SIZE: u64 = size_of::<MeshSSBO>() as u64;
-pub const MESH_SSBO_MAX: u64 = 10000;
+pub const MESH_SSBO_INITIAL_CAPACITY: u64 = DEFAULT_ENTITY_BUFFER_CAPACITY;
 //----------------------------------------------------------------------------------------------------------------------
 
+pub const MESH_META_FLAG_RECEIVE_SHADOWS: u32 = 1;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Field order and padding mirror the std140 EntityMetaSSBO struct declared in the shaders.
 #[repr(C)]
 pub struct MeshMetaSSBO {
-    pub color: Vec4,
+    pub tint: Vec4,
+    pub custom: [f32; INSTANCE_CUSTOM_DATA_LEN],
+    pub emissive: f32,
+    pub id: u32,
+    pub flags: u32,
+    _padding: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl MeshMetaSSBO {
+    pub fn new(renderable: &Renderable) -> Self {
+        let InstanceData {
+            tint,
+            emissive,
+            id,
+            custom,
+        } = renderable.instance_data;
+
+        let mut flags = 0;
+        if renderable.receive_shadows {
+            flags |= MESH_META_FLAG_RECEIVE_SHADOWS;
+        }
+
+        Self {
+            tint,
+            custom,
+            emissive,
+            id,
+            flags,
+            _padding: 0,
+        }
+    }
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -121,6 +157,8 @@ pub const MESH_META_SSBO_SIZE: u64 = size_of::<MeshMetaSSBO>() as u64;
 pub struct VkMesh {
     mesh: Mesh,
     vertex_buffer: VkBuffer,
+    bounds: (Vec3, Vec3),
+    sort_id: u32,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -128,6 +166,7 @@ impl VkMesh {
     pub(in crate::renderer::backend::vk::resources) fn new(
         mesh: Mesh,
         allocator_handle: &AllocatorHandle,
+        sort_id: u32,
     ) -> Self {
         let vertex_buffer = allocator_handle.create_buffer(
             &VkBuffer::create_info(
@@ -137,9 +176,13 @@ impl VkMesh {
             &AllocatorHandle::allocation_create_info(MemoryUsage::GpuOnly, None, None),
         );
 
+        let bounds = mesh.bounds();
+
         Self {
             mesh,
             vertex_buffer,
+            bounds,
+            sort_id,
         }
     }
     //------------------------------------------------------------------------------------------------------------------
@@ -192,6 +235,18 @@ impl VkMesh {
         &self.vertex_buffer
     }
     //------------------------------------------------------------------------------------------------------------------
+
+    /// Object space bounds of the mesh, computed once on creation.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        self.bounds
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Small id used to group draws of the same mesh when sorting.
+    pub fn sort_id(&self) -> u32 {
+        self.sort_id
+    }
+    //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -1,41 +1,69 @@
 mod buffer;
+mod cluster;
 mod command_buffer;
 mod command_pool;
+mod debug_draw;
+mod debug_view;
+mod deferred;
 mod depth_buffer;
+mod draw_list;
 mod fence;
 mod frame;
 mod framebuffer;
+mod frustum;
+mod ibl;
 mod image;
 mod material;
 mod mesh;
+mod picking;
 mod pipeline;
 mod pipeline_layout;
+mod post_process;
 mod render_pass;
 mod resource_manager;
 mod scene;
+mod selection;
 mod semaphore;
 mod shader;
+mod shadow;
+mod skybox;
+mod sprite;
+mod ssao;
 mod swapchain;
 mod texture;
 //----------------------------------------------------------------------------------------------------------------------
 
 pub(in crate::renderer::backend::vk) use self::image::*;
 pub(in crate::renderer::backend::vk) use buffer::*;
+pub(in crate::renderer::backend::vk) use cluster::*;
 pub(in crate::renderer::backend::vk) use command_buffer::*;
 pub(in crate::renderer::backend::vk) use command_pool::*;
+pub(in crate::renderer::backend::vk) use debug_draw::*;
+pub(in crate::renderer::backend::vk) use debug_view::*;
+pub(in crate::renderer::backend::vk) use deferred::*;
 pub(in crate::renderer::backend::vk) use depth_buffer::*;
+pub(in crate::renderer::backend::vk) use draw_list::*;
 pub(in crate::renderer::backend::vk) use fence::*;
 pub(in crate::renderer::backend::vk) use frame::*;
 pub(in crate::renderer::backend::vk) use framebuffer::*;
+pub(in crate::renderer::backend::vk) use frustum::*;
+pub(in crate::renderer::backend::vk) use ibl::*;
 pub(in crate::renderer::backend::vk) use material::*;
 pub(in crate::renderer::backend::vk) use mesh::*;
+pub(in crate::renderer::backend::vk) use picking::*;
 pub(in crate::renderer::backend::vk) use pipeline::*;
 pub(in crate::renderer::backend::vk) use pipeline_layout::*;
+pub(in crate::renderer::backend::vk) use post_process::*;
 pub(in crate::renderer::backend::vk) use render_pass::*;
 pub(in crate::renderer::backend::vk) use resource_manager::*;
 pub(in crate::renderer::backend::vk) use scene::*;
+pub(in crate::renderer::backend::vk) use selection::*;
 pub(in crate::renderer::backend::vk) use semaphore::*;
 pub(in crate::renderer::backend::vk) use shader::*;
+pub(in crate::renderer::backend::vk) use shadow::*;
+pub(in crate::renderer::backend::vk) use skybox::*;
+pub(in crate::renderer::backend::vk) use sprite::*;
+pub(in crate::renderer::backend::vk) use ssao::*;
 pub(in crate::renderer::backend::vk) use swapchain::*;
 pub(in crate::renderer::backend::vk) use texture::*;
 //----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,38 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::vk;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Format of the target holding the entity buffer index of the renderable covering each pixel.
+pub const OBJECT_ID_FORMAT: vk::Format = vk::Format::R32_UINT;
+/// Format of the target holding the bits of the view depth of the renderable covering each pixel. An integer format, so
+/// multisampled targets resolve to a single sample like the id instead of averaging depths across silhouettes.
+pub const OBJECT_DEPTH_FORMAT: vk::Format = vk::Format::R32_UINT;
+
+/// Index the object id target is cleared to, left in pixels no renderable covers.
+pub const NO_OBJECT_ID: u32 = u32::MAX;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// A single pixel of the object id and depth targets, as copied into the pick buffer of a frame.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PickReadback {
+    pub index: u32,
+    /// The depth target holds the bits of an `f32`, so they are read back as one.
+    pub depth: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for PickReadback {
+    fn default() -> Self {
+        Self {
+            index: NO_OBJECT_ID,
+            depth: 0.0,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const PICK_READBACK_SIZE: u64 = size_of::<PickReadback>() as u64;
+//----------------------------------------------------------------------------------------------------------------------
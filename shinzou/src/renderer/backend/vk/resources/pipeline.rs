@@ -20,17 +20,31 @@ pub struct VkPipeline {
 //----------------------------------------------------------------------------------------------------------------------
 
 impl VkPipeline {
-    pub(in crate::renderer::backend::vk::resources) fn builder() -> VkPipelineBuilder {
+    pub(in crate::renderer::backend::vk::resources) fn builder(
+        reversed_depth: bool,
+    ) -> VkPipelineBuilder {
         let builder = VkPipelineBuilder::default().depth_stencil_state(
             true,
             true,
-            vk::CompareOp::LESS_OR_EQUAL,
+            Self::depth_compare_op(reversed_depth),
         );
 
         builder
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Closer fragments have a greater depth when the depth range is reversed.
+    pub(in crate::renderer::backend::vk::resources) fn depth_compare_op(
+        reversed_depth: bool,
+    ) -> vk::CompareOp {
+        if reversed_depth {
+            vk::CompareOp::GREATER_OR_EQUAL
+        } else {
+            vk::CompareOp::LESS_OR_EQUAL
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn get(&self) -> vk::Pipeline {
         self.pipeline.clone()
     }
@@ -66,9 +80,10 @@ pub struct VkPipelineBuilder {
     rasterization_state: vk::PipelineRasterizationStateCreateInfo,
     viewport: vk::Viewport,
     scissor: vk::Rect2D,
-    color_blend_attachment: vk::PipelineColorBlendAttachmentState,
+    color_blend_attachments: Vec<vk::PipelineColorBlendAttachmentState>,
     multisample_state: vk::PipelineMultisampleStateCreateInfo,
     depth_stencil_state: vk::PipelineDepthStencilStateCreateInfo,
+    dynamic_states: Vec<vk::DynamicState>,
     pipeline_layout: vk::PipelineLayout,
 }
 //----------------------------------------------------------------------------------------------------------------------
@@ -150,10 +165,11 @@ impl VkPipelineBuilder {
     }
     //------------------------------------------------------------------------------------------------------------------
 
-    pub fn multisampling_state(mut self) -> Self {
+    /// `samples` must match the sample count of the render pass attachments the pipeline is used with.
+    pub fn multisampling_state(mut self, samples: vk::SampleCountFlags) -> Self {
         self.multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
             .sample_shading_enable(false)
-            .rasterization_samples(vk::SampleCountFlags::TYPE_1)
+            .rasterization_samples(samples)
             .min_sample_shading(1.0)
             .alpha_to_coverage_enable(false)
             .alpha_to_one_enable(false)
@@ -163,21 +179,80 @@ impl VkPipelineBuilder {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Adds the blend state for the next color attachment. Depth-only pipelines simply never call this.
     pub fn color_blend_attachment_state(mut self) -> Self {
-        self.color_blend_attachment = vk::PipelineColorBlendAttachmentState::builder()
-            .color_write_mask(
-                vk::ColorComponentFlags::R
-                    | vk::ColorComponentFlags::G
-                    | vk::ColorComponentFlags::B
-                    | vk::ColorComponentFlags::A,
-            )
-            .blend_enable(false)
-            .build();
+        self.color_blend_attachments.push(
+            vk::PipelineColorBlendAttachmentState::builder()
+                .color_write_mask(
+                    vk::ColorComponentFlags::R
+                        | vk::ColorComponentFlags::G
+                        | vk::ColorComponentFlags::B
+                        | vk::ColorComponentFlags::A,
+                )
+                .blend_enable(false)
+                .build(),
+        );
+
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adds a blend state for the next color attachment that blends the output over it by the output alpha.
+    pub fn alpha_blend_attachment_state(mut self) -> Self {
+        self.color_blend_attachments.push(
+            vk::PipelineColorBlendAttachmentState::builder()
+                .color_write_mask(
+                    vk::ColorComponentFlags::R
+                        | vk::ColorComponentFlags::G
+                        | vk::ColorComponentFlags::B
+                        | vk::ColorComponentFlags::A,
+                )
+                .blend_enable(true)
+                .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+                .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .color_blend_op(vk::BlendOp::ADD)
+                .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .alpha_blend_op(vk::BlendOp::ADD)
+                .build(),
+        );
+
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adds a blend state for the next color attachment that adds the output to it.
+    pub fn additive_blend_attachment_state(mut self) -> Self {
+        self.color_blend_attachments.push(
+            vk::PipelineColorBlendAttachmentState::builder()
+                .color_write_mask(
+                    vk::ColorComponentFlags::R
+                        | vk::ColorComponentFlags::G
+                        | vk::ColorComponentFlags::B
+                        | vk::ColorComponentFlags::A,
+                )
+                .blend_enable(true)
+                .src_color_blend_factor(vk::BlendFactor::ONE)
+                .dst_color_blend_factor(vk::BlendFactor::ONE)
+                .color_blend_op(vk::BlendOp::ADD)
+                .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                .dst_alpha_blend_factor(vk::BlendFactor::ONE)
+                .alpha_blend_op(vk::BlendOp::ADD)
+                .build(),
+        );
 
         self
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn depth_bias(mut self, constant_factor: f32, slope_factor: f32) -> Self {
+        self.rasterization_state.depth_bias_enable = vk::TRUE;
+        self.rasterization_state.depth_bias_constant_factor = constant_factor;
+        self.rasterization_state.depth_bias_slope_factor = slope_factor;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn depth_stencil_state(
         mut self,
         enable_test: bool,
@@ -202,6 +277,34 @@ impl VkPipelineBuilder {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Enables the stencil test on both faces, `pass_op` being applied to fragments passing it. The reference value is
+    /// dynamic, so `DynamicState::STENCIL_REFERENCE` must be set too. Must come after `depth_stencil_state`, which turns
+    /// the stencil test back off.
+    pub fn stencil_state(mut self, compare_op: vk::CompareOp, pass_op: vk::StencilOp) -> Self {
+        let stencil_op_state = vk::StencilOpState::builder()
+            .fail_op(vk::StencilOp::KEEP)
+            .pass_op(pass_op)
+            .depth_fail_op(vk::StencilOp::KEEP)
+            .compare_op(compare_op)
+            .compare_mask(0xFF)
+            .write_mask(0xFF)
+            .reference(0)
+            .build();
+
+        self.depth_stencil_state.stencil_test_enable = vk::TRUE;
+        self.depth_stencil_state.front = stencil_op_state;
+        self.depth_stencil_state.back = stencil_op_state;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Marks a piece of state as dynamic, so it has to be set while recording instead of at pipeline creation.
+    pub fn dynamic_state(mut self, dynamic_state: vk::DynamicState) -> Self {
+        self.dynamic_states.push(dynamic_state);
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn pipeline_layout(mut self, pipeline_layout: vk::PipelineLayout) -> Self {
         self.pipeline_layout = pipeline_layout;
         self
@@ -221,11 +324,13 @@ impl VkPipelineBuilder {
             .scissor_count(1)
             .scissors(&scissors);
 
-        let attachments = [self.color_blend_attachment];
         let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
             .logic_op_enable(false)
             .logic_op(vk::LogicOp::COPY)
-            .attachments(&attachments);
+            .attachments(&self.color_blend_attachments);
+
+        let dynamic_state =
+            vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&self.dynamic_states);
 
         let create_infos = [vk::GraphicsPipelineCreateInfo::builder()
             .stages(&self.shader_stages)
@@ -236,6 +341,7 @@ impl VkPipelineBuilder {
             .multisample_state(&self.multisample_state)
             .color_blend_state(&color_blend_state)
             .depth_stencil_state(&self.depth_stencil_state)
+            .dynamic_state(&dynamic_state)
             .layout(self.pipeline_layout)
             .render_pass(render_pass.get())
             .subpass(0)
@@ -0,0 +1,252 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::Vec4;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::{
+    backend::vk::DeviceDestroy,
+    entities::{MapType, Texture},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Format of the offscreen target the scene is rendered to.
+pub const HDR_COLOR_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+/// Number of successively halved bloom levels, the first one being half the backbuffer size.
+pub const BLOOM_MIP_COUNT: usize = 5;
+/// Bloom downsample and upsample passes, the tonemapping pass and FXAA.
+pub const MAX_POST_PROCESS_PASSES: usize = BLOOM_MIP_COUNT * 2 + 2;
+pub const COLOR_GRADING_LUT_SIZE: u32 = 16;
+pub const DEFAULT_COLOR_GRADING_LUT: &str = "default_color_grading_lut";
+
+pub const POST_PROCESS_PIPELINE_LAYOUT: &str = "post_process_pipeline_layout";
+pub const BLOOM_DOWNSAMPLE_PIPELINE: &str = "bloom_downsample_pipeline";
+pub const BLOOM_UPSAMPLE_PIPELINE: &str = "bloom_upsample_pipeline";
+pub const TONEMAP_PIPELINE: &str = "tonemap_pipeline";
+pub const FXAA_PIPELINE: &str = "fxaa_pipeline";
+
+const POST_PROCESS_SOURCE_BINDING: u32 = 0;
+const POST_PROCESS_SECONDARY_BINDING: u32 = 1;
+const POST_PROCESS_LUT_BINDING: u32 = 2;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the PostProcessConstants block declared in the post-process shaders.
+#[repr(C)]
+pub struct PostProcessPushConstants {
+    /// Effect specific parameters.
+    pub params: Vec4,
+    pub extra: Vec4,
+    /// xy - source texel size, zw - source size
+    pub texel_size: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl PostProcessPushConstants {
+    pub fn new(params: Vec4, extra: Vec4, source_extent: vk::Extent2D) -> Self {
+        let width = source_extent.width as f32;
+        let height = source_extent.height as f32;
+
+        Self {
+            params,
+            extra,
+            texel_size: Vec4::new(1.0 / width, 1.0 / height, width, height),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<PostProcessPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Descriptor sets and sampler shared by the full-screen post-process passes. Every pass samples a source image, a
+/// secondary image and the color grading LUT, and gets its own descriptor set per frame in flight, since the images
+/// behind the render graph's transient targets can change between frames.
+pub struct VkPostProcess {
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_sets: Vec<Vec<vk::DescriptorSet>>,
+    sampler: vk::Sampler,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkPostProcess {
+    pub(in crate::renderer::backend::vk::resources) fn new(
+        device: &Device,
+        buffering: u32,
+    ) -> Self {
+        let set_count = buffering * MAX_POST_PROCESS_PASSES as u32;
+
+        let pool_sizes = [vk::DescriptorPoolSize::builder()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(set_count * 3)
+            .build()];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(set_count)
+            .pool_sizes(&pool_sizes);
+
+        let descriptor_pool = unsafe {
+            device
+                .create_descriptor_pool(&pool_info, None)
+                .expect("VkPostProcess::new - Failed to create descriptor pool!")
+        };
+
+        let bindings = [
+            POST_PROCESS_SOURCE_BINDING,
+            POST_PROCESS_SECONDARY_BINDING,
+            POST_PROCESS_LUT_BINDING,
+        ]
+        .iter()
+        .map(|binding| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(*binding)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build()
+        })
+        .collect::<Vec<vk::DescriptorSetLayoutBinding>>();
+
+        let descriptor_set_layout = unsafe {
+            device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                    None,
+                )
+                .expect("VkPostProcess::new - Failed to create descriptor set layout!")
+        };
+
+        let set_layouts = vec![descriptor_set_layout; MAX_POST_PROCESS_PASSES];
+        let descriptor_sets = (0..buffering)
+            .map(|_| {
+                let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&set_layouts);
+
+                unsafe {
+                    device
+                        .allocate_descriptor_sets(&allocate_info)
+                        .expect("VkPostProcess::new - Failed to allocate descriptor sets!")
+                }
+            })
+            .collect::<Vec<Vec<vk::DescriptorSet>>>();
+
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkPostProcess::new - Failed to create sampler!")
+        };
+
+        Self {
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_sets,
+            sampler,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.descriptor_set_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set(&self, frame_index: usize, pass_slot: usize) -> vk::DescriptorSet {
+        self.descriptor_sets[frame_index][pass_slot]
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Points a pass's descriptor set at the source, secondary and LUT images. The set must belong to the frame being
+    /// recorded, whose previous submission is known to have finished.
+    pub fn write_descriptor_set(
+        &self,
+        device: &Device,
+        descriptor_set: vk::DescriptorSet,
+        source: vk::ImageView,
+        secondary: vk::ImageView,
+        lut: vk::ImageView,
+    ) {
+        let image_infos = [source, secondary, lut]
+            .iter()
+            .map(|image_view| {
+                [vk::DescriptorImageInfo::builder()
+                    .sampler(self.sampler)
+                    .image_view(*image_view)
+                    .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .build()]
+            })
+            .collect::<Vec<[vk::DescriptorImageInfo; 1]>>();
+
+        let writes = [
+            POST_PROCESS_SOURCE_BINDING,
+            POST_PROCESS_SECONDARY_BINDING,
+            POST_PROCESS_LUT_BINDING,
+        ]
+        .iter()
+        .zip(image_infos.iter())
+        .map(|(binding, image_info)| {
+            vk::WriteDescriptorSet::builder()
+                .dst_set(descriptor_set)
+                .dst_binding(*binding)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .image_info(image_info)
+                .build()
+        })
+        .collect::<Vec<vk::WriteDescriptorSet>>();
+
+        unsafe { device.update_descriptor_sets(&writes, &[]) };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Color grading LUT that leaves colors untouched.
+    pub fn identity_color_grading_lut() -> Texture {
+        let size = COLOR_GRADING_LUT_SIZE;
+        let level = |index: u32| ((index * 255 + (size - 1) / 2) / (size - 1)) as u8;
+
+        let mut buffer = Vec::with_capacity((size * size * size * 4) as usize);
+        for green in 0..size {
+            for blue in 0..size {
+                for red in 0..size {
+                    buffer.extend_from_slice(&[level(red), level(green), level(blue), 255]);
+                }
+            }
+        }
+
+        Texture::from_rgba8(
+            &DEFAULT_COLOR_GRADING_LUT.into(),
+            size * size,
+            size,
+            buffer,
+            Some(MapType::Diffuse),
+        )
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceDestroy for VkPostProcess {
+    fn destroy(&self, device: &Device) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+            device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -9,17 +9,140 @@ pub struct VkRenderPass {
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+/// Attachment of a render pass that keeps its image in the attachment layout from start to finish.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VkAttachmentInfo {
+    pub format: vk::Format,
+    pub samples: vk::SampleCountFlags,
+    pub load_op: vk::AttachmentLoadOp,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
 impl VkRenderPass {
+    /// Creates a single-subpass render pass. Without a color attachment format the pass is depth-only, and its depth
+    /// attachment is left in a read-only layout so later passes can sample it. Multisampled color attachments are
+    /// resolved into an additional single-sample attachment at the end of the pass.
     pub(in crate::renderer::backend::vk::resources) fn new(
         device: &Device,
-        color_attachment_format: vk::Format,
+        color_attachment_format: Option<vk::Format>,
         depth_attachment_format: vk::Format,
+        samples: vk::SampleCountFlags,
+    ) -> Self {
+        let render_pass = match color_attachment_format {
+            Some(color_attachment_format) => Self::create_color_depth_pass(
+                device,
+                color_attachment_format,
+                depth_attachment_format,
+                samples,
+            ),
+            None => Self::create_depth_only_pass(device, depth_attachment_format, samples),
+        };
+
+        Self { render_pass }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates a single-subpass render pass without layout transitions or external dependencies, the caller is
+    /// expected to move the attachments into their attachment layouts and synchronize access to them. Resolve
+    /// attachments are either empty or match the color attachments one to one.
+    pub(in crate::renderer::backend::vk) fn with_attachments(
+        device: &Device,
+        color_attachments: &[VkAttachmentInfo],
+        depth_attachment: Option<VkAttachmentInfo>,
+        resolve_attachments: &[VkAttachmentInfo],
     ) -> Self {
-        let attachments = [
+        let attachment_description = |attachment: &VkAttachmentInfo, layout: vk::ImageLayout| {
+            vk::AttachmentDescription::builder()
+                .format(attachment.format)
+                .samples(attachment.samples)
+                .load_op(attachment.load_op)
+                .store_op(vk::AttachmentStoreOp::STORE)
+                .stencil_load_op(attachment.load_op)
+                .stencil_store_op(vk::AttachmentStoreOp::STORE)
+                .initial_layout(layout)
+                .final_layout(layout)
+                .build()
+        };
+
+        let mut attachments = color_attachments
+            .iter()
+            .map(|attachment| {
+                attachment_description(attachment, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            })
+            .collect::<Vec<vk::AttachmentDescription>>();
+
+        let color_attachment_references = (0..color_attachments.len())
+            .map(|index| {
+                vk::AttachmentReference::builder()
+                    .attachment(index as u32)
+                    .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                    .build()
+            })
+            .collect::<Vec<vk::AttachmentReference>>();
+
+        let depth_stencil_attachment = vk::AttachmentReference::builder()
+            .attachment(attachments.len() as u32)
+            .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+            .build();
+
+        let mut subpass = vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(&color_attachment_references);
+
+        if let Some(depth_attachment) = depth_attachment {
+            attachments.push(attachment_description(
+                &depth_attachment,
+                vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+            ));
+            subpass = subpass.depth_stencil_attachment(&depth_stencil_attachment);
+        }
+
+        // Resolve attachments follow the depth attachment
+        let resolve_attachment_references = (0..resolve_attachments.len())
+            .map(|index| {
+                vk::AttachmentReference::builder()
+                    .attachment((attachments.len() + index) as u32)
+                    .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                    .build()
+            })
+            .collect::<Vec<vk::AttachmentReference>>();
+
+        if !resolve_attachments.is_empty() {
+            attachments.extend(resolve_attachments.iter().map(|attachment| {
+                attachment_description(attachment, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            }));
+            subpass = subpass.resolve_attachments(&resolve_attachment_references);
+        }
+
+        let subpasses = [subpass.build()];
+
+        let create_info = vk::RenderPassCreateInfo::builder()
+            .attachments(&attachments)
+            .subpasses(&subpasses);
+
+        let render_pass = unsafe {
+            device
+                .create_render_pass(&create_info, None)
+                .expect("VkRenderPass::with_attachments - Failed to create render pass!")
+        };
+
+        Self { render_pass }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn create_color_depth_pass(
+        device: &Device,
+        color_attachment_format: vk::Format,
+        depth_attachment_format: vk::Format,
+        samples: vk::SampleCountFlags,
+    ) -> vk::RenderPass {
+        let multisampled = samples != vk::SampleCountFlags::TYPE_1;
+
+        let mut attachments = vec![
             // color attachment
             vk::AttachmentDescription::builder()
                 .format(color_attachment_format)
-                .samples(vk::SampleCountFlags::TYPE_1)
+                .samples(samples)
                 .load_op(vk::AttachmentLoadOp::CLEAR)
                 .store_op(vk::AttachmentStoreOp::STORE)
                 .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
@@ -30,7 +153,7 @@ impl VkRenderPass {
             // depth attachment
             vk::AttachmentDescription::builder()
                 .format(depth_attachment_format)
-                .samples(vk::SampleCountFlags::TYPE_1)
+                .samples(samples)
                 .load_op(vk::AttachmentLoadOp::CLEAR)
                 .store_op(vk::AttachmentStoreOp::STORE)
                 .stencil_load_op(vk::AttachmentLoadOp::CLEAR)
@@ -40,6 +163,23 @@ impl VkRenderPass {
                 .build(),
         ];
 
+        if multisampled {
+            // resolve attachment, the multisampled color attachment is only needed within the pass
+            attachments[0].final_layout = vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL;
+            attachments.push(
+                vk::AttachmentDescription::builder()
+                    .format(color_attachment_format)
+                    .samples(vk::SampleCountFlags::TYPE_1)
+                    .load_op(vk::AttachmentLoadOp::DONT_CARE)
+                    .store_op(vk::AttachmentStoreOp::STORE)
+                    .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                    .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                    .initial_layout(vk::ImageLayout::UNDEFINED)
+                    .final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+                    .build(),
+            );
+        }
+
         let color_attachments = [vk::AttachmentReference::builder()
             .attachment(0)
             .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
@@ -49,23 +189,92 @@ impl VkRenderPass {
             .attachment(1)
             .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
 
-        let subpasses = [vk::SubpassDescription::builder()
+        let resolve_attachments = [vk::AttachmentReference::builder()
+            .attachment(2)
+            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            .build()];
+
+        let mut subpass = vk::SubpassDescription::builder()
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
             .color_attachments(&color_attachments)
-            .depth_stencil_attachment(&depth_stencil_attachment)
-            .build()];
+            .depth_stencil_attachment(&depth_stencil_attachment);
+
+        if multisampled {
+            subpass = subpass.resolve_attachments(&resolve_attachments);
+        }
+
+        let subpasses = [subpass.build()];
 
         let create_info = vk::RenderPassCreateInfo::builder()
             .attachments(&attachments)
             .subpasses(&subpasses);
 
-        let render_pass = unsafe {
+        unsafe {
             device
                 .create_render_pass(&create_info, None)
                 .expect("RenderPassHandle::init - Failed to create render pass!")
-        };
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
 
-        Self { render_pass }
+    fn create_depth_only_pass(
+        device: &Device,
+        depth_attachment_format: vk::Format,
+        samples: vk::SampleCountFlags,
+    ) -> vk::RenderPass {
+        let attachments = [vk::AttachmentDescription::builder()
+            .format(depth_attachment_format)
+            .samples(samples)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+            .final_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let depth_stencil_attachment = vk::AttachmentReference::builder()
+            .attachment(0)
+            .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+
+        let subpasses = [vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .depth_stencil_attachment(&depth_stencil_attachment)
+            .build()];
+
+        let dependencies = [
+            // previous reads of the depth attachment must finish before it gets cleared
+            vk::SubpassDependency::builder()
+                .src_subpass(vk::SUBPASS_EXTERNAL)
+                .dst_subpass(0)
+                .src_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                .dst_stage_mask(vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS)
+                .src_access_mask(vk::AccessFlags::SHADER_READ)
+                .dst_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                .dependency_flags(vk::DependencyFlags::BY_REGION)
+                .build(),
+            // depth writes must be visible before later passes sample the attachment
+            vk::SubpassDependency::builder()
+                .src_subpass(0)
+                .dst_subpass(vk::SUBPASS_EXTERNAL)
+                .src_stage_mask(vk::PipelineStageFlags::LATE_FRAGMENT_TESTS)
+                .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                .src_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .dependency_flags(vk::DependencyFlags::BY_REGION)
+                .build(),
+        ];
+
+        let create_info = vk::RenderPassCreateInfo::builder()
+            .attachments(&attachments)
+            .subpasses(&subpasses)
+            .dependencies(&dependencies);
+
+        unsafe {
+            device
+                .create_render_pass(&create_info, None)
+                .expect("VkRenderPass::create_depth_only_pass - Failed to create render pass!")
+        }
     }
     //------------------------------------------------------------------------------------------------------------------
 
@@ -1,7 +1,12 @@
-use std::{collections::HashMap, path::Path, rc::Rc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 //----------------------------------------------------------------------------------------------------------------------
 
 use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::Vec3;
 //----------------------------------------------------------------------------------------------------------------------
 
 use crate::renderer::{
@@ -10,17 +15,109 @@ use crate::renderer::{
             AllocatorFree, AllocatorHandle, InstanceHandle, PhysicalDeviceHandle, SurfaceHandle,
         },
         resources::{
-            VertexInputDescription, VkCommandBuffer, VkCommandPool, VkDepthBuffer, VkFence,
-            VkFrame, VkFramebuffer, VkMaterial, VkMesh, VkPipeline, VkPipelineBuilder,
-            VkPipelineLayout, VkRenderPass, VkScene, VkSemaphore, VkShader, VkSwapchain, VkTexture,
-            MESH_META_SSBO_SIZE, MESH_SSBO_MAX, MESH_SSBO_SIZE, SCENE_UBO_SIZE,
+            environment_cache_key, read_cache, write_cache, BillboardPushConstants,
+            BillboardVertex, DebugVertex, DebugViewPushConstants, DeferredPushConstants, IblMap,
+            IblPushConstants, MaterialUBO, PostProcessPushConstants, SelectionOutlinePushConstants,
+            ShadowPushConstants, SkyboxPushConstants, SpriteBatch, SpriteDraw, SpritePushConstants,
+            SpriteVertex, SsaoPushConstants, VertexInputDescription, VkAttachmentInfo, VkBuffer,
+            VkCommandBuffer, VkCommandPool, VkFence, VkFrame, VkGBuffer, VkIbl, VkIblPass,
+            VkMaterial, VkMaterialPass, VkMesh, VkPipeline, VkPipelineBuilder, VkPipelineLayout,
+            VkPostProcess, VkRenderPass, VkScene, VkSelection, VkSemaphore, VkShader, VkShadowMaps,
+            VkSprites, VkSsao, VkSwapchain, VkTexture, BILLBOARD_PIPELINE,
+            BILLBOARD_PIPELINE_LAYOUT, BLOOM_DOWNSAMPLE_PIPELINE, BLOOM_UPSAMPLE_PIPELINE,
+            BRDF_LUT_FORMAT, BRDF_LUT_PIPELINE, CLUSTER_BUFFER_SIZE,
+            CLUSTER_LIGHT_INDEX_BUFFER_SIZE, DEBUG_LINE_OVERLAY_PIPELINE, DEBUG_LINE_PIPELINE,
+            DEBUG_LINE_PIPELINE_LAYOUT, DEBUG_VIEW_HEAT_MAP_PIPELINE, DEBUG_VIEW_OVERDRAW_PIPELINE,
+            DEBUG_VIEW_PICKING_PIPELINE, DEBUG_VIEW_PIPELINE, DEBUG_VIEW_PIPELINE_LAYOUT,
+            DEFERRED_BILLBOARD_PIPELINE, DEFERRED_DEBUG_LINE_OVERLAY_PIPELINE,
+            DEFERRED_DEBUG_LINE_PIPELINE, DEFERRED_LIGHTING_PIPELINE,
+            DEFERRED_LIGHTING_PIPELINE_LAYOUT, DEFERRED_WIREFRAME_PIPELINE, DEPTH_NORMAL_PIPELINE,
+            DEPTH_NORMAL_PIPELINE_LAYOUT, FXAA_PIPELINE, GBUFFER_COLOR_FORMATS,
+            GBUFFER_DEBUG_PIPELINE, HDR_COLOR_FORMAT, IBL_FORMAT, IBL_PIPELINE_LAYOUT,
+            IRRADIANCE_PIPELINE, LIGHT_BUFFER_SIZE, MATERIAL_UBO_SIZE, MESH_META_SSBO_SIZE,
+            MESH_SSBO_SIZE, OBJECT_DEPTH_FORMAT, OBJECT_ID_FORMAT, OVERDRAW_COUNT_FORMAT,
+            POST_PROCESS_PIPELINE_LAYOUT, PREFILTER_PIPELINE, SCENE_UBO_SIZE,
+            SELECTION_OUTLINE_PIPELINE, SELECTION_OUTLINE_PIPELINE_LAYOUT,
+            SELECTION_STENCIL_PIPELINE, SELECTION_STENCIL_PIPELINE_LAYOUT, SHADOW_RENDER_PASS,
+            SHADOW_UBO_SIZE, SKYBOX_BACKGROUND_PIPELINE, SKYBOX_PICKING_PIPELINE, SKYBOX_PIPELINE,
+            SKYBOX_PIPELINE_LAYOUT, SPRITE_PIPELINE, SPRITE_PIPELINE_LAYOUT, SSAO_BLUR_PIPELINE,
+            SSAO_FORMAT, SSAO_NORMAL_FORMAT, SSAO_PIPELINE, SSAO_PIPELINE_LAYOUT, TONEMAP_PIPELINE,
+            WIREFRAME_PIPELINE,
         },
         DeviceAllocatorDestroy, DeviceDestroy, VkRendererConfig,
     },
-    entities::{Material, Mesh, Texture, CAMERA_UBO_SIZE},
+    entities::{
+        BlendMode, DebugViewMode, MapType, Material, Mesh, Texture, TextureDimension, TextureSlot,
+        CAMERA_UBO_SIZE, TEXTURE_SLOT_COUNT,
+    },
+    DepthMode,
 };
 //----------------------------------------------------------------------------------------------------------------------
 
+const DESCRIPTOR_POOL_MAX_SETS: u32 = 64;
+const DESCRIPTOR_POOL_TYPE_COUNT: u32 = 128;
+
+pub const DEFAULT_WHITE_TEXTURE: &str = "default_white";
+pub const DEFAULT_NORMAL_TEXTURE: &str = "default_normal";
+pub const MATERIAL_UBO_BINDING: u32 = TEXTURE_SLOT_COUNT as u32;
+
+pub const SHADOW_PIPELINE: &str = "shadow_pipeline";
+pub const SHADOW_PIPELINE_LAYOUT: &str = "shadow_pipeline_layout";
+const SHADOW_UBO_BINDING: u32 = 3;
+const SHADOW_MAP_BINDING: u32 = 4;
+const SHADOW_DEPTH_BIAS_CONSTANT: f32 = 1.25;
+const SHADOW_DEPTH_BIAS_SLOPE: f32 = 1.75;
+
+const AMBIENT_OCCLUSION_BINDING: u32 = 5;
+const DEPTH_NORMAL_RENDER_PASS: &str = "depth_normal";
+const SSAO_RENDER_PASS: &str = "ssao";
+
+const FORWARD_PICKING_RENDER_PASS: &str = "forward_picking";
+
+const SELECTION_STENCIL_RENDER_PASS: &str = "selection_stencil";
+const SELECTION_OUTLINE_RENDER_PASS: &str = "selection_outline";
+
+const ENVIRONMENT_BINDING: u32 = 6;
+pub const DEFAULT_ENVIRONMENT_TEXTURE: &str = "default_environment";
+const SKYBOX_VERTEX_SHADER: &str = "resources/shaders/dist/skybox.vert.spv";
+const SKYBOX_FRAGMENT_SHADER: &str = "resources/shaders/dist/skybox.frag.spv";
+
+const IRRADIANCE_BINDING: u32 = 7;
+const PREFILTERED_BINDING: u32 = 8;
+const BRDF_LUT_BINDING: u32 = 9;
+const IBL_RENDER_PASS: &str = "ibl";
+const BRDF_LUT_RENDER_PASS: &str = "ibl_brdf_lut";
+
+const POST_PROCESS_HDR_RENDER_PASS: &str = "post_process_hdr";
+const POST_PROCESS_OUTPUT_RENDER_PASS: &str = "post_process_output";
+
+const GBUFFER_RENDER_PASS: &str = "gbuffer";
+const DEFERRED_LIGHTING_RENDER_PASS: &str = "deferred_lighting";
+const GBUFFER_DEBUG_RENDER_PASS: &str = "gbuffer_debug";
+const DEFERRED_TRANSPARENT_RENDER_PASS: &str = "deferred_transparent";
+const GBUFFER_PICKING_RENDER_PASS: &str = "gbuffer_picking";
+const DEFERRED_TRANSPARENT_PICKING_RENDER_PASS: &str = "deferred_transparent_picking";
+const GBUFFER_FRAGMENT_SHADER: &str = "resources/shaders/dist/gbuffer.frag.spv";
+const FULLSCREEN_VERTEX_SHADER: &str = "resources/shaders/dist/fullscreen.vert.spv";
+const DEBUG_LINE_VERTEX_SHADER: &str = "resources/shaders/dist/debug_line.vert.spv";
+const DEBUG_LINE_FRAGMENT_SHADER: &str = "resources/shaders/dist/debug_line.frag.spv";
+
+const DEBUG_VIEW_RENDER_PASS: &str = "debug_view";
+const DEBUG_VIEW_PICKING_RENDER_PASS: &str = "debug_view_picking";
+const DEBUG_VIEW_OVERDRAW_RENDER_PASS: &str = "debug_view_overdraw";
+const DEBUG_VIEW_VERTEX_SHADER: &str = "resources/shaders/dist/shader.vert.spv";
+const DEBUG_VIEW_FRAGMENT_SHADER: &str = "resources/shaders/dist/debug_view.frag.spv";
+const DEBUG_VIEW_HEAT_MAP_FRAGMENT_SHADER: &str =
+    "resources/shaders/dist/debug_view_overdraw.frag.spv";
+const WIREFRAME_DEPTH_BIAS: f32 = 1.0;
+
+const SPRITE_RENDER_PASS: &str = "sprite";
+const SPRITE_VERTEX_SHADER: &str = "resources/shaders/dist/sprite.vert.spv";
+const SPRITE_FRAGMENT_SHADER: &str = "resources/shaders/dist/sprite.frag.spv";
+const BILLBOARD_VERTEX_SHADER: &str = "resources/shaders/dist/billboard.vert.spv";
+const BILLBOARD_FRAGMENT_SHADER: &str = "resources/shaders/dist/billboard.frag.spv";
+//----------------------------------------------------------------------------------------------------------------------
+
 pub trait ResourceManagerDestroy {
     fn destroy(&self, device: &Device, resource_manager: &ResourceManager);
 }
@@ -30,8 +127,6 @@ pub struct ResourceManager {
     render_passes: HashMap<String, Rc<VkRenderPass>>,
 
     swapchain: Option<Rc<VkSwapchain>>,
-    depth_buffers: Vec<Rc<VkDepthBuffer>>,
-    framebuffers: Vec<Rc<VkFramebuffer>>,
 
     command_pools: HashMap<String, Rc<VkCommandPool>>,
     command_buffers: HashMap<String, Vec<Rc<VkCommandBuffer>>>,
@@ -42,14 +137,31 @@ pub struct ResourceManager {
     global_descriptor_set_layout: vk::DescriptorSetLayout,
     entity_descriptor_set_layout: vk::DescriptorSetLayout,
     texture_descriptor_set_layout: vk::DescriptorSetLayout,
+    texture_sampler: vk::Sampler,
+    environment_sampler: vk::Sampler,
 
     frames: Vec<VkFrame>,
     scene: VkScene,
+    shadow_maps: Option<VkShadowMaps>,
+    post_process: Option<VkPostProcess>,
+    gbuffer: Option<VkGBuffer>,
+    selection: Option<VkSelection>,
+    sprites: Option<VkSprites>,
+    ssao: Option<VkSsao>,
+    /// Cubemap bound as the environment, the default one stands in when unset.
+    environment: Option<VkTexture>,
+    ibl: Option<VkIbl>,
+    ibl_cache_dir: Option<PathBuf>,
+    depth_mode: DepthMode,
 
     pipeline_layouts: HashMap<String, Rc<VkPipelineLayout>>,
     pipelines: HashMap<String, Rc<VkPipeline>>,
     shaders: HashMap<String, Rc<VkShader>>,
     materials: HashMap<String, VkMaterial>,
+    material_shader_paths: HashMap<String, (PathBuf, PathBuf)>,
+    material_blend_modes: HashMap<String, BlendMode>,
+    material_pipeline_sort_ids: HashMap<(PathBuf, PathBuf, BlendMode), u32>,
+    material_buffers: HashMap<String, VkBuffer>,
 
     meshes: HashMap<String, Rc<VkMesh>>,
     textures: HashMap<String, Rc<VkTexture>>,
@@ -65,48 +177,93 @@ impl ResourceManager {
         Self {
             render_passes: HashMap::new(),
             swapchain: None,
-            depth_buffers: Vec::new(),
-            framebuffers: Vec::new(),
             command_pools: HashMap::new(),
             command_buffers: HashMap::new(),
             descriptor_pool: Default::default(),
             global_descriptor_set_layout: Default::default(),
             entity_descriptor_set_layout: Default::default(),
             texture_descriptor_set_layout: Default::default(),
+            texture_sampler: Default::default(),
+            environment_sampler: Default::default(),
             fences: HashMap::new(),
             semaphores: HashMap::new(),
             frames: Vec::new(),
             scene: VkScene::new(allocator_handle, physical_device_handle, config),
+            shadow_maps: None,
+            post_process: None,
+            gbuffer: None,
+            selection: None,
+            sprites: None,
+            ssao: None,
+            environment: None,
+            ibl: None,
+            ibl_cache_dir: None,
+            depth_mode: config.depth_mode,
             pipeline_layouts: HashMap::new(),
             pipelines: HashMap::new(),
             shaders: HashMap::new(),
             materials: HashMap::new(),
+            material_shader_paths: HashMap::new(),
+            material_blend_modes: HashMap::new(),
+            material_pipeline_sort_ids: HashMap::new(),
+            material_buffers: HashMap::new(),
             meshes: HashMap::new(),
             textures: HashMap::new(),
         }
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Creates a render pass, replacing and destroying any previous one with the same id. The device must not be using
+    /// the replaced render pass anymore.
     pub fn create_render_pass(
         &mut self,
         device: &Device,
         id: Option<&str>,
-        color_attachment_format: vk::Format,
+        color_attachment_format: Option<vk::Format>,
         depth_attachment_format: vk::Format,
+        samples: vk::SampleCountFlags,
     ) -> Rc<VkRenderPass> {
         let render_pass_id = id.unwrap_or("default");
         let render_pass = Rc::new(VkRenderPass::new(
             device,
             color_attachment_format,
             depth_attachment_format,
+            samples,
+        ));
+
+        if let Some(previous_render_pass) = self
+            .render_passes
+            .insert(render_pass_id.to_owned(), render_pass.clone())
+        {
+            previous_render_pass.destroy(device);
+        }
+
+        render_pass
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates a render pass that leaves layout transitions to the caller, like the ones the render graph begins.
+    pub fn create_render_pass_with_attachments(
+        &mut self,
+        device: &Device,
+        id: &str,
+        color_attachments: &[VkAttachmentInfo],
+        depth_attachment: Option<VkAttachmentInfo>,
+    ) -> Rc<VkRenderPass> {
+        let render_pass = Rc::new(VkRenderPass::with_attachments(
+            device,
+            color_attachments,
+            depth_attachment,
+            &[],
         ));
         self.render_passes
-            .insert(render_pass_id.to_owned(), render_pass.clone());
+            .insert(id.to_owned(), render_pass.clone());
 
         render_pass
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    #[allow(dead_code)]
     pub fn get_render_pass(&self, id: Option<&str>) -> Rc<VkRenderPass> {
         self.render_passes
             .get(id.unwrap_or("default"))
@@ -141,71 +298,6 @@ impl ResourceManager {
     }
     //------------------------------------------------------------------------------------------------------------------
 
-    fn create_depth_buffer(
-        &mut self,
-        device: &Device,
-        allocator_handle: &AllocatorHandle,
-        swapchain: &VkSwapchain,
-        depth_attachment_format: vk::Format,
-    ) -> Rc<VkDepthBuffer> {
-        let depth_buffer = Rc::new(VkDepthBuffer::new(
-            device,
-            allocator_handle,
-            swapchain.surface_extent(),
-            depth_attachment_format,
-        ));
-
-        self.depth_buffers.push(depth_buffer.clone());
-
-        depth_buffer
-    }
-    //------------------------------------------------------------------------------------------------------------------
-
-    pub fn create_framebuffers(
-        &mut self,
-        device: &Device,
-        allocator_handle: &AllocatorHandle,
-        swapchain: &VkSwapchain,
-        render_pass: &VkRenderPass,
-        depth_attachment_format: vk::Format,
-    ) -> Vec<Rc<VkFramebuffer>> {
-        let swapchain_image_views = swapchain.image_views();
-        let depth_buffers = (0..swapchain_image_views.len())
-            .map(|_| {
-                self.create_depth_buffer(
-                    device,
-                    allocator_handle,
-                    swapchain,
-                    depth_attachment_format,
-                )
-            })
-            .collect::<Vec<Rc<VkDepthBuffer>>>();
-
-        let framebuffers = swapchain_image_views
-            .iter()
-            .zip(depth_buffers)
-            .map(|(image_view, depth_buffer)| {
-                Rc::new(VkFramebuffer::new(
-                    device,
-                    image_view,
-                    &depth_buffer.image_view,
-                    render_pass.get().to_owned(),
-                    swapchain.surface_extent(),
-                ))
-            })
-            .collect::<Vec<Rc<VkFramebuffer>>>();
-
-        self.framebuffers = framebuffers.clone();
-
-        framebuffers
-    }
-    //------------------------------------------------------------------------------------------------------------------
-
-    pub fn get_framebuffers(&self) -> &[Rc<VkFramebuffer>] {
-        &self.framebuffers
-    }
-    //------------------------------------------------------------------------------------------------------------------
-
     pub fn create_command_pool(
         &mut self,
         device: &Device,
@@ -315,6 +407,7 @@ impl ResourceManager {
         device: &Device,
         queue_family_index: u32,
         allocator_handle: &AllocatorHandle,
+        entity_capacity: u64,
     ) {
         let index = self.frames.len();
 
@@ -344,6 +437,7 @@ impl ResourceManager {
 
         self.frames.push(VkFrame::new(
             allocator_handle,
+            entity_capacity,
             present_semaphore,
             render_semaphore,
             render_fence,
@@ -366,6 +460,116 @@ impl ResourceManager {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn reserve_frame_entities(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        frame_number: usize,
+        entity_count: usize,
+    ) {
+        let frame_index = self.get_current_frame_number(frame_number);
+        let frame = &mut self.frames[frame_index];
+
+        let entity_count = entity_count as u64;
+        if entity_count <= frame.entity_capacity {
+            return;
+        }
+
+        let entity_capacity = entity_count
+            .max(frame.entity_capacity * 2)
+            .next_power_of_two();
+        warn!(
+            "ResourceManager::reserve_frame_entities - Frame {} entity buffers grown from {} to {} entries ({} requested)",
+            frame_index, frame.entity_capacity, entity_capacity, entity_count
+        );
+
+        frame.grow_entity_buffers(allocator_handle, entity_capacity);
+        Self::write_entity_descriptor_set(device, frame);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Grows the debug vertex buffer of the given frame so it holds at least `vertex_count` vertices. Must be called
+    /// after the frame's render fence has been waited on.
+    pub fn reserve_frame_debug_vertices(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        frame_number: usize,
+        vertex_count: usize,
+    ) {
+        let frame_index = self.get_current_frame_number(frame_number);
+        let frame = &mut self.frames[frame_index];
+
+        let vertex_count = vertex_count as u64;
+        if vertex_count <= frame.debug_vertex_capacity {
+            return;
+        }
+
+        let vertex_capacity = vertex_count
+            .max(frame.debug_vertex_capacity * 2)
+            .next_power_of_two();
+        info!(
+            "ResourceManager::reserve_frame_debug_vertices - Frame {} debug vertex buffer grown from {} to {} vertices",
+            frame_index, frame.debug_vertex_capacity, vertex_capacity
+        );
+
+        frame.grow_debug_vertex_buffer(allocator_handle, vertex_capacity);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Grows the sprite vertex buffer of the given frame so it holds at least `vertex_count` vertices. Must be called
+    /// after the frame's render fence has been waited on.
+    pub fn reserve_frame_sprite_vertices(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        frame_number: usize,
+        vertex_count: usize,
+    ) {
+        let frame_index = self.get_current_frame_number(frame_number);
+        let frame = &mut self.frames[frame_index];
+
+        let vertex_count = vertex_count as u64;
+        if vertex_count <= frame.sprite_vertex_capacity {
+            return;
+        }
+
+        let vertex_capacity = vertex_count
+            .max(frame.sprite_vertex_capacity * 2)
+            .next_power_of_two();
+        info!(
+            "ResourceManager::reserve_frame_sprite_vertices - Frame {} sprite vertex buffer grown from {} to {} vertices",
+            frame_index, frame.sprite_vertex_capacity, vertex_capacity
+        );
+
+        frame.grow_sprite_vertex_buffer(allocator_handle, vertex_capacity);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn reserve_frame_billboard_vertices(
+        &mut self,
+        allocator_handle: &AllocatorHandle,
+        frame_number: usize,
+        vertex_count: usize,
+    ) {
+        let frame_index = self.get_current_frame_number(frame_number);
+        let frame = &mut self.frames[frame_index];
+
+        let vertex_count = vertex_count as u64;
+        if vertex_count <= frame.billboard_vertex_capacity {
+            return;
+        }
+
+        let vertex_capacity = vertex_count
+            .max(frame.billboard_vertex_capacity * 2)
+            .next_power_of_two();
+        info!(
+            "ResourceManager::reserve_frame_billboard_vertices - Frame {} billboard vertex buffer grown from {} to {} vertices",
+            frame_index, frame.billboard_vertex_capacity, vertex_capacity
+        );
+
+        frame.grow_billboard_vertex_buffer(allocator_handle, vertex_capacity);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn get_scene(&self) -> &VkScene {
         &self.scene
     }
@@ -388,24 +592,28 @@ impl ResourceManager {
         let pool_sizes = [
             vk::DescriptorPoolSize::builder()
                 .ty(vk::DescriptorType::UNIFORM_BUFFER)
-                .descriptor_count(10)
+                .descriptor_count(DESCRIPTOR_POOL_TYPE_COUNT)
                 .build(),
             vk::DescriptorPoolSize::builder()
                 .ty(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
-                .descriptor_count(10)
+                .descriptor_count(DESCRIPTOR_POOL_TYPE_COUNT)
                 .build(),
             vk::DescriptorPoolSize::builder()
                 .ty(vk::DescriptorType::STORAGE_BUFFER)
-                .descriptor_count(10)
+                .descriptor_count(DESCRIPTOR_POOL_TYPE_COUNT)
+                .build(),
+            vk::DescriptorPoolSize::builder()
+                .ty(vk::DescriptorType::STORAGE_BUFFER_DYNAMIC)
+                .descriptor_count(DESCRIPTOR_POOL_TYPE_COUNT)
                 .build(),
             vk::DescriptorPoolSize::builder()
                 .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-                .descriptor_count(10)
+                .descriptor_count(DESCRIPTOR_POOL_TYPE_COUNT)
                 .build(),
         ];
 
         let pool_info = vk::DescriptorPoolCreateInfo::builder()
-            .max_sets(10)
+            .max_sets(DESCRIPTOR_POOL_MAX_SETS)
             .pool_sizes(&pool_sizes);
 
         self.descriptor_pool = unsafe {
@@ -419,7 +627,7 @@ impl ResourceManager {
                 .binding(0)
                 .descriptor_count(1)
                 .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
-                .stage_flags(vk::ShaderStageFlags::VERTEX)
+                .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
                 .build(),
             vk::DescriptorSetLayoutBinding::builder()
                 .binding(1)
@@ -427,6 +635,54 @@ impl ResourceManager {
                 .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
                 .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
                 .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(2)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER_DYNAMIC)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(SHADOW_UBO_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(SHADOW_MAP_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(AMBIENT_OCCLUSION_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(ENVIRONMENT_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(IRRADIANCE_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(PREFILTERED_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(BRDF_LUT_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
         ];
 
         self.global_descriptor_set_layout = unsafe {
@@ -451,7 +707,19 @@ impl ResourceManager {
                 .binding(1)
                 .descriptor_count(1)
                 .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
-                .stage_flags(vk::ShaderStageFlags::VERTEX)
+                .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(2)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(3)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
                 .build(),
         ];
 
@@ -466,12 +734,26 @@ impl ResourceManager {
                 )
         };
 
-        let texture_bindings = [vk::DescriptorSetLayoutBinding::builder()
-            .binding(0)
-            .descriptor_count(1)
-            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
-            .build()];
+        let mut texture_bindings = TextureSlot::ALL
+            .iter()
+            .map(|slot| {
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(slot.binding())
+                    .descriptor_count(1)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                    .build()
+            })
+            .collect::<Vec<vk::DescriptorSetLayoutBinding>>();
+
+        texture_bindings.push(
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(MATERIAL_UBO_BINDING)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build(),
+        );
 
         self.texture_descriptor_set_layout = unsafe {
             device.create_descriptor_set_layout(
@@ -480,6 +762,37 @@ impl ResourceManager {
             ).expect("ResourceManager::create_descriptors - Failed to create texture descriptor set layout!")
         };
 
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::REPEAT)
+            .address_mode_v(vk::SamplerAddressMode::REPEAT)
+            .address_mode_w(vk::SamplerAddressMode::REPEAT);
+
+        self.texture_sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("ResourceManager::create_descriptors - Failed to create texture sampler!")
+        };
+
+        // Filtered across cube faces, which are never tiled, and across the mips of the prefiltered environment
+        let environment_sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .max_lod(vk::LOD_CLAMP_NONE);
+
+        self.environment_sampler = unsafe {
+            device
+                .create_sampler(&environment_sampler_info, None)
+                .expect(
+                    "ResourceManager::create_descriptors - Failed to create environment sampler!",
+                )
+        };
+
         let global_set_layouts = [self.global_descriptor_set_layout];
         let global_set_info = vk::DescriptorSetAllocateInfo::builder()
             .descriptor_pool(self.descriptor_pool)
@@ -491,6 +804,59 @@ impl ResourceManager {
             )[0]
         };
 
+        let camera_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(self.scene.buffer.get())
+            .offset(0) // dynamically offset at bind point
+            .range(CAMERA_UBO_SIZE as u64)
+            .build()];
+
+        let scene_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(self.scene.buffer.get())
+            .offset(self.scene.scene_ubo_offset) // dynamically offset at bind point
+            .range(SCENE_UBO_SIZE as u64)
+            .build()];
+
+        let light_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(self.scene.light_buffer.get())
+            .offset(0) // dynamically offset at bind point
+            .range(LIGHT_BUFFER_SIZE as u64)
+            .build()];
+
+        let shadow_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(self.scene.shadow_buffer.get())
+            .offset(0) // dynamically offset at bind point
+            .range(SHADOW_UBO_SIZE as u64)
+            .build()];
+
+        let scene_write_set = [
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(0)
+                .dst_set(self.scene.descriptor_set)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+                .buffer_info(&camera_buffer_info)
+                .build(),
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(1)
+                .dst_set(self.scene.descriptor_set)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+                .buffer_info(&scene_buffer_info)
+                .build(),
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(2)
+                .dst_set(self.scene.descriptor_set)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER_DYNAMIC)
+                .buffer_info(&light_buffer_info)
+                .build(),
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(SHADOW_UBO_BINDING)
+                .dst_set(self.scene.descriptor_set)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+                .buffer_info(&shadow_buffer_info)
+                .build(),
+        ];
+
+        unsafe { device.update_descriptor_sets(&scene_write_set, &[]) };
+
         // TODO untangle implicit order dependency, frames must exist when this gets called!
         let entity_set_layouts = [self.entity_descriptor_set_layout];
         for frame in &mut self.frames {
@@ -504,141 +870,1742 @@ impl ResourceManager {
                 )[0]
             };
 
-            let camera_buffer_info = [vk::DescriptorBufferInfo::builder()
-                .buffer(self.scene.buffer.get())
-                .offset(0) // dynamically offset at bind point
-                .range(CAMERA_UBO_SIZE as u64)
-                .build()];
-
-            let scene_buffer_info = [vk::DescriptorBufferInfo::builder()
-                .buffer(self.scene.buffer.get())
-                .offset(CAMERA_UBO_SIZE as u64) // dynamically offset at bind point
-                .range(SCENE_UBO_SIZE as u64)
-                .build()];
-
-            let entity_buffer_info = [vk::DescriptorBufferInfo::builder()
-                .buffer(frame.entity_buffer.get())
-                .offset(0)
-                .range(MESH_SSBO_SIZE * MESH_SSBO_MAX)
-                .build()];
-
-            let entity_meta_buffer_info = [vk::DescriptorBufferInfo::builder()
-                .buffer(frame.entity_meta_buffer.get())
-                .offset(0)
-                .range(MESH_META_SSBO_SIZE * MESH_SSBO_MAX)
-                .build()];
-
-            let write_set = [
-                vk::WriteDescriptorSet::builder()
-                    .dst_binding(0)
-                    .dst_set(self.scene.descriptor_set)
-                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
-                    .buffer_info(&camera_buffer_info)
-                    .build(),
-                vk::WriteDescriptorSet::builder()
-                    .dst_binding(1)
-                    .dst_set(self.scene.descriptor_set)
-                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
-                    .buffer_info(&scene_buffer_info)
-                    .build(),
-                vk::WriteDescriptorSet::builder()
-                    .dst_binding(0)
-                    .dst_set(frame.entity_descriptor_set)
-                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
-                    .buffer_info(&entity_buffer_info)
-                    .build(),
-                vk::WriteDescriptorSet::builder()
-                    .dst_binding(1)
-                    .dst_set(frame.entity_descriptor_set)
-                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
-                    .buffer_info(&entity_meta_buffer_info)
-                    .build(),
-            ];
-
-            unsafe { device.update_descriptor_sets(&write_set, &[]) };
+            Self::write_entity_descriptor_set(device, frame);
         }
     }
     //------------------------------------------------------------------------------------------------------------------
 
-    pub fn create_pipeline_layout(
-        &mut self,
-        device: &Device,
-        id: &str,
-        push_constant_ranges: Option<&[vk::PushConstantRange]>,
-        descriptor_set_layouts: Option<&[vk::DescriptorSetLayout]>,
-    ) -> Rc<VkPipelineLayout> {
-        let pipeline_layout = Rc::new(VkPipelineLayout::new(
-            device,
-            push_constant_ranges,
-            descriptor_set_layouts,
-        ));
-        self.pipeline_layouts
-            .insert(id.to_owned(), pipeline_layout.clone());
-
-        pipeline_layout
-    }
-    //------------------------------------------------------------------------------------------------------------------
-
-    #[allow(dead_code)]
-    pub fn get_pipeline_layout(&self, id: &str) -> Rc<VkPipelineLayout> {
-        self.pipeline_layouts.get(id).unwrap().clone()
-    }
-    //------------------------------------------------------------------------------------------------------------------
+    fn write_entity_descriptor_set(device: &Device, frame: &VkFrame) {
+        let entity_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(frame.entity_buffer.get())
+            .offset(0)
+            .range(MESH_SSBO_SIZE * frame.entity_capacity)
+            .build()];
 
-    pub fn get_pipeline_builder() -> VkPipelineBuilder {
-        VkPipeline::builder()
-    }
-    //------------------------------------------------------------------------------------------------------------------
+        let entity_meta_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(frame.entity_meta_buffer.get())
+            .offset(0)
+            .range(MESH_META_SSBO_SIZE * frame.entity_capacity)
+            .build()];
 
-    pub fn create_pipeline(
-        &mut self,
-        device: &Device,
-        id: &str,
-        builder: &VkPipelineBuilder,
-        render_pass: &VkRenderPass,
-    ) -> Rc<VkPipeline> {
-        let pipeline = Rc::new(
-            builder
-                .build(device, render_pass)
-                .expect("ResourceManager::create_pipeline - Failed to create pipeline!"),
-        );
+        let cluster_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(frame.cluster_buffer.get())
+            .offset(0)
+            .range(CLUSTER_BUFFER_SIZE)
+            .build()];
 
-        self.pipelines.insert(id.to_owned(), pipeline.clone());
+        let cluster_light_index_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(frame.cluster_light_index_buffer.get())
+            .offset(0)
+            .range(CLUSTER_LIGHT_INDEX_BUFFER_SIZE)
+            .build()];
 
-        pipeline
-    }
-    //------------------------------------------------------------------------------------------------------------------
+        let write_set = [
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(0)
+                .dst_set(frame.entity_descriptor_set)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&entity_buffer_info)
+                .build(),
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(1)
+                .dst_set(frame.entity_descriptor_set)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&entity_meta_buffer_info)
+                .build(),
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(2)
+                .dst_set(frame.entity_descriptor_set)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&cluster_buffer_info)
+                .build(),
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(3)
+                .dst_set(frame.entity_descriptor_set)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&cluster_light_index_buffer_info)
+                .build(),
+        ];
 
-    #[allow(dead_code)]
-    pub fn get_pipeline(&self, id: &str) -> Rc<VkPipeline> {
-        self.pipelines.get(id).unwrap().clone()
+        unsafe { device.update_descriptor_sets(&write_set, &[]) };
     }
     //------------------------------------------------------------------------------------------------------------------
 
-    pub fn create_material(
+    /// Creates the shadow map array, its depth-only render pass and pipeline, and binds the maps to the global set.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_shadow_maps(
         &mut self,
         device: &Device,
+        allocator_handle: &AllocatorHandle,
+        config: &VkRendererConfig,
+        shadow_map_format: vk::Format,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) {
+        // TODO untangle implicit order dependency, descriptors must exist when this gets called!
+        let render_pass = self.create_render_pass(
+            device,
+            Some(SHADOW_RENDER_PASS),
+            None,
+            shadow_map_format,
+            vk::SampleCountFlags::TYPE_1,
+        );
+
+        let shadow_maps = VkShadowMaps::new(
+            device,
+            allocator_handle,
+            shadow_map_format,
+            config.shadow_map_size,
+            command_pool,
+            fence,
+            queue,
+        );
+
+        let push_constant_ranges = [ShadowPushConstants::get_range()];
+        let descriptor_set_layouts = [
+            self.global_descriptor_set_layout,
+            self.entity_descriptor_set_layout,
+        ];
+        let pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                SHADOW_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        let vertex_shader_path = Path::new("resources/shaders/dist/shadow.vert.spv");
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let vertex_description = VertexInputDescription::get();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+        let extent = shadow_maps.extent();
+
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .viewport(
+                vk::Viewport::builder()
+                    .x(0.0)
+                    .y(0.0)
+                    .width(extent.width as f32)
+                    .height(extent.height as f32)
+                    .min_depth(0.0)
+                    .max_depth(1.0)
+                    .build(),
+            )
+            .scissor(
+                vk::Rect2D::builder()
+                    .offset(vk::Offset2D::default())
+                    .extent(extent)
+                    .build(),
+            )
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(true, true, VkPipeline::depth_compare_op(false))
+            .depth_bias(SHADOW_DEPTH_BIAS_CONSTANT, SHADOW_DEPTH_BIAS_SLOPE)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .pipeline_layout(pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point);
+
+        self.create_pipeline(device, SHADOW_PIPELINE, &pipeline_builder, &render_pass);
+
+        let shadow_map_info = [vk::DescriptorImageInfo::builder()
+            .sampler(shadow_maps.sampler())
+            .image_view(shadow_maps.image_view())
+            .image_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let shadow_write_set = [vk::WriteDescriptorSet::builder()
+            .dst_binding(SHADOW_MAP_BINDING)
+            .dst_set(self.scene.descriptor_set)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&shadow_map_info)
+            .build()];
+
+        unsafe { device.update_descriptor_sets(&shadow_write_set, &[]) };
+
+        self.shadow_maps = Some(shadow_maps);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_shadow_maps(&self) -> &VkShadowMaps {
+        self.shadow_maps
+            .as_ref()
+            .expect("ResourceManager::get_shadow_maps - Shadow maps have not been created!")
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the post-process descriptor sets and the pipelines of every post-process effect. Bloom renders to HDR
+    /// targets, tonemapping and FXAA to targets of `output_format`.
+    pub fn create_post_process(
+        &mut self,
+        device: &Device,
+        config: &VkRendererConfig,
+        output_format: vk::Format,
+    ) {
+        let post_process = VkPostProcess::new(device, config.buffering);
+
+        let push_constant_ranges = [PostProcessPushConstants::get_range()];
+        let descriptor_set_layouts = [post_process.descriptor_set_layout()];
+        let pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                POST_PROCESS_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        let compatible_render_pass = |format: vk::Format| {
+            [VkAttachmentInfo {
+                format,
+                samples: vk::SampleCountFlags::TYPE_1,
+                load_op: vk::AttachmentLoadOp::CLEAR,
+            }]
+        };
+
+        let hdr_render_pass = self.create_render_pass_with_attachments(
+            device,
+            POST_PROCESS_HDR_RENDER_PASS,
+            &compatible_render_pass(HDR_COLOR_FORMAT),
+            None,
+        );
+        let output_render_pass = self.create_render_pass_with_attachments(
+            device,
+            POST_PROCESS_OUTPUT_RENDER_PASS,
+            &compatible_render_pass(output_format),
+            None,
+        );
+
+        let effects = [
+            (
+                BLOOM_DOWNSAMPLE_PIPELINE,
+                "resources/shaders/dist/bloom_downsample.frag.spv",
+                &hdr_render_pass,
+            ),
+            (
+                BLOOM_UPSAMPLE_PIPELINE,
+                "resources/shaders/dist/bloom_upsample.frag.spv",
+                &hdr_render_pass,
+            ),
+            (
+                TONEMAP_PIPELINE,
+                "resources/shaders/dist/tonemap.frag.spv",
+                &output_render_pass,
+            ),
+            (
+                FXAA_PIPELINE,
+                "resources/shaders/dist/fxaa.frag.spv",
+                &output_render_pass,
+            ),
+        ];
+
+        for (pipeline_id, fragment_shader_path, render_pass) in effects.iter() {
+            self.create_fullscreen_pipeline(
+                device,
+                pipeline_id,
+                Path::new(fragment_shader_path),
+                pipeline_layout,
+                render_pass,
+            );
+        }
+
+        self.post_process = Some(post_process);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the G-buffer descriptor sets, the render pass the material G-buffer pipelines are created against, and
+    /// the deferred lighting and G-buffer debug pipelines. Must run after `create_descriptors` and before any material
+    /// is created.
+    pub fn create_deferred(
+        &mut self,
+        device: &Device,
+        config: &VkRendererConfig,
+        output_format: vk::Format,
+        depth_attachment_format: vk::Format,
+    ) {
+        let gbuffer = VkGBuffer::new(device, config.buffering);
+
+        let attachment_info = |format: vk::Format| VkAttachmentInfo {
+            format,
+            samples: vk::SampleCountFlags::TYPE_1,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+        };
+
+        let gbuffer_color_attachments = GBUFFER_COLOR_FORMATS
+            .iter()
+            .map(|format| attachment_info(*format))
+            .collect::<Vec<VkAttachmentInfo>>();
+
+        self.create_render_pass_with_attachments(
+            device,
+            GBUFFER_RENDER_PASS,
+            &gbuffer_color_attachments,
+            Some(attachment_info(depth_attachment_format)),
+        );
+        let lighting_render_pass = self.create_render_pass_with_attachments(
+            device,
+            DEFERRED_LIGHTING_RENDER_PASS,
+            &[attachment_info(HDR_COLOR_FORMAT)],
+            None,
+        );
+        let debug_render_pass = self.create_render_pass_with_attachments(
+            device,
+            GBUFFER_DEBUG_RENDER_PASS,
+            &[attachment_info(output_format)],
+            None,
+        );
+        // Transparent materials are blended over the lit scene, depth tested against the G-buffer depth
+        self.create_render_pass_with_attachments(
+            device,
+            DEFERRED_TRANSPARENT_RENDER_PASS,
+            &[attachment_info(HDR_COLOR_FORMAT)],
+            Some(attachment_info(depth_attachment_format)),
+        );
+
+        // While a pick is pending both passes also write the object id and depth targets
+        let picking_attachments = [
+            attachment_info(OBJECT_ID_FORMAT),
+            attachment_info(OBJECT_DEPTH_FORMAT),
+        ];
+        self.create_render_pass_with_attachments(
+            device,
+            GBUFFER_PICKING_RENDER_PASS,
+            &[gbuffer_color_attachments, picking_attachments.to_vec()].concat(),
+            Some(attachment_info(depth_attachment_format)),
+        );
+        self.create_render_pass_with_attachments(
+            device,
+            DEFERRED_TRANSPARENT_PICKING_RENDER_PASS,
+            &[
+                attachment_info(HDR_COLOR_FORMAT),
+                picking_attachments[0],
+                picking_attachments[1],
+            ],
+            Some(attachment_info(depth_attachment_format)),
+        );
+
+        let push_constant_ranges = [DeferredPushConstants::get_range()];
+        let descriptor_set_layouts = [
+            self.global_descriptor_set_layout,
+            self.entity_descriptor_set_layout,
+            gbuffer.descriptor_set_layout(),
+        ];
+        let pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                DEFERRED_LIGHTING_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        self.create_fullscreen_pipeline(
+            device,
+            DEFERRED_LIGHTING_PIPELINE,
+            Path::new("resources/shaders/dist/deferred_lighting.frag.spv"),
+            pipeline_layout,
+            &lighting_render_pass,
+        );
+        self.create_fullscreen_pipeline(
+            device,
+            GBUFFER_DEBUG_PIPELINE,
+            Path::new("resources/shaders/dist/gbuffer_debug.frag.spv"),
+            pipeline_layout,
+            &debug_render_pass,
+        );
+
+        self.gbuffer = Some(gbuffer);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the ambient occlusion map and points the global descriptor set at it, along with the depth/normal
+    /// prepass of the forward path and the occlusion and blur pipelines. Must run after `create_descriptors`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_ssao(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        config: &VkRendererConfig,
+        depth_attachment_format: vk::Format,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) {
+        let extent = self
+            .get_swapchain()
+            .expect("ResourceManager::create_ssao - Swapchain has not been created!")
+            .surface_extent();
+
+        let ssao = VkSsao::new(
+            device,
+            allocator_handle,
+            config.buffering,
+            extent,
+            command_pool,
+            fence,
+            queue,
+        );
+
+        let attachment_info = |format: vk::Format| VkAttachmentInfo {
+            format,
+            samples: vk::SampleCountFlags::TYPE_1,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+        };
+
+        let depth_normal_render_pass = self.create_render_pass_with_attachments(
+            device,
+            DEPTH_NORMAL_RENDER_PASS,
+            &[attachment_info(SSAO_NORMAL_FORMAT)],
+            Some(attachment_info(depth_attachment_format)),
+        );
+        let ssao_render_pass = self.create_render_pass_with_attachments(
+            device,
+            SSAO_RENDER_PASS,
+            &[attachment_info(SSAO_FORMAT)],
+            None,
+        );
+
+        // The prepass only needs the camera and the entity transforms
+        let descriptor_set_layouts = [
+            self.global_descriptor_set_layout,
+            self.entity_descriptor_set_layout,
+        ];
+        let depth_normal_pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                DEPTH_NORMAL_PIPELINE_LAYOUT,
+                None,
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        let vertex_shader_path = Path::new("resources/shaders/dist/shader.vert.spv");
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let fragment_shader_path = Path::new("resources/shaders/dist/depth_normal.frag.spv");
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let vertex_description = VertexInputDescription::get();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .color_blend_attachment_state()
+            .pipeline_layout(depth_normal_pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(
+            device,
+            DEPTH_NORMAL_PIPELINE,
+            &pipeline_builder,
+            &depth_normal_render_pass,
+        );
+
+        let push_constant_ranges = [SsaoPushConstants::get_range()];
+        let descriptor_set_layouts = [
+            self.global_descriptor_set_layout,
+            ssao.descriptor_set_layout(),
+        ];
+        let ssao_pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                SSAO_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        self.create_fullscreen_pipeline(
+            device,
+            SSAO_PIPELINE,
+            Path::new("resources/shaders/dist/ssao.frag.spv"),
+            ssao_pipeline_layout,
+            &ssao_render_pass,
+        );
+        self.create_fullscreen_pipeline(
+            device,
+            SSAO_BLUR_PIPELINE,
+            Path::new("resources/shaders/dist/ssao_blur.frag.spv"),
+            ssao_pipeline_layout,
+            &ssao_render_pass,
+        );
+
+        let ambient_occlusion_info = [vk::DescriptorImageInfo::builder()
+            .sampler(ssao.sampler())
+            .image_view(ssao.image_view())
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let ambient_occlusion_write_set = [vk::WriteDescriptorSet::builder()
+            .dst_binding(AMBIENT_OCCLUSION_BINDING)
+            .dst_set(self.scene.descriptor_set)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&ambient_occlusion_info)
+            .build()];
+
+        unsafe { device.update_descriptor_sets(&ambient_occlusion_write_set, &[]) };
+
+        self.ssao = Some(ssao);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_ssao(&self) -> &VkSsao {
+        self.ssao
+            .as_ref()
+            .expect("ResourceManager::get_ssao - SSAO resources have not been created!")
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the render pass the forward pipelines drawn while a pick is pending are created against: the main pass
+    /// with the object id and depth targets as additional color attachments, resolved along with the scene color when
+    /// multisampled. Replaces the previous one, which the device must not be using anymore.
+    pub fn create_forward_picking_render_pass(
+        &mut self,
+        device: &Device,
+        depth_attachment_format: vk::Format,
+        samples: vk::SampleCountFlags,
+    ) {
+        let attachment_info =
+            |format: vk::Format, samples: vk::SampleCountFlags| VkAttachmentInfo {
+                format,
+                samples,
+                load_op: vk::AttachmentLoadOp::CLEAR,
+            };
+
+        let color_formats = [HDR_COLOR_FORMAT, OBJECT_ID_FORMAT, OBJECT_DEPTH_FORMAT];
+        let color_attachments = color_formats
+            .iter()
+            .map(|format| attachment_info(*format, samples))
+            .collect::<Vec<VkAttachmentInfo>>();
+        let resolve_attachments = if samples == vk::SampleCountFlags::TYPE_1 {
+            Vec::new()
+        } else {
+            color_formats
+                .iter()
+                .map(|format| attachment_info(*format, vk::SampleCountFlags::TYPE_1))
+                .collect()
+        };
+
+        let render_pass = Rc::new(VkRenderPass::with_attachments(
+            device,
+            &color_attachments,
+            Some(attachment_info(depth_attachment_format, samples)),
+            &resolve_attachments,
+        ));
+
+        if let Some(previous_render_pass) = self
+            .render_passes
+            .insert(FORWARD_PICKING_RENDER_PASS.to_owned(), render_pass)
+        {
+            previous_render_pass.destroy(device);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn get_forward_picking_render_pass(&self) -> Rc<VkRenderPass> {
+        self.render_passes
+            .get(FORWARD_PICKING_RENDER_PASS)
+            .cloned()
+            .expect("ResourceManager::get_forward_picking_render_pass - Render pass has not been created!")
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the pipeline marking highlighted renderables in the selection stencil, and the outline pipeline blending
+    /// their edges over the backbuffer. `selection_format` must have a stencil aspect.
+    pub fn create_selection(
+        &mut self,
+        device: &Device,
+        config: &VkRendererConfig,
+        output_format: vk::Format,
+        selection_format: vk::Format,
+    ) {
+        let selection = VkSelection::new(device, config.buffering);
+
+        let attachment_info = |format: vk::Format| VkAttachmentInfo {
+            format,
+            samples: vk::SampleCountFlags::TYPE_1,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+        };
+
+        let stencil_render_pass = self.create_render_pass_with_attachments(
+            device,
+            SELECTION_STENCIL_RENDER_PASS,
+            &[],
+            Some(attachment_info(selection_format)),
+        );
+        let outline_render_pass = self.create_render_pass_with_attachments(
+            device,
+            SELECTION_OUTLINE_RENDER_PASS,
+            &[VkAttachmentInfo {
+                load_op: vk::AttachmentLoadOp::LOAD,
+                ..attachment_info(output_format)
+            }],
+            None,
+        );
+
+        let descriptor_set_layouts = [
+            self.global_descriptor_set_layout,
+            self.entity_descriptor_set_layout,
+        ];
+        let stencil_pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                SELECTION_STENCIL_PIPELINE_LAYOUT,
+                None,
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        let vertex_shader_path = Path::new("resources/shaders/dist/shader.vert.spv");
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let vertex_description = VertexInputDescription::get();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        // Vertex only, like the shadow pipeline. Outlines show through occluders, so depth is left untested
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .dynamic_state(vk::DynamicState::STENCIL_REFERENCE)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(false, false, vk::CompareOp::ALWAYS)
+            .stencil_state(vk::CompareOp::ALWAYS, vk::StencilOp::REPLACE)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .pipeline_layout(stencil_pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point);
+
+        self.create_pipeline(
+            device,
+            SELECTION_STENCIL_PIPELINE,
+            &pipeline_builder,
+            &stencil_render_pass,
+        );
+
+        let push_constant_ranges = [SelectionOutlinePushConstants::get_range()];
+        let outline_descriptor_set_layouts = [selection.descriptor_set_layout()];
+        let outline_pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                SELECTION_OUTLINE_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&outline_descriptor_set_layouts),
+            )
+            .get();
+
+        let fragment_shader_path = Path::new("resources/shaders/dist/selection_outline.frag.spv");
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let vertex_shader_path = Path::new(FULLSCREEN_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        // Blended over the finished frame, the fragment shader discards everything but the outlines
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(false, false, vk::CompareOp::ALWAYS)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .alpha_blend_attachment_state()
+            .pipeline_layout(outline_pipeline_layout)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(
+            device,
+            SELECTION_OUTLINE_PIPELINE,
+            &pipeline_builder,
+            &outline_render_pass,
+        );
+
+        self.selection = Some(selection);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the debug line pipelines of the forward and deferred paths, with and without depth testing. `render_pass`
+    /// and `samples` must match the forward pass, like the material pipelines. Must run after `create_deferred`.
+    pub fn create_debug_lines(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        let descriptor_set_layouts = [self.global_descriptor_set_layout];
+        self.create_pipeline_layout(
+            device,
+            DEBUG_LINE_PIPELINE_LAYOUT,
+            None,
+            Some(&descriptor_set_layouts),
+        );
+
+        let transparent_render_pass = self
+            .render_passes
+            .get(DEFERRED_TRANSPARENT_RENDER_PASS)
+            .cloned()
+            .expect("ResourceManager::create_debug_lines - Deferred transparent render pass has not been created!");
+
+        for &(id, depth_test) in [
+            (DEFERRED_DEBUG_LINE_PIPELINE, true),
+            (DEFERRED_DEBUG_LINE_OVERLAY_PIPELINE, false),
+        ]
+        .iter()
+        {
+            self.create_debug_line_pipeline(
+                device,
+                id,
+                &transparent_render_pass,
+                vk::SampleCountFlags::TYPE_1,
+                depth_test,
+            );
+        }
+
+        self.rebuild_debug_line_pipelines(device, render_pass, samples);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Recreates the forward debug line pipelines, e.g. after the sample count of the main pass changed. The device must
+    /// be idle.
+    pub fn rebuild_debug_line_pipelines(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        for &(id, depth_test) in [
+            (DEBUG_LINE_PIPELINE, true),
+            (DEBUG_LINE_OVERLAY_PIPELINE, false),
+        ]
+        .iter()
+        {
+            self.create_debug_line_pipeline(device, id, render_pass, samples, depth_test);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn create_debug_line_pipeline(
+        &mut self,
+        device: &Device,
+        id: &str,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+        depth_test: bool,
+    ) {
+        let pipeline_layout = self.get_pipeline_layout(DEBUG_LINE_PIPELINE_LAYOUT).get();
+
+        let vertex_shader_path = Path::new(DEBUG_LINE_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let fragment_shader_path = Path::new(DEBUG_LINE_FRAGMENT_SHADER);
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let vertex_description = DebugVertex::input_description();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        // Lines never write depth, so they don't hide each other or anything drawn after them
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::LINE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(depth_test, false, self.depth_compare_op())
+            .multisampling_state(samples)
+            .alpha_blend_attachment_state()
+            .pipeline_layout(pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(device, id, &pipeline_builder, render_pass);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the sprite descriptor sets and the pipeline drawing sprites over the backbuffer.
+    pub fn create_sprites(&mut self, device: &Device, output_format: vk::Format) {
+        let sprites = VkSprites::new(device);
+
+        let push_constant_ranges = [SpritePushConstants::get_range()];
+        let descriptor_set_layouts = [sprites.descriptor_set_layout()];
+        let pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                SPRITE_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        let render_pass = self.create_render_pass_with_attachments(
+            device,
+            SPRITE_RENDER_PASS,
+            &[VkAttachmentInfo {
+                format: output_format,
+                samples: vk::SampleCountFlags::TYPE_1,
+                load_op: vk::AttachmentLoadOp::LOAD,
+            }],
+            None,
+        );
+
+        let vertex_shader_path = Path::new(SPRITE_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let fragment_shader_path = Path::new(SPRITE_FRAGMENT_SHADER);
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let vertex_description = SpriteVertex::input_description();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        // Drawn in order over the finished image, layering comes from the draw order alone
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(false, false, vk::CompareOp::ALWAYS)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .alpha_blend_attachment_state()
+            .pipeline_layout(pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(device, SPRITE_PIPELINE, &pipeline_builder, &render_pass);
+
+        self.sprites = Some(sprites);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the billboard pipelines of the forward and deferred paths. `render_pass` and `samples` must match the
+    /// forward pass, like the material pipelines. Must run after `create_deferred` and `create_sprites`.
+    pub fn create_billboards(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        let sprite_set_layout = self
+            .sprites
+            .as_ref()
+            .expect("ResourceManager::create_billboards - Sprite resources have not been created!")
+            .descriptor_set_layout();
+
+        let push_constant_ranges = [BillboardPushConstants::get_range()];
+        let descriptor_set_layouts = [self.global_descriptor_set_layout, sprite_set_layout];
+        self.create_pipeline_layout(
+            device,
+            BILLBOARD_PIPELINE_LAYOUT,
+            Some(&push_constant_ranges),
+            Some(&descriptor_set_layouts),
+        );
+
+        let transparent_render_pass = self
+            .render_passes
+            .get(DEFERRED_TRANSPARENT_RENDER_PASS)
+            .cloned()
+            .expect("ResourceManager::create_billboards - Deferred transparent render pass has not been created!");
+
+        self.create_billboard_pipeline(
+            device,
+            DEFERRED_BILLBOARD_PIPELINE,
+            &transparent_render_pass,
+            vk::SampleCountFlags::TYPE_1,
+        );
+
+        self.rebuild_billboard_pipeline(device, render_pass, samples);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Recreates the forward billboard pipeline, e.g. after the sample count of the main pass changed. The device must
+    /// be idle.
+    pub fn rebuild_billboard_pipeline(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        self.create_billboard_pipeline(device, BILLBOARD_PIPELINE, render_pass, samples);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn create_billboard_pipeline(
+        &mut self,
+        device: &Device,
+        id: &str,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        let pipeline_layout = self.get_pipeline_layout(BILLBOARD_PIPELINE_LAYOUT).get();
+
+        let vertex_shader_path = Path::new(BILLBOARD_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let fragment_shader_path = Path::new(BILLBOARD_FRAGMENT_SHADER);
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let vertex_description = BillboardVertex::input_description();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        // Depth tested against the scene, but like debug lines they never write it
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(true, false, self.depth_compare_op())
+            .multisampling_state(samples)
+            .alpha_blend_attachment_state()
+            .pipeline_layout(pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(device, id, &pipeline_builder, render_pass);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Descriptor set sprites textured with `texture_name` are drawn with, allocated on first use. `None` stands for the
+    /// plain white texture of untextured sprites. Returns `None` for unknown textures and once every set is taken.
+    pub fn prepare_sprite_descriptor_set(
+        &mut self,
+        device: &Device,
+        texture_name: Option<&str>,
+    ) -> Option<vk::DescriptorSet> {
+        let texture_name = texture_name.unwrap_or(DEFAULT_WHITE_TEXTURE);
+        let image_view = self.textures.get(texture_name)?.image_view();
+
+        self.sprites
+            .as_mut()
+            .expect("ResourceManager::prepare_sprite_descriptor_set - Sprite resources have not been created!")
+            .prepare_descriptor_set(device, texture_name, image_view)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Descriptor sets of the textures of `batches`. Batches whose texture doesn't exist are left out rather than drawn
+    /// with another one.
+    pub fn prepare_sprite_draws(
+        &mut self,
+        device: &Device,
+        batches: &[SpriteBatch],
+    ) -> Vec<SpriteDraw> {
+        batches
+            .iter()
+            .filter_map(|batch| {
+                let descriptor_set = self.prepare_sprite_descriptor_set(device, batch.texture_name);
+                if descriptor_set.is_none() {
+                    warn!(
+                        "ResourceManager::prepare_sprite_draws - Can't draw sprites with texture {:?}!",
+                        batch.texture_name
+                    );
+                }
+
+                descriptor_set.map(|descriptor_set| SpriteDraw {
+                    descriptor_set,
+                    distance_field: batch.distance_field,
+                    first_vertex: batch.first_vertex,
+                    vertex_count: batch.vertex_count,
+                })
+            })
+            .collect()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the pipelines of the debug view modes replacing the materials, and the wireframe overlay ones when
+    /// `wireframe` is set, i.e. the device supports line fill modes. `render_pass` and `samples` must match the
+    /// forward pass, like the material pipelines. Must run after `create_post_process` and `create_deferred`.
+    pub fn create_debug_view(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+        output_format: vk::Format,
+        depth_attachment_format: vk::Format,
+        wireframe: bool,
+    ) {
+        let push_constant_ranges = [DebugViewPushConstants::get_range()];
+        let descriptor_set_layouts = [
+            self.global_descriptor_set_layout,
+            self.entity_descriptor_set_layout,
+            self.texture_descriptor_set_layout,
+        ];
+        self.create_pipeline_layout(
+            device,
+            DEBUG_VIEW_PIPELINE_LAYOUT,
+            Some(&push_constant_ranges),
+            Some(&descriptor_set_layouts),
+        );
+
+        let attachment_info = |format: vk::Format| VkAttachmentInfo {
+            format,
+            samples: vk::SampleCountFlags::TYPE_1,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+        };
+
+        let debug_view_render_pass = self.create_render_pass_with_attachments(
+            device,
+            DEBUG_VIEW_RENDER_PASS,
+            &[attachment_info(output_format)],
+            Some(attachment_info(depth_attachment_format)),
+        );
+        // While a pick is pending the debug view also writes the object id and depth targets, the materials don't run
+        let debug_view_picking_render_pass = self.create_render_pass_with_attachments(
+            device,
+            DEBUG_VIEW_PICKING_RENDER_PASS,
+            &[
+                attachment_info(output_format),
+                attachment_info(OBJECT_ID_FORMAT),
+                attachment_info(OBJECT_DEPTH_FORMAT),
+            ],
+            Some(attachment_info(depth_attachment_format)),
+        );
+        let overdraw_render_pass = self.create_render_pass_with_attachments(
+            device,
+            DEBUG_VIEW_OVERDRAW_RENDER_PASS,
+            &[attachment_info(OVERDRAW_COUNT_FORMAT)],
+            None,
+        );
+
+        self.create_debug_view_pipeline(
+            device,
+            DEBUG_VIEW_PIPELINE,
+            &debug_view_render_pass,
+            vk::SampleCountFlags::TYPE_1,
+            DebugViewMode::Normals,
+            false,
+        );
+        self.create_debug_view_pipeline(
+            device,
+            DEBUG_VIEW_PICKING_PIPELINE,
+            &debug_view_picking_render_pass,
+            vk::SampleCountFlags::TYPE_1,
+            DebugViewMode::Normals,
+            true,
+        );
+        self.create_debug_view_pipeline(
+            device,
+            DEBUG_VIEW_OVERDRAW_PIPELINE,
+            &overdraw_render_pass,
+            vk::SampleCountFlags::TYPE_1,
+            DebugViewMode::Overdraw,
+            false,
+        );
+
+        let output_render_pass = self
+            .render_passes
+            .get(POST_PROCESS_OUTPUT_RENDER_PASS)
+            .cloned()
+            .expect("ResourceManager::create_debug_view - Post-process render passes have not been created!");
+        let post_process_pipeline_layout =
+            self.get_pipeline_layout(POST_PROCESS_PIPELINE_LAYOUT).get();
+        self.create_fullscreen_pipeline(
+            device,
+            DEBUG_VIEW_HEAT_MAP_PIPELINE,
+            Path::new(DEBUG_VIEW_HEAT_MAP_FRAGMENT_SHADER),
+            post_process_pipeline_layout,
+            &output_render_pass,
+        );
+
+        if wireframe {
+            let transparent_render_pass = self
+                .render_passes
+                .get(DEFERRED_TRANSPARENT_RENDER_PASS)
+                .cloned()
+                .expect("ResourceManager::create_debug_view - Deferred transparent render pass has not been created!");
+
+            self.create_debug_view_pipeline(
+                device,
+                DEFERRED_WIREFRAME_PIPELINE,
+                &transparent_render_pass,
+                vk::SampleCountFlags::TYPE_1,
+                DebugViewMode::Wireframe,
+                false,
+            );
+            self.rebuild_wireframe_pipeline(device, render_pass, samples);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Recreates the forward wireframe pipeline, e.g. after the sample count of the main pass changed. The device must
+    /// be idle.
+    pub fn rebuild_wireframe_pipeline(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        self.create_debug_view_pipeline(
+            device,
+            WIREFRAME_PIPELINE,
+            render_pass,
+            samples,
+            DebugViewMode::Wireframe,
+            false,
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Wireframes are blended over the scene without writing depth, overdraw counts every fragment, and the other
+    /// modes draw like opaque materials. `picking` adds the unblended object id and depth targets of the other modes.
+    fn create_debug_view_pipeline(
+        &mut self,
+        device: &Device,
+        id: &str,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+        mode: DebugViewMode,
+        picking: bool,
+    ) {
+        let pipeline_layout = self.get_pipeline_layout(DEBUG_VIEW_PIPELINE_LAYOUT).get();
+
+        let vertex_shader_path = Path::new(DEBUG_VIEW_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let fragment_shader_path = Path::new(DEBUG_VIEW_FRAGMENT_SHADER);
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let vertex_description = VertexInputDescription::get();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .multisampling_state(samples)
+            .pipeline_layout(pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        let pipeline_builder = match mode {
+            DebugViewMode::Wireframe => {
+                // Pulled towards the camera, so the edges win over the faces they were rasterized from
+                let bias = if self.depth_mode.is_reversed() {
+                    WIREFRAME_DEPTH_BIAS
+                } else {
+                    -WIREFRAME_DEPTH_BIAS
+                };
+
+                pipeline_builder
+                    .rasterization_state(vk::PolygonMode::LINE)
+                    .depth_bias(bias, bias)
+                    .depth_stencil_state(true, false, self.depth_compare_op())
+                    .alpha_blend_attachment_state()
+            }
+            DebugViewMode::Overdraw => pipeline_builder
+                .rasterization_state(vk::PolygonMode::FILL)
+                .depth_stencil_state(false, false, vk::CompareOp::ALWAYS)
+                .additive_blend_attachment_state(),
+            _ if picking => pipeline_builder
+                .rasterization_state(vk::PolygonMode::FILL)
+                .color_blend_attachment_state()
+                .color_blend_attachment_state()
+                .color_blend_attachment_state(),
+            _ => pipeline_builder
+                .rasterization_state(vk::PolygonMode::FILL)
+                .color_blend_attachment_state(),
+        };
+
+        self.create_pipeline(device, id, &pipeline_builder, render_pass);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the skybox pipelines of the forward and deferred paths. `render_pass` and `samples` must match the
+    /// forward pass, like the material pipelines. Must run after `create_deferred`.
+    pub fn create_skybox(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        let push_constant_ranges = [SkyboxPushConstants::get_range()];
+        let descriptor_set_layouts = [self.global_descriptor_set_layout];
+        let pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                SKYBOX_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        let lighting_render_pass = self
+            .render_passes
+            .get(DEFERRED_LIGHTING_RENDER_PASS)
+            .cloned()
+            .expect("ResourceManager::create_skybox - Deferred lighting render pass has not been created!");
+
+        self.create_fullscreen_pipeline(
+            device,
+            SKYBOX_BACKGROUND_PIPELINE,
+            Path::new(SKYBOX_FRAGMENT_SHADER),
+            pipeline_layout,
+            &lighting_render_pass,
+        );
+
+        self.rebuild_skybox_pipeline(device, render_pass, samples);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Recreates the forward skybox pipelines, e.g. after the sample count of the main pass changed. The device must be
+    /// idle. Must run after `create_forward_picking_render_pass`.
+    pub fn rebuild_skybox_pipeline(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        let pipeline_layout = self.get_pipeline_layout(SKYBOX_PIPELINE_LAYOUT).get();
+
+        let vertex_shader_path = Path::new(SKYBOX_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let fragment_shader_path = Path::new(SKYBOX_FRAGMENT_SHADER);
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        // Only fills the pixels left at the cleared far depth, without writing depth of its own
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(true, false, self.depth_compare_op())
+            .multisampling_state(samples)
+            .color_blend_attachment_state()
+            .pipeline_layout(pipeline_layout)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(device, SKYBOX_PIPELINE, &pipeline_builder, render_pass);
+
+        // Integer targets can't be blended, the object id and depth are written as is
+        let picking_render_pass = self.get_forward_picking_render_pass();
+        let picking_pipeline_builder = pipeline_builder
+            .color_blend_attachment_state()
+            .color_blend_attachment_state();
+
+        self.create_pipeline(
+            device,
+            SKYBOX_PICKING_PIPELINE,
+            &picking_pipeline_builder,
+            &picking_render_pass,
+        );
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the image based lighting maps and points the global descriptor set at them, along with the passes
+    /// precomputing them. The BRDF lookup table only depends on the shading model and is generated right away. Must run
+    /// after `create_descriptors`.
+    pub fn create_ibl(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        config: &VkRendererConfig,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) {
+        let ibl = VkIbl::new(device, allocator_handle, command_pool, fence, queue);
+
+        // Every texel gets written, nothing needs to be loaded
+        let attachment_info = |format: vk::Format| VkAttachmentInfo {
+            format,
+            samples: vk::SampleCountFlags::TYPE_1,
+            load_op: vk::AttachmentLoadOp::DONT_CARE,
+        };
+
+        let ibl_render_pass = self.create_render_pass_with_attachments(
+            device,
+            IBL_RENDER_PASS,
+            &[attachment_info(IBL_FORMAT)],
+            None,
+        );
+        let brdf_lut_render_pass = self.create_render_pass_with_attachments(
+            device,
+            BRDF_LUT_RENDER_PASS,
+            &[attachment_info(BRDF_LUT_FORMAT)],
+            None,
+        );
+
+        let push_constant_ranges = [IblPushConstants::get_range()];
+        let descriptor_set_layouts = [ibl.descriptor_set_layout()];
+        let pipeline_layout = self
+            .create_pipeline_layout(
+                device,
+                IBL_PIPELINE_LAYOUT,
+                Some(&push_constant_ranges),
+                Some(&descriptor_set_layouts),
+            )
+            .get();
+
+        self.create_fullscreen_pipeline(
+            device,
+            IRRADIANCE_PIPELINE,
+            Path::new("resources/shaders/dist/ibl_irradiance.frag.spv"),
+            pipeline_layout,
+            &ibl_render_pass,
+        );
+        self.create_fullscreen_pipeline(
+            device,
+            PREFILTER_PIPELINE,
+            Path::new("resources/shaders/dist/ibl_prefilter.frag.spv"),
+            pipeline_layout,
+            &ibl_render_pass,
+        );
+        self.create_fullscreen_pipeline(
+            device,
+            BRDF_LUT_PIPELINE,
+            Path::new("resources/shaders/dist/ibl_brdf.frag.spv"),
+            pipeline_layout,
+            &brdf_lut_render_pass,
+        );
+
+        ibl.render(
+            device,
+            command_pool,
+            fence,
+            queue,
+            IblMap::BrdfLut,
+            self.get_ibl_pass(BRDF_LUT_PIPELINE, BRDF_LUT_RENDER_PASS),
+        );
+
+        let image_infos = [
+            (IRRADIANCE_BINDING, IblMap::Irradiance),
+            (PREFILTERED_BINDING, IblMap::Prefiltered),
+            (BRDF_LUT_BINDING, IblMap::BrdfLut),
+        ]
+        .iter()
+        .map(|(binding, map)| {
+            let image_info = [vk::DescriptorImageInfo::builder()
+                .sampler(self.environment_sampler)
+                .image_view(ibl.image_view(*map))
+                .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .build()];
+
+            (*binding, image_info)
+        })
+        .collect::<Vec<(u32, [vk::DescriptorImageInfo; 1])>>();
+
+        let ibl_write_sets = image_infos
+            .iter()
+            .map(|(binding, image_info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_binding(*binding)
+                    .dst_set(self.scene.descriptor_set)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .image_info(image_info)
+                    .build()
+            })
+            .collect::<Vec<vk::WriteDescriptorSet>>();
+
+        unsafe { device.update_descriptor_sets(&ibl_write_sets, &[]) };
+
+        self.ibl = Some(ibl);
+        self.ibl_cache_dir = config.ibl_cache_dir.clone();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_ibl(&self) -> &VkIbl {
+        self.ibl
+            .as_ref()
+            .expect("ResourceManager::get_ibl - IBL resources have not been created!")
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn get_ibl_pass(&self, pipeline_id: &str, render_pass_id: &str) -> VkIblPass {
+        VkIblPass {
+            render_pass: self.get_render_pass(Some(render_pass_id)).get(),
+            pipeline: self.get_pipeline(pipeline_id).get(),
+            pipeline_layout: self.get_pipeline_layout(IBL_PIPELINE_LAYOUT).get(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Fills the irradiance and prefiltered maps from the environment last passed to `VkIbl::write_environment`, loading
+    /// them from `cache_path` when it holds them and storing them there otherwise.
+    fn update_ibl(
+        &self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+        cache_path: Option<PathBuf>,
+    ) {
+        let ibl = self.get_ibl();
+
+        if let Some(data) = cache_path.as_deref().and_then(read_cache) {
+            ibl.upload(device, allocator_handle, command_pool, fence, queue, &data);
+            return;
+        }
+
+        ibl.render(
+            device,
+            command_pool,
+            fence,
+            queue,
+            IblMap::Irradiance,
+            self.get_ibl_pass(IRRADIANCE_PIPELINE, IBL_RENDER_PASS),
+        );
+        ibl.render(
+            device,
+            command_pool,
+            fence,
+            queue,
+            IblMap::Prefiltered,
+            self.get_ibl_pass(PREFILTER_PIPELINE, IBL_RENDER_PASS),
+        );
+
+        if let Some(cache_path) = cache_path {
+            let data = ibl.read_back(device, allocator_handle, command_pool, fence, queue);
+
+            if let Err(error) = write_cache(&cache_path, &data) {
+                warn!(
+                    "ResourceManager::update_ibl - Failed to cache environment maps at {:?}: {}",
+                    cache_path, error
+                );
+            }
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Uploads `environment` and binds it to the global descriptor set for the skybox and reflections, replacing and
+    /// destroying the previous one, then precomputes its image based lighting maps. `None` goes back to the default
+    /// environment. The device must be idle.
+    pub fn set_environment(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+        environment: Option<Texture>,
+    ) {
+        let environment = environment.map(|texture| {
+            assert_eq!(
+                texture.dimension,
+                TextureDimension::Cube,
+                "ResourceManager::set_environment - Environment {} is not a cubemap!",
+                texture.name
+            );
+
+            let cache_path = self.ibl_cache_dir.as_ref().map(|cache_dir| {
+                cache_dir.join(format!(
+                    "{:016x}.ibl",
+                    environment_cache_key(&texture.load_raw())
+                ))
+            });
+
+            let texture = VkTexture::new(
+                texture,
+                device,
+                command_pool,
+                fence,
+                queue,
+                allocator_handle,
+            );
+
+            self.get_ibl()
+                .write_environment(device, texture.image_view());
+            self.update_ibl(
+                device,
+                allocator_handle,
+                command_pool,
+                fence,
+                queue,
+                cache_path,
+            );
+
+            texture
+        });
+
+        if let Some(previous_environment) = self.environment.take() {
+            previous_environment.destroy(device, &allocator_handle.allocator);
+        }
+
+        let image_view = match &environment {
+            Some(texture) => texture.image_view(),
+            None => self
+                .get_texture(DEFAULT_ENVIRONMENT_TEXTURE)
+                .expect(
+                    "ResourceManager::set_environment - Default textures have not been created!",
+                )
+                .image_view(),
+        };
+        self.write_environment_descriptor_set(device, image_view);
+
+        self.environment = environment;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn has_environment(&self) -> bool {
+        self.environment.is_some()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn write_environment_descriptor_set(&self, device: &Device, image_view: vk::ImageView) {
+        let environment_info = [vk::DescriptorImageInfo::builder()
+            .sampler(self.environment_sampler)
+            .image_view(image_view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let environment_write_set = [vk::WriteDescriptorSet::builder()
+            .dst_binding(ENVIRONMENT_BINDING)
+            .dst_set(self.scene.descriptor_set)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&environment_info)
+            .build()];
+
+        unsafe { device.update_descriptor_sets(&environment_write_set, &[]) };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_gbuffer(&self) -> &VkGBuffer {
+        self.gbuffer
+            .as_ref()
+            .expect("ResourceManager::get_gbuffer - G-buffer resources have not been created!")
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_selection(&self) -> &VkSelection {
+        self.selection
+            .as_ref()
+            .expect("ResourceManager::get_selection - Selection resources have not been created!")
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Full-screen triangle generated from the vertex index, sized per pass with dynamic viewport and scissor.
+    fn create_fullscreen_pipeline(
+        &mut self,
+        device: &Device,
+        id: &str,
+        fragment_shader_path: &Path,
+        pipeline_layout: vk::PipelineLayout,
+        render_pass: &VkRenderPass,
+    ) -> Rc<VkPipeline> {
+        let vertex_shader_path = Path::new(FULLSCREEN_VERTEX_SHADER);
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, vertex_shader_path),
+        }
+        .get();
+
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
+
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
+
+        let pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .depth_stencil_state(false, false, vk::CompareOp::ALWAYS)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .color_blend_attachment_state()
+            .pipeline_layout(pipeline_layout)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
+
+        self.create_pipeline(device, id, &pipeline_builder, render_pass)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_post_process(&self) -> &VkPostProcess {
+        self.post_process.as_ref().expect(
+            "ResourceManager::get_post_process - Post-process resources have not been created!",
+        )
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn create_pipeline_layout(
+        &mut self,
+        device: &Device,
+        id: &str,
+        push_constant_ranges: Option<&[vk::PushConstantRange]>,
+        descriptor_set_layouts: Option<&[vk::DescriptorSetLayout]>,
+    ) -> Rc<VkPipelineLayout> {
+        let pipeline_layout = Rc::new(VkPipelineLayout::new(
+            device,
+            push_constant_ranges,
+            descriptor_set_layouts,
+        ));
+        self.pipeline_layouts
+            .insert(id.to_owned(), pipeline_layout.clone());
+
+        pipeline_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_pipeline_layout(&self, id: &str) -> Rc<VkPipelineLayout> {
+        self.pipeline_layouts.get(id).unwrap().clone()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Depth tested and written with the compare op of the configured depth mode.
+    pub fn get_pipeline_builder(&self) -> VkPipelineBuilder {
+        VkPipeline::builder(self.depth_mode.is_reversed())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Compare op passing fragments at least as close as the stored depth.
+    pub fn depth_compare_op(&self) -> vk::CompareOp {
+        VkPipeline::depth_compare_op(self.depth_mode.is_reversed())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates a pipeline, replacing and destroying any previous one with the same id. The device must not be using the
+    /// replaced pipeline anymore.
+    pub fn create_pipeline(
+        &mut self,
+        device: &Device,
+        id: &str,
+        builder: &VkPipelineBuilder,
+        render_pass: &VkRenderPass,
+    ) -> Rc<VkPipeline> {
+        let pipeline = Rc::new(
+            builder
+                .build(device, render_pass)
+                .expect("ResourceManager::create_pipeline - Failed to create pipeline!"),
+        );
+
+        if let Some(previous_pipeline) = self.pipelines.insert(id.to_owned(), pipeline.clone()) {
+            previous_pipeline.destroy(device);
+        }
+
+        pipeline
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_pipeline(&self, id: &str) -> Rc<VkPipeline> {
+        self.pipelines.get(id).unwrap().clone()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates the pipelines, textures descriptor set and factors buffer of `material`. `samples` must match the
+    /// attachments of the passes the material is drawn in. Must run after `create_forward_picking_render_pass`.
+    pub fn create_material(
+        &mut self,
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
         render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
         material: &Material,
     ) -> VkMaterial {
         let Material {
             name,
             vertex_shader_path,
             fragment_shader_path,
+            factors,
+            blend_mode,
+            ..
         } = material;
-        let vert_id = vertex_shader_path.to_str().unwrap();
-        let vert = match self.get_shader(vert_id) {
-            Some(shader) => shader,
-            None => self.create_shader(device, vert_id, vertex_shader_path),
-        }
-        .get();
-
-        let frag_id = fragment_shader_path.to_str().unwrap();
-        let frag = match self.get_shader(frag_id) {
-            Some(shader) => shader,
-            None => self.create_shader(device, frag_id, fragment_shader_path),
-        }
-        .get();
 
         // let push_constant_ranges = [MeshPushConstants::get_range()];
         let descriptor_set_layouts = [
@@ -655,13 +2622,259 @@ impl ResourceManager {
             )
             .get();
 
+        self.material_shader_paths.insert(
+            name.clone(),
+            (vertex_shader_path.clone(), fragment_shader_path.clone()),
+        );
+        self.material_blend_modes.insert(name.clone(), *blend_mode);
+
+        let pipeline = self.create_material_pipeline(
+            device,
+            name,
+            pipeline_layout,
+            render_pass,
+            samples,
+            VkMaterialPass::Forward,
+            false,
+        );
+        let picking_render_pass = self.get_forward_picking_render_pass();
+        let picking_pipeline = self.create_material_pipeline(
+            device,
+            name,
+            pipeline_layout,
+            &picking_render_pass,
+            samples,
+            VkMaterialPass::Forward,
+            true,
+        );
+
+        // Transparent materials skip the G-buffer, the deferred path shades them after lighting instead
+        let (gbuffer_pipelines, transparent_pipelines) = if *blend_mode == BlendMode::Transparent {
+            let mut transparent_pipeline = |render_pass_id: &str, picking: bool| {
+                let render_pass = self.render_passes.get(render_pass_id).cloned().expect(
+                    "ResourceManager::create_material - Deferred transparent render pass has not been created!",
+                );
+
+                self.create_material_pipeline(
+                    device,
+                    name,
+                    pipeline_layout,
+                    &render_pass,
+                    vk::SampleCountFlags::TYPE_1,
+                    VkMaterialPass::Transparent,
+                    picking,
+                )
+            };
+
+            (
+                (vk::Pipeline::null(), vk::Pipeline::null()),
+                (
+                    transparent_pipeline(DEFERRED_TRANSPARENT_RENDER_PASS, false),
+                    transparent_pipeline(DEFERRED_TRANSPARENT_PICKING_RENDER_PASS, true),
+                ),
+            )
+        } else {
+            (
+                (
+                    self.create_material_gbuffer_pipeline(device, name, pipeline_layout, false),
+                    self.create_material_gbuffer_pipeline(device, name, pipeline_layout, true),
+                ),
+                (vk::Pipeline::null(), vk::Pipeline::null()),
+            )
+        };
+        let (gbuffer_pipeline, gbuffer_picking_pipeline) = gbuffer_pipelines;
+        let (transparent_pipeline, transparent_picking_pipeline) = transparent_pipelines;
+
+        let texture_set_layouts = [self.texture_descriptor_set_layout];
+        let texture_set_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(self.descriptor_pool)
+            .set_layouts(&texture_set_layouts);
+
+        let descriptor_set = unsafe {
+            device
+                .allocate_descriptor_sets(&texture_set_info)
+                .expect("Failed to allocate texture descriptor set!")[0]
+        };
+
+        let material_ubo = MaterialUBO::new(factors, *blend_mode);
+        let material_buffer = allocator_handle.create_buffer(
+            &VkBuffer::create_info(
+                MATERIAL_UBO_SIZE as vk::DeviceSize,
+                vk::BufferUsageFlags::UNIFORM_BUFFER,
+            ),
+            &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None),
+        );
+        allocator_handle.write_buffer(
+            &material_buffer,
+            &material_ubo as *const MaterialUBO,
+            1,
+            None,
+        );
+
+        let image_infos = TextureSlot::ALL
+            .iter()
+            .map(|slot| {
+                [vk::DescriptorImageInfo::builder()
+                    .sampler(self.texture_sampler)
+                    .image_view(self.get_material_texture(material, *slot).image_view())
+                    .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .build()]
+            })
+            .collect::<Vec<[vk::DescriptorImageInfo; 1]>>();
+
+        let material_buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(material_buffer.get())
+            .offset(0)
+            .range(MATERIAL_UBO_SIZE as vk::DeviceSize)
+            .build()];
+
+        let mut texture_descriptor_set_write = TextureSlot::ALL
+            .iter()
+            .zip(image_infos.iter())
+            .map(|(slot, image_info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(descriptor_set)
+                    .dst_binding(slot.binding())
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .image_info(image_info)
+                    .build()
+            })
+            .collect::<Vec<vk::WriteDescriptorSet>>();
+
+        texture_descriptor_set_write.push(
+            vk::WriteDescriptorSet::builder()
+                .dst_set(descriptor_set)
+                .dst_binding(MATERIAL_UBO_BINDING)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                .buffer_info(&material_buffer_info)
+                .build(),
+        );
+
+        unsafe { device.update_descriptor_sets(&texture_descriptor_set_write, &[]) };
+
+        if let Some(previous_buffer) = self.material_buffers.insert(name.clone(), material_buffer) {
+            previous_buffer.free(&allocator_handle.allocator);
+        }
+
+        let material = VkMaterial::new(
+            pipeline,
+            gbuffer_pipeline,
+            transparent_pipeline,
+            pipeline_layout,
+            descriptor_set,
+            *blend_mode,
+        )
+        .with_picking_pipelines(
+            picking_pipeline,
+            gbuffer_picking_pipeline,
+            transparent_picking_pipeline,
+        );
+
+        // Recreated materials keep their ids, new ones get the next free one
+        let next_pipeline_sort_id = self.material_pipeline_sort_ids.len() as u32;
+        let pipeline_sort_id = *self
+            .material_pipeline_sort_ids
+            .entry((
+                vertex_shader_path.clone(),
+                fragment_shader_path.clone(),
+                *blend_mode,
+            ))
+            .or_insert(next_pipeline_sort_id);
+        let sort_id = self
+            .materials
+            .get(name)
+            .map_or(self.materials.len() as u32, |material| material.sort_id);
+
+        let material = material.with_sort_ids(pipeline_sort_id, sort_id);
+        self.materials.insert(name.clone(), material.clone());
+
+        material
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Recreates the forward pipelines of every material, e.g. after the sample count of the main pass changed. The
+    /// device must be idle, and the forward picking render pass recreated for the new sample count.
+    pub fn rebuild_material_pipelines(
+        &mut self,
+        device: &Device,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+    ) {
+        let names = self.materials.keys().cloned().collect::<Vec<String>>();
+        let picking_render_pass = self.get_forward_picking_render_pass();
+
+        for name in names {
+            let pipeline_layout = self.materials[&name].pipeline_layout;
+            let pipeline = self.create_material_pipeline(
+                device,
+                &name,
+                pipeline_layout,
+                render_pass,
+                samples,
+                VkMaterialPass::Forward,
+                false,
+            );
+            let picking_pipeline = self.create_material_pipeline(
+                device,
+                &name,
+                pipeline_layout,
+                &picking_render_pass,
+                samples,
+                VkMaterialPass::Forward,
+                true,
+            );
+
+            if let Some(material) = self.materials.get_mut(&name) {
+                material.pipeline = pipeline;
+                material.picking_pipeline = picking_pipeline;
+            }
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Shading pipeline of the material for `pass`, either `Forward` or `Transparent`. Transparent materials blend
+    /// over the scene and only test depth. Picking pipelines also write the object id and depth targets, the material's
+    /// fragment shader is expected to output them at locations 1 and 2.
+    #[allow(clippy::too_many_arguments)]
+    fn create_material_pipeline(
+        &mut self,
+        device: &Device,
+        name: &str,
+        pipeline_layout: vk::PipelineLayout,
+        render_pass: &VkRenderPass,
+        samples: vk::SampleCountFlags,
+        pass: VkMaterialPass,
+        picking: bool,
+    ) -> vk::Pipeline {
+        let (vertex_shader_path, fragment_shader_path) = self
+            .material_shader_paths
+            .get(name)
+            .cloned()
+            .expect("ResourceManager::create_material_pipeline - Unknown material!");
+        let blend_mode = self.material_blend_modes[name];
+
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, &vertex_shader_path),
+        }
+        .get();
+
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, &fragment_shader_path),
+        }
+        .get();
+
         let vertex_description = VertexInputDescription::get();
         let shader_entry_point = VkShader::get_default_shader_entry_point();
 
         let surface_extent = self.get_swapchain().unwrap().surface_extent();
         let vk::Extent2D { width, height } = surface_extent;
 
-        let pipeline_builder = Self::get_pipeline_builder()
+        let mut pipeline_builder = self
+            .get_pipeline_builder()
             .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
             .viewport(
                 vk::Viewport::builder()
@@ -680,65 +2893,109 @@ impl ResourceManager {
                     .build(),
             )
             .rasterization_state(vk::PolygonMode::FILL)
-            .multisampling_state()
-            .color_blend_attachment_state()
+            .multisampling_state(samples)
             .pipeline_layout(pipeline_layout)
             .vertex_input_state(&vertex_description)
             .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
             .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
 
-        let pipeline = self
-            .create_pipeline(
-                device,
-                &format!("{}_pipeline", name),
-                &pipeline_builder,
-                &render_pass,
-            )
-            .get();
+        pipeline_builder = if blend_mode == BlendMode::Transparent {
+            pipeline_builder
+                .depth_stencil_state(true, false, self.depth_compare_op())
+                .alpha_blend_attachment_state()
+        } else {
+            pipeline_builder.color_blend_attachment_state()
+        };
 
-        let texture_set_layouts = [self.texture_descriptor_set_layout];
-        let texture_set_info = vk::DescriptorSetAllocateInfo::builder()
-            .descriptor_pool(self.descriptor_pool)
-            .set_layouts(&texture_set_layouts);
+        // Integer targets can't be blended, the object id and depth are written as is
+        if picking {
+            pipeline_builder = pipeline_builder
+                .color_blend_attachment_state()
+                .color_blend_attachment_state();
+        }
 
-        let descriptor_set = unsafe {
-            device
-                .allocate_descriptor_sets(&texture_set_info)
-                .expect("Failed to allocate texture descriptor set!")[0]
+        let id = match (pass, picking) {
+            (VkMaterialPass::Transparent, false) => format!("{}_transparent_pipeline", name),
+            (VkMaterialPass::Transparent, true) => format!("{}_transparent_picking_pipeline", name),
+            (_, false) => format!("{}_pipeline", name),
+            (_, true) => format!("{}_picking_pipeline", name),
         };
 
-        let sampler_info = vk::SamplerCreateInfo::builder()
-            .mag_filter(vk::Filter::NEAREST)
-            .min_filter(vk::Filter::NEAREST)
-            .address_mode_u(vk::SamplerAddressMode::REPEAT)
-            .address_mode_v(vk::SamplerAddressMode::REPEAT)
-            .address_mode_w(vk::SamplerAddressMode::REPEAT);
+        self.create_pipeline(device, &id, &pipeline_builder, render_pass)
+            .get()
+    }
+    //------------------------------------------------------------------------------------------------------------------
 
-        let sampler = unsafe {
-            device
-                .create_sampler(&sampler_info, None)
-                .expect("Failed to create texture sampler!")
+    /// G-buffer pipelines share the material's vertex shader and descriptor sets, but write the surface parameters
+    /// with a common fragment shader instead of shading. The G-buffer is never multisampled. Picking pipelines also
+    /// write the object id and depth targets.
+    fn create_material_gbuffer_pipeline(
+        &mut self,
+        device: &Device,
+        name: &str,
+        pipeline_layout: vk::PipelineLayout,
+        picking: bool,
+    ) -> vk::Pipeline {
+        let (vertex_shader_path, _) = self
+            .material_shader_paths
+            .get(name)
+            .cloned()
+            .expect("ResourceManager::create_material_gbuffer_pipeline - Unknown material!");
+
+        let render_pass_id = if picking {
+            GBUFFER_PICKING_RENDER_PASS
+        } else {
+            GBUFFER_RENDER_PASS
         };
+        let render_pass = self.render_passes.get(render_pass_id).cloned().expect(
+            "ResourceManager::create_material_gbuffer_pipeline - G-buffer render pass has not been created!",
+        );
 
-        let image_info = [vk::DescriptorImageInfo::builder()
-            .sampler(sampler)
-            .image_view(self.textures.get("empire_diffuse").unwrap().image_view())
-            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
-            .build()];
+        let vert_id = vertex_shader_path.to_str().unwrap();
+        let vert = match self.get_shader(vert_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, vert_id, &vertex_shader_path),
+        }
+        .get();
 
-        let texture_descriptor_set_write = [vk::WriteDescriptorSet::builder()
-            .dst_set(descriptor_set)
-            .dst_binding(0)
-            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-            .image_info(&image_info)
-            .build()];
+        let fragment_shader_path = Path::new(GBUFFER_FRAGMENT_SHADER);
+        let frag_id = fragment_shader_path.to_str().unwrap();
+        let frag = match self.get_shader(frag_id) {
+            Some(shader) => shader,
+            None => self.create_shader(device, frag_id, fragment_shader_path),
+        }
+        .get();
 
-        unsafe { device.update_descriptor_sets(&texture_descriptor_set_write, &[]) };
+        let vertex_description = VertexInputDescription::get();
+        let shader_entry_point = VkShader::get_default_shader_entry_point();
 
-        let material = VkMaterial::new(pipeline, pipeline_layout, descriptor_set);
-        self.materials.insert(name.clone(), material.clone());
+        let mut pipeline_builder = self
+            .get_pipeline_builder()
+            .input_assembly_state(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .dynamic_state(vk::DynamicState::VIEWPORT)
+            .dynamic_state(vk::DynamicState::SCISSOR)
+            .rasterization_state(vk::PolygonMode::FILL)
+            .multisampling_state(vk::SampleCountFlags::TYPE_1)
+            .pipeline_layout(pipeline_layout)
+            .vertex_input_state(&vertex_description)
+            .shader_stage(vert, vk::ShaderStageFlags::VERTEX, &shader_entry_point)
+            .shader_stage(frag, vk::ShaderStageFlags::FRAGMENT, &shader_entry_point);
 
-        material
+        for _ in GBUFFER_COLOR_FORMATS.iter() {
+            pipeline_builder = pipeline_builder.color_blend_attachment_state();
+        }
+
+        let id = if picking {
+            pipeline_builder = pipeline_builder
+                .color_blend_attachment_state()
+                .color_blend_attachment_state();
+            format!("{}_gbuffer_picking_pipeline", name)
+        } else {
+            format!("{}_gbuffer_pipeline", name)
+        };
+
+        self.create_pipeline(device, &id, &pipeline_builder, &render_pass)
+            .get()
     }
     //------------------------------------------------------------------------------------------------------------------
 
@@ -747,9 +3004,37 @@ impl ResourceManager {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    fn get_material_texture(&self, material: &Material, slot: TextureSlot) -> Rc<VkTexture> {
+        let default_texture = match slot {
+            TextureSlot::Normal => DEFAULT_NORMAL_TEXTURE,
+            _ => DEFAULT_WHITE_TEXTURE,
+        };
+
+        let texture = material.texture(slot).and_then(|texture_name| {
+            let texture = self.textures.get(texture_name);
+            if texture.is_none() {
+                warn!(
+                    "ResourceManager::create_material - Material {} references missing texture {}, using default!",
+                    material.name, texture_name
+                );
+            }
+            texture
+        });
+
+        texture
+            .or_else(|| self.textures.get(default_texture))
+            .expect("ResourceManager::create_material - Default textures must be created before materials!")
+            .clone()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn create_mesh(&mut self, mesh: Mesh, allocator_handle: &AllocatorHandle) -> Rc<VkMesh> {
         let mesh_name = mesh.name.clone();
-        let vk_mesh = Rc::new(VkMesh::new(mesh, allocator_handle));
+        let sort_id = self
+            .meshes
+            .get(&mesh_name)
+            .map_or(self.meshes.len() as u32, |mesh| mesh.sort_id());
+        let vk_mesh = Rc::new(VkMesh::new(mesh, allocator_handle, sort_id));
         self.meshes.insert(mesh_name, vk_mesh.clone());
 
         vk_mesh
@@ -761,6 +3046,11 @@ impl ResourceManager {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn get_texture(&self, id: &str) -> Option<Rc<VkTexture>> {
+        self.textures.get(id).cloned()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn create_texture(
         &mut self,
         texture: Texture,
@@ -785,6 +3075,49 @@ impl ResourceManager {
 
         vk_texture
     }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn create_default_textures(
+        &mut self,
+        device: &Device,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+        allocator_handle: &AllocatorHandle,
+    ) {
+        let default_textures = [
+            Texture::solid_color(
+                &DEFAULT_WHITE_TEXTURE.into(),
+                [255, 255, 255, 255],
+                Some(MapType::Occlusion),
+            ),
+            Texture::solid_color(
+                &DEFAULT_NORMAL_TEXTURE.into(),
+                [128, 128, 255, 255],
+                Some(MapType::Normal),
+            ),
+            VkPostProcess::identity_color_grading_lut(),
+            Texture::solid_color_cubemap(&DEFAULT_ENVIRONMENT_TEXTURE.into(), Vec3::zero()),
+        ];
+
+        for texture in default_textures {
+            self.create_texture(
+                texture,
+                device,
+                command_pool,
+                fence,
+                queue,
+                allocator_handle,
+            );
+        }
+
+        // Keeps the environment binding valid until an environment is set
+        let default_environment = self.get_texture(DEFAULT_ENVIRONMENT_TEXTURE).expect(
+            "ResourceManager::create_default_textures - Failed to create default environment!",
+        );
+        self.write_environment_descriptor_set(device, default_environment.image_view());
+    }
+    //------------------------------------------------------------------------------------------------------------------
 
     // NB! Not a trait impl because we need custom cleanup logic (i.e. allocator and Vulkan object destructors).
     pub unsafe fn destroy(&mut self, device: &Device, allocator: &vk_mem::Allocator) {
@@ -792,10 +3125,49 @@ impl ResourceManager {
             texture.destroy(device, allocator);
         }
 
+        if let Some(environment) = &self.environment {
+            environment.destroy(device, allocator);
+        }
+
         for mesh in self.meshes.values() {
             mesh.free(allocator);
         }
 
+        if let Some(shadow_maps) = &self.shadow_maps {
+            shadow_maps.destroy(device, allocator);
+        }
+
+        if let Some(post_process) = &self.post_process {
+            post_process.destroy(device);
+        }
+
+        if let Some(gbuffer) = &self.gbuffer {
+            gbuffer.destroy(device);
+        }
+
+        if let Some(selection) = &self.selection {
+            selection.destroy(device);
+        }
+
+        if let Some(sprites) = &self.sprites {
+            sprites.destroy(device);
+        }
+
+        if let Some(ibl) = &self.ibl {
+            ibl.destroy(device, allocator);
+        }
+
+        if let Some(ssao) = &self.ssao {
+            ssao.destroy(device, allocator);
+        }
+
+        for material_buffer in self.material_buffers.values() {
+            material_buffer.free(allocator);
+        }
+
+        device.destroy_sampler(self.texture_sampler, None);
+        device.destroy_sampler(self.environment_sampler, None);
+
         for shader in self.shaders.values() {
             shader.destroy(device);
         }
@@ -808,6 +3180,7 @@ impl ResourceManager {
 
         device.destroy_descriptor_set_layout(self.global_descriptor_set_layout, None);
         device.destroy_descriptor_set_layout(self.entity_descriptor_set_layout, None);
+        device.destroy_descriptor_set_layout(self.texture_descriptor_set_layout, None);
         device.destroy_descriptor_pool(self.descriptor_pool, None);
 
         for pipeline in self.pipelines.values() {
@@ -818,14 +3191,6 @@ impl ResourceManager {
             pipeline_layout.destroy(device);
         }
 
-        for depth_buffer in self.depth_buffers.iter() {
-            depth_buffer.destroy(device, allocator);
-        }
-
-        for framebuffer in self.framebuffers.iter() {
-            framebuffer.destroy(device);
-        }
-
         for render_pass in self.render_passes.values() {
             render_pass.destroy(device);
         }
@@ -1,3 +1,6 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
 use ash::vk;
 use ultraviolet::Vec4;
 use vk_mem::Allocator;
@@ -5,22 +8,40 @@ use vk_mem::Allocator;
 
 use crate::renderer::backend::vk::{
     handles::{AllocatorFree, AllocatorHandle, PhysicalDeviceHandle},
-    resources::VkBuffer,
+    resources::{VkBuffer, PREFILTERED_MIP_COUNT, SHADOW_UBO_SIZE},
     VkRendererConfig,
 };
-use crate::renderer::entities::CAMERA_UBO_SIZE;
+use crate::renderer::entities::{Light, LightType, CAMERA_UBO_SIZE};
 //----------------------------------------------------------------------------------------------------------------------
 
 // TODO pack correctly, instead of booking always full vec4/mat4
 #[repr(C)]
 pub struct SceneUBO {
     pub ambient_color: Vec4,
+    /// x - ambient occlusion map is valid this frame
+    pub ambient_occlusion: Vec4,
+    /// x - environment map is bound, y - environment intensity, z - highest prefiltered environment mip
+    pub environment: Vec4,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
 impl SceneUBO {
-    pub fn new(ambient_color: Vec4) -> Self {
-        Self { ambient_color }
+    pub fn new(
+        ambient_color: Vec4,
+        ambient_occlusion: bool,
+        environment: bool,
+        environment_intensity: f32,
+    ) -> Self {
+        Self {
+            ambient_color,
+            ambient_occlusion: Vec4::new(if ambient_occlusion { 1.0 } else { 0.0 }, 0.0, 0.0, 0.0),
+            environment: Vec4::new(
+                if environment { 1.0 } else { 0.0 },
+                environment_intensity,
+                (PREFILTERED_MIP_COUNT - 1) as f32,
+                0.0,
+            ),
+        }
     }
 }
 //----------------------------------------------------------------------------------------------------------------------
@@ -28,9 +49,76 @@ impl SceneUBO {
 pub const SCENE_UBO_SIZE: usize = std::mem::size_of::<SceneUBO>();
 //----------------------------------------------------------------------------------------------------------------------
 
+// NB! Must match the LightSSBO struct declared in the lit shaders.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LightSSBO {
+    /// xyz - world position, w - range
+    pub position_range: Vec4,
+    /// xyz - world direction, w - light type
+    pub direction_type: Vec4,
+    /// rgb - color, a - intensity
+    pub color_intensity: Vec4,
+    /// x - cos(inner cone angle), y - cos(outer cone angle), z - first shadow map layer or -1
+    pub cone: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl LightSSBO {
+    pub fn new(light: &Light, shadow_layer: Option<u32>) -> Self {
+        let light_type = match light.light_type {
+            LightType::Directional => 0.0,
+            LightType::Point => 1.0,
+            LightType::Spot => 2.0,
+        };
+
+        Self {
+            position_range: Vec4::new(
+                light.position.x,
+                light.position.y,
+                light.position.z,
+                light.range,
+            ),
+            direction_type: Vec4::new(
+                light.direction.x,
+                light.direction.y,
+                light.direction.z,
+                light_type,
+            ),
+            color_intensity: Vec4::new(
+                light.color.x,
+                light.color.y,
+                light.color.z,
+                light.intensity,
+            ),
+            cone: Vec4::new(
+                light.inner_cone_angle_rad.cos(),
+                light.outer_cone_angle_rad.cos(),
+                shadow_layer.map_or(-1.0, |layer| layer as f32),
+                0.0,
+            ),
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const LIGHT_SSBO_SIZE: usize = size_of::<LightSSBO>();
+pub const MAX_LIGHTS: usize = 4096;
+/// Light count and directional light count, padded to a full uvec4 to keep the light array std430-aligned.
+pub const LIGHT_BUFFER_HEADER_SIZE: usize = size_of::<[u32; 4]>();
+pub const LIGHT_BUFFER_SIZE: usize = LIGHT_BUFFER_HEADER_SIZE + LIGHT_SSBO_SIZE * MAX_LIGHTS;
+//----------------------------------------------------------------------------------------------------------------------
+
 pub struct VkScene {
     pub descriptor_set: vk::DescriptorSet,
+    /// Camera UBO followed by the scene UBO for every frame in flight.
     pub buffer: VkBuffer,
+    /// Size of a frame's camera and scene UBOs in `buffer`.
+    pub buffer_stride: vk::DeviceSize,
+    /// Offset of the scene UBO from the camera UBO of the same frame, aligned for binding.
+    pub scene_ubo_offset: vk::DeviceSize,
+    pub light_buffer: VkBuffer,
+    pub shadow_buffer: VkBuffer,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -40,13 +128,33 @@ impl VkScene {
         physical_device_handle: &PhysicalDeviceHandle,
         config: &VkRendererConfig,
     ) -> Self {
+        let scene_ubo_offset = VkBuffer::pad_ubo_size(physical_device_handle, CAMERA_UBO_SIZE);
+        let buffer_stride = VkBuffer::pad_ubo_size(
+            physical_device_handle,
+            scene_ubo_offset as usize + SCENE_UBO_SIZE,
+        );
+
         let buffer = allocator.create_buffer(
+            &VkBuffer::create_info(
+                config.buffering as vk::DeviceSize * buffer_stride,
+                vk::BufferUsageFlags::UNIFORM_BUFFER,
+            ),
+            &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None),
+        );
+
+        let light_buffer = allocator.create_buffer(
+            &VkBuffer::create_info(
+                config.buffering as vk::DeviceSize
+                    * VkBuffer::pad_ssbo_size(physical_device_handle, LIGHT_BUFFER_SIZE),
+                vk::BufferUsageFlags::STORAGE_BUFFER,
+            ),
+            &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None),
+        );
+
+        let shadow_buffer = allocator.create_buffer(
             &VkBuffer::create_info(
                 config.buffering as vk::DeviceSize
-                    * VkBuffer::pad_ubo_size(
-                        physical_device_handle,
-                        CAMERA_UBO_SIZE + SCENE_UBO_SIZE,
-                    ),
+                    * VkBuffer::pad_ubo_size(physical_device_handle, SHADOW_UBO_SIZE),
                 vk::BufferUsageFlags::UNIFORM_BUFFER,
             ),
             &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::CpuToGpu, None, None),
@@ -54,6 +162,10 @@ impl VkScene {
 
         Self {
             buffer,
+            buffer_stride,
+            scene_ubo_offset,
+            light_buffer,
+            shadow_buffer,
             descriptor_set: Default::default(),
         }
     }
@@ -63,6 +175,8 @@ impl VkScene {
 impl AllocatorFree for VkScene {
     fn free(&self, allocator: &Allocator) {
         self.buffer.free(allocator);
+        self.light_buffer.free(allocator);
+        self.shadow_buffer.free(allocator);
     }
 }
 //----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,174 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::Vec4;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::backend::vk::DeviceDestroy;
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const SELECTION_STENCIL_PIPELINE_LAYOUT: &str = "selection_stencil_pipeline_layout";
+pub const SELECTION_STENCIL_PIPELINE: &str = "selection_stencil_pipeline";
+pub const SELECTION_OUTLINE_PIPELINE_LAYOUT: &str = "selection_outline_pipeline_layout";
+pub const SELECTION_OUTLINE_PIPELINE: &str = "selection_outline_pipeline";
+
+// NB! Must match the stencil values tested in selection_outline.frag.
+/// Stencil value written under selected renderables.
+pub const SELECTED_STENCIL: u32 = 1;
+/// Stencil value written under hovered renderables, selected ones drawn afterwards take precedence.
+pub const HOVERED_STENCIL: u32 = 2;
+
+const SELECTION_STENCIL_BINDING: u32 = 0;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the SelectionOutlineConstants block declared in selection_outline.frag.
+#[repr(C)]
+pub struct SelectionOutlinePushConstants {
+    pub selected_color: Vec4,
+    pub hovered_color: Vec4,
+    /// x - thickness in pixels
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SelectionOutlinePushConstants {
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<SelectionOutlinePushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Descriptor sets the outline pass reads the selection stencil through, one per frame in flight since the render
+/// graph image behind them can change between frames.
+pub struct VkSelection {
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_sets: Vec<vk::DescriptorSet>,
+    sampler: vk::Sampler,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkSelection {
+    pub(in crate::renderer::backend::vk::resources) fn new(
+        device: &Device,
+        buffering: u32,
+    ) -> Self {
+        let bindings = [vk::DescriptorSetLayoutBinding::builder()
+            .binding(SELECTION_STENCIL_BINDING)
+            .descriptor_count(1)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()];
+
+        let pool_sizes = [vk::DescriptorPoolSize::builder()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(buffering)
+            .build()];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(buffering)
+            .pool_sizes(&pool_sizes);
+
+        let descriptor_pool = unsafe {
+            device
+                .create_descriptor_pool(&pool_info, None)
+                .expect("VkSelection::new - Failed to create descriptor pool!")
+        };
+
+        let descriptor_set_layout = unsafe {
+            device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                    None,
+                )
+                .expect("VkSelection::new - Failed to create descriptor set layout!")
+        };
+
+        let set_layouts = vec![descriptor_set_layout; buffering as usize];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(descriptor_pool)
+            .set_layouts(&set_layouts);
+
+        let descriptor_sets = unsafe {
+            device
+                .allocate_descriptor_sets(&allocate_info)
+                .expect("VkSelection::new - Failed to allocate descriptor sets!")
+        };
+
+        // Stencil values are fetched as is, integer images can't be filtered anyway
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkSelection::new - Failed to create sampler!")
+        };
+
+        Self {
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_sets,
+            sampler,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.descriptor_set_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set(&self, frame_index: usize) -> vk::DescriptorSet {
+        self.descriptor_sets[frame_index]
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Points the set at the stencil aspect of this frame's selection image. The set must belong to the frame being
+    /// recorded, whose previous submission is known to have finished.
+    pub fn write_descriptor_set(
+        &self,
+        device: &Device,
+        descriptor_set: vk::DescriptorSet,
+        stencil_view: vk::ImageView,
+    ) {
+        let image_info = [vk::DescriptorImageInfo::builder()
+            .sampler(self.sampler)
+            .image_view(stencil_view)
+            .image_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let writes = [vk::WriteDescriptorSet::builder()
+            .dst_set(descriptor_set)
+            .dst_binding(SELECTION_STENCIL_BINDING)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&image_info)
+            .build()];
+
+        unsafe { device.update_descriptor_sets(&writes, &[]) };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceDestroy for VkSelection {
+    fn destroy(&self, device: &Device) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+            device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,444 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::{projection, Mat4, Vec3, Vec4};
+use vk_mem::Allocator;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::{
+    backend::vk::{
+        handles::{AllocatorFree, AllocatorHandle, InstanceHandle, PhysicalDeviceHandle},
+        resources::{CameraFrustum, VkImage},
+        utils, DeviceAllocatorDestroy, VkRendererConfig,
+    },
+    entities::{Light, LightType},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Layers in the shadow map array. Every directional caster takes one layer per cascade, every spot caster one layer.
+pub const MAX_SHADOW_MAPS: usize = 8;
+pub const MAX_SHADOW_CASCADES: usize = 4;
+pub const SHADOW_RENDER_PASS: &str = "shadow";
+
+/// Blend between logarithmic (1.0) and uniform (0.0) cascade splits.
+const CASCADE_SPLIT_LAMBDA: f32 = 0.75;
+/// Distance behind each cascade's bounding sphere still captured, so off-screen occluders cast into view.
+const SHADOW_CASTER_MARGIN: f32 = 50.0;
+const SPOT_SHADOW_Z_NEAR: f32 = 0.1;
+const SHADOW_NORMAL_OFFSET: f32 = 0.02;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the ShadowUBO block declared in the lit shaders.
+#[repr(C)]
+pub struct ShadowUBO {
+    pub view_projections: [Mat4; MAX_SHADOW_MAPS],
+    /// View depth at which each cascade ends.
+    pub cascade_splits: Vec4,
+    /// xyz - camera forward, w - cascade count
+    pub camera_forward: Vec4,
+    /// x - normal offset, y - texel size
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const SHADOW_UBO_SIZE: usize = size_of::<ShadowUBO>();
+//----------------------------------------------------------------------------------------------------------------------
+
+#[repr(C)]
+pub struct ShadowPushConstants {
+    light_view_projection: Mat4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const SHADOW_PUSH_CONSTANTS_SIZE: u32 = size_of::<ShadowPushConstants>() as u32;
+//----------------------------------------------------------------------------------------------------------------------
+
+impl ShadowPushConstants {
+    pub fn new(light_view_projection: Mat4) -> Self {
+        Self {
+            light_view_projection,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(SHADOW_PUSH_CONSTANTS_SIZE)
+            .stage_flags(vk::ShaderStageFlags::VERTEX)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Per-frame assignment of shadow map layers to the lights in the scene.
+pub struct ShadowLayout {
+    pub ubo: ShadowUBO,
+    /// First shadow map layer of every light, in scene order.
+    pub light_layers: Vec<Option<u32>>,
+    pub layer_count: usize,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl ShadowLayout {
+    pub fn new(frustum: &CameraFrustum, lights: &[Light], config: &VkRendererConfig) -> Self {
+        let cascade_count = config.shadow_cascade_count as usize;
+
+        let shadow_far = frustum.far.min(frustum.near + config.shadow_distance);
+        let cascade_splits = cascade_splits(frustum.near, shadow_far, cascade_count);
+
+        let mut view_projections = [Mat4::identity(); MAX_SHADOW_MAPS];
+        let mut layer_count = 0;
+
+        let light_layers = lights
+            .iter()
+            .map(|light| {
+                if !light.cast_shadows {
+                    return None;
+                }
+
+                let required_layers = match light.light_type {
+                    LightType::Directional => cascade_count,
+                    LightType::Spot => 1,
+                    LightType::Point => return None,
+                };
+
+                if layer_count + required_layers > MAX_SHADOW_MAPS {
+                    warn!(
+                        "ShadowLayout::new - Out of shadow map layers, light will not cast shadows!"
+                    );
+                    return None;
+                }
+
+                let first_layer = layer_count;
+                match light.light_type {
+                    LightType::Directional => {
+                        let mut cascade_near = frustum.near;
+                        for (cascade, cascade_far) in cascade_splits.iter().enumerate() {
+                            view_projections[first_layer + cascade] = directional_view_projection(
+                                frustum,
+                                light.direction,
+                                cascade_near,
+                                *cascade_far,
+                                config.shadow_map_size,
+                            );
+                            cascade_near = *cascade_far;
+                        }
+                    }
+                    _ => view_projections[first_layer] = spot_view_projection(light),
+                }
+
+                layer_count += required_layers;
+                Some(first_layer as u32)
+            })
+            .collect::<Vec<Option<u32>>>();
+
+        let mut splits = [f32::MAX; MAX_SHADOW_CASCADES];
+        splits[..cascade_count].copy_from_slice(&cascade_splits);
+
+        let ubo = ShadowUBO {
+            view_projections,
+            cascade_splits: Vec4::from(splits),
+            camera_forward: Vec4::new(
+                frustum.forward.x,
+                frustum.forward.y,
+                frustum.forward.z,
+                cascade_count as f32,
+            ),
+            params: Vec4::new(
+                SHADOW_NORMAL_OFFSET,
+                1.0 / config.shadow_map_size as f32,
+                0.0,
+                0.0,
+            ),
+        };
+
+        Self {
+            ubo,
+            light_layers,
+            layer_count,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn cascade_splits(near: f32, far: f32, cascade_count: usize) -> Vec<f32> {
+    (1..=cascade_count)
+        .map(|i| {
+            let p = i as f32 / cascade_count as f32;
+            let log = near * (far / near).powf(p);
+            let uniform = near + (far - near) * p;
+            CASCADE_SPLIT_LAMBDA * log + (1.0 - CASCADE_SPLIT_LAMBDA) * uniform
+        })
+        .collect()
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn light_up(direction: Vec3) -> Vec3 {
+    if direction.y.abs() > 0.99 {
+        Vec3::unit_z()
+    } else {
+        Vec3::unit_y()
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn directional_view_projection(
+    frustum: &CameraFrustum,
+    direction: Vec3,
+    cascade_near: f32,
+    cascade_far: f32,
+    shadow_map_size: u32,
+) -> Mat4 {
+    let corners = frustum.slice_corners(cascade_near, cascade_far);
+    let center = corners.iter().fold(Vec3::zero(), |acc, c| acc + *c) / 8.0;
+
+    // A bounding sphere keeps the projection size constant while the camera rotates, which avoids shimmering
+    let radius = corners
+        .iter()
+        .map(|corner| (*corner - center).mag())
+        .fold(0.0f32, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let eye = center - direction * (radius + SHADOW_CASTER_MARGIN);
+    let view = Mat4::look_at(eye, center, light_up(direction));
+    let mut projection = projection::orthographic_vk(
+        -radius,
+        radius,
+        -radius,
+        radius,
+        0.0,
+        2.0 * radius + SHADOW_CASTER_MARGIN,
+    );
+
+    // Snap the origin to whole texels so moving the camera doesn't make the shadow edges crawl
+    let half_size = shadow_map_size as f32 * 0.5;
+    let origin = projection * view * Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let offset_x = (origin.x * half_size).round() / half_size - origin.x;
+    let offset_y = (origin.y * half_size).round() / half_size - origin.y;
+    projection.cols[3].x += offset_x;
+    projection.cols[3].y += offset_y;
+
+    projection * view
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn spot_view_projection(light: &Light) -> Mat4 {
+    let view = Mat4::look_at(
+        light.position,
+        light.position + light.direction,
+        light_up(light.direction),
+    );
+    let fov = (light.outer_cone_angle_rad * 2.0).min(std::f32::consts::PI * 0.95);
+    let projection = projection::perspective_vk(fov, 1.0, SPOT_SHADOW_Z_NEAR, light.range);
+
+    projection * view
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub struct VkShadowMaps {
+    image: VkImage,
+    /// 2D array view over every layer, bound for sampling.
+    image_view: vk::ImageView,
+    /// Single layer views, rendered to by the shadow passes.
+    layer_views: Vec<vk::ImageView>,
+    sampler: vk::Sampler,
+    format: vk::Format,
+    size: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkShadowMaps {
+    pub fn find_supported_format(
+        instance_handle: &InstanceHandle,
+        physical_device_handle: &PhysicalDeviceHandle,
+    ) -> vk::Format {
+        VkImage::find_supported_format(
+            instance_handle,
+            physical_device_handle,
+            &[vk::Format::D32_SFLOAT, vk::Format::D16_UNORM],
+            vk::ImageTiling::OPTIMAL,
+            vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT
+                | vk::FormatFeatureFlags::SAMPLED_IMAGE
+                | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR,
+        )
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    #[allow(clippy::too_many_arguments)]
+    pub(in crate::renderer::backend::vk::resources) fn new(
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        format: vk::Format,
+        size: u32,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) -> Self {
+        let mut create_info = VkImage::image_create_info(
+            format,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::Extent3D::builder()
+                .width(size)
+                .height(size)
+                .depth(1)
+                .build(),
+            vk::ImageTiling::OPTIMAL,
+        );
+        create_info.array_layers = MAX_SHADOW_MAPS as u32;
+
+        let image = allocator_handle.create_image(
+            &create_info,
+            &AllocatorHandle::allocation_create_info(
+                vk_mem::MemoryUsage::GpuOnly,
+                None,
+                Some(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            ),
+        );
+
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::DEPTH)
+            .base_mip_level(0)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(MAX_SHADOW_MAPS as u32)
+            .build();
+
+        // Every layer starts in the read-only layout the render graph imports the maps with, even the ones never rendered to
+        let layout_transition = |cmd: &vk::CommandBuffer| {
+            let barrier = [vk::ImageMemoryBarrier::builder()
+                .old_layout(vk::ImageLayout::UNDEFINED)
+                .new_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+                .image(image.get())
+                .subresource_range(subresource_range)
+                .src_access_mask(vk::AccessFlags::empty())
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build()];
+
+            unsafe {
+                device.cmd_pipeline_barrier(
+                    *cmd,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &barrier,
+                )
+            }
+        };
+
+        utils::immediate_submit(device, command_pool, fence, queue, &layout_transition);
+
+        let mut view_info = VkImage::image_view_create_info(
+            format,
+            image.get(),
+            vk::ImageAspectFlags::DEPTH,
+            vk::ImageViewType::TYPE_2D_ARRAY,
+        );
+        view_info.subresource_range = subresource_range;
+
+        let image_view = unsafe {
+            device
+                .create_image_view(&view_info, None)
+                .expect("VkShadowMaps::new - Failed to create shadow map image view!")
+        };
+
+        let layer_views = (0..MAX_SHADOW_MAPS as u32)
+            .map(|layer| {
+                let mut view_info = VkImage::image_view_create_info(
+                    format,
+                    image.get(),
+                    vk::ImageAspectFlags::DEPTH,
+                    vk::ImageViewType::TYPE_2D,
+                );
+                view_info.subresource_range.base_array_layer = layer;
+
+                unsafe {
+                    device
+                        .create_image_view(&view_info, None)
+                        .expect("VkShadowMaps::new - Failed to create shadow map layer view!")
+                }
+            })
+            .collect::<Vec<vk::ImageView>>();
+
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_BORDER)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_BORDER)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_BORDER)
+            .border_color(vk::BorderColor::FLOAT_OPAQUE_WHITE)
+            .compare_enable(true)
+            .compare_op(vk::CompareOp::LESS_OR_EQUAL);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkShadowMaps::new - Failed to create shadow map sampler!")
+        };
+
+        Self {
+            image,
+            image_view,
+            layer_views,
+            sampler,
+            format,
+            size,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn image(&self) -> vk::Image {
+        self.image.get()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn image_view(&self) -> vk::ImageView {
+        self.image_view
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn sampler(&self) -> vk::Sampler {
+        self.sampler
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn layer_views(&self) -> &[vk::ImageView] {
+        &self.layer_views
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn format(&self) -> vk::Format {
+        self.format
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn extent(&self) -> vk::Extent2D {
+        vk::Extent2D::builder()
+            .width(self.size)
+            .height(self.size)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceAllocatorDestroy for VkShadowMaps {
+    fn destroy(&self, device: &Device, allocator: &Allocator) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+
+            for layer_view in self.layer_views.iter() {
+                device.destroy_image_view(*layer_view, None);
+            }
+            device.destroy_image_view(self.image_view, None);
+        }
+
+        self.image.free(allocator);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,48 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::vk;
+use ultraviolet::{Mat4, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::Camera;
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const SKYBOX_PIPELINE_LAYOUT: &str = "skybox_pipeline_layout";
+/// Drawn last in the forward pass, depth tested against the far plane.
+pub const SKYBOX_PIPELINE: &str = "skybox_pipeline";
+/// `SKYBOX_PIPELINE` for the forward pass while a pick is pending, clearing the object id and depth targets it covers.
+pub const SKYBOX_PICKING_PIPELINE: &str = "skybox_picking_pipeline";
+/// Drawn first in the deferred lighting pass, which only shades the pixels covered by geometry.
+pub const SKYBOX_BACKGROUND_PIPELINE: &str = "skybox_background_pipeline";
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the SkyboxConstants block declared in skybox.frag.
+#[repr(C)]
+pub struct SkyboxPushConstants {
+    pub inverse_view_projection: Mat4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SkyboxPushConstants {
+    /// Drops the camera translation, the environment is infinitely far away.
+    pub fn new(camera: &Camera) -> Self {
+        let mut view = camera.view();
+        view.cols[3] = Vec4::new(0.0, 0.0, 0.0, 1.0);
+
+        Self {
+            inverse_view_projection: (camera.projection() * view).inversed(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<SkyboxPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
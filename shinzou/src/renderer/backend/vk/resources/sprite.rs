@@ -0,0 +1,498 @@
+use std::{collections::HashMap, mem::size_of};
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use field_offset::offset_of;
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::{
+    backend::vk::{resources::VertexInputDescription, DeviceDestroy},
+    entities::{Billboard, Sprite},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const SPRITE_PIPELINE_LAYOUT: &str = "sprite_pipeline_layout";
+pub const SPRITE_PIPELINE: &str = "sprite_pipeline";
+pub const BILLBOARD_PIPELINE_LAYOUT: &str = "billboard_pipeline_layout";
+/// Billboards of the forward pass, created against its sample count.
+pub const BILLBOARD_PIPELINE: &str = "billboard_pipeline";
+/// Billboards of the deferred path, drawn over the lit scene like transparent materials.
+pub const DEFERRED_BILLBOARD_PIPELINE: &str = "deferred_billboard_pipeline";
+
+/// Vertices the per-frame sprite buffers start out with, they grow as needed.
+pub const DEFAULT_SPRITE_VERTEX_CAPACITY: u64 = 6 * 1024;
+/// Vertices the per-frame billboard buffers start out with, they grow as needed.
+pub const DEFAULT_BILLBOARD_VERTEX_CAPACITY: u64 = 6 * 256;
+/// Distinct textures sprites can be drawn with over the renderer's lifetime.
+pub const MAX_SPRITE_TEXTURES: u32 = 64;
+
+const SPRITE_TEXTURE_BINDING: u32 = 0;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the vertex inputs declared in sprite.vert.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SpriteVertex {
+    pub position: Vec2,
+    pub tex_coord: Vec2,
+    pub color: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const SPRITE_VERTEX_SIZE: u64 = size_of::<SpriteVertex>() as u64;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the vertex inputs declared in billboard.vert.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct BillboardVertex {
+    pub position: Vec3,
+    /// From `position`, in the plane facing the camera with y pointing down.
+    pub offset: Vec2,
+    pub tex_coord: Vec2,
+    pub color: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const BILLBOARD_VERTEX_SIZE: u64 = size_of::<BillboardVertex>() as u64;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Consecutive sprite or billboard vertices sharing a texture, drawn with a single call.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteBatch<'a> {
+    pub texture_name: Option<&'a str>,
+    pub distance_field: bool,
+    pub first_vertex: u32,
+    pub vertex_count: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Sprite or billboard batch whose texture descriptor set is ready, see `ResourceManager::prepare_sprite_draws`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpriteDraw {
+    pub descriptor_set: vk::DescriptorSet,
+    pub distance_field: bool,
+    pub first_vertex: u32,
+    pub vertex_count: u32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl<'a> SpriteBatch<'a> {
+    /// Appends `vertex_count` vertices to the last of `batches` when it is drawn the same way, or starts a new one.
+    fn push(
+        batches: &mut Vec<SpriteBatch<'a>>,
+        texture_name: Option<&'a str>,
+        distance_field: bool,
+        first_vertex: u32,
+        vertex_count: u32,
+    ) {
+        match batches.last_mut() {
+            Some(batch)
+                if batch.texture_name == texture_name && batch.distance_field == distance_field =>
+            {
+                batch.vertex_count += vertex_count
+            }
+            _ => batches.push(SpriteBatch {
+                texture_name,
+                distance_field,
+                first_vertex,
+                vertex_count,
+            }),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SpriteVertex {
+    pub fn input_description() -> VertexInputDescription {
+        let bindings = vec![vk::VertexInputBindingDescription::builder()
+            .binding(0)
+            .stride(SPRITE_VERTEX_SIZE as u32)
+            .input_rate(vk::VertexInputRate::VERTEX)
+            .build()];
+
+        let attributes = vec![
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(0)
+                .format(vk::Format::R32G32_SFLOAT)
+                .offset(offset_of!(SpriteVertex => position).get_byte_offset() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(1)
+                .format(vk::Format::R32G32_SFLOAT)
+                .offset(offset_of!(SpriteVertex => tex_coord).get_byte_offset() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(2)
+                .format(vk::Format::R32G32B32A32_SFLOAT)
+                .offset(offset_of!(SpriteVertex => color).get_byte_offset() as u32)
+                .build(),
+        ];
+
+        VertexInputDescription {
+            bindings,
+            attributes,
+            flags: vk::PipelineVertexInputStateCreateFlags::empty(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Two triangles per quad of every sprite, lowest layer first and otherwise in the given order, along with the
+    /// batches of consecutive vertices sharing a texture.
+    pub fn from_sprites<'a>(sprites: &[&'a Sprite]) -> (Vec<SpriteVertex>, Vec<SpriteBatch<'a>>) {
+        let mut sorted = sprites.to_vec();
+        // Stable, so sprites on the same layer keep their order
+        sorted.sort_by_key(|sprite| sprite.layer);
+
+        let mut vertices = Vec::with_capacity(sprites.len() * 6);
+        let mut batches: Vec<SpriteBatch> = Vec::new();
+
+        for sprite in sorted {
+            let first_vertex = vertices.len() as u32;
+
+            for (min, max, uv_rect) in sprite.quads() {
+                let corner = |local: Vec2, tex_coord: Vec2| SpriteVertex {
+                    position: sprite.to_screen(local),
+                    tex_coord,
+                    color: sprite.tint,
+                };
+
+                let top_left = corner(min, uv_rect.min);
+                let top_right = corner(
+                    Vec2::new(max.x, min.y),
+                    Vec2::new(uv_rect.max.x, uv_rect.min.y),
+                );
+                let bottom_right = corner(max, uv_rect.max);
+                let bottom_left = corner(
+                    Vec2::new(min.x, max.y),
+                    Vec2::new(uv_rect.min.x, uv_rect.max.y),
+                );
+
+                vertices.extend_from_slice(&[
+                    top_left,
+                    top_right,
+                    bottom_right,
+                    bottom_right,
+                    bottom_left,
+                    top_left,
+                ]);
+            }
+
+            SpriteBatch::push(
+                &mut batches,
+                sprite.texture_name.as_deref(),
+                sprite.distance_field,
+                first_vertex,
+                vertices.len() as u32 - first_vertex,
+            );
+        }
+
+        (vertices, batches)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl BillboardVertex {
+    pub fn input_description() -> VertexInputDescription {
+        let bindings = vec![vk::VertexInputBindingDescription::builder()
+            .binding(0)
+            .stride(BILLBOARD_VERTEX_SIZE as u32)
+            .input_rate(vk::VertexInputRate::VERTEX)
+            .build()];
+
+        let attributes = vec![
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(0)
+                .format(vk::Format::R32G32B32_SFLOAT)
+                .offset(offset_of!(BillboardVertex => position).get_byte_offset() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(1)
+                .format(vk::Format::R32G32_SFLOAT)
+                .offset(offset_of!(BillboardVertex => offset).get_byte_offset() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(2)
+                .format(vk::Format::R32G32_SFLOAT)
+                .offset(offset_of!(BillboardVertex => tex_coord).get_byte_offset() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(3)
+                .format(vk::Format::R32G32B32A32_SFLOAT)
+                .offset(offset_of!(BillboardVertex => color).get_byte_offset() as u32)
+                .build(),
+        ];
+
+        VertexInputDescription {
+            bindings,
+            attributes,
+            flags: vk::PipelineVertexInputStateCreateFlags::empty(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Two triangles per billboard in the given order, along with the batches of consecutive vertices sharing a texture.
+    pub fn from_billboards<'a>(
+        billboards: &[&'a Billboard],
+    ) -> (Vec<BillboardVertex>, Vec<SpriteBatch<'a>>) {
+        let mut vertices = Vec::with_capacity(billboards.len() * 6);
+        let mut batches: Vec<SpriteBatch> = Vec::new();
+
+        for &billboard in billboards.iter() {
+            let first_vertex = vertices.len() as u32;
+            let (min, max) = (billboard.offset, billboard.offset + billboard.size);
+            let uv_rect = billboard.uv_rect;
+
+            let corner = |offset: Vec2, tex_coord: Vec2| BillboardVertex {
+                position: billboard.position,
+                offset,
+                tex_coord,
+                color: billboard.tint,
+            };
+
+            let top_left = corner(min, uv_rect.min);
+            let top_right = corner(
+                Vec2::new(max.x, min.y),
+                Vec2::new(uv_rect.max.x, uv_rect.min.y),
+            );
+            let bottom_right = corner(max, uv_rect.max);
+            let bottom_left = corner(
+                Vec2::new(min.x, max.y),
+                Vec2::new(uv_rect.min.x, uv_rect.max.y),
+            );
+
+            vertices.extend_from_slice(&[
+                top_left,
+                top_right,
+                bottom_right,
+                bottom_right,
+                bottom_left,
+                top_left,
+            ]);
+
+            SpriteBatch::push(
+                &mut batches,
+                billboard.texture_name.as_deref(),
+                billboard.distance_field,
+                first_vertex,
+                6,
+            );
+        }
+
+        (vertices, batches)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the SpriteConstants block declared in sprite.vert and sprite.frag.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SpritePushConstants {
+    pub projection: Mat4,
+    /// x - 1 when the texture holds a signed distance field
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SpritePushConstants {
+    /// Orthographic projection of window pixels onto the screen, with the origin at the top left corner.
+    pub fn screen_space(extent: vk::Extent2D) -> Self {
+        let width = extent.width.max(1) as f32;
+        let height = extent.height.max(1) as f32;
+
+        // Vulkan's clip space already points y down
+        let projection = Mat4::new(
+            Vec4::new(2.0 / width, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 / height, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(-1.0, -1.0, 0.0, 1.0),
+        );
+
+        Self {
+            projection,
+            params: Vec4::zero(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<SpritePushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the BillboardConstants block declared in billboard.frag.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BillboardPushConstants {
+    /// x - 1 when the texture holds a signed distance field
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl BillboardPushConstants {
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<BillboardPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Sampler and per-texture descriptor sets sprites and billboards are drawn with. Sets are allocated the first time a texture is used
+/// and kept for the renderer's lifetime, textures never being replaced.
+pub struct VkSprites {
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_sets: HashMap<String, vk::DescriptorSet>,
+    sampler: vk::Sampler,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkSprites {
+    pub(in crate::renderer::backend::vk::resources) fn new(device: &Device) -> Self {
+        let bindings = [vk::DescriptorSetLayoutBinding::builder()
+            .binding(SPRITE_TEXTURE_BINDING)
+            .descriptor_count(1)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()];
+
+        let pool_sizes = [vk::DescriptorPoolSize::builder()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(MAX_SPRITE_TEXTURES)
+            .build()];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(MAX_SPRITE_TEXTURES)
+            .pool_sizes(&pool_sizes);
+
+        let descriptor_pool = unsafe {
+            device
+                .create_descriptor_pool(&pool_info, None)
+                .expect("VkSprites::new - Failed to create descriptor pool!")
+        };
+
+        let descriptor_set_layout = unsafe {
+            device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                    None,
+                )
+                .expect("VkSprites::new - Failed to create descriptor set layout!")
+        };
+
+        // Filtered so scaled and rotated sprites stay smooth, clamped so atlas regions don't bleed across the edges
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkSprites::new - Failed to create sampler!")
+        };
+
+        Self {
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_sets: HashMap::new(),
+            sampler,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.descriptor_set_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set(&self, texture_name: &str) -> Option<vk::DescriptorSet> {
+        self.descriptor_sets.get(texture_name).copied()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Allocates and writes the set of `texture_name` unless it already has one. Returns `None` once
+    /// `MAX_SPRITE_TEXTURES` sets have been allocated.
+    pub fn prepare_descriptor_set(
+        &mut self,
+        device: &Device,
+        texture_name: &str,
+        image_view: vk::ImageView,
+    ) -> Option<vk::DescriptorSet> {
+        if let Some(descriptor_set) = self.descriptor_set(texture_name) {
+            return Some(descriptor_set);
+        }
+
+        if self.descriptor_sets.len() as u32 >= MAX_SPRITE_TEXTURES {
+            return None;
+        }
+
+        let set_layouts = [self.descriptor_set_layout];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(self.descriptor_pool)
+            .set_layouts(&set_layouts);
+
+        let descriptor_set = unsafe {
+            device
+                .allocate_descriptor_sets(&allocate_info)
+                .expect("VkSprites::prepare_descriptor_set - Failed to allocate descriptor set!")[0]
+        };
+
+        let image_info = [vk::DescriptorImageInfo::builder()
+            .sampler(self.sampler)
+            .image_view(image_view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .build()];
+
+        let writes = [vk::WriteDescriptorSet::builder()
+            .dst_set(descriptor_set)
+            .dst_binding(SPRITE_TEXTURE_BINDING)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&image_info)
+            .build()];
+
+        unsafe { device.update_descriptor_sets(&writes, &[]) };
+
+        self.descriptor_sets
+            .insert(texture_name.to_owned(), descriptor_set);
+
+        Some(descriptor_set)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceDestroy for VkSprites {
+    fn destroy(&self, device: &Device) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+            device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,299 @@
+use std::mem::size_of;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ash::{version::DeviceV1_0, vk, Device};
+use ultraviolet::{Mat4, Vec4};
+use vk_mem::Allocator;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::backend::vk::{
+    handles::{AllocatorFree, AllocatorHandle},
+    resources::VkImage,
+    utils, DeviceAllocatorDestroy,
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Format of the raw and blurred ambient occlusion terms.
+pub const SSAO_FORMAT: vk::Format = vk::Format::R8_UNORM;
+/// Format of the world space normals written by the depth/normal prepass of the forward path.
+pub const SSAO_NORMAL_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+
+pub const DEPTH_NORMAL_PIPELINE_LAYOUT: &str = "depth_normal_pipeline_layout";
+pub const DEPTH_NORMAL_PIPELINE: &str = "depth_normal_pipeline";
+pub const SSAO_PIPELINE_LAYOUT: &str = "ssao_pipeline_layout";
+pub const SSAO_PIPELINE: &str = "ssao_pipeline";
+pub const SSAO_BLUR_PIPELINE: &str = "ssao_blur_pipeline";
+
+const SSAO_DEPTH_BINDING: u32 = 0;
+const SSAO_NORMAL_BINDING: u32 = 1;
+const SSAO_RAW_BINDING: u32 = 2;
+//----------------------------------------------------------------------------------------------------------------------
+
+// NB! Must match the SsaoConstants block declared in ssao.frag and ssao_blur.frag.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SsaoPushConstants {
+    /// Reconstructs world positions from the depth buffer.
+    pub inverse_view_projection: Mat4,
+    /// x - radius, y - bias, z - sample count, w - intensity
+    pub params: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SsaoPushConstants {
+    pub fn get_range() -> vk::PushConstantRange {
+        vk::PushConstantRange::builder()
+            .offset(0)
+            .size(size_of::<SsaoPushConstants>() as u32)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Ambient occlusion map sampled by the lighting shaders through the global descriptor set, plus the per-frame
+/// descriptor sets of the passes that fill it. The map lives outside the render graph so the global set can point at
+/// it once, and starts out in the read-only layout it is imported with.
+pub struct VkSsao {
+    image: VkImage,
+    image_view: vk::ImageView,
+    extent: vk::Extent2D,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_sets: Vec<vk::DescriptorSet>,
+    sampler: vk::Sampler,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl VkSsao {
+    pub(in crate::renderer::backend::vk::resources) fn new(
+        device: &Device,
+        allocator_handle: &AllocatorHandle,
+        buffering: u32,
+        extent: vk::Extent2D,
+        command_pool: vk::CommandPool,
+        fence: vk::Fence,
+        queue: &vk::Queue,
+    ) -> Self {
+        let create_info = VkImage::image_create_info(
+            SSAO_FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::Extent3D::builder()
+                .width(extent.width)
+                .height(extent.height)
+                .depth(1)
+                .build(),
+            vk::ImageTiling::OPTIMAL,
+        );
+
+        let image = allocator_handle.create_image(
+            &create_info,
+            &AllocatorHandle::allocation_create_info(
+                vk_mem::MemoryUsage::GpuOnly,
+                None,
+                Some(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            ),
+        );
+
+        let view_info = VkImage::image_view_create_info(
+            SSAO_FORMAT,
+            image.get(),
+            vk::ImageAspectFlags::COLOR,
+            vk::ImageViewType::TYPE_2D,
+        );
+        let subresource_range = view_info.subresource_range;
+
+        let layout_transition = |cmd: &vk::CommandBuffer| {
+            let barrier = [vk::ImageMemoryBarrier::builder()
+                .old_layout(vk::ImageLayout::UNDEFINED)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .image(image.get())
+                .subresource_range(subresource_range)
+                .src_access_mask(vk::AccessFlags::empty())
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build()];
+
+            unsafe {
+                device.cmd_pipeline_barrier(
+                    *cmd,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &barrier,
+                )
+            }
+        };
+
+        utils::immediate_submit(device, command_pool, fence, queue, &layout_transition);
+
+        let image_view = unsafe {
+            device
+                .create_image_view(&view_info, None)
+                .expect("VkSsao::new - Failed to create ambient occlusion image view!")
+        };
+
+        let bindings = [SSAO_DEPTH_BINDING, SSAO_NORMAL_BINDING, SSAO_RAW_BINDING]
+            .iter()
+            .map(|binding| {
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(*binding)
+                    .descriptor_count(1)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                    .build()
+            })
+            .collect::<Vec<vk::DescriptorSetLayoutBinding>>();
+
+        let pool_sizes = [vk::DescriptorPoolSize::builder()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(buffering * bindings.len() as u32)
+            .build()];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(buffering)
+            .pool_sizes(&pool_sizes);
+
+        let descriptor_pool = unsafe {
+            device
+                .create_descriptor_pool(&pool_info, None)
+                .expect("VkSsao::new - Failed to create descriptor pool!")
+        };
+
+        let descriptor_set_layout = unsafe {
+            device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                    None,
+                )
+                .expect("VkSsao::new - Failed to create descriptor set layout!")
+        };
+
+        let set_layouts = vec![descriptor_set_layout; buffering as usize];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(descriptor_pool)
+            .set_layouts(&set_layouts);
+
+        let descriptor_sets = unsafe {
+            device
+                .allocate_descriptor_sets(&allocate_info)
+                .expect("VkSsao::new - Failed to allocate descriptor sets!")
+        };
+
+        // Every image sampled here matches the backbuffer size
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE);
+
+        let sampler = unsafe {
+            device
+                .create_sampler(&sampler_info, None)
+                .expect("VkSsao::new - Failed to create sampler!")
+        };
+
+        Self {
+            image,
+            image_view,
+            extent,
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_sets,
+            sampler,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn image(&self) -> vk::Image {
+        self.image.get()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn image_view(&self) -> vk::ImageView {
+        self.image_view
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn sampler(&self) -> vk::Sampler {
+        self.sampler
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set_layout(&self) -> vk::DescriptorSetLayout {
+        self.descriptor_set_layout
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn descriptor_set(&self, frame_index: usize) -> vk::DescriptorSet {
+        self.descriptor_sets[frame_index]
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Points the set at this frame's depth, normal and raw occlusion images. The occlusion pass leaves the raw image
+    /// unread and the blur pass the normals, so both share the set. The set must belong to the frame being recorded,
+    /// whose previous submission is known to have finished.
+    pub fn write_descriptor_set(
+        &self,
+        device: &Device,
+        descriptor_set: vk::DescriptorSet,
+        depth_view: vk::ImageView,
+        normal_view: vk::ImageView,
+        raw_view: vk::ImageView,
+    ) {
+        let image_infos = [
+            (depth_view, vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL),
+            (normal_view, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+            (raw_view, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+        ]
+        .iter()
+        .map(|(image_view, image_layout)| {
+            [vk::DescriptorImageInfo::builder()
+                .sampler(self.sampler)
+                .image_view(*image_view)
+                .image_layout(*image_layout)
+                .build()]
+        })
+        .collect::<Vec<[vk::DescriptorImageInfo; 1]>>();
+
+        let writes = [SSAO_DEPTH_BINDING, SSAO_NORMAL_BINDING, SSAO_RAW_BINDING]
+            .iter()
+            .zip(image_infos.iter())
+            .map(|(binding, image_info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(descriptor_set)
+                    .dst_binding(*binding)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .image_info(image_info)
+                    .build()
+            })
+            .collect::<Vec<vk::WriteDescriptorSet>>();
+
+        unsafe { device.update_descriptor_sets(&writes, &[]) };
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DeviceAllocatorDestroy for VkSsao {
+    fn destroy(&self, device: &Device, allocator: &Allocator) {
+        unsafe {
+            device.destroy_sampler(self.sampler, None);
+            device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+            device.destroy_image_view(self.image_view, None);
+        }
+
+        self.image.free(allocator);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
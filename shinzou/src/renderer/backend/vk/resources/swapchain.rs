@@ -15,7 +15,6 @@ pub struct VkSwapchain {
 
     #[allow(dead_code)]
     present_mode: vk::PresentModeKHR,
-    #[allow(dead_code)]
     images: Vec<vk::Image>,
 
     image_views: Vec<vk::ImageView>,
@@ -148,6 +147,11 @@ impl VkSwapchain {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn images(&self) -> &[vk::Image] {
+        &self.images
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn image_views(&self) -> &[vk::ImageView] {
         &self.image_views
     }
@@ -7,7 +7,7 @@ use crate::renderer::backend::vk::{
     resources::{VkBuffer, VkImage},
     utils, DeviceAllocatorDestroy,
 };
-use crate::renderer::entities::Texture;
+use crate::renderer::entities::{MapType, Texture, TextureDimension};
 use ash::version::DeviceV1_0;
 use image::GenericImageView;
 //----------------------------------------------------------------------------------------------------------------------
@@ -29,15 +29,28 @@ impl VkTexture {
         queue: &vk::Queue,
         allocator_handle: &AllocatorHandle,
     ) -> Self {
-        let (staging_buffer, extent) = create_texture_staging_buffer(&texture, allocator_handle);
+        let (staging_buffer, extent, layers, hdr) =
+            create_texture_staging_buffer(&texture, allocator_handle);
+        let format = texture_format(&texture.map_type, hdr);
+
+        let mut image_info = VkImage::image_create_info(
+            format,
+            vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST,
+            extent,
+            vk::ImageTiling::OPTIMAL,
+        );
+        image_info.array_layers = layers;
+
+        let view_type = match texture.dimension {
+            TextureDimension::D2 => vk::ImageViewType::TYPE_2D,
+            TextureDimension::Cube => {
+                image_info.flags = vk::ImageCreateFlags::CUBE_COMPATIBLE;
+                vk::ImageViewType::CUBE
+            }
+        };
 
         let image = allocator_handle.create_image(
-            &VkImage::image_create_info(
-                vk::Format::R8G8B8A8_SRGB,
-                vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST,
-                extent,
-                vk::ImageTiling::OPTIMAL,
-            ),
+            &image_info,
             &AllocatorHandle::allocation_create_info(vk_mem::MemoryUsage::GpuOnly, None, None),
         );
 
@@ -47,7 +60,7 @@ impl VkTexture {
                 .base_mip_level(0)
                 .level_count(1)
                 .base_array_layer(0)
-                .layer_count(1)
+                .layer_count(layers)
                 .build();
 
             let image_to_transfer_barrier = [vk::ImageMemoryBarrier::builder()
@@ -75,7 +88,7 @@ impl VkTexture {
                 .aspect_mask(vk::ImageAspectFlags::COLOR)
                 .mip_level(0)
                 .base_array_layer(0)
-                .layer_count(1)
+                .layer_count(layers)
                 .build();
 
             let copy_regions = [vk::BufferImageCopy::builder()
@@ -123,9 +136,10 @@ impl VkTexture {
         staging_buffer.free(&allocator_handle.allocator);
 
         let image_view_info = VkImage::image_view_create_info(
-            vk::Format::R8G8B8A8_SRGB,
+            format,
             image.get(),
             vk::ImageAspectFlags::COLOR,
+            view_type,
         );
 
         let image_view = unsafe {
@@ -160,13 +174,15 @@ impl DeviceAllocatorDestroy for VkTexture {
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+/// Returns the staging buffer with every layer of the texture, the extent of a single layer, the layer count and
+/// whether the texels are half floats.
 fn create_texture_staging_buffer(
     texture: &Texture,
     allocator_handle: &AllocatorHandle,
-) -> (VkBuffer, vk::Extent3D) {
-    let img = texture.load_raw_from_file();
+) -> (VkBuffer, vk::Extent3D, u32, bool) {
+    let img = texture.load_raw();
 
-    let img_size = (img.width * img.height * img.bit_depth) as vk::DeviceSize;
+    let img_size = (img.width * img.height * img.bit_depth * img.layers) as vk::DeviceSize;
 
     let staging_buffer = allocator_handle.create_buffer(
         &VkBuffer::create_info(img_size, vk::BufferUsageFlags::TRANSFER_SRC),
@@ -187,6 +203,19 @@ fn create_texture_staging_buffer(
             .height(img.height)
             .depth(1)
             .build(),
+        img.layers,
+        img.hdr,
     )
 }
 //----------------------------------------------------------------------------------------------------------------------
+
+fn texture_format(map_type: &MapType, hdr: bool) -> vk::Format {
+    if hdr {
+        vk::Format::R16G16B16A16_SFLOAT
+    } else if map_type.is_srgb() {
+        vk::Format::R8G8B8A8_SRGB
+    } else {
+        vk::Format::R8G8B8A8_UNORM
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const DEFAULT_ENTITY_BUFFER_CAPACITY: u64 = 10000;
+pub const DEFAULT_SHADOW_MAP_SIZE: u32 = 2048;
+pub const DEFAULT_SHADOW_CASCADE_COUNT: u32 = 4;
+pub const DEFAULT_SHADOW_DISTANCE: f32 = 150.0;
+pub const DEFAULT_MSAA_SAMPLES: u32 = 4;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// How the scene gets lit. Both paths feed the same HDR post-process chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderPath {
+    /// Every material shades and lights its own fragments in a single pass. Supports MSAA.
+    Forward,
+    /// Materials write their surface parameters to a G-buffer that a full-screen pass lights afterwards. Lighting cost
+    /// no longer depends on overdraw, but the G-buffer is never multisampled and material fragment shaders are
+    /// replaced by a common G-buffer shader.
+    Deferred,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// How view depth maps to the depth buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthMode {
+    /// Near plane at depth 0 and far plane at depth 1.
+    Standard,
+    /// Near plane at depth 1 and far plane at depth 0. Together with the floating-point depth buffer it requires, depth
+    /// precision is spread much more evenly over the view distance, which keeps distant geometry from z-fighting.
+    Reversed,
+    /// Reversed, with the far plane pushed to infinity. The camera's `z_far` still bounds culling, shadows and light
+    /// clustering.
+    ReversedInfinite,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DepthMode {
+    pub fn is_reversed(&self) -> bool {
+        *self != DepthMode::Standard
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Depth the buffer is cleared to, nothing is ever further away.
+    pub fn far_depth(&self) -> f32 {
+        if self.is_reversed() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct RendererConfig {
+    /// Initial number of renderables each per-frame entity buffer can hold. Buffers grow on demand past this value.
+    pub entity_buffer_capacity: u64,
+    /// Width and height of every shadow map layer, in texels.
+    pub shadow_map_size: u32,
+    /// Number of cascades each shadow-casting directional light is split into. Clamped to `[1, 4]`.
+    pub shadow_cascade_count: u32,
+    /// View distance covered by directional light cascades.
+    pub shadow_distance: f32,
+    /// Samples per pixel of the main color and depth targets. Lowered to the highest count the device supports for
+    /// both, 1 disables MSAA.
+    pub msaa_samples: u32,
+    pub render_path: RenderPath,
+    /// Directory the image based lighting maps of each environment are cached in, keyed by the environment texels.
+    /// `None` precomputes them every time an environment is set.
+    pub ibl_cache_dir: Option<PathBuf>,
+    /// Depth range of the camera projection and the depth buffer. Shadow maps always use the standard range.
+    pub depth_mode: DepthMode,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            entity_buffer_capacity: DEFAULT_ENTITY_BUFFER_CAPACITY,
+            shadow_map_size: DEFAULT_SHADOW_MAP_SIZE,
+            shadow_cascade_count: DEFAULT_SHADOW_CASCADE_COUNT,
+            shadow_distance: DEFAULT_SHADOW_DISTANCE,
+            msaa_samples: DEFAULT_MSAA_SAMPLES,
+            render_path: RenderPath::Forward,
+            ibl_cache_dir: None,
+            depth_mode: DepthMode::Standard,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,32 @@
+/// Settings of the screen-space ambient occlusion pass, which darkens the ambient light in creases and contact areas.
+#[derive(Clone, Debug)]
+pub struct AmbientOcclusion {
+    pub enabled: bool,
+    /// World space radius of the hemisphere sampled around every pixel.
+    pub radius: f32,
+    /// Depth difference below which a sample doesn't count as occluding, avoids self-occlusion on flat surfaces.
+    pub bias: f32,
+    /// Samples taken per pixel, clamped to `MAX_SAMPLE_COUNT`.
+    pub sample_count: u32,
+    /// Exponent applied to the blurred result, higher values give darker occlusion.
+    pub intensity: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl AmbientOcclusion {
+    pub const MAX_SAMPLE_COUNT: u32 = 64;
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            radius: 0.5,
+            bias: 0.025,
+            sample_count: 16,
+            intensity: 1.0,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
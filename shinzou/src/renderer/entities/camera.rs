@@ -1,17 +1,42 @@
 use std::mem::size_of;
 //----------------------------------------------------------------------------------------------------------------------
 
-use ultraviolet::{projection, rotor::Rotor3, Mat4, Vec3, Vec4};
+use ultraviolet::{projection, rotor::Rotor3, Mat4, Vec2, Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::{renderer::DepthMode, utils::ray::Ray};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// How a camera maps view space to clip space. Switchable at runtime with `Camera::set_projection`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective {
+        v_fov_rad: f32,
+    },
+    /// `height` world units fit the viewport vertically, the width follows the aspect ratio.
+    Orthographic {
+        height: f32,
+    },
+    /// Orthographic, with `pixels_per_unit` viewport pixels covering one world unit whatever the viewport size.
+    PixelPerfect {
+        pixels_per_unit: f32,
+    },
+    /// Used as is, so it must map to the renderer's depth mode. The clip planes and viewport size are ignored, and
+    /// reversed matrices must have a finite far plane for the frustum math.
+    Custom(Mat4),
+}
 //----------------------------------------------------------------------------------------------------------------------
 
 pub struct Camera {
     position: Vec3,
     direction: Vec3,
     up: Vec3,
-    v_fov_rad: f32,
-    aspect_ratio: f32,
+    projection: Projection,
+    /// In pixels, only its aspect ratio matters unless the projection is pixel-perfect.
+    viewport_size: Vec2,
     z_far: f32,
     z_near: f32,
+    depth_mode: DepthMode,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -29,63 +54,264 @@ impl Camera {
             position,
             direction,
             up,
-            aspect_ratio,
-            v_fov_rad,
+            projection: Projection::Perspective { v_fov_rad },
+            viewport_size: Vec2::new(aspect_ratio, 1.0),
             z_near,
             z_far,
+            depth_mode: DepthMode::Standard,
         }
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Must match the depth mode the renderer was configured with, which the renderer's own camera does.
+    pub fn with_depth_mode(mut self, depth_mode: DepthMode) -> Self {
+        self.depth_mode = depth_mode;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn position(&self) -> Vec3 {
         self.position
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn z_near(&self) -> f32 {
+        self.z_near
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn z_far(&self) -> f32 {
+        self.z_far
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_clip_planes(&mut self, z_near: f32, z_far: f32) {
+        self.z_near = z_near;
+        self.z_far = z_far;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn depth_mode(&self) -> DepthMode {
+        self.depth_mode
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn projection_type(&self) -> Projection {
+        self.projection
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn viewport_size(&self) -> Vec2 {
+        self.viewport_size
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Updates the aspect ratio, and the visible area of pixel-perfect projections.
+    pub fn set_viewport_size(&mut self, width: f32, height: f32) {
+        self.viewport_size = Vec2::new(width.max(1.0), height.max(1.0));
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.viewport_size.x / self.viewport_size.y
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn view(&self) -> Mat4 {
-        look_at(self.position, self.direction, self.up)
+        Mat4::look_at(self.position, self.position + self.direction, self.up)
     }
     //------------------------------------------------------------------------------------------------------------------
 
     pub fn projection(&self) -> Mat4 {
-        projection::perspective_vk(self.v_fov_rad, self.aspect_ratio, self.z_near, self.z_far)
+        let aspect_ratio = self.aspect_ratio();
+
+        match (self.projection, self.depth_mode) {
+            (Projection::Custom(projection), _) => projection,
+            (_, DepthMode::Standard) => self.frustum_projection(),
+            (Projection::Perspective { v_fov_rad }, DepthMode::Reversed) => {
+                projection::perspective_reversed_z_vk(
+                    v_fov_rad,
+                    aspect_ratio,
+                    self.z_near,
+                    self.z_far,
+                )
+            }
+            (Projection::Perspective { v_fov_rad }, DepthMode::ReversedInfinite) => {
+                projection::perspective_reversed_infinite_z_vk(v_fov_rad, aspect_ratio, self.z_near)
+            }
+            // Orthographic projections can't reach infinity, swapping the planes reverses depth
+            _ => {
+                let (half_width, half_height) = self.orthographic_half_extent();
+                projection::orthographic_vk(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.z_far,
+                    self.z_near,
+                )
+            }
+        }
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Standard depth range projection bounded by `z_far` whatever the depth mode, for the frustum math done on the CPU.
+    pub fn frustum_projection(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { v_fov_rad } => {
+                projection::perspective_vk(v_fov_rad, self.aspect_ratio(), self.z_near, self.z_far)
+            }
+            Projection::Orthographic { .. } | Projection::PixelPerfect { .. } => {
+                let (half_width, half_height) = self.orthographic_half_extent();
+                projection::orthographic_vk(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.z_near,
+                    self.z_far,
+                )
+            }
+            Projection::Custom(mut projection) => {
+                // Reversed depth is flipped back with z' = w - z
+                if self.depth_mode.is_reversed() {
+                    for col in projection.cols.iter_mut() {
+                        col.z = col.w - col.z;
+                    }
+                }
+                projection
+            }
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// World position at `ndc`, where x and y span `[-1, 1]` and z goes from 0 at the near plane to 1 at the far plane
+    /// whatever the depth mode. Holds for every projection type, so picking rays can join the near and far points.
+    pub fn unproject(&self, ndc: Vec3) -> Vec3 {
+        let inverse_view_projection = (self.frustum_projection() * self.view()).inversed();
+        let world = inverse_view_projection * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+        world.truncated() / world.w
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// World space ray through the window pixel `(x, y)`, counted from the top left corner of the viewport. Starts on the
+    /// near plane, so rays of orthographic projections are parallel instead of sharing an origin.
+    pub fn screen_to_ray(&self, x: f32, y: f32) -> Ray {
+        let ndc_x = 2.0 * x / self.viewport_size.x - 1.0;
+        let ndc_y = 2.0 * y / self.viewport_size.y - 1.0;
+
+        let near = self.unproject(Vec3::new(ndc_x, ndc_y, 0.0));
+        let far = self.unproject(Vec3::new(ndc_x, ndc_y, 1.0));
+
+        Ray::new(near, (far - near).normalized())
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Window pixel `point` lands on, counted from the top left corner of the viewport, with its distance along the
+    /// view direction in z. `None` when it is behind a perspective camera. Points outside the viewport or the clip
+    /// planes are still projected.
+    pub fn world_to_screen(&self, point: Vec3) -> Option<Vec3> {
+        let clip = self.frustum_projection() * self.view() * point.into_homogeneous_point();
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let ndc = clip.truncated() / clip.w;
+
+        Some(Vec3::new(
+            (ndc.x + 1.0) * 0.5 * self.viewport_size.x,
+            (ndc.y + 1.0) * 0.5 * self.viewport_size.y,
+            (point - self.position).dot(self.direction),
+        ))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    fn orthographic_half_extent(&self) -> (f32, f32) {
+        let height = match self.projection {
+            Projection::Orthographic { height } => height,
+            Projection::PixelPerfect { pixels_per_unit } => {
+                self.viewport_size.y / pixels_per_unit.max(f32::EPSILON)
+            }
+            _ => 2.0,
+        };
+
+        (height * 0.5 * self.aspect_ratio(), height * 0.5)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Moves the camera along its own axes, x to the right, y up and z forward.
     pub fn translate(&mut self, translation: Vec3) {
-        // TODO inefficient
-        let local = look_at(self.position, self.direction, self.up).inversed();
-        self.position += (local * Vec4::from(translation)).truncated();
+        let up = self.right().cross(self.direction);
+        self.position +=
+            self.right() * translation.x + up * translation.y + self.direction * translation.z;
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    /// Rotates the view direction only, the up vector stays put.
     pub fn rotate(&mut self, rotor: Rotor3) {
         self.direction = (rotor * self.direction).normalized();
     }
     //------------------------------------------------------------------------------------------------------------------
-}
-//----------------------------------------------------------------------------------------------------------------------
 
-fn look_at(position: Vec3, direction: Vec3, up: Vec3) -> Mat4 {
-    // Kindly borrowed from https://stackoverflow.com/a/21830596
-    let z = direction;
-    let mut y = up;
-    let mut x = y.cross(z);
-    y = z.cross(x);
-    x.normalize();
-    y.normalize();
-    let w = Vec4::new(-x.dot(direction), -y.dot(direction), -z.dot(direction), 1.0);
-    let rotation = Mat4::new(Vec4::from(x), Vec4::from(y), Vec4::from(z), w);
-    let translation = Mat4::identity().translated(&position);
-    rotation * translation
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn direction(&self) -> Vec3 {
+        self.direction
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_direction(&mut self, direction: Vec3) {
+        self.direction = direction.normalized();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Reference up vector the view is kept level with, the direction must never be parallel to it.
+    pub fn up(&self) -> Vec3 {
+        self.up
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_up(&mut self, up: Vec3) {
+        self.up = up.normalized();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn right(&self) -> Vec3 {
+        self.direction.cross(self.up).normalized()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn look_at(&mut self, target: Vec3) {
+        self.set_direction(target - self.position);
+    }
+    //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+// NB! Must match the CameraBuffer block declared in the shaders. The scene UBO follows it in the same buffer, at the
+// next offset aligned for binding.
 #[repr(C)]
 pub struct CameraUBO {
     view: Mat4,
     projection: Mat4,
     view_projection: Mat4,
+    position: Vec4,
+    /// x - depth of the far plane, 0 with reversed depth and 1 otherwise
+    depth: Vec4,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -101,6 +327,8 @@ impl CameraUBO {
             view,
             projection,
             view_projection,
+            position: view.inversed().cols[3],
+            depth: Vec4::new(camera.depth_mode().far_depth(), 0.0, 0.0, 0.0),
         }
     }
 }
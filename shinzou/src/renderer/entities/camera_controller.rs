@@ -0,0 +1,399 @@
+use std::f32::consts::FRAC_PI_2;
+//----------------------------------------------------------------------------------------------------------------------
+
+use ultraviolet::{Vec2, Vec3};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::Camera;
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const DEFAULT_PITCH_LIMIT_RAD: f32 = FRAC_PI_2 - 0.01;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Input axes driving a controller for a single frame, filled from whatever action context is active. Axes are
+/// expected in `[-1, 1]`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CameraInput {
+    /// x - right, y - up, z - forward
+    pub movement: Vec3,
+    /// x - turn right, y - look up
+    pub look: Vec2,
+    /// Positive zooms in.
+    pub zoom: f32,
+    /// Scales the movement speed by `CameraControllerSettings::boost_multiplier`.
+    pub boost: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug)]
+pub struct CameraControllerSettings {
+    /// Top movement speed, in world units per second.
+    pub move_speed: f32,
+    pub boost_multiplier: f32,
+    /// Top turning speed, in radians per second.
+    pub look_speed: f32,
+    /// Top zoom speed, in world units per second for orbiting controllers.
+    pub zoom_speed: f32,
+    /// How fast velocities reach the speed asked for by the input, as a fraction of the top speed per second.
+    /// Infinite reacts right away.
+    pub acceleration: f32,
+    /// Rate velocities decay at once the input is released, per second. Infinite stops right away.
+    pub damping: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for CameraControllerSettings {
+    fn default() -> Self {
+        Self {
+            move_speed: 10.0,
+            boost_multiplier: 2.0,
+            look_speed: 2.5,
+            zoom_speed: 20.0,
+            acceleration: 8.0,
+            damping: 10.0,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub trait CameraController {
+    fn update(&mut self, camera: &mut Camera, input: &CameraInput, frame_delta: f32);
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Velocity eased towards the one asked for by the input, accelerating while there is input and damped otherwise.
+#[derive(Clone, Copy, Debug, Default)]
+struct SmoothedVelocity {
+    value: Vec3,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SmoothedVelocity {
+    fn update(
+        &mut self,
+        target: Vec3,
+        top_speed: f32,
+        settings: &CameraControllerSettings,
+        frame_delta: f32,
+    ) -> Vec3 {
+        if target.mag_sq() > 0.0 {
+            let step = settings.acceleration * top_speed * frame_delta;
+            let difference = target - self.value;
+            self.value = if difference.mag() <= step {
+                target
+            } else {
+                self.value + difference.normalized() * step
+            };
+        } else {
+            self.value *= (-settings.damping * frame_delta).exp();
+        }
+
+        self.value
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Flies along the camera's own axes and turns around them, so looking past straight up rolls the horizon over
+/// instead of flipping the view.
+pub struct FreeFlyController {
+    pub settings: CameraControllerSettings,
+    velocity: SmoothedVelocity,
+    turn_velocity: SmoothedVelocity,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl FreeFlyController {
+    pub fn new(settings: CameraControllerSettings) -> Self {
+        Self {
+            settings,
+            velocity: SmoothedVelocity::default(),
+            turn_velocity: SmoothedVelocity::default(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl CameraController for FreeFlyController {
+    fn update(&mut self, camera: &mut Camera, input: &CameraInput, frame_delta: f32) {
+        let settings = self.settings;
+
+        let look_speed = settings.look_speed;
+        let turn = self.turn_velocity.update(
+            Vec3::new(input.look.x, input.look.y, 0.0) * look_speed,
+            look_speed,
+            &settings,
+            frame_delta,
+        ) * frame_delta;
+
+        let right = camera.right();
+        let up = right.cross(camera.direction());
+        let direction = rotate_around(camera.direction(), up, -turn.x);
+        let direction = rotate_around(direction, right, turn.y);
+
+        camera.set_up(right.cross(direction));
+        camera.set_direction(direction);
+
+        let move_speed = move_speed(&settings, input);
+        let velocity = self.velocity.update(
+            input.movement * move_speed,
+            move_speed,
+            &settings,
+            frame_delta,
+        );
+        camera.translate(velocity * frame_delta);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Walks on the horizontal plane and turns with yaw and pitch angles, the pitch clamped short of straight up and down.
+pub struct FpsController {
+    pub settings: CameraControllerSettings,
+    pub pitch_limit_rad: f32,
+    yaw: f32,
+    pitch: f32,
+    velocity: SmoothedVelocity,
+    turn_velocity: SmoothedVelocity,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl FpsController {
+    /// Starts from the current view direction of `camera`.
+    pub fn new(camera: &Camera, settings: CameraControllerSettings) -> Self {
+        let (yaw, pitch) = angles_from_direction(camera.direction());
+
+        Self {
+            settings,
+            pitch_limit_rad: DEFAULT_PITCH_LIMIT_RAD,
+            yaw,
+            pitch,
+            velocity: SmoothedVelocity::default(),
+            turn_velocity: SmoothedVelocity::default(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl CameraController for FpsController {
+    fn update(&mut self, camera: &mut Camera, input: &CameraInput, frame_delta: f32) {
+        let settings = self.settings;
+
+        let look_speed = settings.look_speed;
+        let turn = self.turn_velocity.update(
+            Vec3::new(input.look.x, input.look.y, 0.0) * look_speed,
+            look_speed,
+            &settings,
+            frame_delta,
+        ) * frame_delta;
+
+        self.yaw += turn.x;
+        self.pitch = (self.pitch + turn.y).clamp(-self.pitch_limit_rad, self.pitch_limit_rad);
+
+        camera.set_up(Vec3::unit_y());
+        camera.set_direction(direction_from_angles(self.yaw, self.pitch));
+
+        // Forward follows the heading only, looking up or down doesn't lift the camera off the ground
+        let forward = direction_from_angles(self.yaw, 0.0);
+        let right = forward.cross(Vec3::unit_y());
+        let movement = right * input.movement.x
+            + Vec3::unit_y() * input.movement.y
+            + forward * input.movement.z;
+
+        let move_speed = move_speed(&settings, input);
+        let velocity =
+            self.velocity
+                .update(movement * move_speed, move_speed, &settings, frame_delta);
+        camera.set_position(camera.position() + velocity * frame_delta);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Orbits a target at a distance, zoom moves closer or further and movement pans the target across the view.
+pub struct OrbitController {
+    pub settings: CameraControllerSettings,
+    pub target: Vec3,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub pitch_limit_rad: f32,
+    yaw: f32,
+    pitch: f32,
+    velocity: SmoothedVelocity,
+    turn_velocity: SmoothedVelocity,
+    zoom_velocity: SmoothedVelocity,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl OrbitController {
+    pub fn new(target: Vec3, distance: f32, settings: CameraControllerSettings) -> Self {
+        Self {
+            settings,
+            target,
+            distance,
+            min_distance: 0.5,
+            max_distance: 1000.0,
+            pitch_limit_rad: DEFAULT_PITCH_LIMIT_RAD,
+            yaw: 0.0,
+            pitch: 0.0,
+            velocity: SmoothedVelocity::default(),
+            turn_velocity: SmoothedVelocity::default(),
+            zoom_velocity: SmoothedVelocity::default(),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Yaw and pitch of the view direction towards the target, in radians.
+    pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-self.pitch_limit_rad, self.pitch_limit_rad);
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl CameraController for OrbitController {
+    fn update(&mut self, camera: &mut Camera, input: &CameraInput, frame_delta: f32) {
+        let settings = self.settings;
+
+        let look_speed = settings.look_speed;
+        let turn = self.turn_velocity.update(
+            Vec3::new(input.look.x, input.look.y, 0.0) * look_speed,
+            look_speed,
+            &settings,
+            frame_delta,
+        ) * frame_delta;
+
+        self.yaw += turn.x;
+        self.pitch = (self.pitch + turn.y).clamp(-self.pitch_limit_rad, self.pitch_limit_rad);
+
+        let zoom_speed = settings.zoom_speed;
+        let zoom = self.zoom_velocity.update(
+            Vec3::new(input.zoom, 0.0, 0.0) * zoom_speed,
+            zoom_speed,
+            &settings,
+            frame_delta,
+        ) * frame_delta;
+        self.distance = (self.distance - zoom.x).clamp(self.min_distance, self.max_distance);
+
+        let direction = direction_from_angles(self.yaw, self.pitch);
+        let right = direction.cross(Vec3::unit_y()).normalized();
+        let up = right.cross(direction);
+
+        // Panning covers the same share of the view whatever the distance
+        let move_speed = move_speed(&settings, input) * self.distance / 10.0;
+        let velocity = self.velocity.update(
+            (right * input.movement.x + up * input.movement.y + direction * input.movement.z)
+                * move_speed,
+            move_speed,
+            &settings,
+            frame_delta,
+        );
+        self.target += velocity * frame_delta;
+
+        camera.set_up(Vec3::unit_y());
+        camera.set_direction(direction);
+        camera.set_position(self.target - direction * self.distance);
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Trails behind a moving target, easing towards a point `distance` behind and `height` above it while looking at it.
+/// Look input swings the camera around the target and zoom changes the distance.
+pub struct FollowController {
+    pub settings: CameraControllerSettings,
+    pub distance: f32,
+    pub height: f32,
+    /// Rate the camera catches up with the target at, per second. Infinite sticks to it.
+    pub stiffness: f32,
+    target: Vec3,
+    heading: Vec3,
+    swing: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl FollowController {
+    pub fn new(distance: f32, height: f32, settings: CameraControllerSettings) -> Self {
+        Self {
+            settings,
+            distance,
+            height,
+            stiffness: 5.0,
+            target: Vec3::zero(),
+            heading: -Vec3::unit_z(),
+            swing: 0.0,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Position of the followed target and the direction it faces, the camera settles behind it. Set every frame.
+    pub fn set_target(&mut self, position: Vec3, heading: Vec3) {
+        self.target = position;
+
+        let level_heading = Vec3::new(heading.x, 0.0, heading.z);
+        if level_heading.mag_sq() > f32::EPSILON {
+            self.heading = level_heading.normalized();
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl CameraController for FollowController {
+    fn update(&mut self, camera: &mut Camera, input: &CameraInput, frame_delta: f32) {
+        let settings = self.settings;
+
+        self.swing += input.look.x * settings.look_speed * frame_delta;
+        self.distance = (self.distance - input.zoom * settings.zoom_speed * frame_delta).max(0.5);
+
+        let behind = rotate_around(-self.heading, Vec3::unit_y(), self.swing);
+        let desired_position = self.target + behind * self.distance + Vec3::unit_y() * self.height;
+
+        let blend = 1.0 - (-self.stiffness * frame_delta).exp();
+        let position = camera.position() + (desired_position - camera.position()) * blend;
+
+        camera.set_up(Vec3::unit_y());
+        camera.set_position(position);
+        if (self.target - position).mag_sq() > f32::EPSILON {
+            camera.look_at(self.target);
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn move_speed(settings: &CameraControllerSettings, input: &CameraInput) -> f32 {
+    if input.boost {
+        settings.move_speed * settings.boost_multiplier
+    } else {
+        settings.move_speed
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Yaw turns right from -z and pitch looks up from the horizon, both in radians.
+fn direction_from_angles(yaw: f32, pitch: f32) -> Vec3 {
+    Vec3::new(
+        yaw.sin() * pitch.cos(),
+        pitch.sin(),
+        -yaw.cos() * pitch.cos(),
+    )
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+fn angles_from_direction(direction: Vec3) -> (f32, f32) {
+    let direction = direction.normalized();
+    (
+        direction.x.atan2(-direction.z),
+        direction.y.clamp(-1.0, 1.0).asin(),
+    )
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Rodrigues' rotation of `vector` around the normalized `axis`, counter-clockwise looking down the axis.
+fn rotate_around(vector: Vec3, axis: Vec3, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    vector * cos + axis.cross(vector) * sin + axis * axis.dot(vector) * (1.0 - cos)
+}
+//----------------------------------------------------------------------------------------------------------------------
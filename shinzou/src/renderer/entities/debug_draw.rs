@@ -0,0 +1,278 @@
+use std::{
+    f32::consts::PI,
+    time::{Duration, Instant},
+};
+//----------------------------------------------------------------------------------------------------------------------
+
+use ultraviolet::{Mat4, Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::Camera;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Segments of each circle making up a debug sphere.
+const SPHERE_SEGMENTS: usize = 32;
+/// Arrow heads are this fraction of the arrow length.
+const ARROW_HEAD_RATIO: f32 = 0.2;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// How long a debug shape stays queued and whether scene geometry hides it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebugDrawOptions {
+    /// In seconds, `None` draws the shape on the next frame only.
+    pub duration: Option<f32>,
+    /// Hidden behind scene geometry when set, drawn over everything otherwise.
+    pub depth_test: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DebugDrawOptions {
+    pub const ONE_FRAME: Self = Self {
+        duration: None,
+        depth_test: true,
+    };
+
+    pub const OVERLAY: Self = Self {
+        duration: None,
+        depth_test: false,
+    };
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn for_duration(seconds: f32) -> Self {
+        Self {
+            duration: Some(seconds),
+            ..Self::ONE_FRAME
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for DebugDrawOptions {
+    fn default() -> Self {
+        Self::ONE_FRAME
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// World space line segment, every debug shape is broken down into these.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebugLine {
+    pub start: Vec3,
+    pub end: Vec3,
+    /// Blended into the HDR scene color, so it goes through exposure and tonemapping like the rest of the scene.
+    pub color: Vec4,
+    pub depth_test: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Immediate-mode queue of debug shapes, drawn over the scene separately from the renderables. Shapes queued for a
+/// single frame are dropped once it is drawn, the others once their duration has elapsed.
+#[derive(Default)]
+pub struct DebugDraw {
+    lines: Vec<(DebugLine, Option<Instant>)>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DebugDraw {
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Vec4, options: DebugDrawOptions) {
+        let expires_at = options
+            .duration
+            .map(|seconds| Instant::now() + Duration::from_secs_f32(seconds.max(0.0)));
+
+        self.lines.push((
+            DebugLine {
+                start,
+                end,
+                color,
+                depth_test: options.depth_test,
+            },
+            expires_at,
+        ));
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Line from `start` to `end` with a four-pronged head at `end`.
+    pub fn arrow(&mut self, start: Vec3, end: Vec3, color: Vec4, options: DebugDrawOptions) {
+        self.line(start, end, color, options);
+
+        let shaft = end - start;
+        let length = shaft.mag();
+        if length <= f32::EPSILON {
+            return;
+        }
+
+        let direction = shaft / length;
+        let (side, up) = orthonormal_basis(direction);
+        let head_length = length * ARROW_HEAD_RATIO;
+        let head_base = end - direction * head_length;
+        let head_radius = head_length * 0.5;
+
+        for offset in [side, -side, up, -up].iter() {
+            self.line(end, head_base + *offset * head_radius, color, options);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Axis aligned box spanning `min` to `max`.
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: Vec4, options: DebugDrawOptions) {
+        let corners = [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+        ];
+
+        self.box_edges(&corners, color, options);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Three circles around `center`, one in each axis plane.
+    pub fn sphere(&mut self, center: Vec3, radius: f32, color: Vec4, options: DebugDrawOptions) {
+        let axes = [
+            (Vec3::unit_x(), Vec3::unit_y()),
+            (Vec3::unit_y(), Vec3::unit_z()),
+            (Vec3::unit_z(), Vec3::unit_x()),
+        ];
+
+        for (u, v) in axes.iter() {
+            let point = |segment: usize| {
+                let angle = 2.0 * PI * segment as f32 / SPHERE_SEGMENTS as f32;
+                center + (*u * angle.cos() + *v * angle.sin()) * radius
+            };
+
+            for segment in 0..SPHERE_SEGMENTS {
+                self.line(point(segment), point(segment + 1), color, options);
+            }
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Volume `camera` sees between its clip planes, whatever its projection.
+    pub fn frustum(&mut self, camera: &Camera, color: Vec4, options: DebugDrawOptions) {
+        let mut corners = [Vec3::zero(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let x = if i & 1 == 0 { -1.0 } else { 1.0 };
+            let y = if i & 2 == 0 { -1.0 } else { 1.0 };
+            let z = if i < 4 { 0.0 } else { 1.0 };
+            *corner = camera.unproject(Vec3::new(x, y, z));
+        }
+
+        // Same winding as the AABB corners, near plane first
+        let corners = [
+            corners[0], corners[1], corners[3], corners[2], corners[4], corners[5], corners[7],
+            corners[6],
+        ];
+
+        self.box_edges(&corners, color, options);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Square grid on the horizontal plane through `center`, `size` units wide with `divisions` cells along each side.
+    pub fn grid(
+        &mut self,
+        center: Vec3,
+        size: f32,
+        divisions: u32,
+        color: Vec4,
+        options: DebugDrawOptions,
+    ) {
+        let divisions = divisions.max(1);
+        let half_size = size * 0.5;
+        let step = size / divisions as f32;
+
+        for i in 0..=divisions {
+            let offset = -half_size + step * i as f32;
+
+            self.line(
+                center + Vec3::new(offset, 0.0, -half_size),
+                center + Vec3::new(offset, 0.0, half_size),
+                color,
+                options,
+            );
+            self.line(
+                center + Vec3::new(-half_size, 0.0, offset),
+                center + Vec3::new(half_size, 0.0, offset),
+                color,
+                options,
+            );
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Red, green and blue arrows along the x, y and z axes of `transform`, `size` units long before scaling.
+    pub fn axes(&mut self, transform: &Mat4, size: f32, options: DebugDrawOptions) {
+        let origin = transform.transform_point3(Vec3::zero());
+        let axes = [
+            (Vec3::unit_x(), Vec4::new(1.0, 0.0, 0.0, 1.0)),
+            (Vec3::unit_y(), Vec4::new(0.0, 1.0, 0.0, 1.0)),
+            (Vec3::unit_z(), Vec4::new(0.0, 0.0, 1.0, 1.0)),
+        ];
+
+        for (axis, color) in axes.iter() {
+            let end = transform.transform_point3(*axis * size);
+            self.arrow(origin, end, *color, options);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn lines(&self) -> impl Iterator<Item = &DebugLine> {
+        self.lines.iter().map(|(line, _)| line)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Drops every queued shape, including those with time left.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Drops the shapes queued for the frame just drawn and those whose duration has elapsed.
+    pub(in crate::renderer) fn end_frame(&mut self) {
+        let now = Instant::now();
+        self.lines
+            .retain(|(_, expires_at)| expires_at.is_some_and(|expires_at| expires_at > now));
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Edges of a hexahedron given by its four near corners followed by the matching four far ones, each face wound
+    /// in order.
+    fn box_edges(&mut self, corners: &[Vec3; 8], color: Vec4, options: DebugDrawOptions) {
+        for i in 0..4 {
+            let next = (i + 1) % 4;
+            self.line(corners[i], corners[next], color, options);
+            self.line(corners[i + 4], corners[next + 4], color, options);
+            self.line(corners[i], corners[i + 4], color, options);
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Two unit vectors perpendicular to `direction` and to each other.
+fn orthonormal_basis(direction: Vec3) -> (Vec3, Vec3) {
+    let reference = if direction.y.abs() < 0.99 {
+        Vec3::unit_y()
+    } else {
+        Vec3::unit_x()
+    };
+
+    let side = direction.cross(reference).normalized();
+    (side, side.cross(direction))
+}
+//----------------------------------------------------------------------------------------------------------------------
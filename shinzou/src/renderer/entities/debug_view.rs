@@ -0,0 +1,58 @@
+/// How the scene is shown. Every mode but `Shaded` and `Wireframe` draws all renderables with a single debug shader in
+/// place of their materials, skipping lighting and post-processing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DebugViewMode {
+    #[default]
+    Shaded,
+    /// Shaded scene with the triangle edges of every renderable drawn over it.
+    Wireframe,
+    /// World space normals, mapped from [-1, 1] to [0, 1].
+    Normals,
+    /// Texture coordinates wrapped to [0, 1] in the red and green channels.
+    Uvs,
+    /// Color hashed from the index of each renderable.
+    InstanceIds,
+    /// Fragments drawn to each pixel, from black through blue, green and yellow to red.
+    Overdraw,
+    /// Linearized between the camera's near and far planes.
+    Depth,
+    /// Level a fully mipmapped base color texture would be sampled from, blue being the full resolution.
+    MipLevel,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl DebugViewMode {
+    pub const ALL: [DebugViewMode; 8] = [
+        DebugViewMode::Shaded,
+        DebugViewMode::Wireframe,
+        DebugViewMode::Normals,
+        DebugViewMode::Uvs,
+        DebugViewMode::InstanceIds,
+        DebugViewMode::Overdraw,
+        DebugViewMode::Depth,
+        DebugViewMode::MipLevel,
+    ];
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// NB! Must match the mode constants in debug_view.frag.
+    pub fn index(&self) -> u32 {
+        match self {
+            DebugViewMode::Shaded => 0,
+            DebugViewMode::Wireframe => 1,
+            DebugViewMode::Normals => 2,
+            DebugViewMode::Uvs => 3,
+            DebugViewMode::InstanceIds => 4,
+            DebugViewMode::Overdraw => 5,
+            DebugViewMode::Depth => 6,
+            DebugViewMode::MipLevel => 7,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Whether the mode draws the renderables with the debug shader instead of shading them.
+    pub fn replaces_shading(&self) -> bool {
+        !matches!(self, DebugViewMode::Shaded | DebugViewMode::Wireframe)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,426 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+//----------------------------------------------------------------------------------------------------------------------
+
+use rusttype::{point, Font as FontFace, GlyphId, Scale};
+use ultraviolet::Vec2;
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::{Texture, UvRect};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Empty texels kept between glyphs in the atlas, so filtering doesn't pick up their neighbours.
+const GLYPH_ATLAS_PADDING: u32 = 1;
+/// Squared distance standing for "no feature found" in the distance transform.
+const DISTANCE_FIELD_INFINITY: f32 = 1e20;
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FontAtlasType {
+    /// Glyph coverage, sharpest when drawn at the size the font was baked at.
+    Bitmap,
+    /// Signed distance to the glyph outlines, stays sharp when scaled up or rotated. `spread` is the distance in texels
+    /// covered by the field on either side of an outline.
+    DistanceField { spread: u32 },
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Glyph baked into a font atlas. Positions are in pixels at the size the font was baked at.
+#[derive(Clone, Copy, Debug)]
+pub struct FontGlyph {
+    id: GlyphId,
+    /// Top left corner of the glyph's quad from the pen position on the baseline, y pointing down.
+    pub offset: Vec2,
+    /// Zero for glyphs without an outline, e.g. spaces.
+    pub size: Vec2,
+    /// Distance the pen moves after the glyph, before kerning.
+    pub advance: f32,
+    pub uv_rect: UvRect,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// TrueType or OpenType font whose glyphs are rasterized into an atlas texture once, at `pixel_height`. Text of any size
+/// is then laid out with its metrics and drawn from the atlas.
+pub struct Font {
+    pub name: String,
+    face: FontFace<'static>,
+    pixel_height: f32,
+    atlas_type: FontAtlasType,
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+    glyphs: HashMap<char, FontGlyph>,
+    atlas: Option<Texture>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Font {
+    /// Loads the font at `file_path` and bakes `characters` into its atlas, printable ASCII when `None`.
+    pub fn new(
+        name: &str,
+        file_path: PathBuf,
+        pixel_height: f32,
+        atlas_type: FontAtlasType,
+        characters: Option<&str>,
+    ) -> Self {
+        let data = fs::read(&file_path)
+            .unwrap_or_else(|_| panic!("Failed to open font file {:?}", &file_path));
+
+        Self::from_bytes(name, data, pixel_height, atlas_type, characters)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Same as `new`, with the font file already in memory.
+    pub fn from_bytes(
+        name: &str,
+        data: Vec<u8>,
+        pixel_height: f32,
+        atlas_type: FontAtlasType,
+        characters: Option<&str>,
+    ) -> Self {
+        let face =
+            FontFace::try_from_vec(data).expect("Font::from_bytes - Failed to parse font data!");
+        let pixel_height = pixel_height.max(1.0);
+        let scale = Scale::uniform(pixel_height);
+        let v_metrics = face.v_metrics(scale);
+
+        let default_characters = (' '..='~').collect::<String>();
+        let characters = characters.unwrap_or(&default_characters);
+
+        let padding = match atlas_type {
+            FontAtlasType::Bitmap => 0,
+            FontAtlasType::DistanceField { spread } => spread,
+        };
+
+        let mut glyphs = HashMap::new();
+        let mut bitmaps = Vec::new();
+        for character in characters.chars() {
+            if glyphs.contains_key(&character) {
+                continue;
+            }
+
+            let glyph = face.glyph(character);
+            // Glyph 0 is the font's placeholder for characters it doesn't have
+            if glyph.id().0 == 0 {
+                warn!(
+                    "Font::from_bytes - Font {} has no glyph for {:?}!",
+                    name, character
+                );
+                continue;
+            }
+
+            let glyph = glyph.scaled(scale);
+            let advance = glyph.h_metrics().advance_width;
+            let glyph = glyph.positioned(point(0.0, 0.0));
+
+            let mut font_glyph = FontGlyph {
+                id: glyph.id(),
+                offset: Vec2::zero(),
+                size: Vec2::zero(),
+                advance,
+                uv_rect: UvRect::new(Vec2::zero(), Vec2::zero()),
+            };
+
+            if let Some(bounds) = glyph.pixel_bounding_box() {
+                let width = bounds.width() as u32;
+                let height = bounds.height() as u32;
+
+                let mut coverage = vec![0.0; (width * height) as usize];
+                glyph.draw(|x, y, value| coverage[(y * width + x) as usize] = value);
+
+                let bitmap = match atlas_type {
+                    FontAtlasType::Bitmap => coverage
+                        .iter()
+                        .map(|value| (value.min(1.0) * 255.0).round() as u8)
+                        .collect(),
+                    FontAtlasType::DistanceField { spread } => {
+                        distance_field(&coverage, width, height, spread)
+                    }
+                };
+
+                font_glyph.offset = Vec2::new(
+                    (bounds.min.x - padding as i32) as f32,
+                    (bounds.min.y - padding as i32) as f32,
+                );
+                font_glyph.size =
+                    Vec2::new((width + 2 * padding) as f32, (height + 2 * padding) as f32);
+                bitmaps.push((character, width + 2 * padding, height + 2 * padding, bitmap));
+            }
+
+            glyphs.insert(character, font_glyph);
+        }
+
+        let atlas = pack_atlas(name, &mut glyphs, bitmaps);
+
+        Self {
+            name: name.to_owned(),
+            face,
+            pixel_height,
+            atlas_type,
+            ascent: v_metrics.ascent,
+            descent: v_metrics.descent,
+            line_gap: v_metrics.line_gap,
+            glyphs,
+            atlas: Some(atlas),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Name the atlas texture is loaded under.
+    pub fn atlas_name(&self) -> String {
+        format!("{}_atlas", self.name)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Hands over the atlas texture for uploading, `None` once it has been taken.
+    pub fn take_atlas(&mut self) -> Option<Texture> {
+        self.atlas.take()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn atlas_type(&self) -> FontAtlasType {
+        self.atlas_type
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn is_distance_field(&self) -> bool {
+        matches!(self.atlas_type, FontAtlasType::DistanceField { .. })
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Height of a line from its descent to its ascent at the baked size, in pixels.
+    pub fn pixel_height(&self) -> f32 {
+        self.pixel_height
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Distance from the baseline to the top of the tallest glyphs, at the baked size.
+    pub fn ascent(&self) -> f32 {
+        self.ascent
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Distance from the baseline to the bottom of the lowest glyphs, negative, at the baked size.
+    pub fn descent(&self) -> f32 {
+        self.descent
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Distance between consecutive baselines at the baked size.
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Baked glyph of `character`, falling back to the one of '?' for characters that weren't baked.
+    pub fn glyph(&self, character: char) -> Option<&FontGlyph> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&'?'))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adjustment of the pen position between `first` and `second` at the baked size, usually negative.
+    pub fn kerning(&self, first: &FontGlyph, second: &FontGlyph) -> f32 {
+        self.face
+            .pair_kerning(Scale::uniform(self.pixel_height), first.id, second.id)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Packs the glyph `bitmaps` into rows of a power of two sized RGBA atlas, white with the bitmaps as alpha, and fills in
+/// the UV rectangles of their `glyphs`.
+fn pack_atlas(
+    name: &str,
+    glyphs: &mut HashMap<char, FontGlyph>,
+    mut bitmaps: Vec<(char, u32, u32, Vec<u8>)>,
+) -> Texture {
+    // Tallest first keeps the rows tightly filled
+    bitmaps.sort_by(|(_, _, a, _), (_, _, b, _)| b.cmp(a));
+
+    let area = bitmaps
+        .iter()
+        .map(|(_, width, height, _)| (width + GLYPH_ATLAS_PADDING) * (height + GLYPH_ATLAS_PADDING))
+        .sum::<u32>();
+    let widest = bitmaps
+        .iter()
+        .map(|(_, width, _, _)| width + GLYPH_ATLAS_PADDING)
+        .max()
+        .unwrap_or(0);
+    let atlas_width = ((area as f32).sqrt().ceil() as u32)
+        .max(widest)
+        .max(1)
+        .next_power_of_two();
+
+    let mut positions = Vec::with_capacity(bitmaps.len());
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for (_, width, height, _) in bitmaps.iter() {
+        if x + width > atlas_width {
+            x = 0;
+            y += row_height + GLYPH_ATLAS_PADDING;
+            row_height = 0;
+        }
+
+        positions.push((x, y));
+        x += width + GLYPH_ATLAS_PADDING;
+        row_height = row_height.max(*height);
+    }
+    let atlas_height = (y + row_height).max(1).next_power_of_two();
+
+    let mut buffer = [255, 255, 255, 0].repeat((atlas_width * atlas_height) as usize);
+    for ((character, width, height, bitmap), (x, y)) in bitmaps.iter().zip(positions) {
+        for row in 0..*height {
+            for column in 0..*width {
+                let texel = ((y + row) * atlas_width + x + column) as usize;
+                buffer[texel * 4 + 3] = bitmap[(row * width + column) as usize];
+            }
+        }
+
+        if let Some(glyph) = glyphs.get_mut(character) {
+            glyph.uv_rect = UvRect::from_pixels(x, y, *width, *height, atlas_width, atlas_height);
+        }
+    }
+
+    Texture::from_rgba8(
+        &format!("{}_atlas", name),
+        atlas_width,
+        atlas_height,
+        buffer,
+        None,
+    )
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Signed distance field of a `width` by `height` glyph `coverage`, grown by `spread` texels on every side. Texels on
+/// the outline map to 0.5, those `spread` texels inside or outside of it to 1 and 0.
+fn distance_field(coverage: &[f32], width: u32, height: u32, spread: u32) -> Vec<u8> {
+    let spread = spread.max(1);
+    let field_width = (width + 2 * spread) as usize;
+    let field_height = (height + 2 * spread) as usize;
+
+    let inside = (0..field_width * field_height)
+        .map(|index| {
+            let x = (index % field_width) as i64 - spread as i64;
+            let y = (index / field_width) as i64 - spread as i64;
+            x >= 0
+                && y >= 0
+                && x < width as i64
+                && y < height as i64
+                && coverage[(y as u32 * width + x as u32) as usize] >= 0.5
+        })
+        .collect::<Vec<bool>>();
+
+    let outside = inside.iter().map(|inside| !inside).collect::<Vec<bool>>();
+    let to_inside = squared_distances(&inside, field_width, field_height);
+    let to_outside = squared_distances(&outside, field_width, field_height);
+
+    // Distances are between texel centers, the outline lies half a texel from them
+    inside
+        .iter()
+        .zip(to_inside.iter().zip(to_outside.iter()))
+        .map(|(&inside, (&to_inside, &to_outside))| {
+            let distance = if inside {
+                -(to_outside.sqrt() - 0.5)
+            } else {
+                to_inside.sqrt() - 0.5
+            };
+            let value = 0.5 - distance / (2.0 * spread as f32);
+
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect()
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Squared distance from every texel of a `width` by `height` grid to the nearest `feature` texel, using the separable
+/// exact distance transform of Felzenszwalb and Huttenlocher.
+fn squared_distances(features: &[bool], width: usize, height: usize) -> Vec<f32> {
+    let mut distances = features
+        .iter()
+        .map(|&feature| {
+            if feature {
+                0.0
+            } else {
+                DISTANCE_FIELD_INFINITY
+            }
+        })
+        .collect::<Vec<f32>>();
+
+    let length = width.max(height);
+    let mut input = vec![0.0; length];
+    let mut output = vec![0.0; length];
+    let mut parabolas = vec![0; length];
+    let mut boundaries = vec![0.0; length + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            input[y] = distances[y * width + x];
+        }
+        distance_transform(
+            &input[..height],
+            &mut output,
+            &mut parabolas,
+            &mut boundaries,
+        );
+        for y in 0..height {
+            distances[y * width + x] = output[y];
+        }
+    }
+
+    for y in 0..height {
+        input[..width].copy_from_slice(&distances[y * width..(y + 1) * width]);
+        distance_transform(
+            &input[..width],
+            &mut output,
+            &mut parabolas,
+            &mut boundaries,
+        );
+        distances[y * width..(y + 1) * width].copy_from_slice(&output[..width]);
+    }
+
+    distances
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// One dimensional squared distance transform of `input` into `output`, as the lower envelope of the parabolas rooted at
+/// every sample.
+fn distance_transform(
+    input: &[f32],
+    output: &mut [f32],
+    parabolas: &mut [usize],
+    boundaries: &mut [f32],
+) {
+    let intersection = |q: usize, p: usize| {
+        let (q_f, p_f) = (q as f32, p as f32);
+        ((input[q] + q_f * q_f) - (input[p] + p_f * p_f)) / (2.0 * q_f - 2.0 * p_f)
+    };
+
+    let mut k = 0;
+    parabolas[0] = 0;
+    boundaries[0] = f32::NEG_INFINITY;
+    boundaries[1] = f32::INFINITY;
+
+    for q in 1..input.len() {
+        let mut s = intersection(q, parabolas[k]);
+        while s <= boundaries[k] {
+            k -= 1;
+            s = intersection(q, parabolas[k]);
+        }
+
+        k += 1;
+        parabolas[k] = q;
+        boundaries[k] = s;
+        boundaries[k + 1] = f32::INFINITY;
+    }
+
+    k = 0;
+    for (q, distance) in output.iter_mut().take(input.len()).enumerate() {
+        while boundaries[k + 1] < q as f32 {
+            k += 1;
+        }
+
+        let offset = q as f32 - parabolas[k] as f32;
+        *distance = offset * offset + input[parabolas[k]];
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,41 @@
+/// G-buffer channel shown in place of the lit scene while the deferred path is active.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GBufferChannel {
+    Albedo,
+    Normal,
+    Metallic,
+    Roughness,
+    Occlusion,
+    Emissive,
+    /// Linearized between the camera's near and far planes.
+    Depth,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl GBufferChannel {
+    pub const ALL: [GBufferChannel; 7] = [
+        GBufferChannel::Albedo,
+        GBufferChannel::Normal,
+        GBufferChannel::Metallic,
+        GBufferChannel::Roughness,
+        GBufferChannel::Occlusion,
+        GBufferChannel::Emissive,
+        GBufferChannel::Depth,
+    ];
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// NB! Must match the channel constants in gbuffer_debug.frag.
+    pub fn index(&self) -> u32 {
+        match self {
+            GBufferChannel::Albedo => 0,
+            GBufferChannel::Normal => 1,
+            GBufferChannel::Metallic => 2,
+            GBufferChannel::Roughness => 3,
+            GBufferChannel::Occlusion => 4,
+            GBufferChannel::Emissive => 5,
+            GBufferChannel::Depth => 6,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
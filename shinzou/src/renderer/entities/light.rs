@@ -0,0 +1,113 @@
+use ultraviolet::{Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::AmbientOcclusion;
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightType {
+    Directional,
+    Point,
+    Spot,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub light_type: LightType,
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+    pub inner_cone_angle_rad: f32,
+    pub outer_cone_angle_rad: f32,
+    /// Renders a shadow map for this light. Only directional and spot lights support shadows.
+    pub cast_shadows: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Light {
+    pub fn directional(direction: Vec3, color: Vec3, intensity: f32) -> Self {
+        Self {
+            light_type: LightType::Directional,
+            position: Vec3::zero(),
+            direction: direction.normalized(),
+            color,
+            intensity,
+            range: f32::MAX,
+            inner_cone_angle_rad: 0.0,
+            outer_cone_angle_rad: 0.0,
+            cast_shadows: false,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn point(position: Vec3, color: Vec3, intensity: f32, range: f32) -> Self {
+        Self {
+            light_type: LightType::Point,
+            position,
+            direction: Vec3::new(0.0, -1.0, 0.0),
+            color,
+            intensity,
+            range,
+            inner_cone_angle_rad: 0.0,
+            outer_cone_angle_rad: 0.0,
+            cast_shadows: false,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn spot(
+        position: Vec3,
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+        inner_cone_angle_rad: f32,
+        outer_cone_angle_rad: f32,
+    ) -> Self {
+        Self {
+            light_type: LightType::Spot,
+            position,
+            direction: direction.normalized(),
+            color,
+            intensity,
+            range,
+            inner_cone_angle_rad: inner_cone_angle_rad.min(outer_cone_angle_rad),
+            outer_cone_angle_rad,
+            cast_shadows: false,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_shadows(mut self, cast_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+pub struct Lighting {
+    /// RGB ambient color, with the intensity stored in the alpha channel. Replaced by image based lighting while an
+    /// environment is set.
+    pub ambient: Vec4,
+    pub ambient_occlusion: AmbientOcclusion,
+    /// Scales the skybox and the reflections of the environment cubemap, if one is set.
+    pub environment_intensity: f32,
+    pub lights: Vec<Light>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            ambient: Vec4::new(1.0, 1.0, 1.0, 0.1),
+            ambient_occlusion: AmbientOcclusion::default(),
+            environment_intensity: 1.0,
+            lights: Vec::new(),
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
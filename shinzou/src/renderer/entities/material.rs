@@ -1,10 +1,92 @@
 use std::path::PathBuf;
 //----------------------------------------------------------------------------------------------------------------------
 
+use ultraviolet::{Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const TEXTURE_SLOT_COUNT: usize = 5;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Material texture slots, following the glTF metallic-roughness model. The discriminant is the binding index in
+/// the material descriptor set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureSlot {
+    BaseColor = 0,
+    /// Roughness in the green channel, metallic in the blue channel.
+    MetallicRoughness = 1,
+    Normal = 2,
+    Occlusion = 3,
+    Emissive = 4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl TextureSlot {
+    pub const ALL: [TextureSlot; TEXTURE_SLOT_COUNT] = [
+        TextureSlot::BaseColor,
+        TextureSlot::MetallicRoughness,
+        TextureSlot::Normal,
+        TextureSlot::Occlusion,
+        TextureSlot::Emissive,
+    ];
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn binding(&self) -> u32 {
+        *self as u32
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// How a material's base color alpha affects what gets drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Alpha is ignored.
+    Opaque,
+    /// Fragments with an alpha below `PbrFactors::alpha_cutoff` are discarded, the rest are drawn opaque.
+    Cutout,
+    /// Blended over the opaque scene after it has been drawn, sorted back-to-front. Doesn't write depth.
+    Transparent,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug)]
+pub struct PbrFactors {
+    pub base_color: Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    pub emissive: Vec3,
+    /// Only used by `BlendMode::Cutout` materials.
+    pub alpha_cutoff: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for PbrFactors {
+    fn default() -> Self {
+        Self {
+            base_color: Vec4::one(),
+            metallic: 0.0,
+            roughness: 1.0,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            emissive: Vec3::zero(),
+            alpha_cutoff: 0.5,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
 pub struct Material {
     pub name: String,
     pub vertex_shader_path: PathBuf,
+    /// Also drawn while a pick is pending, the shader must then write the entity index as a `uint` to location 1 and
+    /// the bits of the view depth as a `uint` to location 2, like `shader.frag` does.
     pub fragment_shader_path: PathBuf,
+    /// Texture names per `TextureSlot`. Missing maps are bound to 1x1 defaults which leave the factors unchanged.
+    pub textures: [Option<String>; TEXTURE_SLOT_COUNT],
+    pub factors: PbrFactors,
+    pub blend_mode: BlendMode,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -14,7 +96,44 @@ impl Material {
             name,
             vertex_shader_path,
             fragment_shader_path,
+            textures: Default::default(),
+            factors: PbrFactors::default(),
+            blend_mode: BlendMode::Opaque,
         }
     }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Material using the Cook-Torrance GGX metallic-roughness shaders.
+    pub fn pbr(name: String) -> Self {
+        Self::new(
+            name,
+            PathBuf::from("resources/shaders/dist/shader.vert.spv"),
+            PathBuf::from("resources/shaders/dist/pbr.frag.spv"),
+        )
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_texture(mut self, slot: TextureSlot, texture_name: &str) -> Self {
+        self.textures[slot as usize] = Some(texture_name.to_owned());
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_factors(mut self, factors: PbrFactors) -> Self {
+        self.factors = factors;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn texture(&self, slot: TextureSlot) -> Option<&String> {
+        self.textures[slot as usize].as_ref()
+    }
+    //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
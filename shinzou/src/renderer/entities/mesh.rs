@@ -5,6 +5,9 @@ use tobj;
 use ultraviolet::{Vec2, Vec3};
 //----------------------------------------------------------------------------------------------------------------------
 
+use crate::utils::ray::Ray;
+//----------------------------------------------------------------------------------------------------------------------
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub struct Vertex {
@@ -30,6 +33,7 @@ impl Vertex {
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+#[derive(Clone)]
 pub struct Mesh {
     pub name: String,
     pub vertices: Vec<Vertex>,
@@ -37,6 +41,42 @@ pub struct Mesh {
 //----------------------------------------------------------------------------------------------------------------------
 
 impl Mesh {
+    /// Minimum and maximum corners of the object space box around the vertices.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let mut vertices = self.vertices.iter();
+
+        match vertices.next() {
+            Some(first) => vertices.fold((first.position, first.position), |(min, max), vertex| {
+                (
+                    min.min_by_component(vertex.position),
+                    max.max_by_component(vertex.position),
+                )
+            }),
+            None => (Vec3::zero(), Vec3::zero()),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Closest object space hit of `ray` against the triangles, culled by the bounding box first.
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let (min, max) = self.bounds();
+        ray.intersect_aabb(min, max)?;
+
+        self.vertices
+            .chunks_exact(3)
+            .filter_map(|triangle| {
+                ray.intersect_triangle(
+                    triangle[0].position,
+                    triangle[1].position,
+                    triangle[2].position,
+                )
+            })
+            .fold(None, |closest: Option<f32>, t| {
+                Some(closest.map_or(t, |closest| closest.min(t)))
+            })
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     #[allow(dead_code)]
     pub fn test_triangle() -> Self {
         const COLOR: Vec3 = Vec3 {
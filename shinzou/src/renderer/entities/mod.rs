@@ -1,13 +1,37 @@
+mod ambient_occlusion;
 mod camera;
+mod camera_controller;
+mod debug_draw;
+mod debug_view;
+mod font;
+mod gbuffer;
+mod light;
 mod material;
 mod mesh;
+mod picking;
+mod post_process;
 mod renderable;
+mod selection_outline;
+mod sprite;
+mod text;
 mod texture;
 //----------------------------------------------------------------------------------------------------------------------
 
+pub use ambient_occlusion::*;
 pub use camera::*;
+pub use camera_controller::*;
+pub use debug_draw::*;
+pub use debug_view::*;
+pub use font::*;
+pub use gbuffer::*;
+pub use light::*;
 pub use material::*;
 pub use mesh::*;
+pub use picking::*;
+pub use post_process::*;
 pub use renderable::*;
+pub use selection_outline::*;
+pub use sprite::*;
+pub use text::*;
 pub use texture::*;
 //----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,29 @@
+use ultraviolet::Vec3;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Closest renderable hit by a ray cast against the scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// Index of the renderable in the scene.
+    pub renderable_index: usize,
+    /// Distance from the ray origin, in world units for normalized ray directions.
+    pub distance: f32,
+    pub position: Vec3,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Renderable covering a window pixel, read back from the GPU picking targets a few frames after `Renderer::pick`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickResult {
+    /// Window pixel that was picked, counted from the top left corner.
+    pub x: u32,
+    pub y: u32,
+    /// Index of the renderable in the scene as it was on the picked frame, `None` when the pixel only shows the
+    /// background. The scene may have changed since, prefer `id` to find the renderable again.
+    pub renderable_index: Option<usize>,
+    /// `InstanceData::id` of the renderable at `renderable_index` on the picked frame.
+    pub id: Option<u32>,
+    /// Distance along the view direction of the picked surface in world units, infinite when there is none.
+    pub depth: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,96 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tonemapper {
+    Reinhard,
+    Aces,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostEffect {
+    Exposure,
+    Tonemapping,
+    Bloom,
+    Fxaa,
+    Vignette,
+    ColorGrading,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Settings of the post-process chain that turns the HDR scene into the swapchain image. Bloom runs first, exposure,
+/// tonemapping, vignette and color grading share a single pass, FXAA runs last on the tonemapped image.
+#[derive(Clone, Debug)]
+pub struct PostProcess {
+    pub exposure_enabled: bool,
+    /// Exposure compensation in stops, scene radiance is scaled by `2^exposure`.
+    pub exposure: f32,
+    /// Without tonemapping, radiance above 1.0 is clipped.
+    pub tonemapping_enabled: bool,
+    pub tonemapper: Tonemapper,
+    pub bloom_enabled: bool,
+    /// Brightness above which pixels start to bloom.
+    pub bloom_threshold: f32,
+    /// Width of the soft transition below the threshold.
+    pub bloom_knee: f32,
+    pub bloom_intensity: f32,
+    pub fxaa_enabled: bool,
+    pub vignette_enabled: bool,
+    pub vignette_intensity: f32,
+    /// Distance over which the vignette fades in, as a fraction of the distance from center to corner.
+    pub vignette_smoothness: f32,
+    pub color_grading_enabled: bool,
+    /// Name of the texture holding the grading LUT. The LUT is a horizontal strip of N slices of NxN texels, with red
+    /// increasing along each slice, green downwards and blue from slice to slice. An identity LUT is used if `None`.
+    pub color_grading_lut: Option<String>,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl PostProcess {
+    pub fn is_enabled(&self, effect: PostEffect) -> bool {
+        match effect {
+            PostEffect::Exposure => self.exposure_enabled,
+            PostEffect::Tonemapping => self.tonemapping_enabled,
+            PostEffect::Bloom => self.bloom_enabled,
+            PostEffect::Fxaa => self.fxaa_enabled,
+            PostEffect::Vignette => self.vignette_enabled,
+            PostEffect::ColorGrading => self.color_grading_enabled,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_enabled(&mut self, effect: PostEffect, enabled: bool) {
+        let flag = match effect {
+            PostEffect::Exposure => &mut self.exposure_enabled,
+            PostEffect::Tonemapping => &mut self.tonemapping_enabled,
+            PostEffect::Bloom => &mut self.bloom_enabled,
+            PostEffect::Fxaa => &mut self.fxaa_enabled,
+            PostEffect::Vignette => &mut self.vignette_enabled,
+            PostEffect::ColorGrading => &mut self.color_grading_enabled,
+        };
+
+        *flag = enabled;
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self {
+            exposure_enabled: true,
+            exposure: 0.0,
+            tonemapping_enabled: true,
+            tonemapper: Tonemapper::Aces,
+            bloom_enabled: true,
+            bloom_threshold: 1.0,
+            bloom_knee: 0.5,
+            bloom_intensity: 0.05,
+            fxaa_enabled: true,
+            vignette_enabled: false,
+            vignette_intensity: 0.35,
+            vignette_smoothness: 0.5,
+            color_grading_enabled: false,
+            color_grading_lut: None,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
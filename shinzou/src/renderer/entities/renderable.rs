@@ -1,7 +1,43 @@
 use std::cmp::Ordering;
 //----------------------------------------------------------------------------------------------------------------------
 
-use ultraviolet::Mat4;
+use ultraviolet::{Mat4, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+pub const INSTANCE_CUSTOM_DATA_LEN: usize = 4;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Per-instance values uploaded to the entity meta SSBO, readable from every shader stage.
+#[derive(Clone, Copy, Debug)]
+pub struct InstanceData {
+    pub tint: Vec4,
+    pub emissive: f32,
+    pub id: u32,
+    pub custom: [f32; INSTANCE_CUSTOM_DATA_LEN],
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for InstanceData {
+    fn default() -> Self {
+        Self {
+            tint: Vec4::one(),
+            emissive: 0.0,
+            id: 0,
+            custom: [0.0; INSTANCE_CUSTOM_DATA_LEN],
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Outline drawn around a renderable, in the colors of the renderer's `SelectionOutline` settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Highlight {
+    #[default]
+    None,
+    Hovered,
+    /// Drawn over the outlines of hovered renderables.
+    Selected,
+}
 //----------------------------------------------------------------------------------------------------------------------
 
 #[repr(C)]
@@ -9,6 +45,10 @@ pub struct Renderable {
     pub mesh_name: String,
     pub material_name: String,
     pub transform: Mat4,
+    pub instance_data: InstanceData,
+    pub cast_shadows: bool,
+    pub receive_shadows: bool,
+    pub highlight: Highlight,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -18,11 +58,59 @@ impl Renderable {
             mesh_name,
             material_name,
             transform,
+            instance_data: InstanceData::default(),
+            cast_shadows: true,
+            receive_shadows: true,
+            highlight: Highlight::None,
         }
     }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_instance_data(mut self, instance_data: InstanceData) -> Self {
+        self.instance_data = instance_data;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_tint(mut self, tint: Vec4) -> Self {
+        self.instance_data.tint = tint;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_emissive(mut self, emissive: f32) -> Self {
+        self.instance_data.emissive = emissive;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_id(mut self, id: u32) -> Self {
+        self.instance_data.id = id;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_cast_shadows(mut self, cast_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_receive_shadows(mut self, receive_shadows: bool) -> Self {
+        self.receive_shadows = receive_shadows;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_highlight(mut self, highlight: Highlight) -> Self {
+        self.highlight = highlight;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+// Draw order no longer depends on it, the backend sorts every frame by packed keys
 impl Ord for Renderable {
     fn cmp(&self, other: &Self) -> Ordering {
         match self.material_name.cmp(&other.material_name) {
@@ -0,0 +1,32 @@
+use ultraviolet::Vec4;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Settings of the outlines drawn around highlighted renderables. Outlines show through other geometry, and are drawn
+/// over the post-processed frame so their colors are used as is.
+#[derive(Clone, Debug)]
+pub struct SelectionOutline {
+    /// Outlines need a stencil depth format, they stay off on devices without one whatever this is set to.
+    pub enabled: bool,
+    pub selected_color: Vec4,
+    pub hovered_color: Vec4,
+    /// In pixels, clamped to `MAX_THICKNESS`.
+    pub thickness: f32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl SelectionOutline {
+    pub const MAX_THICKNESS: f32 = 8.0;
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for SelectionOutline {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            selected_color: Vec4::new(1.0, 0.55, 0.1, 1.0),
+            hovered_color: Vec4::new(0.4, 0.75, 1.0, 0.8),
+            thickness: 2.0,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,329 @@
+use ultraviolet::{Vec2, Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Region of a texture in normalized coordinates, `min` being its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvRect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl UvRect {
+    pub const FULL: Self = Self {
+        min: Vec2 { x: 0.0, y: 0.0 },
+        max: Vec2 { x: 1.0, y: 1.0 },
+    };
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Region of `width` by `height` texels at `(x, y)` in an atlas of `atlas_width` by `atlas_height` texels.
+    pub fn from_pixels(
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        atlas_width: u32,
+        atlas_height: u32,
+    ) -> Self {
+        let atlas_size = Vec2::new(atlas_width as f32, atlas_height as f32);
+        let min = Vec2::new(x as f32, y as f32) / atlas_size;
+        let max = Vec2::new((x + width) as f32, (y + height) as f32) / atlas_size;
+
+        Self { min, max }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Cell `index` of an atlas split into a grid of `columns` by `rows` equally sized cells, counted row by row from
+    /// the top left.
+    pub fn grid_cell(columns: u32, rows: u32, index: u32) -> Self {
+        let columns = columns.max(1);
+        let rows = rows.max(1);
+        let cell_size = Vec2::new(1.0 / columns as f32, 1.0 / rows as f32);
+        let min = Vec2::new((index % columns) as f32, (index / columns % rows) as f32) * cell_size;
+
+        Self {
+            min,
+            max: min + cell_size,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Default for UvRect {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Splits a sprite into a 3x3 grid whose corners keep their size while the edges and center stretch, so panels and
+/// buttons can be resized without distorting their borders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NineSlice {
+    /// Left, top, right and bottom border sizes in pixels on screen.
+    pub borders: Vec4,
+    /// Left, top, right and bottom border sizes as fractions of the sprite's UV rectangle.
+    pub uv_borders: Vec4,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl NineSlice {
+    pub fn new(borders: Vec4, uv_borders: Vec4) -> Self {
+        Self {
+            borders,
+            uv_borders,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Same border on every side, `border` pixels on screen and `uv_border` of the UV rectangle.
+    pub fn uniform(border: f32, uv_border: f32) -> Self {
+        Self::new(Vec4::broadcast(border), Vec4::broadcast(uv_border))
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Textured quad drawn in screen space over the 3D scene. Positions and sizes are in window pixels, with the origin at
+/// the top left corner and y pointing down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sprite {
+    /// Texture loaded with `Renderer::load_texture` or passed to `init_resources`, `None` draws a solid quad.
+    pub texture_name: Option<String>,
+    /// Where the pivot lies on screen.
+    pub position: Vec2,
+    pub size: Vec2,
+    /// In radians, clockwise around the pivot.
+    pub rotation: f32,
+    /// Point the sprite is positioned and rotated by, as a fraction of its size from its top left corner.
+    pub pivot: Vec2,
+    /// Multiplies the texture color, in linear space.
+    pub tint: Vec4,
+    pub uv_rect: UvRect,
+    /// Sprites on higher layers are drawn over lower ones, those on the same layer in the order they were added.
+    pub layer: i32,
+    pub nine_slice: Option<NineSlice>,
+    /// The texture's alpha holds a signed distance field, e.g. of font glyphs, drawn with edges smoothed at any scale.
+    pub distance_field: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Sprite {
+    pub fn new(texture_name: &str, position: Vec2, size: Vec2) -> Self {
+        Self {
+            texture_name: Some(texture_name.to_owned()),
+            ..Self::solid(position, size, Vec4::one())
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Untextured quad of a single color.
+    pub fn solid(position: Vec2, size: Vec2, color: Vec4) -> Self {
+        Self {
+            texture_name: None,
+            position,
+            size,
+            rotation: 0.0,
+            pivot: Vec2::zero(),
+            tint: color,
+            uv_rect: UvRect::FULL,
+            layer: 0,
+            nine_slice: None,
+            distance_field: false,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = pivot;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_tint(mut self, tint: Vec4) -> Self {
+        self.tint = tint;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_uv_rect(mut self, uv_rect: UvRect) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = Some(nine_slice);
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_distance_field(mut self, distance_field: bool) -> Self {
+        self.distance_field = distance_field;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Screen position of a point given in pixels from the sprite's top left corner, before rotation.
+    pub fn to_screen(&self, local: Vec2) -> Vec2 {
+        let offset = local - self.pivot * self.size;
+        let (sin, cos) = self.rotation.sin_cos();
+
+        self.position
+            + Vec2::new(
+                offset.x * cos - offset.y * sin,
+                offset.x * sin + offset.y * cos,
+            )
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Quads making up the sprite, as their top left and bottom right corners in pixels from the sprite's top left
+    /// corner, along with their UV rectangles. A single quad unless the sprite is nine-sliced.
+    pub fn quads(&self) -> Vec<(Vec2, Vec2, UvRect)> {
+        let nine_slice = match self.nine_slice {
+            Some(nine_slice) => nine_slice,
+            None => return vec![(Vec2::zero(), self.size, self.uv_rect)],
+        };
+
+        // Borders shrink together when the sprite is smaller than both of them
+        let scale = |start: f32, end: f32, size: f32| {
+            let total = start + end;
+            if total > size && total > 0.0 {
+                size / total
+            } else {
+                1.0
+            }
+        };
+        let scale_x = scale(nine_slice.borders.x, nine_slice.borders.z, self.size.x);
+        let scale_y = scale(nine_slice.borders.y, nine_slice.borders.w, self.size.y);
+
+        let xs = [
+            0.0,
+            nine_slice.borders.x * scale_x,
+            self.size.x - nine_slice.borders.z * scale_x,
+            self.size.x,
+        ];
+        let ys = [
+            0.0,
+            nine_slice.borders.y * scale_y,
+            self.size.y - nine_slice.borders.w * scale_y,
+            self.size.y,
+        ];
+
+        let UvRect { min, max } = self.uv_rect;
+        let uv_size = self.uv_rect.size();
+        let us = [
+            min.x,
+            min.x + nine_slice.uv_borders.x * uv_size.x,
+            max.x - nine_slice.uv_borders.z * uv_size.x,
+            max.x,
+        ];
+        let vs = [
+            min.y,
+            min.y + nine_slice.uv_borders.y * uv_size.y,
+            max.y - nine_slice.uv_borders.w * uv_size.y,
+            max.y,
+        ];
+
+        let mut quads = Vec::with_capacity(9);
+        for row in 0..3 {
+            for column in 0..3 {
+                quads.push((
+                    Vec2::new(xs[column], ys[row]),
+                    Vec2::new(xs[column + 1], ys[row + 1]),
+                    UvRect::new(
+                        Vec2::new(us[column], vs[row]),
+                        Vec2::new(us[column + 1], vs[row + 1]),
+                    ),
+                ));
+            }
+        }
+
+        quads
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Textured quad placed in the world that always faces the camera, sizes being in world units. Hidden by the scene in
+/// front of it, without hiding anything itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Billboard {
+    /// Same as `Sprite::texture_name`.
+    pub texture_name: Option<String>,
+    /// Point in the world the quad is placed around.
+    pub position: Vec3,
+    /// Top left corner of the quad from `position`, in the plane facing the camera with y pointing down.
+    pub offset: Vec2,
+    pub size: Vec2,
+    /// Multiplies the texture color, in linear space.
+    pub tint: Vec4,
+    pub uv_rect: UvRect,
+    /// Same as `Sprite::distance_field`.
+    pub distance_field: bool,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Billboard {
+    /// Quad centered on `position`.
+    pub fn new(texture_name: &str, position: Vec3, size: Vec2) -> Self {
+        Self {
+            texture_name: Some(texture_name.to_owned()),
+            position,
+            offset: size * -0.5,
+            size,
+            tint: Vec4::one(),
+            uv_rect: UvRect::FULL,
+            distance_field: false,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_tint(mut self, tint: Vec4) -> Self {
+        self.tint = tint;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_uv_rect(mut self, uv_rect: UvRect) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_distance_field(mut self, distance_field: bool) -> Self {
+        self.distance_field = distance_field;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
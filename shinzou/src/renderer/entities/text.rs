@@ -0,0 +1,274 @@
+use ultraviolet::{Vec2, Vec3, Vec4};
+//----------------------------------------------------------------------------------------------------------------------
+
+use crate::renderer::entities::{Billboard, Font, FontGlyph, Sprite, UvRect};
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlignment {
+    Left,
+    Center,
+    Right,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextPlacement {
+    /// Window pixels from the top left corner, drawn over everything like sprites.
+    Screen(Vec2),
+    /// Point in the world the text is placed around, facing the camera and hidden by the scene in front of it. Sizes are
+    /// in world units.
+    World(Vec3),
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Glyph quad of laid out text, from the top left corner of the text block with y pointing down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LaidOutGlyph {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub uv_rect: UvRect,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<LaidOutGlyph>,
+    /// Width of the widest line, or the maximum width when wrapping, by the height of all lines.
+    pub size: Vec2,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// String drawn with a font loaded by `Renderer::load_font`, either on screen or in the world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Text {
+    pub content: String,
+    pub font_name: String,
+    pub placement: TextPlacement,
+    /// Height of a line from its descent to its ascent, in pixels on screen or world units.
+    pub size: f32,
+    /// In linear space.
+    pub color: Vec4,
+    pub alignment: TextAlignment,
+    /// Lines are wrapped between words to fit, words that don't fit on their own are broken up.
+    pub max_width: Option<f32>,
+    /// Multiplies the distance between consecutive lines.
+    pub line_spacing: f32,
+    /// Point of the text block at its placement, as a fraction of its size from its top left corner.
+    pub pivot: Vec2,
+    /// Sprite layer of screen text, see `Sprite::layer`.
+    pub layer: i32,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Text {
+    /// Text whose top left corner is at `position` in window pixels.
+    pub fn screen(content: &str, font_name: &str, position: Vec2, size: f32) -> Self {
+        Self {
+            content: content.to_owned(),
+            font_name: font_name.to_owned(),
+            placement: TextPlacement::Screen(position),
+            size,
+            color: Vec4::one(),
+            alignment: TextAlignment::Left,
+            max_width: None,
+            line_spacing: 1.0,
+            pivot: Vec2::zero(),
+            layer: 0,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Label whose bottom center is at `position` in the world, e.g. above an object.
+    pub fn world(content: &str, font_name: &str, position: Vec3, size: f32) -> Self {
+        Self {
+            placement: TextPlacement::World(position),
+            alignment: TextAlignment::Center,
+            pivot: Vec2::new(0.5, 1.0),
+            ..Self::screen(content, font_name, Vec2::zero(), size)
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = pivot;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Positions the glyphs of the content with `font`, applying kerning, line breaks, wrapping and alignment.
+    pub fn layout(&self, font: &Font) -> TextLayout {
+        let scale = self.size / font.pixel_height();
+        let max_width = self.max_width.map(|max_width| max_width / scale);
+
+        // Laid out at the baked size first, then scaled
+        let mut lines = Vec::new();
+        for paragraph in self.content.split('\n') {
+            let mut line: Vec<(char, f32, FontGlyph)> = Vec::new();
+            let mut pen = 0.0;
+            // Index of the glyph following the last space, where the line can be broken
+            let mut break_index = None;
+
+            for character in paragraph
+                .chars()
+                .filter(|character| !character.is_control())
+            {
+                let glyph = match font.glyph(character) {
+                    Some(glyph) => *glyph,
+                    None => continue,
+                };
+
+                let mut x = pen;
+                if let Some((_, _, previous)) = line.last() {
+                    x += font.kerning(previous, &glyph);
+                }
+
+                if character == ' ' {
+                    break_index = Some(line.len() + 1);
+                } else if let Some(max_width) = max_width {
+                    if x + glyph.advance > max_width && !line.is_empty() {
+                        // Break after the last space, or right here when the word doesn't fit on its own
+                        let split = break_index.take().unwrap_or(line.len());
+
+                        let mut next_line = line.split_off(split);
+                        lines.push(line);
+
+                        let start = next_line.first().map_or(x, |(_, x, _)| *x);
+                        for (_, glyph_x, _) in next_line.iter_mut() {
+                            *glyph_x -= start;
+                        }
+                        x -= start;
+                        line = next_line;
+                    }
+                }
+
+                line.push((character, x, glyph));
+                pen = x + glyph.advance;
+            }
+
+            lines.push(line);
+        }
+
+        // Trailing spaces don't count towards the width of a line
+        let line_widths = lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .filter(|(character, _, _)| *character != ' ')
+                    .map(|(_, x, glyph)| x + glyph.advance)
+                    .fold(0.0, f32::max)
+            })
+            .collect::<Vec<f32>>();
+        let block_width =
+            max_width.unwrap_or_else(|| line_widths.iter().copied().fold(0.0, f32::max));
+
+        let line_height = font.line_height() * self.line_spacing;
+        let mut glyphs = Vec::new();
+        for (index, (line, line_width)) in lines.iter().zip(line_widths.iter()).enumerate() {
+            let indent = match self.alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => (block_width - line_width) * 0.5,
+                TextAlignment::Right => block_width - line_width,
+            };
+            let baseline = index as f32 * line_height + font.ascent();
+
+            for (_, x, glyph) in line.iter().filter(|(_, _, glyph)| glyph.size.x > 0.0) {
+                let min = Vec2::new(indent + x, baseline) + glyph.offset;
+                glyphs.push(LaidOutGlyph {
+                    min: min * scale,
+                    max: (min + glyph.size) * scale,
+                    uv_rect: glyph.uv_rect,
+                });
+            }
+        }
+
+        let block_height = (lines.len() - 1) as f32 * line_height + font.ascent() - font.descent();
+
+        TextLayout {
+            glyphs,
+            size: Vec2::new(block_width, block_height) * scale,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Glyph sprites of screen text, nothing for world text.
+    pub fn to_sprites(&self, font: &Font) -> Vec<Sprite> {
+        let position = match self.placement {
+            TextPlacement::Screen(position) => position,
+            TextPlacement::World(_) => return Vec::new(),
+        };
+
+        let layout = self.layout(font);
+        let origin = position - self.pivot * layout.size;
+        let atlas_name = font.atlas_name();
+
+        layout
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                Sprite::new(&atlas_name, origin + glyph.min, glyph.max - glyph.min)
+                    .with_uv_rect(glyph.uv_rect)
+                    .with_tint(self.color)
+                    .with_layer(self.layer)
+                    .with_distance_field(font.is_distance_field())
+            })
+            .collect()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Glyph billboards of world text, nothing for screen text.
+    pub fn to_billboards(&self, font: &Font) -> Vec<Billboard> {
+        let position = match self.placement {
+            TextPlacement::Screen(_) => return Vec::new(),
+            TextPlacement::World(position) => position,
+        };
+
+        let layout = self.layout(font);
+        let origin = -self.pivot * layout.size;
+        let atlas_name = font.atlas_name();
+
+        layout
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                Billboard::new(&atlas_name, position, glyph.max - glyph.min)
+                    .with_offset(origin + glyph.min)
+                    .with_uv_rect(glyph.uv_rect)
+                    .with_tint(self.color)
+                    .with_distance_field(font.is_distance_field())
+            })
+            .collect()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------
@@ -1,13 +1,42 @@
-use std::path::PathBuf;
+use std::{fs::File, io::BufReader, path::PathBuf};
 //----------------------------------------------------------------------------------------------------------------------
 
+use image::codecs::hdr::HdrDecoder;
 #[allow(unused_imports)]
 use image::GenericImageView;
+use ultraviolet::Vec3;
 //----------------------------------------------------------------------------------------------------------------------
 
+/// Number of faces of a cubemap, stored as array layers in +X, -X, +Y, -Y, +Z, -Z order.
+pub const CUBEMAP_FACE_COUNT: u32 = 6;
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MapType {
     Diffuse,
     Normal,
+    MetallicRoughness,
+    Occlusion,
+    Emissive,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl MapType {
+    /// Whether texel values are sRGB-encoded colors (as opposed to linear data such as normals or roughness).
+    pub fn is_srgb(&self) -> bool {
+        match self {
+            MapType::Diffuse | MapType::Emissive => true,
+            MapType::Normal | MapType::MetallicRoughness | MapType::Occlusion => false,
+        }
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureDimension {
+    D2,
+    /// Six square faces, sampled with a direction.
+    Cube,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -15,15 +44,22 @@ pub struct Texture {
     pub name: String,
     pub file_path: PathBuf,
     pub map_type: MapType,
+    pub dimension: TextureDimension,
+    pub data: Option<TextureRaw>,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
+#[derive(Clone)]
 pub struct TextureRaw {
     pub buffer: Vec<u8>,
     pub width: u32,
     pub height: u32,
     pub bit_depth: u32,
     pub size: u32,
+    /// 1 for 2D textures, `CUBEMAP_FACE_COUNT` for cubemaps, with the faces stored one after the other.
+    pub layers: u32,
+    /// Texels are RGBA16 half floats instead of RGBA8.
+    pub hdr: bool,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -33,6 +69,201 @@ impl Texture {
             name: name.to_owned(),
             file_path,
             map_type: map_type.unwrap_or(MapType::Diffuse),
+            dimension: TextureDimension::D2,
+            data: None,
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Creates a texture from tightly packed RGBA8 texels instead of an image file.
+    pub fn from_rgba8(
+        name: &String,
+        width: u32,
+        height: u32,
+        buffer: Vec<u8>,
+        map_type: Option<MapType>,
+    ) -> Self {
+        let bit_depth = 4;
+        let size = width * height * bit_depth;
+        assert_eq!(
+            size as usize,
+            buffer.len(),
+            "Texture::from_rgba8 - Buffer size does not match texture dimensions!"
+        );
+
+        Self {
+            name: name.to_owned(),
+            file_path: PathBuf::new(),
+            map_type: map_type.unwrap_or(MapType::Diffuse),
+            dimension: TextureDimension::D2,
+            data: Some(TextureRaw {
+                buffer,
+                width,
+                height,
+                bit_depth,
+                size,
+                layers: 1,
+                hdr: false,
+            }),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Loads a cubemap from six square images of the same size, in +X, -X, +Y, -Y, +Z, -Z order.
+    pub fn cubemap_from_faces(name: &String, face_paths: [PathBuf; 6]) -> Self {
+        let faces = face_paths
+            .iter()
+            .map(|path| Texture::new(name, path.clone(), None).load_raw_from_file())
+            .collect::<Vec<TextureRaw>>();
+
+        let TextureRaw { width, height, .. } = faces[0];
+        assert!(
+            width == height
+                && faces
+                    .iter()
+                    .all(|face| face.width == width && face.height == height),
+            "Texture::cubemap_from_faces - Cubemap faces must be square and of the same size!"
+        );
+
+        let buffer = faces
+            .into_iter()
+            .flat_map(|face| face.buffer)
+            .collect::<Vec<u8>>();
+
+        Self {
+            name: name.to_owned(),
+            file_path: face_paths[0].clone(),
+            map_type: MapType::Diffuse,
+            dimension: TextureDimension::Cube,
+            data: Some(TextureRaw {
+                size: buffer.len() as u32,
+                buffer,
+                width,
+                height,
+                bit_depth: 4,
+                layers: CUBEMAP_FACE_COUNT,
+                hdr: false,
+            }),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Loads an equirectangular Radiance HDR image and resamples it into a cubemap with faces of `face_size` texels.
+    pub fn cubemap_from_equirectangular(name: &String, file_path: PathBuf, face_size: u32) -> Self {
+        let file = File::open(&file_path).unwrap_or_else(|_| {
+            panic!(
+                "Texture::cubemap_from_equirectangular - Failed to open {:?}",
+                &file_path
+            )
+        });
+        let decoder = HdrDecoder::new(BufReader::new(file)).unwrap_or_else(|_| {
+            panic!(
+                "Texture::cubemap_from_equirectangular - {:?} is not a Radiance HDR image",
+                &file_path
+            )
+        });
+        let metadata = decoder.metadata();
+        let texels = decoder.read_image_hdr().unwrap_or_else(|_| {
+            panic!(
+                "Texture::cubemap_from_equirectangular - Failed to decode {:?}",
+                &file_path
+            )
+        });
+
+        let (source_width, source_height) = (metadata.width, metadata.height);
+        let source_texel = |x: u32, y: u32| {
+            let texel =
+                texels[(y.min(source_height - 1) * source_width + x % source_width) as usize];
+            Vec3::new(texel[0], texel[1], texel[2])
+        };
+
+        // Bilinear lookup, wrapping around horizontally
+        let sample = |direction: Vec3| {
+            let longitude = direction.z.atan2(direction.x);
+            let latitude = direction.y.clamp(-1.0, 1.0).asin();
+            let u = (0.5 + longitude / (2.0 * std::f32::consts::PI)) * source_width as f32 - 0.5;
+            let v = (0.5 - latitude / std::f32::consts::PI) * source_height as f32 - 0.5;
+
+            let (x0, y0) = (u.floor(), v.floor().max(0.0));
+            let (tx, ty) = (u - x0, (v - y0).clamp(0.0, 1.0));
+            let x0 = (x0 as i64).rem_euclid(source_width as i64) as u32;
+            let y0 = y0 as u32;
+
+            let top = source_texel(x0, y0) * (1.0 - tx) + source_texel(x0 + 1, y0) * tx;
+            let bottom = source_texel(x0, y0 + 1) * (1.0 - tx) + source_texel(x0 + 1, y0 + 1) * tx;
+            top * (1.0 - ty) + bottom * ty
+        };
+
+        let bit_depth = 8;
+        let mut buffer =
+            Vec::with_capacity((face_size * face_size * bit_depth * CUBEMAP_FACE_COUNT) as usize);
+        for face in 0..CUBEMAP_FACE_COUNT {
+            for y in 0..face_size {
+                for x in 0..face_size {
+                    let u = 2.0 * (x as f32 + 0.5) / face_size as f32 - 1.0;
+                    let v = 2.0 * (y as f32 + 0.5) / face_size as f32 - 1.0;
+                    let color = sample(cubemap_direction(face, u, v).normalized());
+
+                    for channel in [color.x, color.y, color.z, 1.0].iter() {
+                        buffer.extend_from_slice(&f32_to_f16(*channel).to_le_bytes());
+                    }
+                }
+            }
+        }
+
+        Self {
+            name: name.to_owned(),
+            file_path,
+            map_type: MapType::Diffuse,
+            dimension: TextureDimension::Cube,
+            data: Some(TextureRaw {
+                size: buffer.len() as u32,
+                buffer,
+                width: face_size,
+                height: face_size,
+                bit_depth,
+                layers: CUBEMAP_FACE_COUNT,
+                hdr: true,
+            }),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Single color cubemap, in linear HDR values.
+    pub fn solid_color_cubemap(name: &String, color: Vec3) -> Self {
+        let texel = [color.x, color.y, color.z, 1.0]
+            .iter()
+            .flat_map(|channel| f32_to_f16(*channel).to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        let buffer = texel.repeat(CUBEMAP_FACE_COUNT as usize);
+
+        Self {
+            name: name.to_owned(),
+            file_path: PathBuf::new(),
+            map_type: MapType::Diffuse,
+            dimension: TextureDimension::Cube,
+            data: Some(TextureRaw {
+                size: buffer.len() as u32,
+                buffer,
+                width: 1,
+                height: 1,
+                bit_depth: 8,
+                layers: CUBEMAP_FACE_COUNT,
+                hdr: true,
+            }),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn solid_color(name: &String, rgba: [u8; 4], map_type: Option<MapType>) -> Self {
+        Self::from_rgba8(name, 1, 1, rgba.to_vec(), map_type)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn load_raw(&self) -> TextureRaw {
+        match &self.data {
+            Some(raw) => raw.clone(),
+            None => self.load_raw_from_file(),
         }
     }
     //------------------------------------------------------------------------------------------------------------------
@@ -49,13 +280,48 @@ impl Texture {
         let size = width * height * bit_depth;
 
         TextureRaw {
-            buffer: img.into_bytes(),
+            buffer: img.to_rgba8().into_raw(),
             width,
             height,
             bit_depth,
             size,
+            layers: 1,
+            hdr: false,
         }
     }
     //------------------------------------------------------------------------------------------------------------------
 }
 //----------------------------------------------------------------------------------------------------------------------
+
+/// Direction through texel `(u, v)` of a cubemap face, both in [-1, 1], following the Vulkan face orientations.
+fn cubemap_direction(face: u32, u: f32, v: f32) -> Vec3 {
+    match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0),
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+/// IEEE half precision bits of `value`, rounding toward zero. Values beyond the half range become infinity and those
+/// too small for a normal half become zero, which is plenty for radiance.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x007f_ffff;
+
+    if value.is_nan() {
+        sign | 0x7e00
+    } else if exponent >= 0x1f {
+        sign | 0x7c00
+    } else if exponent <= 0 {
+        sign
+    } else {
+        sign | ((exponent as u16) << 10) | (mantissa >> 13) as u16
+    }
+}
+//----------------------------------------------------------------------------------------------------------------------
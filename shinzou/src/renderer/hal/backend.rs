@@ -1,4 +1,10 @@
-use crate::renderer::entities::{Camera, Material, Mesh, Renderable, Texture};
+use crate::renderer::{
+    entities::{
+        Billboard, Camera, DebugDraw, DebugViewMode, GBufferChannel, Lighting, Material, Mesh,
+        PickResult, PostProcess, Renderable, SelectionOutline, Sprite, Texture,
+    },
+    RenderPath,
+};
 //----------------------------------------------------------------------------------------------------------------------
 
 pub trait RendererBackend {
@@ -8,7 +14,38 @@ pub trait RendererBackend {
         meshes: Vec<Mesh>,
         textures: Vec<Texture>,
     );
-    fn draw(&mut self, camera: &Camera, renderables: &[Renderable]);
+    #[allow(clippy::too_many_arguments)]
+    fn draw(
+        &mut self,
+        camera: &Camera,
+        lighting: &Lighting,
+        post_process: &PostProcess,
+        selection_outline: &SelectionOutline,
+        debug_draw: &DebugDraw,
+        sprites: &[&Sprite],
+        billboards: &[&Billboard],
+        renderables: &[Renderable],
+    );
+    /// Changes the MSAA sample count, rebuilding the targets and pipelines that depend on it. The count is clamped to
+    /// what the device supports.
+    fn set_msaa_samples(&mut self, samples: u32);
+    fn msaa_samples(&self) -> u32;
+    fn set_render_path(&mut self, render_path: RenderPath);
+    fn render_path(&self) -> RenderPath;
+    /// Shows a single G-buffer channel instead of the lit scene. Only honored by the deferred path.
+    fn set_gbuffer_view(&mut self, channel: Option<GBufferChannel>);
+    /// Replaces how every renderable is shaded without touching the scene, see `DebugViewMode`.
+    fn set_debug_view_mode(&mut self, mode: DebugViewMode);
+    fn debug_view_mode(&self) -> DebugViewMode;
+    /// Replaces the cubemap drawn as the skybox and reflected by materials, `None` goes back to the background color.
+    fn set_environment(&mut self, environment: Option<Texture>);
+    /// Uploads `texture` so materials created later and sprites can use it by name. Names already taken are ignored.
+    fn load_texture(&mut self, texture: Texture);
+    /// Renders the renderable ids under the window pixel `(x, y)` on the next frame and copies them back to the host.
+    /// A later request made before the frame is drawn replaces it.
+    fn request_pick(&mut self, x: u32, y: u32);
+    /// Result of the oldest pick whose frame has completed, without a renderable index. Each result is returned once.
+    fn take_pick_result(&mut self) -> Option<PickResult>;
     fn await_device_idle(&mut self);
 }
 //----------------------------------------------------------------------------------------------------------------------
@@ -1,8 +1,10 @@
 mod backend;
+mod config;
 pub mod entities;
 mod hal;
 mod renderer;
 //----------------------------------------------------------------------------------------------------------------------
 
+pub use config::*;
 pub use renderer::Renderer;
 //----------------------------------------------------------------------------------------------------------------------
@@ -1,47 +1,81 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 //----------------------------------------------------------------------------------------------------------------------
 
-use ultraviolet::{Mat3, Mat4, Vec3};
+use ultraviolet::{Mat3, Mat4, Vec3, Vec4};
 //----------------------------------------------------------------------------------------------------------------------
 
 use crate::{
     core::window::WindowHandle,
     renderer::{
         backend::vk::VkRenderer,
-        entities::{Camera, Material, Mesh, Renderable, Texture},
+        entities::{
+            Billboard, Camera, DebugDraw, DebugViewMode, Font, GBufferChannel, Highlight, Light,
+            Lighting, Material, Mesh, PickResult, PostEffect, PostProcess, RayHit, Renderable,
+            SelectionOutline, Sprite, Text, Texture, TextureSlot,
+        },
         hal::RendererBackend,
+        RenderPath, RendererConfig,
     },
+    utils::ray::Ray,
 };
 //----------------------------------------------------------------------------------------------------------------------
 
 pub struct Renderer {
     backend: VkRenderer,
     camera: Camera,
+    lighting: Lighting,
+    post_process: PostProcess,
+    selection_outline: SelectionOutline,
+    debug_draw: DebugDraw,
     scene: Vec<Renderable>,
+    /// Drawn over the scene in screen space every frame.
+    sprites: Vec<Sprite>,
+    billboards: Vec<Billboard>,
+    /// Laid out into sprites and billboards every frame.
+    texts: Vec<Text>,
+    /// Loaded fonts by name, their atlases having been uploaded.
+    fonts: HashMap<String, Font>,
+    /// CPU copies of the uploaded meshes, ray casts test their triangles.
+    meshes: HashMap<String, Mesh>,
 }
 //----------------------------------------------------------------------------------------------------------------------
 
 impl Renderer {
-    pub fn init(app_name: &str, window: &WindowHandle) -> Self {
+    pub fn init(app_name: &str, window: &WindowHandle, config: Option<RendererConfig>) -> Self {
         info!("----- Renderer::init -----");
 
-        let backend = VkRenderer::init(app_name, window);
+        let config = config.unwrap_or_default();
+        let backend = VkRenderer::init(app_name, window, &config);
         let inner_size = window.inner_size();
         let aspect_ratio = inner_size.width as f32 / inner_size.height as f32;
 
-        let camera = Camera::new(
+        let mut camera = Camera::new(
             Vec3::new(0.0, 0.0, -2.0),
             Vec3::new(0.0, 0.0, 1.0),
             f32::to_radians(60.0),
             aspect_ratio,
             0.1,
             200.0,
-        );
+        )
+        .with_depth_mode(config.depth_mode);
+        camera.set_viewport_size(inner_size.width as f32, inner_size.height as f32);
 
         let mut renderer = Self {
             backend,
             camera,
+            lighting: Lighting::default(),
+            post_process: PostProcess::default(),
+            selection_outline: SelectionOutline::default(),
+            debug_draw: DebugDraw::default(),
             scene: Vec::new(),
+            sprites: Vec::new(),
+            billboards: Vec::new(),
+            texts: Vec::new(),
+            fonts: HashMap::new(),
+            meshes: HashMap::new(),
         };
 
         renderer.init_resources();
@@ -58,19 +92,15 @@ impl Renderer {
         //     PathBuf::from("resources/shaders/dist/shader.frag.spv"),
         // );
 
-        // let debug_material_name: String = "debug".into();
-        // let debug_material = Material::new(
-        //     debug_material_name.to_owned(),
-        //     PathBuf::from("resources/shaders/dist/shader.vert.spv"),
-        //     PathBuf::from("resources/shaders/dist/debug.frag.spv"),
-        // );
+        let empire_diffuse_name: String = "empire_diffuse".into();
 
         let textured_material_name: String = "textured".into();
         let textured_material = Material::new(
             textured_material_name.to_owned(),
             PathBuf::from("resources/shaders/dist/shader.vert.spv"),
             PathBuf::from("resources/shaders/dist/shader.frag.spv"),
-        );
+        )
+        .with_texture(TextureSlot::BaseColor, &empire_diffuse_name);
 
         // let monkey = Mesh::from_obj(Path::new("assets/models/monkey/monkey_smooth.obj"), true);
         // let monkey_name = monkey.name.clone();
@@ -82,13 +112,15 @@ impl Renderer {
             Mesh::from_obj(Path::new("assets/models/lost_empire/lost_empire.obj"), true);
         let empire_mesh_name = empire_mesh.name.clone();
 
-        let empire_diffuse_name = "empire_diffuse".into();
         let empire_diffuse = Texture::new(
             &empire_diffuse_name,
             PathBuf::from("assets/textures/lost_empire/lost_empire-RGBA.png"),
             None,
         );
 
+        self.meshes
+            .insert(empire_mesh_name.clone(), empire_mesh.clone());
+
         self.backend.init_resources(
             vec![textured_material],
             vec![empire_mesh],
@@ -111,7 +143,7 @@ impl Renderer {
         //         let scale = (Mat3::identity() * 0.2).into_homogeneous();
         //         self.scene.push(Renderable::new(
         //             triangle_name.clone(),
-        //             debug_material_name.to_owned(),
+        //             default_material_name.to_owned(),
         //             translation * scale,
         //         ));
         //     }
@@ -123,6 +155,12 @@ impl Renderer {
             Mat4::identity().translated(&Vec3::new(5.0, -10.0, 0.0)),
         ));
         self.scene.sort_unstable();
+
+        self.lighting.lights.clear();
+        self.lighting.lights.push(
+            Light::directional(Vec3::new(-0.3, -1.0, 0.4), Vec3::new(1.0, 0.95, 0.85), 1.0)
+                .with_shadows(true),
+        );
     }
     //------------------------------------------------------------------------------------------------------------------
 
@@ -136,9 +174,325 @@ impl Renderer {
     }
     //------------------------------------------------------------------------------------------------------------------
 
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn scene(&self) -> &[Renderable] {
+        &self.scene
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Outlines the renderable at `index` in the scene, or stops outlining it with `Highlight::None`.
+    pub fn set_highlight(&mut self, index: usize, highlight: Highlight) {
+        if let Some(renderable) = self.scene.get_mut(index) {
+            renderable.highlight = highlight;
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Removes the highlight of every renderable, or only of those highlighted with `highlight` when given.
+    pub fn clear_highlights(&mut self, highlight: Option<Highlight>) {
+        for renderable in self.scene.iter_mut() {
+            if highlight.is_none_or(|highlight| renderable.highlight == highlight) {
+                renderable.highlight = Highlight::None;
+            }
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Closest renderable hit by the world space `ray`, tested against the triangles of its mesh on the CPU.
+    pub fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        let mut closest: Option<RayHit> = None;
+
+        for (renderable_index, renderable) in self.scene.iter().enumerate() {
+            let mesh = match self.meshes.get(&renderable.mesh_name) {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+
+            // Object space ray with the same parameterization as the world space one
+            let object_ray = ray.transformed(&renderable.transform.inversed());
+
+            if let Some(distance) = mesh.intersect_ray(&object_ray) {
+                if closest.is_none_or(|hit| distance < hit.distance) {
+                    closest = Some(RayHit {
+                        renderable_index,
+                        distance,
+                        position: ray.at(distance),
+                    });
+                }
+            }
+        }
+
+        closest
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Picks the renderable under the window pixel `(x, y)`, counted from the top left corner, on the GPU. The pixel
+    /// is read back asynchronously, its result is returned by `picked` a frame or two later.
+    pub fn pick(&mut self, x: u32, y: u32) {
+        self.backend.request_pick(x, y);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Oldest completed pick.
+    pub fn picked(&mut self) -> Option<PickResult> {
+        self.backend.take_pick_result()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn lighting_mut(&mut self) -> &mut Lighting {
+        &mut self.lighting
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_ambient_light(&mut self, color: Vec3, intensity: f32) {
+        self.lighting.ambient = Vec4::new(color.x, color.y, color.z, intensity);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adds a light to the scene, returning its index in `Lighting::lights`.
+    pub fn add_light(&mut self, light: Light) -> usize {
+        self.lighting.lights.push(light);
+        self.lighting.lights.len() - 1
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn light_mut(&mut self, index: usize) -> Option<&mut Light> {
+        self.lighting.lights.get_mut(index)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Removes the light at `index`. Indices of lights added after it shift down by one.
+    pub fn remove_light(&mut self, index: usize) -> Option<Light> {
+        if index < self.lighting.lights.len() {
+            Some(self.lighting.lights.remove(index))
+        } else {
+            None
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn clear_lights(&mut self) {
+        self.lighting.lights.clear();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn post_process_mut(&mut self) -> &mut PostProcess {
+        &mut self.post_process
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn set_post_effect_enabled(&mut self, effect: PostEffect, enabled: bool) {
+        self.post_process.set_enabled(effect, enabled);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn selection_outline_mut(&mut self) -> &mut SelectionOutline {
+        &mut self.selection_outline
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Queue of debug lines and shapes drawn over the scene, emptied of one-frame shapes by every `draw`.
+    pub fn debug_draw_mut(&mut self) -> &mut DebugDraw {
+        &mut self.debug_draw
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Samples per pixel of the scene, 1 disables MSAA. Clamped to what the device supports.
+    pub fn set_msaa_samples(&mut self, samples: u32) {
+        self.backend.set_msaa_samples(samples);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn msaa_samples(&self) -> u32 {
+        self.backend.msaa_samples()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Switches between forward and deferred shading, takes effect on the next frame.
+    pub fn set_render_path(&mut self, render_path: RenderPath) {
+        self.backend.set_render_path(render_path);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn render_path(&self) -> RenderPath {
+        self.backend.render_path()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Displays a single G-buffer channel in place of the lit scene when using the deferred path, `None` goes back to
+    /// the final image.
+    pub fn set_gbuffer_view(&mut self, channel: Option<GBufferChannel>) {
+        self.backend.set_gbuffer_view(channel);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Shows the scene in one of the debug view modes, e.g. as normals or with its wireframe over it. Takes effect on
+    /// the next frame and leaves the renderables and their materials untouched.
+    pub fn set_debug_view_mode(&mut self, mode: DebugViewMode) {
+        self.backend.set_debug_view_mode(mode);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn debug_view_mode(&self) -> DebugViewMode {
+        self.backend.debug_view_mode()
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Uses `environment`, a cubemap `Texture`, as the skybox and for reflections. Uploaded right away, `None` goes back
+    /// to the plain background.
+    pub fn set_environment(&mut self, environment: Option<Texture>) {
+        self.backend.set_environment(environment);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Uploads `texture` for sprites to use by name. Loading a name twice keeps the first texture.
+    pub fn load_texture(&mut self, texture: Texture) {
+        self.backend.load_texture(texture);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adds a sprite drawn every frame until removed, returning its index.
+    pub fn add_sprite(&mut self, sprite: Sprite) -> usize {
+        self.sprites.push(sprite);
+        self.sprites.len() - 1
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn sprite_mut(&mut self, index: usize) -> Option<&mut Sprite> {
+        self.sprites.get_mut(index)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Removes the sprite at `index`. Indices of sprites added after it shift down by one.
+    pub fn remove_sprite(&mut self, index: usize) -> Option<Sprite> {
+        if index < self.sprites.len() {
+            Some(self.sprites.remove(index))
+        } else {
+            None
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn clear_sprites(&mut self) {
+        self.sprites.clear();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adds a camera facing quad drawn in the world every frame until removed, returning its index.
+    pub fn add_billboard(&mut self, billboard: Billboard) -> usize {
+        self.billboards.push(billboard);
+        self.billboards.len() - 1
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn billboard_mut(&mut self, index: usize) -> Option<&mut Billboard> {
+        self.billboards.get_mut(index)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Removes the billboard at `index`. Indices of billboards added after it shift down by one.
+    pub fn remove_billboard(&mut self, index: usize) -> Option<Billboard> {
+        if index < self.billboards.len() {
+            Some(self.billboards.remove(index))
+        } else {
+            None
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn clear_billboards(&mut self) {
+        self.billboards.clear();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Uploads the atlas of `font` and keeps it for laying out texts referring to it by name. Loading a name twice
+    /// keeps the first atlas.
+    pub fn load_font(&mut self, mut font: Font) {
+        if let Some(atlas) = font.take_atlas() {
+            self.backend.load_texture(atlas);
+        }
+
+        self.fonts.entry(font.name.clone()).or_insert(font);
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Loaded font, e.g. to measure text with `Text::layout`.
+    pub fn font(&self, name: &str) -> Option<&Font> {
+        self.fonts.get(name)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Adds a text drawn every frame until removed, returning its index.
+    pub fn add_text(&mut self, text: Text) -> usize {
+        self.texts.push(text);
+        self.texts.len() - 1
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn text_mut(&mut self, index: usize) -> Option<&mut Text> {
+        self.texts.get_mut(index)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Removes the text at `index`. Indices of texts added after it shift down by one.
+    pub fn remove_text(&mut self, index: usize) -> Option<Text> {
+        if index < self.texts.len() {
+            Some(self.texts.remove(index))
+        } else {
+            None
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn clear_texts(&mut self) {
+        self.texts.clear();
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
     pub fn draw(&mut self) {
+        // Texts are laid out again every frame, so they can change freely
+        let mut text_sprites = Vec::new();
+        let mut text_billboards = Vec::new();
+        for text in self.texts.iter() {
+            match self.fonts.get(&text.font_name) {
+                Some(font) => {
+                    text_sprites.extend(text.to_sprites(font));
+                    text_billboards.extend(text.to_billboards(font));
+                }
+                None => warn!(
+                    "Renderer::draw - Font {} has not been loaded, its texts are skipped!",
+                    text.font_name
+                ),
+            }
+        }
+
+        let sprites = self
+            .sprites
+            .iter()
+            .chain(text_sprites.iter())
+            .collect::<Vec<&Sprite>>();
+        let billboards = self
+            .billboards
+            .iter()
+            .chain(text_billboards.iter())
+            .collect::<Vec<&Billboard>>();
+
         // let frame_start = std::time::Instant::now();
-        self.backend.draw(&self.camera, &self.scene);
+        self.backend.draw(
+            &self.camera,
+            &self.lighting,
+            &self.post_process,
+            &self.selection_outline,
+            &self.debug_draw,
+            &sprites,
+            &billboards,
+            &self.scene,
+        );
+        self.debug_draw.end_frame();
         // eprintln!("Frame time: {:?}", frame_start.elapsed());
     }
     //------------------------------------------------------------------------------------------------------------------
@@ -1,6 +1,7 @@
 pub mod ffi;
 mod logger;
 pub mod math;
+pub mod ray;
 pub(crate) mod traits;
 //----------------------------------------------------------------------------------------------------------------------
 
@@ -0,0 +1,128 @@
+use ultraviolet::{Mat4, Vec3};
+//----------------------------------------------------------------------------------------------------------------------
+
+const INTERSECTION_EPSILON: f32 = 1e-7;
+//----------------------------------------------------------------------------------------------------------------------
+
+/// Half-line starting at `origin`. Hits are reported as the parameter `t` of `origin + direction * t`, a distance as
+/// long as the direction is normalized, which camera rays are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+//----------------------------------------------------------------------------------------------------------------------
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self { origin, direction }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// The direction isn't renormalized, so hits against the transformed ray keep the `t` of the original one. Used to
+    /// test world space rays against object space geometry with the inverse model matrix.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        Self {
+            origin: matrix.transform_point3(self.origin),
+            direction: matrix.transform_vec3(self.direction),
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Slab test against an axis aligned box, 0 when the origin is inside it.
+    pub fn intersect_aabb(&self, min: Vec3, max: Vec3) -> Option<f32> {
+        let mut t_near = 0.0f32;
+        let mut t_far = f32::INFINITY;
+
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+
+            if direction.abs() < INTERSECTION_EPSILON {
+                if origin < min[axis] || origin > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let t0 = (min[axis] - origin) / direction;
+            let t1 = (max[axis] - origin) / direction;
+
+            t_near = t_near.max(t0.min(t1));
+            t_far = t_far.min(t0.max(t1));
+
+            if t_near > t_far {
+                return None;
+            }
+        }
+
+        Some(t_near)
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Nearest hit in front of the origin, 0 when the origin is inside the sphere.
+    pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Option<f32> {
+        let offset = self.origin - center;
+        let a = self.direction.mag_sq();
+        let half_b = offset.dot(self.direction);
+        let c = offset.mag_sq() - radius * radius;
+
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 || a < INTERSECTION_EPSILON {
+            return None;
+        }
+
+        let t = (-half_b - discriminant.sqrt()) / a;
+        if t >= 0.0 {
+            Some(t)
+        } else {
+            None
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+
+    /// Möller-Trumbore test, triangles are hit from both sides.
+    /// https://www.graphics.cornell.edu/pubs/1997/MT97.pdf
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+        let edge_ab = b - a;
+        let edge_ac = c - a;
+
+        let p = self.direction.cross(edge_ac);
+        let determinant = edge_ab.dot(p);
+        if determinant.abs() < INTERSECTION_EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let to_origin = self.origin - a;
+
+        let u = to_origin.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross(edge_ab);
+        let v = self.direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge_ac.dot(q) * inverse_determinant;
+        if t >= 0.0 {
+            Some(t)
+        } else {
+            None
+        }
+    }
+    //------------------------------------------------------------------------------------------------------------------
+}
+//----------------------------------------------------------------------------------------------------------------------